@@ -0,0 +1,276 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Runs a mixed get/set workload against the cache for a fixed duration
+//! and reports the latency percentiles of each command and the throughput
+//! of each second, as text, JSON, or CSV, so that results can be compared
+//! across client versions.
+//!
+//! ```sh
+//! cargo run --release --example bench -- paper://127.0.0.1:3145 json > bench.json
+//! ```
+//!
+//! The workload is configured with `PAPER_BENCH_SECS` (default 10),
+//! `PAPER_BENCH_KEYS` (default 1000), `PAPER_BENCH_VALUE_SIZE` (default 64),
+//! and `PAPER_BENCH_SET_RATIO` (default 0.1).
+
+use std::{
+	env,
+	str::FromStr,
+	time::{Duration, Instant},
+};
+
+use paper_client::{PaperClient, PaperClientError, error::PaperCacheError};
+
+const PERCENTILES: [(&str, f64); 4] = [
+	("p50", 0.5),
+	("p90", 0.9),
+	("p99", 0.99),
+	("p999", 0.999),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+	Text,
+	Json,
+	Csv,
+}
+
+struct Report {
+	secs: u64,
+	ops:  [OpStats; 2],
+
+	// the number of commands completed in each second of the run
+	throughput: Vec<u64>,
+}
+
+struct OpStats {
+	name:      &'static str,
+	latencies: Vec<Duration>,
+	errors:    u64,
+}
+
+fn main() -> Result<(), PaperClientError> {
+	let mut args = env::args().skip(1);
+
+	let paper_addr = args
+		.next()
+		.unwrap_or_else(|| "paper://127.0.0.1:3145".to_owned());
+
+	let format = match args.next().as_deref() {
+		Some("json") => Format::Json,
+		Some("csv") => Format::Csv,
+		_ => Format::Text,
+	};
+
+	let secs = env_or("PAPER_BENCH_SECS", 10);
+	let keys = env_or("PAPER_BENCH_KEYS", 1000u64).max(1);
+	let value = "v".repeat(env_or("PAPER_BENCH_VALUE_SIZE", 64));
+	let set_ratio = env_or("PAPER_BENCH_SET_RATIO", 0.1);
+
+	let mut client = PaperClient::new(paper_addr)?;
+
+	if let Ok(token) = env::var("PAPER_AUTH_TOKEN") {
+		client.auth(&token)?;
+	}
+
+	let mut report = Report {
+		secs,
+		ops: [OpStats::new("get"), OpStats::new("set")],
+		throughput: vec![0; secs as usize],
+	};
+
+	let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+
+	let start = Instant::now();
+	let duration = Duration::from_secs(secs);
+
+	while start.elapsed() < duration {
+		let key = format!("bench:{}", rng.next() % keys);
+		let is_set = (rng.next() % 10_000) as f64 / 10_000.0 < set_ratio;
+
+		let op_start = Instant::now();
+
+		let is_ok = match is_set {
+			true => client.set(&key, value.as_str(), None).is_ok(),
+
+			// a miss is a successful get
+			false => matches!(
+				client.get(&key),
+				Ok(_) | Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)),
+			),
+		};
+
+		report.ops[usize::from(is_set)].record(op_start.elapsed(), is_ok);
+
+		if let Some(count) = report.throughput.get_mut(start.elapsed().as_secs() as usize) {
+			*count += 1;
+		}
+	}
+
+	for op in &mut report.ops {
+		op.latencies.sort_unstable();
+	}
+
+	match format {
+		Format::Text => print_text(&report),
+		Format::Json => print_json(&report),
+		Format::Csv => print_csv(&report),
+	}
+
+	Ok(())
+}
+
+fn print_text(report: &Report) {
+	println!("paper-client {} ({}s)", env!("CARGO_PKG_VERSION"), report.secs);
+
+	for op in &report.ops {
+		let percentiles = PERCENTILES
+			.iter()
+			.map(|(name, percentile)| format!("{name}={}us", op.percentile_us(*percentile)))
+			.collect::<Vec<_>>()
+			.join(" ");
+
+		println!(
+			"{}: {} ops, {} errors, {percentiles} max={}us",
+			op.name,
+			op.latencies.len(),
+			op.errors,
+			op.max_us(),
+		);
+	}
+
+	for (second, count) in report.throughput.iter().enumerate() {
+		println!("second {second}: {count} ops/s");
+	}
+}
+
+fn print_json(report: &Report) {
+	let ops = report
+		.ops
+		.iter()
+		.map(|op| {
+			let percentiles = PERCENTILES
+				.iter()
+				.map(|(name, percentile)| format!("\"{name}_us\":{}", op.percentile_us(*percentile)))
+				.collect::<Vec<_>>()
+				.join(",");
+
+			format!(
+				"{{\"op\":\"{}\",\"count\":{},\"errors\":{},{percentiles},\"max_us\":{}}}",
+				op.name,
+				op.latencies.len(),
+				op.errors,
+				op.max_us(),
+			)
+		})
+		.collect::<Vec<_>>()
+		.join(",");
+
+	let throughput = report
+		.throughput
+		.iter()
+		.enumerate()
+		.map(|(second, count)| format!("{{\"second\":{second},\"ops\":{count}}}"))
+		.collect::<Vec<_>>()
+		.join(",");
+
+	println!(
+		"{{\"client_version\":\"{}\",\"duration_secs\":{},\"ops\":[{ops}],\"throughput\":[{throughput}]}}",
+		env!("CARGO_PKG_VERSION"),
+		report.secs,
+	);
+}
+
+// a single table, with a row per command and a row per second, so that the
+// report can be loaded as is
+fn print_csv(report: &Report) {
+	let percentiles = PERCENTILES
+		.iter()
+		.map(|(name, _)| format!("{name}_us"))
+		.collect::<Vec<_>>()
+		.join(",");
+
+	println!("client_version,kind,op,second,count,errors,{percentiles},max_us");
+
+	let version = env!("CARGO_PKG_VERSION");
+
+	for op in &report.ops {
+		let percentiles = PERCENTILES
+			.iter()
+			.map(|(_, percentile)| op.percentile_us(*percentile).to_string())
+			.collect::<Vec<_>>()
+			.join(",");
+
+		println!(
+			"{version},latency,{},,{},{},{percentiles},{}",
+			op.name,
+			op.latencies.len(),
+			op.errors,
+			op.max_us(),
+		);
+	}
+
+	let empty = vec![""; PERCENTILES.len()].join(",");
+
+	for (second, count) in report.throughput.iter().enumerate() {
+		println!("{version},throughput,all,{second},{count},,{empty},");
+	}
+}
+
+impl OpStats {
+	fn new(name: &'static str) -> Self {
+		OpStats {
+			name,
+			latencies: Vec::new(),
+			errors: 0,
+		}
+	}
+
+	fn record(&mut self, latency: Duration, is_ok: bool) {
+		self.latencies.push(latency);
+
+		if !is_ok {
+			self.errors += 1;
+		}
+	}
+
+	// expects the latencies to be sorted
+	fn percentile_us(&self, percentile: f64) -> u128 {
+		if self.latencies.is_empty() {
+			return 0;
+		}
+
+		let index = ((self.latencies.len() - 1) as f64 * percentile).round() as usize;
+		self.latencies[index].as_micros()
+	}
+
+	fn max_us(&self) -> u128 {
+		self.latencies
+			.last()
+			.map_or(0, Duration::as_micros)
+	}
+}
+
+// a small deterministic generator, so that runs issue the same commands
+struct XorShift(u64);
+
+impl XorShift {
+	fn next(&mut self) -> u64 {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		self.0
+	}
+}
+
+fn env_or<T: FromStr>(name: &str, default: T) -> T {
+	env::var(name)
+		.ok()
+		.and_then(|value| value.parse().ok())
+		.unwrap_or(default)
+}
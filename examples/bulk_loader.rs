@@ -0,0 +1,62 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Loads `key=value` lines from stdin into the cache, sending them in
+//! batches so that each batch costs a single round trip.
+//!
+//! ```sh
+//! printf 'a=1\nb=2\n' | cargo run --example bulk_loader -- paper://127.0.0.1:3145
+//! ```
+
+use std::{
+	env,
+	io::{self, BufRead},
+};
+
+use paper_client::{PaperClient, PaperClientError};
+
+const BATCH_SIZE: usize = 256;
+
+fn main() -> Result<(), PaperClientError> {
+	let paper_addr = env::args()
+		.nth(1)
+		.unwrap_or_else(|| "paper://127.0.0.1:3145".to_owned());
+
+	let mut client = PaperClient::new(paper_addr)?;
+
+	if let Ok(token) = env::var("PAPER_AUTH_TOKEN") {
+		client.auth(&token)?;
+	}
+
+	let lines = io::stdin()
+		.lock()
+		.lines()
+		.map_while(Result::ok)
+		.collect::<Vec<_>>();
+
+	let mut loaded = 0;
+	let mut failed = 0;
+
+	for chunk in lines.chunks(BATCH_SIZE) {
+		let responses = client.batch(|batch| {
+			for (key, value) in chunk.iter().filter_map(|line| line.split_once('=')) {
+				batch.set(key, value, None);
+			}
+		})?;
+
+		for response in responses {
+			match response.is_ok() {
+				true => loaded += 1,
+				false => failed += 1,
+			}
+		}
+	}
+
+	println!("loaded {loaded} keys ({failed} failed)");
+
+	Ok(())
+}
@@ -0,0 +1,60 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Shares an `AsyncPaperPool` between several worker tasks, each of which
+//! checks out a client for every job it processes.
+//!
+//! ```sh
+//! cargo run --example pooled_worker --features tokio -- paper://127.0.0.1:3145
+//! ```
+
+use std::env;
+
+use paper_client::{AsyncPaperPool, PaperClientError};
+
+const NUM_WORKERS: usize = 4;
+const JOBS_PER_WORKER: usize = 100;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), PaperClientError> {
+	let paper_addr = env::args()
+		.nth(1)
+		.unwrap_or_else(|| "paper://127.0.0.1:3145".to_owned());
+
+	let pool = AsyncPaperPool::new(paper_addr, NUM_WORKERS).await?;
+
+	if let Ok(token) = env::var("PAPER_AUTH_TOKEN") {
+		pool.auth(&token).await?;
+	}
+
+	let workers = (0..NUM_WORKERS)
+		.map(|worker| tokio::spawn(work(pool.clone(), worker)))
+		.collect::<Vec<_>>();
+
+	for worker in workers {
+		worker.await.map_err(|_| PaperClientError::Internal)??;
+	}
+
+	let metrics = pool.metrics();
+	println!("{} checkouts, {} timeouts", metrics.checkouts(), metrics.timeouts());
+
+	Ok(())
+}
+
+async fn work(pool: AsyncPaperPool, worker: usize) -> Result<(), PaperClientError> {
+	for job in 0..JOBS_PER_WORKER {
+		let key = format!("worker:{worker}:job:{job}");
+
+		// the client is returned to the pool when the guard is dropped
+		let mut client = pool.client().await;
+
+		client.set(key.as_str(), "done", Some(60)).await?;
+		client.get(key.as_str()).await?;
+	}
+
+	Ok(())
+}
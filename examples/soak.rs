@@ -0,0 +1,283 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Issues randomized commands against the cache for a long period while
+//! checking the responses against a model of what the cache should hold,
+//! and reports each divergence (e.g., to validate a server upgrade). The
+//! cache is wiped periodically, so this must not be run against a cache
+//! holding data which is still needed.
+//!
+//! ```sh
+//! PAPER_SOAK_SECS=3600 cargo run --release --example soak -- paper://127.0.0.1:3145
+//! ```
+//!
+//! The following invariants are checked:
+//! - a `get` of a key returns the value it was most recently set to, until
+//!   the key is deleted, expires, or the cache is wiped,
+//! - a key set with a ttl can still be read until shortly before its ttl
+//!   has elapsed and cannot be read shortly after,
+//! - a key cannot be read once it has been deleted,
+//! - no key can be read once the cache has been wiped.
+//!
+//! A key which cannot be read while it should still be held is counted as
+//! an eviction rather than a divergence, as the server may evict any key
+//! once the cache is full.
+//!
+//! The run is configured with `PAPER_SOAK_SECS` (default 60),
+//! `PAPER_SOAK_KEYS` (default 1000), and `PAPER_SOAK_WIPE_EVERY` (the
+//! number of commands between wipes, default 10000). The process exits
+//! with status 1 if any divergence was found.
+
+use std::{
+	collections::HashMap,
+	env,
+	process,
+	str::FromStr,
+	time::{Duration, Instant},
+};
+
+use paper_client::{PaperClient, PaperClientError, PaperValue, error::PaperCacheError};
+
+// the ttls (in seconds) of the keys which are set with one
+const TTLS: [u32; 3] = [1, 2, 3];
+
+// the uncertainty in when the server expires a key, as ttls are only
+// accurate to the second
+const EXPIRY_SLACK: Duration = Duration::from_millis(1500);
+
+struct Model {
+	entries: HashMap<String, Entry>,
+}
+
+struct Entry {
+	value:      String,
+	expires_at: Option<Instant>,
+}
+
+// what a get of a key should return according to the model
+enum Expected<'a> {
+	Value(&'a str),
+	Missing,
+	// the key may or may not have expired yet
+	Unknown,
+}
+
+#[derive(Default)]
+struct Summary {
+	commands:    u64,
+	errors:      u64,
+	evictions:   u64,
+	divergences: u64,
+}
+
+fn main() -> Result<(), PaperClientError> {
+	let paper_addr = env::args()
+		.nth(1)
+		.unwrap_or_else(|| "paper://127.0.0.1:3145".to_owned());
+
+	let secs = env_or("PAPER_SOAK_SECS", 60);
+	let keys = env_or("PAPER_SOAK_KEYS", 1000u64).max(1);
+	let wipe_every = env_or("PAPER_SOAK_WIPE_EVERY", 10_000u64).max(1);
+
+	let mut client = PaperClient::new(paper_addr)?;
+
+	if let Ok(token) = env::var("PAPER_AUTH_TOKEN") {
+		client.auth(&token)?;
+	}
+
+	client.wipe()?;
+
+	let mut model = Model {
+		entries: HashMap::new(),
+	};
+
+	let mut summary = Summary::default();
+	let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+
+	let start = Instant::now();
+	let duration = Duration::from_secs(secs);
+
+	while start.elapsed() < duration {
+		summary.commands += 1;
+
+		if summary.commands % wipe_every == 0 {
+			wipe(&mut client, &mut model, &mut summary);
+			continue;
+		}
+
+		let key = format!("soak:{}", rng.next() % keys);
+
+		match rng.next() % 10 {
+			0..4 => get(&mut client, &model, &key, &mut summary),
+
+			4..8 => {
+				let value = format!("{}:{}", key, rng.next());
+
+				let ttl = match rng.next() % 4 {
+					0 => Some(TTLS[(rng.next() % TTLS.len() as u64) as usize]),
+					_ => None,
+				};
+
+				set(&mut client, &mut model, key, value, ttl, &mut summary);
+			},
+
+			_ => del(&mut client, &mut model, &key, &mut summary),
+		}
+	}
+
+	println!(
+		"{} commands in {secs}s: {} errors, {} evictions, {} divergences",
+		summary.commands, summary.errors, summary.evictions, summary.divergences,
+	);
+
+	if summary.divergences > 0 {
+		process::exit(1);
+	}
+
+	Ok(())
+}
+
+fn get(client: &mut PaperClient, model: &Model, key: &str, summary: &mut Summary) {
+	let result = client.get(key);
+
+	match (model.expected(key), result) {
+		(_, Err(err)) if !is_miss(&err) => error(summary, "get", key, &err),
+
+		(Expected::Value(expected), Ok(value)) if !is_value(&value, expected) => {
+			diverge(summary, key, &format!("got {value:?} instead of {expected:?}"));
+		},
+
+		(Expected::Value(_), Err(_)) => summary.evictions += 1,
+
+		(Expected::Missing, Ok(value)) => {
+			diverge(summary, key, &format!("got {value:?} for a key which should be missing"));
+		},
+
+		_ => {},
+	}
+}
+
+fn set(
+	client: &mut PaperClient,
+	model: &mut Model,
+	key: String,
+	value: String,
+	ttl: Option<u32>,
+	summary: &mut Summary,
+) {
+	match client.set(&key, value.as_str(), ttl) {
+		Ok(_) => {
+			let expires_at = ttl.map(|ttl| Instant::now() + Duration::from_secs(ttl.into()));
+
+			model.entries.insert(key, Entry {
+				value,
+				expires_at,
+			});
+		},
+
+		Err(err) => {
+			error(summary, "set", &key, &err);
+
+			// the set may or may not have been applied
+			model.entries.remove(&key);
+		},
+	}
+}
+
+fn del(client: &mut PaperClient, model: &mut Model, key: &str, summary: &mut Summary) {
+	let result = client.del(key);
+
+	match (model.expected(key), result) {
+		(_, Err(err)) if !is_miss(&err) => error(summary, "del", key, &err),
+
+		(Expected::Missing, Ok(_)) => {
+			diverge(summary, key, "deleted a key which should be missing");
+		},
+
+		(Expected::Value(_), Err(_)) => summary.evictions += 1,
+
+		_ => {},
+	}
+
+	model.entries.remove(key);
+}
+
+fn wipe(client: &mut PaperClient, model: &mut Model, summary: &mut Summary) {
+	if let Err(err) = client.wipe() {
+		error(summary, "wipe", "*", &err);
+		return;
+	}
+
+	for key in model.entries.keys() {
+		match client.has(key) {
+			Ok(true) => diverge(summary, key, "still held after the cache was wiped"),
+			Ok(false) => {},
+			Err(err) => error(summary, "has", key, &err),
+		}
+	}
+
+	model.entries.clear();
+}
+
+impl Model {
+	fn expected(&self, key: &str) -> Expected<'_> {
+		let Some(entry) = self.entries.get(key) else {
+			return Expected::Missing;
+		};
+
+		let Some(expires_at) = entry.expires_at else {
+			return Expected::Value(&entry.value);
+		};
+
+		let now = Instant::now();
+
+		if now + EXPIRY_SLACK < expires_at {
+			Expected::Value(&entry.value)
+		} else if now > expires_at + EXPIRY_SLACK {
+			Expected::Missing
+		} else {
+			Expected::Unknown
+		}
+	}
+}
+
+fn is_miss(err: &PaperClientError) -> bool {
+	matches!(err, PaperClientError::CacheError(PaperCacheError::KeyNotFound))
+}
+
+fn is_value(value: &PaperValue, expected: &str) -> bool {
+	value.as_ref() == expected.as_bytes()
+}
+
+fn error(summary: &mut Summary, command: &str, key: &str, err: &PaperClientError) {
+	summary.errors += 1;
+	eprintln!("error: {command} {key}: {err}");
+}
+
+fn diverge(summary: &mut Summary, key: &str, reason: &str) {
+	summary.divergences += 1;
+	eprintln!("divergence: {key}: {reason}");
+}
+
+// a small deterministic generator, so that runs issue the same commands
+struct XorShift(u64);
+
+impl XorShift {
+	fn next(&mut self) -> u64 {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		self.0
+	}
+}
+
+fn env_or<T: FromStr>(name: &str, default: T) -> T {
+	env::var(name)
+		.ok()
+		.and_then(|value| value.parse().ok())
+		.unwrap_or(default)
+}
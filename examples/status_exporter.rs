@@ -0,0 +1,57 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Polls the cache's status and prints it in the Prometheus text format,
+//! suitable for a textfile collector.
+//!
+//! ```sh
+//! cargo run --example status_exporter -- paper://127.0.0.1:3145 > paper.prom
+//! ```
+
+use std::env;
+
+use paper_client::{PaperClient, PaperClientError, Status};
+
+fn main() -> Result<(), PaperClientError> {
+	let paper_addr = env::args()
+		.nth(1)
+		.unwrap_or_else(|| "paper://127.0.0.1:3145".to_owned());
+
+	let mut client = PaperClient::new(paper_addr)?;
+
+	if let Ok(token) = env::var("PAPER_AUTH_TOKEN") {
+		client.auth(&token)?;
+	}
+
+	export(&client.status()?);
+
+	Ok(())
+}
+
+fn export(status: &Status) {
+	gauge("paper_max_size_bytes", status.max_size() as f64);
+	gauge("paper_used_size_bytes", status.used_size() as f64);
+	gauge("paper_objects", status.num_objects() as f64);
+	gauge("paper_rss_bytes", status.rss() as f64);
+
+	counter("paper_gets_total", status.total_gets());
+	counter("paper_sets_total", status.total_sets());
+	counter("paper_dels_total", status.total_dels());
+
+	gauge("paper_miss_ratio", status.miss_ratio());
+	gauge("paper_uptime_seconds", status.uptime().as_secs_f64());
+}
+
+fn gauge(name: &str, value: f64) {
+	println!("# TYPE {name} gauge");
+	println!("{name} {value}");
+}
+
+fn counter(name: &str, value: u64) {
+	println!("# TYPE {name} counter");
+	println!("{name} {value}");
+}
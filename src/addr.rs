@@ -5,8 +5,12 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::fmt::{self, Display};
+
 use crate::error::{PaperClientError, PaperClientResult};
 
+const MAX_DISPLAY_LEN: usize = 64;
+
 pub trait FromPaperAddr: Clone {
 	fn to_addr(&self) -> PaperClientResult<String>;
 }
@@ -32,3 +36,15 @@ impl FromPaperAddr for &String {
 		self.as_str().to_addr()
 	}
 }
+
+/// Displays an address, truncated to a reasonable length for logging.
+pub(crate) struct DisplayAddr<'a>(pub &'a str);
+
+impl Display for DisplayAddr<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.0.char_indices().nth(MAX_DISPLAY_LEN) {
+			Some((index, _)) => write!(f, "paper://{}...", &self.0[..index]),
+			None => write!(f, "paper://{}", self.0),
+		}
+	}
+}
@@ -5,6 +5,13 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::time::{Duration, SystemTime};
+
+use crate::{
+	error::{PaperClientError, PaperClientResult},
+	ttl::Ttl,
+};
+
 pub trait AsPaperKey {
 	fn as_paper_key(&self) -> &str;
 }
@@ -13,6 +20,13 @@ pub trait AsPaperAuthToken {
 	fn as_paper_auth_token(&self) -> &str;
 }
 
+pub trait AsPaperTtl {
+	/// Returns the ttl in seconds, where zero means no expiry. A ttl which
+	/// would expire the key immediately (e.g., `Some(0)`) is rejected with
+	/// `InvalidTtl` rather than being mistaken for no expiry.
+	fn as_paper_ttl(&self) -> PaperClientResult<u32>;
+}
+
 impl AsPaperKey for &str {
 	fn as_paper_key(&self) -> &str {
 		self
@@ -48,3 +62,43 @@ impl AsPaperAuthToken for &String {
 		self
 	}
 }
+
+impl AsPaperTtl for Option<u32> {
+	fn as_paper_ttl(&self) -> PaperClientResult<u32> {
+		match self {
+			Some(0) => Err(PaperClientError::InvalidTtl),
+			Some(ttl) => Ok(*ttl),
+			None => Ok(0),
+		}
+	}
+}
+
+impl AsPaperTtl for Duration {
+	fn as_paper_ttl(&self) -> PaperClientResult<u32> {
+		if self.is_zero() {
+			return Err(PaperClientError::InvalidTtl);
+		}
+
+		// a sub-second ttl is rounded up rather than to zero (no expiry)
+		let secs = match self.subsec_nanos() {
+			0 => self.as_secs(),
+			_ => self.as_secs().saturating_add(1),
+		};
+
+		Ok(u32::try_from(secs).unwrap_or(u32::MAX))
+	}
+}
+
+impl AsPaperTtl for Ttl {
+	fn as_paper_ttl(&self) -> PaperClientResult<u32> {
+		match self {
+			Ttl::None => Ok(0),
+			Ttl::Seconds(secs) => Ok(secs.get()),
+
+			Ttl::At(time) => time
+				.duration_since(SystemTime::now())
+				.map_err(|_| PaperClientError::InvalidTtl)?
+				.as_paper_ttl(),
+		}
+	}
+}
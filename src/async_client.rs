@@ -1,26 +1,88 @@
-use paper_utils::stream::{AsyncStreamReader, StreamError};
-use tokio::{io::BufStream, net::TcpStream};
+use std::{
+	fmt::{self, Debug, Display},
+	path::Path,
+	process,
+	slice,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+pub use paper_utils::stream::AsyncStreamReader;
+use paper_utils::stream::{self, StreamError};
+use tokio::{
+	io::{AsyncWrite, AsyncWriteExt, BufStream},
+	net::TcpStream,
+	time,
+};
 
 use crate::{
-	addr::FromPaperAddr,
-	arg::{AsPaperAuthToken, AsPaperKey},
+	addr::{DisplayAddr, FromPaperAddr},
+	arg::{AsPaperAuthToken, AsPaperKey, AsPaperTtl},
+	auth::{AuthTokenProvider, StaticTokenProvider},
+	batch::{Batch, BatchResponse},
+	capture::CaptureStream,
 	command::Command,
-	error::{PaperClientError, PaperClientResult},
+	config::PaperClientConfig,
+	connection_info::{ConnectionInfo, PeerAddr},
+	deadline,
+	decode::DecodePaperResponseAsync,
+	error::{PaperCacheError, PaperClientError, PaperClientResult},
+	get_result::GetResult,
+	health::NodeHealth,
+	key_display::KeyDisplay,
+	metrics::{ClientStats, ConnectionStats},
+	numeric::NumericValue,
+	observer::{AuditCommand, AuditEvent, PaperEvent},
 	policy::PaperPolicy,
+	range::RangeWriter,
+	response::{Ack, Custom, DecodeResponseAsync, Flag, IntoSlice, IntoVec, Size, StatusFrame, ToWriter, Value},
+	sequence::{self, SequenceToken, Sequencer},
+	settings::PaperSettings,
+	singleflight::{self, LOCK_POLL_INTERVAL, LOCK_TTL},
+	stale::{self, StaleResult, STALE_MARKER_VALUE},
 	status::Status,
+	tenant::{AsyncTenant, TenantUsage},
+	transport::AsyncPaperTransport,
 	value::PaperValue,
 };
 
 const RECONNECT_MAX_ATTEMPTS: u8 = 3;
 
-#[derive(Debug)]
-pub struct AsyncPaperClient {
-	addr: String,
+// the maximum delay between polls of wait_for, as a multiple of the poll interval
+const MAX_WAIT_BACKOFF_FACTOR: u32 = 8;
+
+pub struct AsyncPaperClient<T = TcpStream> {
+	addr:   String,
+	config: PaperClientConfig,
 
-	auth_token:         Option<String>,
+	auth_provider:      Option<Arc<dyn AuthTokenProvider>>,
 	reconnect_attempts: u8,
+	is_closed:          bool,
+
+	// bounds each command, defaulting to the config's command timeout
+	command_timeout: Option<Duration>,
+
+	// set while a command's response has not been fully read, in which
+	// case the connection must be re-established before the next command
+	is_stale: bool,
+
+	// the token with which a reconnected connection is authorized, which
+	// is sent along with the next command rather than on its own
+	pending_auth: Option<String>,
+
+	// the process which opened the connection, which differs from the
+	// current process once the client has crossed a fork
+	pid: u32,
 
-	stream: BufStream<TcpStream>,
+	// the time at which the most recent command completed
+	last_used: Instant,
+
+	sequencer: Arc<Sequencer>,
+
+	health: Option<Arc<NodeHealth>>,
+	stats:  Arc<ConnectionStats>,
+
+	stream: BufStream<T>,
 }
 
 impl AsyncPaperClient {
@@ -35,21 +97,197 @@ impl AsyncPaperClient {
 	/// let client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
 	/// ```
 	pub async fn new(paper_addr: impl FromPaperAddr) -> PaperClientResult<Self> {
+		AsyncPaperClient::with_config(paper_addr, PaperClientConfig::default()).await
+	}
+
+	/// Creates a new instance of the client with the supplied configuration
+	/// and connects to the server. If a connection could not be established,
+	/// a `PaperClientError` is returned.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use std::time::Duration;
+	/// use paper_client::{AsyncPaperClient, PaperClientConfig};
+	///
+	/// let config = PaperClientConfig::default()
+	///     .handshake_timeout(Some(Duration::from_secs(1)))
+	///     .connect_retries(3);
+	///
+	/// let client = AsyncPaperClient::with_config("paper://127.0.0.1:3145", config)
+	///     .await
+	///     .unwrap();
+	/// ```
+	pub async fn with_config(
+		paper_addr: impl FromPaperAddr,
+		config: PaperClientConfig,
+	) -> PaperClientResult<Self> {
+		let addr = paper_addr.to_addr()?;
+		AsyncPaperClient::connect(addr, config).await
+	}
+
+	/// Creates a new instance of the client using the settings read from the
+	/// environment (see [`PaperSettings`]), and authorizes the client if
+	/// an auth token was supplied.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// match AsyncPaperClient::from_env().await {
+	///     Ok(_) => println!("connected"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn from_env() -> PaperClientResult<Self> {
+		AsyncPaperClient::from_settings(PaperSettings::from_env()?).await
+	}
+
+	/// Creates a new instance of the client using the settings read from the
+	/// TOML file at the supplied path (see [`PaperSettings`]), and authorizes
+	/// the client if an auth token was supplied.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// match AsyncPaperClient::from_config("paper.toml").await {
+	///     Ok(_) => println!("connected"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn from_config(path: impl AsRef<Path>) -> PaperClientResult<Self> {
+		AsyncPaperClient::from_settings(PaperSettings::from_file(path)?).await
+	}
+
+	/// Creates a new instance of the client using the supplied settings, and
+	/// authorizes the client if an auth token was supplied.
+	pub async fn from_settings(settings: PaperSettings) -> PaperClientResult<Self> {
+		let mut client = AsyncPaperClient::with_config(settings.addr(), settings.config().clone()).await?;
+
+		if let Some(token) = settings.auth_token() {
+			client.auth(token).await?;
+		}
+
+		Ok(client)
+	}
+}
+
+impl<T: AsyncPaperTransport> AsyncPaperClient<T> {
+	/// Creates a new instance of the client with the supplied configuration
+	/// and connects to the server over the transport `T`. If a connection
+	/// could not be established, a `PaperClientError` is returned.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use tokio::net::UnixStream;
+	/// use paper_client::{AsyncPaperClient, PaperClientConfig};
+	///
+	/// let client = AsyncPaperClient::<UnixStream>::with_transport(
+	///     "paper:///tmp/paper.sock",
+	///     PaperClientConfig::default(),
+	/// )
+	/// .await
+	/// .unwrap();
+	/// ```
+	pub async fn with_transport(
+		paper_addr: impl FromPaperAddr,
+		config: PaperClientConfig,
+	) -> PaperClientResult<Self> {
+		let addr = paper_addr.to_addr()?;
+		AsyncPaperClient::connect(addr, config).await
+	}
+
+	/// Creates a new instance of the client over an already established
+	/// transport, and waits for the server's handshake. The supplied
+	/// address is used if the client needs to reconnect.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::{AsyncPaperClient, ScriptedResponder};
+	///
+	/// let responder = ScriptedResponder::new();
+	///
+	/// let client = AsyncPaperClient::from_transport(
+	///     "paper://loopback",
+	///     responder.transport(),
+	/// )
+	/// .await
+	/// .unwrap();
+	/// ```
+	pub async fn from_transport(
+		paper_addr: impl FromPaperAddr,
+		transport: T,
+	) -> PaperClientResult<Self> {
+		let config = PaperClientConfig::default();
+		AsyncPaperClient::from_transport_with_config(paper_addr, transport, config).await
+	}
+
+	/// Creates a new instance of the client over an already established
+	/// transport with the supplied configuration, and waits for the
+	/// server's handshake.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::{AsyncPaperClient, PaperClientConfig, ScriptedResponder};
+	///
+	/// let responder = ScriptedResponder::new();
+	///
+	/// let client = AsyncPaperClient::from_transport_with_config(
+	///     "paper://loopback",
+	///     responder.transport(),
+	///     PaperClientConfig::default(),
+	/// )
+	/// .await
+	/// .unwrap();
+	/// ```
+	pub async fn from_transport_with_config(
+		paper_addr: impl FromPaperAddr,
+		transport: T,
+		config: PaperClientConfig,
+	) -> PaperClientResult<Self> {
 		let addr = paper_addr.to_addr()?;
-		let stream = init_stream(&addr).await?;
 
-		let mut client = AsyncPaperClient {
+		let mut stream = BufStream::new(transport);
+		handshake(&mut stream, config.handshake_timeout).await?;
+
+		Ok(AsyncPaperClient::from_stream(addr, config, stream))
+	}
+
+	pub(crate) async fn connect(
+		addr: String,
+		config: PaperClientConfig,
+	) -> PaperClientResult<Self> {
+		let stream = init_stream(&addr, &config).await?;
+
+		Ok(AsyncPaperClient::from_stream(addr, config, stream))
+	}
+
+	fn from_stream(addr: String, config: PaperClientConfig, stream: BufStream<T>) -> Self {
+		let command_timeout = config.command_timeout;
+		let last_used = config.clock.now();
+
+		AsyncPaperClient {
 			addr,
+			config,
 
-			auth_token: None,
+			auth_provider: None,
 			reconnect_attempts: 0,
+			is_closed: false,
 
-			stream,
-		};
+			command_timeout,
 
-		client.handshake().await?;
+			is_stale: false,
+			pending_auth: None,
+			pid: process::id(),
+			last_used,
 
-		Ok(client)
+			sequencer: Arc::default(),
+
+			health: None,
+			stats: Arc::default(),
+
+			stream,
+		}
 	}
 
 	/// Pings the server.
@@ -66,7 +304,36 @@ impl AsyncPaperClient {
 	/// }
 	/// ```
 	pub async fn ping(&mut self) -> PaperClientResult<PaperValue> {
-		self.process_value(&Command::Ping).await
+		self.process(&Command::Ping, Value(self.config.max_response_size)).await
+	}
+
+	/// Pings the server if no command has completed within the supplied
+	/// duration, returning whether the server was pinged. Calling this
+	/// periodically keeps an idle connection from being expired by NATs or
+	/// firewalls, and detects a dead connection before the next command
+	/// stalls on it. A pool's clients can be kept alive in the background
+	/// with `AsyncPaperPool::with_keep_alive`.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use std::time::Duration;
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.ping_if_idle(Duration::from_secs(60)).await {
+	///     Ok(pinged) => println!("{pinged}"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn ping_if_idle(&mut self, idle: Duration) -> PaperClientResult<bool> {
+		let now = self.config.clock.now();
+
+		if now.saturating_duration_since(self.last_used) < idle {
+			return Ok(false);
+		}
+
+		self.ping().await.map(|_| true)
 	}
 
 	/// Gets the cache version.
@@ -83,7 +350,7 @@ impl AsyncPaperClient {
 	/// }
 	/// ```
 	pub async fn version(&mut self) -> PaperClientResult<PaperValue> {
-		self.process_value(&Command::Version).await
+		self.process(&Command::Version, Value(self.config.max_response_size)).await
 	}
 
 	/// Attempts to authorize the connection with the supplied auth token. This
@@ -102,35 +369,36 @@ impl AsyncPaperClient {
 	/// }
 	/// ```
 	pub async fn auth(&mut self, token: impl AsPaperAuthToken) -> PaperClientResult<()> {
-		let auth_token = token.as_paper_auth_token();
-
-		let command = Command::Auth(auth_token);
-		let result = self.process(&command).await;
-
-		self.auth_token = Some(auth_token.to_owned());
-
-		result
+		let provider = StaticTokenProvider::new(token.as_paper_auth_token());
+		self.auth_with(provider).await
 	}
 
-	/// Gets the value of the supplied key from the cache.
+	/// Attempts to authorize the connection with the token supplied by the
+	/// supplied `AuthTokenProvider`. The provider is queried again each time
+	/// the connection is reauthorized or reconnected.
 	///
 	/// # Examples
 	/// ```ignore
-	/// use paper_client::AsyncPaperClient;
+	/// use paper_client::{AsyncPaperClient, EnvTokenProvider};
 	///
 	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
 	///
-	/// match client.get("key").await {
-	///     Ok(value) => println!("{value:?}"),
+	/// match client.auth_with(EnvTokenProvider::new("PAPER_AUTH_TOKEN")).await {
+	///     Ok(_) => println!("done"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub async fn get(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
-		let command = Command::Get(key.as_paper_key());
-		self.process_value(&command).await
+	pub async fn auth_with(
+		&mut self,
+		provider: impl AuthTokenProvider + 'static,
+	) -> PaperClientResult<()> {
+		self.set_auth_provider(Arc::new(provider));
+		self.reauth().await
 	}
 
-	/// Sets the supplied key, value, and ttl to the cache.
+	/// Reauthorizes the existing connection with the token currently
+	/// supplied by the client's `AuthTokenProvider`, without reconnecting.
+	/// Does nothing if the client has not been authorized.
 	///
 	/// # Examples
 	/// ```ignore
@@ -138,27 +406,22 @@ impl AsyncPaperClient {
 	///
 	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
 	///
-	/// match client.set("key", "value", None).await {
+	/// match client.reauth().await {
 	///     Ok(_) => println!("done"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub async fn set(
-		&mut self,
-		key: impl AsPaperKey,
-		value: impl TryInto<PaperValue>,
-		ttl: Option<u32>,
-	) -> PaperClientResult<()> {
-		let value: PaperValue = value
-			.try_into()
-			.map_err(|_| PaperClientError::InvalidValue)?;
-
-		let command = Command::Set(key.as_paper_key(), value, ttl.unwrap_or(0));
+	pub async fn reauth(&mut self) -> PaperClientResult<()> {
+		let Some(provider) = self.auth_provider.clone() else {
+			return Ok(());
+		};
 
-		self.process(&command).await
+		let token = provider.token()?;
+		self.process(&Command::Auth(&token), Ack).await
 	}
 
-	/// Deletes the value of the supplied key from the cache.
+	/// Replaces the client's auth token with the supplied token and
+	/// reauthorizes the existing connection, without reconnecting.
 	///
 	/// # Examples
 	/// ```ignore
@@ -166,18 +429,16 @@ impl AsyncPaperClient {
 	///
 	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
 	///
-	/// match client.del("key").await {
+	/// match client.rotate_token("my_new_token").await {
 	///     Ok(_) => println!("done"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub async fn del(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
-		let command = Command::Del(key.as_paper_key());
-		self.process(&command).await
+	pub async fn rotate_token(&mut self, token: impl AsPaperAuthToken) -> PaperClientResult<()> {
+		self.auth(token).await
 	}
 
-	/// Checks if the cache contains an object with the supplied key
-	/// without altering the eviction order of the objects.
+	/// Gets the value of the supplied key from the cache.
 	///
 	/// # Examples
 	/// ```ignore
@@ -185,18 +446,21 @@ impl AsyncPaperClient {
 	///
 	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
 	///
-	/// match client.has("key").await {
-	///     Ok(has) => println!("{has}"),
+	/// match client.get("key").await {
+	///     Ok(value) => println!("{value:?}"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub async fn has(&mut self, key: impl AsPaperKey) -> PaperClientResult<bool> {
-		let command = Command::Has(key.as_paper_key());
-		self.process_has(&command).await
+	pub async fn get(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Get(&key);
+		let value = self.process(&command, Value(self.config.max_response_size)).await?;
+
+		self.config.decode_value(value)
 	}
 
-	/// Gets (peeks) the value of the supplied key from the cache without
-	/// altering the eviction order of the objects.
+	/// Gets the value of the supplied key from the cache, reporting a
+	/// missing key as a miss rather than as a `KeyNotFound` error.
 	///
 	/// # Examples
 	/// ```ignore
@@ -204,17 +468,72 @@ impl AsyncPaperClient {
 	///
 	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
 	///
-	/// match client.peek("key").await {
+	/// match client.get_result("key").await {
+	///     Ok(result) if result.is_hit() => println!("hit: {:?}", result.size()),
+	///     Ok(_) => println!("miss"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn get_result(&mut self, key: impl AsPaperKey) -> PaperClientResult<GetResult> {
+		match self.get(key).await {
+			Ok(value) => Ok(GetResult::hit(value)),
+			Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => Ok(GetResult::miss()),
+			Err(err) => Err(err),
+		}
+	}
+
+	/// Waits for the supplied key to appear in the cache and returns its
+	/// value, polling with `get`. The delay between polls starts at the
+	/// supplied interval and doubles after each poll, up to eight times the
+	/// interval. If the key does not appear within the supplied timeout,
+	/// `WaitTimeout` is returned. Any error other than `KeyNotFound` is
+	/// returned immediately.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use std::time::Duration;
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.wait_for("job:42", Duration::from_secs(5), Duration::from_millis(10)).await {
 	///     Ok(value) => println!("{value:?}"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub async fn peek(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
-		let command = Command::Peek(key.as_paper_key());
-		self.process_value(&command).await
+	pub async fn wait_for(
+		&mut self,
+		key: impl AsPaperKey,
+		timeout: Duration,
+		poll_interval: Duration,
+	) -> PaperClientResult<PaperValue> {
+		let key = key.as_paper_key();
+
+		let start = Instant::now();
+		let mut interval = poll_interval;
+
+		loop {
+			match self.get(key).await {
+				Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => {},
+				result => return result,
+			}
+
+			let remaining = timeout.saturating_sub(start.elapsed());
+
+			if remaining.is_zero() {
+				return Err(PaperClientError::WaitTimeout);
+			}
+
+			time::sleep(interval.min(remaining)).await;
+			interval = (interval * 2).min(poll_interval * MAX_WAIT_BACKOFF_FACTOR);
+		}
 	}
 
-	/// Sets the TTL associated with the supplied key.
+	/// Marks the value of the supplied key as stale without deleting it,
+	/// by setting its stale marker (the key followed by
+	/// `STALE_MARKER_SUFFIX`). The next `get_allow_stale` of the key
+	/// reports the value as stale, so that a single reader refreshes it
+	/// while others continue to be served the stale value.
 	///
 	/// # Examples
 	/// ```ignore
@@ -222,17 +541,24 @@ impl AsyncPaperClient {
 	///
 	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
 	///
-	/// match client.ttl("key", Some(5)).await {
+	/// match client.invalidate_soft("key").await {
 	///     Ok(_) => println!("done"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub async fn ttl(&mut self, key: impl AsPaperKey, ttl: Option<u32>) -> PaperClientResult<()> {
-		let command = Command::Ttl(key.as_paper_key(), ttl.unwrap_or(0));
-		self.process(&command).await
+	pub async fn invalidate_soft(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
+		self.set(stale::marker_key(key.as_paper_key()), STALE_MARKER_VALUE, None)
+			.await
 	}
 
-	/// Gets the size of the value of the supplied key from the cache in bytes.
+	/// Gets the value of the supplied key from the cache, along with
+	/// whether it was marked stale by `invalidate_soft`. Reading a stale
+	/// value consumes its marker, so exactly one reader observes each soft
+	/// invalidation and is responsible for refreshing the value.
+	///
+	/// A marker which is set while the key is missing is consumed by the
+	/// next read of the key, in which case the value is reported as stale
+	/// even if it was set after the invalidation.
 	///
 	/// # Examples
 	/// ```ignore
@@ -240,17 +566,46 @@ impl AsyncPaperClient {
 	///
 	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
 	///
-	/// match client.size("key").await {
-	///     Ok(size) => println!("{size}"),
+	/// match client.get_allow_stale("key").await {
+	///     Ok(result) if result.is_stale() => println!("refresh {:?}", result.value()),
+	///     Ok(result) => println!("{:?}", result.value()),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub async fn size(&mut self, key: impl AsPaperKey) -> PaperClientResult<u32> {
-		let command = Command::Size(key.as_paper_key());
-		self.process_size(&command).await
+	pub async fn get_allow_stale(
+		&mut self,
+		key: impl AsPaperKey,
+	) -> PaperClientResult<StaleResult> {
+		let key = key.as_paper_key();
+
+		// the marker is consumed even if the key is missing, matching the
+		// batched read of the sync client
+		let value = match self.get(key).await {
+			Ok(value) => Ok(value),
+			Err(err @ PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => Err(err),
+			Err(err) => return Err(err),
+		};
+
+		let is_stale = match self.del(stale::marker_key(key)).await {
+			Ok(_) => true,
+			Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => false,
+			Err(err) => return Err(err),
+		};
+
+		Ok(StaleResult::new(value?, is_stale))
 	}
 
-	/// Wipes the contents of the cache.
+	/// Gets the value of the supplied key from the cache or, if it is
+	/// missing, computes it with the supplied future and sets it with the
+	/// supplied ttl. Only one process computes a missing value: the others
+	/// wait (for up to `LOCK_TTL`) for it to be set, rather than computing
+	/// it themselves.
+	///
+	/// The computing process is elected with a lock key (the key followed
+	/// by `LOCK_KEY_SUFFIX`). As the server has no atomic set-if-absent
+	/// command, processes which miss the key at the same instant may both
+	/// acquire the lock and compute the value, so this reduces dogpiles
+	/// rather than strictly preventing them.
 	///
 	/// # Examples
 	/// ```ignore
@@ -258,269 +613,1365 @@ impl AsyncPaperClient {
 	///
 	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
 	///
-	/// match client.wipe().await {
-	///     Ok(_) => println!("done"),
+	/// match client.get_or_compute_coordinated("key", None, || async { "value" }).await {
+	///     Ok(value) => println!("{value:?}"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub async fn wipe(&mut self) -> PaperClientResult<()> {
-		self.process(&Command::Wipe).await
+	pub async fn get_or_compute_coordinated<V, F>(
+		&mut self,
+		key: impl AsPaperKey,
+		ttl: impl AsPaperTtl,
+		f: impl FnOnce() -> F,
+	) -> PaperClientResult<PaperValue>
+	where
+		V: TryInto<PaperValue>,
+		F: Future<Output = V>,
+	{
+		let key = key.as_paper_key();
+
+		match self.get(key).await {
+			Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => {},
+			result => return result,
+		}
+
+		let lock = singleflight::lock_key(key);
+		let is_locked = self.try_lock(&lock).await?;
+
+		if !is_locked {
+			match self.wait_for(key, LOCK_TTL, LOCK_POLL_INTERVAL).await {
+				// the lock holder failed to set the value in time
+				Err(PaperClientError::WaitTimeout) => {},
+				result => return result,
+			}
+		}
+
+		let value: PaperValue = f()
+			.await
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidValue)?;
+
+		let result = self.set(key, value.clone(), ttl).await;
+
+		if is_locked {
+			let _ = self.del(&lock).await;
+		}
+
+		result.map(|_| value)
 	}
 
-	/// Resizes the cache to the supplied size.
+	/// Gets the value of the supplied key from the cache and reads it into
+	/// the supplied buffer, replacing its contents. Reusing a buffer across
+	/// calls avoids allocating for each value. Returns the size of the value.
 	///
 	/// # Examples
 	/// ```ignore
 	/// use paper_client::AsyncPaperClient;
 	///
 	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	/// let mut buf = Vec::new();
 	///
-	/// match client.resize(10).await {
-	///     Ok(_) => println!("done"),
+	/// match client.get_into("key", &mut buf).await {
+	///     Ok(size) => println!("{:?}", &buf[..size]),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub async fn resize(&mut self, size: u64) -> PaperClientResult<()> {
-		let command = Command::Resize(size);
-		self.process(&command).await
+	pub async fn get_into(
+		&mut self,
+		key: impl AsPaperKey,
+		buf: &mut Vec<u8>,
+	) -> PaperClientResult<usize> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Get(&key);
+
+		let size = self
+			.process(&command, IntoVec(buf, self.config.max_response_size))
+			.await?;
+
+		self.config.decode_vec(buf, size)
 	}
 
-	/// Sets the cache's eviction policy.
+	/// Gets the value of the supplied key from the cache and reads it into
+	/// the start of the supplied slice. Returns the size of the value. If the
+	/// value does not fit in the slice, it is discarded and `ResponseTooLarge`
+	/// is returned.
 	///
 	/// # Examples
 	/// ```ignore
-	/// use paper_client::{AsyncPaperClient, PaperPolicy};
+	/// use paper_client::AsyncPaperClient;
 	///
 	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	/// let mut buf = [0u8; 1024];
 	///
-	/// match client.policy(PaperPolicy::Lru).await {
-	///     Ok(_) => println!("done"),
+	/// match client.get_into_slice("key", &mut buf).await {
+	///     Ok(size) => println!("{:?}", &buf[..size]),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub async fn policy(&mut self, policy: PaperPolicy) -> PaperClientResult<()> {
-		let command = Command::Policy(policy);
-		self.process(&command).await
+	pub async fn get_into_slice(
+		&mut self,
+		key: impl AsPaperKey,
+		buf: &mut [u8],
+	) -> PaperClientResult<usize> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Get(&key);
+		let size = self.process(&command, IntoSlice(buf)).await?;
+
+		self.config.decode_slice(buf, size)
 	}
 
-	/// Gets the cache's status.
+	/// Gets the value of the supplied key from the cache and writes it to
+	/// the supplied writer in chunks, without buffering the whole value in
+	/// memory. Returns the size of the value. Unlike `get`, the value is not
+	/// limited by the configured maximum response size. If the writer fails,
+	/// the rest of the value is still read and `InvalidWriter` is returned.
 	///
 	/// # Examples
 	/// ```ignore
 	/// use paper_client::AsyncPaperClient;
 	///
 	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	/// let mut value = Vec::new();
 	///
-	/// match client.status().await {
-	///     Ok(status) => println!("{status:?}"),
+	/// match client.get_to_writer("key", &mut value).await {
+	///     Ok(size) => println!("{size}"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub async fn status(&mut self) -> PaperClientResult<Status> {
-		self.process_status(&Command::Status).await
-	}
+	pub async fn get_to_writer<W>(
+		&mut self,
+		key: impl AsPaperKey,
+		writer: &mut W,
+	) -> PaperClientResult<u64>
+	where
+		W: AsyncWrite + Unpin,
+	{
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Get(&key);
+
+		let Some(codec) = self.config.value_codec.clone() else {
+			return self.process(&command, ToWriter(writer)).await;
+		};
 
-	async fn process(&mut self, command: &Command<'_>) -> PaperClientResult<()> {
-		if let Err(err) = self.send(command).await
-			&& matches!(err, PaperClientError::InvalidResponse)
-		{
-			self.reconnect_attempts += 1;
-			self.reconnect().await?;
-			return Box::pin(self.process(command)).await;
-		}
+		// the whole value is needed to decode it
+		let mut buf = Vec::new();
+		self.process(&command, ToWriter(&mut buf)).await?;
 
-		match self.receive(command).await {
-			Ok(response) => {
-				self.reconnect_attempts = 0;
-				Ok(response)
-			},
+		let value = codec.decode(&buf)?;
 
-			Err(PaperClientError::InvalidResponse) => {
-				self.reconnect_attempts += 1;
-				self.reconnect().await?;
-				Box::pin(self.process(command)).await
-			},
+		writer
+			.write_all(&value)
+			.await
+			.map_err(|_| PaperClientError::InvalidWriter)?;
 
-			err => err,
-		}
+		Ok(value.len() as u64)
 	}
 
-	async fn process_value(&mut self, command: &Command<'_>) -> PaperClientResult<PaperValue> {
-		if let Err(err) = self.send(command).await
-			&& matches!(err, PaperClientError::InvalidResponse)
-		{
-			self.reconnect_attempts += 1;
-			self.reconnect().await?;
-			return Box::pin(self.process_value(command)).await;
-		}
-
-		match self.receive_value(command).await {
-			Ok(response) => {
-				self.reconnect_attempts = 0;
-				Ok(response)
-			},
+	/// Gets the bytes of the value of the supplied key from the supplied
+	/// offset, up to the supplied length (e.g., to read the header of a
+	/// large cached artifact). If the value is shorter than the range, the
+	/// bytes up to its end are returned.
+	///
+	/// The server cannot send part of a value, so the whole value is still
+	/// transferred, but only the range is kept in memory. Unlike `get`, the
+	/// value is not limited by the configured maximum response size.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.get_range("key", 0, 16).await {
+	///     Ok(header) => println!("{header:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn get_range(
+		&mut self,
+		key: impl AsPaperKey,
+		offset: u64,
+		len: usize,
+	) -> PaperClientResult<PaperValue> {
+		let mut writer = RangeWriter::new(offset, len);
+		self.get_to_writer(key, &mut writer).await?;
 
-			Err(PaperClientError::InvalidResponse) => {
-				self.reconnect_attempts += 1;
-				self.reconnect().await?;
-				Box::pin(self.process_value(command)).await
-			},
+		Ok(PaperValue::from(writer.into_inner()))
+	}
 
-			err => err,
+	/// Sets the supplied key, value, and ttl to the cache. The ttl is
+	/// either a number of seconds (`None` for no expiry), a `Duration`,
+	/// which is rounded up to the nearest second, or a `Ttl`. A ttl which
+	/// would expire the key immediately (e.g., `Some(0)`) is rejected with
+	/// `InvalidTtl`.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.set("key", "value", None).await {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn set(
+		&mut self,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let value: PaperValue = value
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidValue)?;
+
+		let value = self.config.encode_value(value)?;
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Set(&key, value, ttl.as_paper_ttl()?);
+
+		self.process(&command, Ack).await
+	}
+
+	/// Sets the supplied key to the supplied number. See
+	/// `PaperClient::set_num`.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.set_num("hits", 42u64, None).await {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn set_num(
+		&mut self,
+		key: impl AsPaperKey,
+		value: impl NumericValue,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		self.set(key, value.to_paper_value()?, ttl).await
+	}
+
+	/// Gets the number stored at the supplied key. See
+	/// `PaperClient::get_num`.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.get_num::<u64>("hits").await {
+	///     Ok(hits) => println!("{hits}"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn get_num<N: NumericValue>(&mut self, key: impl AsPaperKey) -> PaperClientResult<N> {
+		N::from_paper_value(&self.get(key).await?)
+	}
+
+	/// Deletes the value of the supplied key from the cache.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.del("key").await {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn del(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Del(&key);
+		self.process(&command, Ack).await
+	}
+
+	/// Deletes each of the supplied keys which starts with the supplied
+	/// prefix, and returns the number of keys which were deleted. See
+	/// `PaperClient::del_prefix`. The async client does not batch
+	/// commands, so the keys are deleted one at a time.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.del_prefix("user:", ["user:1", "user:2", "item:1"]).await {
+	///     Ok(deleted) => println!("{deleted} deleted"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn del_prefix<K: AsPaperKey>(
+		&mut self,
+		prefix: &str,
+		keys: impl IntoIterator<Item = K>,
+	) -> PaperClientResult<u64> {
+		let mut count = 0;
+
+		for key in keys {
+			let key = key.as_paper_key();
+
+			if !key.starts_with(prefix) {
+				continue;
+			}
+
+			match self.del(key).await {
+				Ok(_) => count += 1,
+				Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => {},
+				Err(err) => return Err(err),
+			}
+		}
+
+		Ok(count)
+	}
+
+	/// Returns the number of the supplied keys which `del_prefix` would
+	/// delete, without deleting them. See
+	/// `PaperClient::del_prefix_dry_run`.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.del_prefix_dry_run("user:", ["user:1", "user:2", "item:1"]).await {
+	///     Ok(count) => println!("{count} would be deleted"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn del_prefix_dry_run<K: AsPaperKey>(
+		&mut self,
+		prefix: &str,
+		keys: impl IntoIterator<Item = K>,
+	) -> PaperClientResult<u64> {
+		let mut count = 0;
+
+		for key in keys {
+			let key = key.as_paper_key();
+
+			if key.starts_with(prefix) && self.has(key).await? {
+				count += 1;
+			}
+		}
+
+		Ok(count)
+	}
+
+	/// Checks if the cache contains an object with the supplied key
+	/// without altering the eviction order of the objects.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.has("key").await {
+	///     Ok(has) => println!("{has}"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn has(&mut self, key: impl AsPaperKey) -> PaperClientResult<bool> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Has(&key);
+		self.process(&command, Flag).await
+	}
+
+	/// Gets (peeks) the value of the supplied key from the cache without
+	/// altering the eviction order of the objects.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.peek("key").await {
+	///     Ok(value) => println!("{value:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn peek(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Peek(&key);
+		let value = self.process(&command, Value(self.config.max_response_size)).await?;
+
+		self.config.decode_value(value)
+	}
+
+	/// Sets the TTL associated with the supplied key. The ttl is either a
+	/// number of seconds (`None` for no expiry), a `Duration`, which is
+	/// rounded up to the nearest second, or a `Ttl`. A ttl which would
+	/// expire the key immediately is rejected with `InvalidTtl`.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.ttl("key", Some(5)).await {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn ttl(&mut self, key: impl AsPaperKey, ttl: impl AsPaperTtl) -> PaperClientResult<()> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Ttl(&key, ttl.as_paper_ttl()?);
+		self.process(&command, Ack).await
+	}
+
+	/// Gets the size of the value of the supplied key from the cache in bytes.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.size("key").await {
+	///     Ok(size) => println!("{size}"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn size(&mut self, key: impl AsPaperKey) -> PaperClientResult<u32> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Size(&key);
+		self.process(&command, Size).await
+	}
+
+	/// Wipes the contents of the cache.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.wipe().await {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn wipe(&mut self) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Wipe, None, false).await
+	}
+
+	/// Wipes the contents of the cache in the same way as `wipe`, recording
+	/// the supplied reason in the `AuditEvent` sent to the observer.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.wipe_with_reason("rebuilding the catalog").await {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn wipe_with_reason(&mut self, reason: &str) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Wipe, Some(reason), false).await
+	}
+
+	/// Resizes the cache to the supplied size.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.resize(10).await {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn resize(&mut self, size: u64) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Resize(size), None, false).await
+	}
+
+	/// Resizes the cache in the same way as `resize`, recording the supplied
+	/// reason in the `AuditEvent` sent to the observer.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.resize_with_reason(10, "scaling down for maintenance").await {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn resize_with_reason(&mut self, size: u64, reason: &str) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Resize(size), Some(reason), false).await
+	}
+
+	/// Resizes the cache in the same way as `resize_with_reason`, bypassing
+	/// the client's `Guardrails`. The `AuditEvent` sent to the observer is
+	/// marked as forced.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.force_resize(10, "emergency memory reclaim").await {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn force_resize(&mut self, size: u64, reason: &str) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Resize(size), Some(reason), true).await
+	}
+
+	/// Sets the cache's eviction policy.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::{AsyncPaperClient, PaperPolicy};
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.policy(PaperPolicy::Lru).await {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn policy(&mut self, policy: PaperPolicy) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Policy(policy), None, false).await
+	}
+
+	/// Sets the cache's eviction policy in the same way as `policy`,
+	/// recording the supplied reason in the `AuditEvent` sent to the
+	/// observer.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::{AsyncPaperClient, PaperPolicy};
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.policy_with_reason(PaperPolicy::Lru, "lfu thrashing").await {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn policy_with_reason(
+		&mut self,
+		policy: PaperPolicy,
+		reason: &str,
+	) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Policy(policy), Some(reason), false).await
+	}
+
+	/// Sets the cache's eviction policy in the same way as
+	/// `policy_with_reason`, bypassing the client's `Guardrails`. The
+	/// `AuditEvent` sent to the observer is marked as forced.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::{AsyncPaperClient, PaperPolicy};
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.force_policy(PaperPolicy::Lru, "rolling back lfu").await {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn force_policy(
+		&mut self,
+		policy: PaperPolicy,
+		reason: &str,
+	) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Policy(policy), Some(reason), true).await
+	}
+
+	/// Gets the cache's status.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.status().await {
+	///     Ok(status) => println!("{status:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn status(&mut self) -> PaperClientResult<Status> {
+		self.process(&Command::Status, StatusFrame).await
+	}
+
+	/// Returns a token for the most recent mutation issued by the client,
+	/// which can be waited on (e.g., by another task before it reads from
+	/// another pooled connection) until the server has acknowledged it. See
+	/// [`SequenceToken`].
+	///
+	/// # Examples
+	/// ```ignore
+	/// use std::time::Duration;
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	/// let _ = client.set("key", "value", None).await;
+	///
+	/// let token = client.sequence();
+	/// assert!(token.wait_async(Duration::from_secs(1)).await.is_ok());
+	/// ```
+	#[must_use]
+	pub fn sequence(&self) -> SequenceToken {
+		self.sequencer.token()
+	}
+
+	/// Returns a view of the client for the supplied tenant. See
+	/// `PaperClient::tenant`.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	/// let mut tenant = client.tenant("team-a");
+	///
+	/// match tenant.set("key", "value", None).await {
+	///     Ok(_) => println!("{} bytes written", tenant.usage().bytes_written()),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn tenant(&mut self, tenant: &str) -> AsyncTenant<'_, T> {
+		let recorder = self.config.tenants.recorder(tenant);
+		AsyncTenant::new(self, tenant, recorder)
+	}
+
+	/// Returns a snapshot of the usage of every tenant. See
+	/// `PaperClient::tenant_usage`.
+	#[must_use]
+	pub fn tenant_usage(&self) -> Vec<TenantUsage> {
+		self.config.tenants.usage()
+	}
+
+	/// Executes the supplied command and decodes its response with the
+	/// supplied `DecodePaperResponseAsync`. See `PaperClient::execute`.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::{AsyncPaperClient, Command};
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// match client.execute(Command::Size("key"), Kibibytes).await {
+	///     Ok(size) => println!("{size} KiB"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn execute<D: DecodePaperResponseAsync>(
+		&mut self,
+		command: Command<'_>,
+		decoder: D,
+	) -> PaperClientResult<D::Output> {
+		self.process(&command, Custom(decoder)).await
+	}
+
+	/// Sends every command added to the batch by the supplied closure in a
+	/// single write once the closure returns, and then reads all of their
+	/// responses. See `PaperClient::batch`.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// let responses = client.batch(|batch| {
+	///     batch.get("a");
+	///     batch.set("b", "value", None);
+	/// }).await;
+	///
+	/// match responses {
+	///     Ok(responses) => println!("{responses:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn batch(
+		&mut self,
+		f: impl FnOnce(&mut Batch),
+	) -> PaperClientResult<Vec<PaperClientResult<BatchResponse>>> {
+		let mut batch = Batch::default();
+		f(&mut batch);
+
+		batch.hash_keys(&self.config);
+
+		if let Some(codec) = &self.config.value_codec {
+			batch.encode_values(codec.as_ref());
 		}
+
+		let responses = self.process_batch(&batch).await?;
+
+		let responses = responses
+			.into_iter()
+			.map(|response| match response {
+				Ok(BatchResponse::Value(value)) => self
+					.config
+					.decode_value(value)
+					.map(BatchResponse::Value),
+
+				response => response,
+			})
+			.collect();
+
+		Ok(responses)
 	}
 
-	async fn process_has(&mut self, command: &Command<'_>) -> PaperClientResult<bool> {
-		if let Err(err) = self.send(command).await
-			&& matches!(err, PaperClientError::InvalidResponse)
-		{
-			self.reconnect_attempts += 1;
-			self.reconnect().await?;
-			return Box::pin(self.process_has(command)).await;
+	/// Returns the details of the client's current connection (e.g., the
+	/// address of the server it landed on, which may differ between
+	/// reconnects if the server's hostname resolves to several addresses).
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// if let Some(tls) = client.connection_info().tls() {
+	///     println!("{} with {}", tls.protocol(), tls.cipher());
+	/// }
+	/// ```
+	#[must_use]
+	pub fn connection_info(&self) -> ConnectionInfo {
+		self.stream.get_ref().connection_info()
+	}
+
+	/// Returns the address of the server the client is currently connected
+	/// to, if its transport reports it. See `connection_info`.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	///
+	/// if let Some(addr) = client.peer_addr() {
+	///     println!("connected to {addr}");
+	/// }
+	/// ```
+	#[must_use]
+	pub fn peer_addr(&self) -> Option<PeerAddr> {
+		self.connection_info().peer_addr().cloned()
+	}
+
+	/// Returns the number of times the most recent command reconnected to
+	/// the server in order to be retried. This is reset to 0 whenever a
+	/// command succeeds.
+	#[must_use]
+	pub fn reconnect_attempts(&self) -> u8 {
+		self.reconnect_attempts
+	}
+
+	/// Returns a snapshot of the commands the client has sent.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	/// let _ = client.ping().await;
+	///
+	/// let stats = client.client_stats();
+	/// println!("{} commands, {} errors", stats.commands(), stats.errors());
+	/// ```
+	#[must_use]
+	pub fn client_stats(&self) -> ClientStats {
+		self.stats.snapshot(&self.addr)
+	}
+
+	/// Sets the maximum time each of the client's commands may take,
+	/// overriding the configured `command_timeout`. If `None`, commands are
+	/// only bounded by the deadline, if any.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use std::time::Duration;
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	/// client.set_command_timeout(Some(Duration::from_millis(50)));
+	/// ```
+	pub fn set_command_timeout(&mut self, timeout: Option<Duration>) {
+		self.command_timeout = timeout;
+	}
+
+	/// Returns the maximum time each of the client's commands may take.
+	#[must_use]
+	pub fn command_timeout(&self) -> Option<Duration> {
+		self.command_timeout
+	}
+
+	pub(crate) fn reset_command_timeout(&mut self) {
+		self.command_timeout = self.config.command_timeout;
+	}
+
+	async fn process<D: DecodeResponseAsync>(
+		&mut self,
+		command: &Command<'_>,
+		decoder: D,
+	) -> PaperClientResult<D::Output> {
+		let is_mutation = command.kind().is_mutation();
+
+		if is_mutation {
+			self.sequencer.issue();
 		}
 
-		match self.receive_has(command).await {
-			Ok(response) => {
-				self.reconnect_attempts = 0;
-				Ok(response)
+		let result = match self.command_timeout {
+			Some(timeout) => {
+				deadline::with_deadline_async(Instant::now() + timeout, self.attempt(command, decoder)).await
 			},
 
-			Err(PaperClientError::InvalidResponse) => {
-				self.reconnect_attempts += 1;
-				self.reconnect().await?;
-				Box::pin(self.process_has(command)).await
-			},
+			None => self.attempt(command, decoder).await,
+		};
 
-			err => err,
+		if is_mutation && sequence::is_response(&result) {
+			self.sequencer.acknowledge();
 		}
+
+		result
 	}
 
-	async fn process_size(&mut self, command: &Command<'_>) -> PaperClientResult<u32> {
-		if let Err(err) = self.send(command).await
-			&& matches!(err, PaperClientError::InvalidResponse)
-		{
-			self.reconnect_attempts += 1;
-			self.reconnect().await?;
-			return Box::pin(self.process_size(command)).await;
+	async fn attempt<D: DecodeResponseAsync>(
+		&mut self,
+		command: &Command<'_>,
+		mut decoder: D,
+	) -> PaperClientResult<D::Output> {
+		let start = Instant::now();
+		let mut is_partially_read = false;
+
+		let result = match self.send(command).await {
+			Ok(_) => {
+				let mut stream = self.capture_stream();
+
+				within_deadline(decoder.decode_async(command, &mut stream))
+					.await
+					.inspect_err(|err| is_partially_read = D::is_partially_read(err))
+			},
+			Err(err) => Err(err),
+		};
+
+		self.record(&result, start);
+
+		if is_partially_read {
+			self.is_stale = true;
 		}
 
-		match self.receive_size(command).await {
+		match result {
 			Ok(response) => {
 				self.reconnect_attempts = 0;
 				Ok(response)
 			},
 
-			Err(PaperClientError::InvalidResponse) => {
-				self.reconnect_attempts += 1;
-				self.reconnect().await?;
-				Box::pin(self.process_size(command)).await
+			Err(err) if D::IS_RETRYABLE && self.config.is_retryable(&err) => {
+				self.retry(err).await?;
+				Box::pin(self.attempt(command, decoder)).await
 			},
 
 			err => err,
 		}
 	}
 
-	async fn process_status(&mut self, command: &Command<'_>) -> PaperClientResult<Status> {
-		if let Err(err) = self.send(command).await
-			&& matches!(err, PaperClientError::InvalidResponse)
-		{
-			self.reconnect_attempts += 1;
-			self.reconnect().await?;
-			return Box::pin(self.process_status(command)).await;
+	async fn process_batch(
+		&mut self,
+		batch: &Batch,
+	) -> PaperClientResult<Vec<PaperClientResult<BatchResponse>>> {
+		let commands = batch.commands();
+
+		if commands.is_empty() {
+			return batch.receive_async(&commands, &mut tokio::io::empty(), None).await;
 		}
 
-		match self.receive_status(command).await {
-			Ok(response) => {
+		let start = Instant::now();
+
+		let max_response_size = self.config.max_response_size;
+
+		let mutations = commands
+			.iter()
+			.filter(|command| command.kind().is_mutation())
+			.count();
+
+		for _ in 0..mutations {
+			self.sequencer.issue();
+		}
+
+		let command_timeout = self.command_timeout;
+
+		let exchange = async {
+			match self.send_all(&commands).await {
+				Ok(_) => {
+					let mut stream = self.capture_stream();
+					within_deadline(batch.receive_async(&commands, &mut stream, max_response_size)).await
+				},
+				Err(err) => Err(err),
+			}
+		};
+
+		let result = match command_timeout {
+			Some(timeout) => deadline::with_deadline_async(Instant::now() + timeout, exchange).await,
+			None => exchange.await,
+		};
+
+		self.record(&result, start);
+
+		if result.is_ok() {
+			self.reconnect_attempts = 0;
+
+			// every response of the batch has been received
+			if mutations > 0 {
+				self.sequencer.acknowledge();
+			}
+		}
+
+		result
+	}
+
+	pub(crate) fn stats(&self) -> Arc<ConnectionStats> {
+		self.stats.clone()
+	}
+
+	pub(crate) fn set_auth_provider(&mut self, provider: Arc<dyn AuthTokenProvider>) {
+		self.auth_provider = Some(provider);
+	}
+
+	pub(crate) fn set_health(&mut self, health: Arc<NodeHealth>) {
+		self.health = Some(health);
+	}
+
+	pub(crate) async fn close(&mut self) {
+		// the client is never reconnected once closed
+		self.is_closed = true;
+
+		// shutting down an inherited connection would also close it for
+		// the parent process
+		if self.pid == process::id() {
+			let _ = self.stream.shutdown().await;
+		}
+	}
+
+	// re-establishes the connection of a client whose most recent command
+	// exhausted its reconnects, or of a strict client whose connection
+	// failed (e.g., before it is handed out by a pool), returning whether
+	// the connection was replaced
+	pub(crate) async fn replace_if_exhausted(&mut self) -> bool {
+		let is_exhausted = self.reconnect_attempts >= RECONNECT_MAX_ATTEMPTS
+			|| (self.config.strict && self.is_stale);
+
+		if self.is_closed || !is_exhausted {
+			return false;
+		}
+
+		match self.reconnect().await {
+			Ok(_) => {
 				self.reconnect_attempts = 0;
-				Ok(response)
+				true
 			},
 
-			Err(PaperClientError::InvalidResponse) => {
-				self.reconnect_attempts += 1;
-				self.reconnect().await?;
-				Box::pin(self.process_status(command)).await
-			},
+			Err(_) => false,
+		}
+	}
 
-			err => err,
+	pub(crate) async fn probe(&mut self) -> PaperClientResult<()> {
+		self.reconnect_attempts = 0;
+
+		if self.ping().await.is_ok() {
+			return Ok(());
 		}
+
+		self.reconnect().await?;
+		self.ping().await.map(|_| ())
 	}
 
-	async fn send(&mut self, command: &Command<'_>) -> PaperClientResult<()> {
-		command
-			.write_async(&mut self.stream)
-			.await
-			.map_err(|err| match err {
-				StreamError::InvalidStream => PaperClientError::Disconnected,
-				_ => PaperClientError::InvalidCommand,
-			})
+	async fn audit(
+		&mut self,
+		command: AuditCommand,
+		reason: Option<&str>,
+		is_forced: bool,
+	) -> PaperClientResult<()> {
+		if !is_forced {
+			self.check_guardrails(&command).await?;
+		}
+
+		let result = self.process(&command.to_command(), Ack).await;
+
+		if result.is_ok()
+			&& matches!(command, AuditCommand::Policy(_))
+			&& let Some(guardrails) = &self.config.guardrails
+		{
+			guardrails.record_policy(self.config.clock.now());
+		}
+
+		if let Some(observer) = &self.config.observer {
+			let event = AuditEvent::new(&self.addr, command, reason, result.is_ok(), is_forced);
+			observer.on_event(&PaperEvent::Audit(event));
+		}
+
+		result
 	}
 
-	async fn receive(&mut self, command: &Command<'_>) -> PaperClientResult<()> {
-		command.parse_reader_async(&mut self.stream).await
+	async fn check_guardrails(&mut self, command: &AuditCommand) -> PaperClientResult<()> {
+		let Some(guardrails) = self.config.guardrails.clone() else {
+			return Ok(());
+		};
+
+		match command {
+			AuditCommand::Resize(size) if guardrails.limits_resize() => {
+				let status = self.status().await?;
+				guardrails.check_resize(status.max_size(), *size)
+			},
+
+			AuditCommand::Policy(_) => guardrails.check_policy(self.config.clock.now()),
+
+			_ => Ok(()),
+		}
 	}
 
-	async fn receive_value(&mut self, command: &Command<'_>) -> PaperClientResult<PaperValue> {
-		command
-			.parse_buf_reader_async(&mut self.stream)
-			.await
+	// acquires the supplied lock key unless it is already held, returning
+	// whether it was acquired (i.e., whether this client's token survived
+	// any concurrent acquisitions)
+	async fn try_lock(&mut self, lock: &str) -> PaperClientResult<bool> {
+		if self.has(lock).await? {
+			return Ok(false);
+		}
+
+		let token = singleflight::lock_token();
+		self.set(lock, token.as_str(), LOCK_TTL).await?;
+
+		match self.get(lock).await {
+			Ok(value) => Ok(value == token.as_str()),
+			Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => Ok(false),
+			Err(err) => Err(err),
+		}
 	}
 
-	async fn receive_has(&mut self, command: &Command<'_>) -> PaperClientResult<bool> {
-		command
-			.parse_has_reader_async(&mut self.stream)
-			.await
+	fn record<R>(&mut self, result: &PaperClientResult<R>, start: Instant) {
+		let latency = start.elapsed();
+		self.last_used = self.config.clock.now();
+
+		if !result.as_ref().is_err_and(is_stream_error) {
+			self.is_stale = false;
+		}
+
+		// only a command's first attempt contributes to the retry budget
+		if self.reconnect_attempts == 0
+			&& let Some(budget) = &self.config.retry_budget
+		{
+			budget.deposit();
+		}
+
+		let is_error = result
+			.as_ref()
+			.is_err_and(|err| !matches!(err, PaperClientError::CacheError(_)));
+
+		self.stats.record_command(is_error);
+
+		if result.is_ok() {
+			self.stats.record_latency(latency);
+		}
+
+		if let Some(health) = &self.health {
+			health.record(result, latency);
+		}
 	}
 
-	async fn receive_size(&mut self, command: &Command<'_>) -> PaperClientResult<u32> {
-		command
-			.parse_size_reader_async(&mut self.stream)
-			.await
+	async fn send(&mut self, command: &Command<'_>) -> PaperClientResult<()> {
+		self.send_all(slice::from_ref(command)).await
 	}
 
-	async fn receive_status(&mut self, command: &Command<'_>) -> PaperClientResult<Status> {
-		command
-			.parse_status_reader_async(&mut self.stream)
-			.await
+	async fn send_all(&mut self, commands: &[Command<'_>]) -> PaperClientResult<()> {
+		if deadline::is_expired() {
+			return Err(PaperClientError::DeadlineExceeded);
+		}
+
+		// a connection inherited across a fork is shared with the parent
+		// process, so a new one is opened rather than interleaving writes
+		if self.pid != process::id() {
+			self.is_stale = true;
+		}
+
+		if self.is_stale {
+			// a strict client is never reconnected on behalf of a command
+			if self.config.strict {
+				return Err(PaperClientError::Disconnected);
+			}
+
+			self.reconnect().await?;
+		}
+
+		// checked once the connection is known to be in sync, as a denied
+		// command is not a stream error
+		if !commands
+			.iter()
+			.all(|command| self.config.command_policy.is_allowed(command.kind()))
+		{
+			return Err(PaperClientError::CommandDenied);
+		}
+
+		for command in commands {
+			command.validate()?;
+		}
+
+		self.is_stale = true;
+
+		let pending_auth = self.pending_auth.take();
+		let auth = pending_auth.as_deref().map(Command::Auth);
+
+		let key_display = self.config.key_display;
+		let is_redacted = self.config.capture.is_some()
+			&& (key_display != KeyDisplay::Full
+				|| auth.is_some()
+				|| commands.iter().any(|command| matches!(command, Command::Auth(_))));
+
+		let mut stream = self.capture_stream();
+
+		// the commands are captured with their keys (and any auth token)
+		// redacted rather than as they are written
+		if is_redacted {
+			for command in auth.iter().chain(commands) {
+				stream.record_sent(&command.to_display_bytes(key_display));
+			}
+
+			stream = stream.without_sent();
+		}
+
+		let result = match (&auth, commands) {
+			(None, [command]) => command.write_async(&mut stream).await,
+
+			// the commands are written together so that a batch is sent in
+			// as few packets as possible, along with the pending auth, if any
+			(auth, commands) => {
+				let buf = auth
+					.iter()
+					.chain(commands)
+					.flat_map(Command::to_bytes)
+					.collect::<Vec<_>>();
+
+				stream::write_buf_async(&mut stream, &buf).await
+			},
+		};
+
+		result.map_err(|err| match err {
+			StreamError::InvalidStream => PaperClientError::Disconnected,
+			_ => PaperClientError::InvalidCommand,
+		})?;
+
+		// the auth's response precedes the commands'. If the token was
+		// rejected, the commands fail as unauthorized and their responses
+		// are read as usual
+		if let Some(auth) = &auth
+			&& let Err(err) = within_deadline(auth.parse_reader_async(&mut stream)).await
+			&& is_stream_error(&err)
+		{
+			return Err(err);
+		}
+
+		Ok(())
 	}
 
-	async fn handshake(&mut self) -> PaperClientResult<()> {
-		let mut reader = AsyncStreamReader::new(&mut self.stream);
+	fn capture_stream(&mut self) -> CaptureStream<'_, BufStream<T>> {
+		CaptureStream::new(&mut self.stream, self.config.capture.as_deref(), &self.addr)
+	}
 
-		let is_ok = reader
-			.read_bool()
-			.await
-			.map_err(|_| PaperClientError::UnreachableServer)?;
+	async fn retry(&mut self, err: PaperClientError) -> PaperClientResult<()> {
+		if self.reconnect_attempts >= RECONNECT_MAX_ATTEMPTS {
+			return Err(PaperClientError::ReconnectExhausted {
+				attempts:   self.reconnect_attempts,
+				last_error: Box::new(err),
+			});
+		}
+
+		if deadline::is_expired() {
+			return Err(PaperClientError::DeadlineExceeded);
+		}
+
+		if let Some(budget) = &self.config.retry_budget
+			&& !budget.withdraw()
+		{
+			return Err(PaperClientError::RetryBudgetExhausted);
+		}
+
+		self.reconnect_attempts += 1;
+		self.reconnect().await
+	}
 
-		match is_ok {
-			true => Ok(()),
-			false => Err(PaperClientError::from_reader_async(reader).await),
+	async fn backoff(&self) {
+		let delay = self.config.reconnect_delay(self.reconnect_attempts);
+		let delay = deadline::remaining().map_or(delay, |remaining| delay.min(remaining));
+
+		if !delay.is_zero() {
+			time::sleep(delay).await;
 		}
 	}
 
 	async fn reconnect(&mut self) -> PaperClientResult<()> {
-		if self.reconnect_attempts > RECONNECT_MAX_ATTEMPTS {
+		if self.is_closed {
 			return Err(PaperClientError::Disconnected);
 		}
 
-		self.stream = init_stream(&self.addr).await?;
-		self.handshake().await?;
+		self.backoff().await;
+		self.stats.reconnect();
 
-		if let Some(token) = self.auth_token.clone() {
-			Box::pin(self.auth(token)).await?;
-		}
+		self.stream = init_stream(&self.addr, &self.config).await?;
+
+		self.is_stale = false;
+		self.pid = process::id();
+
+		// the connection is authorized along with the next command, so that
+		// traffic resumes without waiting a round trip for the auth alone
+		self.pending_auth = match &self.auth_provider {
+			Some(provider) => Some(provider.token()?),
+			None => None,
+		};
 
 		Ok(())
 	}
 }
 
-async fn init_stream(addr: &str) -> PaperClientResult<BufStream<TcpStream>> {
-	let stream = TcpStream::connect(addr)
+impl<T> Debug for AsyncPaperClient<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("AsyncPaperClient")
+			.field("addr", &format_args!("{}", DisplayAddr(&self.addr)))
+			.field("config", &self.config)
+			.field("auth_token", &self.auth_provider.as_ref().map(|_| "<redacted>"))
+			.field("reconnect_attempts", &self.reconnect_attempts)
+			.finish_non_exhaustive()
+	}
+}
+
+impl<T> Display for AsyncPaperClient<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.auth_provider.is_some() {
+			true => write!(f, "AsyncPaperClient({}, authed)", DisplayAddr(&self.addr)),
+			false => write!(f, "AsyncPaperClient({})", DisplayAddr(&self.addr)),
+		}
+	}
+}
+
+async fn init_stream<T: AsyncPaperTransport>(
+	addr: &str,
+	config: &PaperClientConfig,
+) -> PaperClientResult<BufStream<T>> {
+	let mut attempt = 0;
+
+	loop {
+		match connect(addr, config.handshake_timeout).await {
+			Err(PaperClientError::UnreachableServer) if attempt < config.connect_retries => {
+				time::sleep(config.connect_delay(attempt)).await;
+				attempt += 1;
+			},
+
+			result => return result,
+		}
+	}
+}
+
+async fn connect<T: AsyncPaperTransport>(
+	addr: &str,
+	handshake_timeout: Option<Duration>,
+) -> PaperClientResult<BufStream<T>> {
+	let mut stream = BufStream::new(T::open(addr).await?);
+
+	handshake(&mut stream, handshake_timeout).await?;
+
+	Ok(stream)
+}
+
+async fn within_deadline<R>(
+	future: impl Future<Output = PaperClientResult<R>>,
+) -> PaperClientResult<R> {
+	match deadline::current_deadline() {
+		Some(deadline) => time::timeout_at(deadline.into(), future)
+			.await
+			.unwrap_or(Err(PaperClientError::DeadlineExceeded)),
+
+		None => future.await,
+	}
+}
+
+fn is_stream_error(err: &PaperClientError) -> bool {
+	matches!(
+		err,
+		PaperClientError::InvalidCommand
+			| PaperClientError::InvalidResponse
+			| PaperClientError::Disconnected
+			| PaperClientError::DeadlineExceeded
+	)
+}
+
+async fn handshake<T: AsyncPaperTransport>(
+	stream: &mut BufStream<T>,
+	timeout: Option<Duration>,
+) -> PaperClientResult<()> {
+	match timeout {
+		Some(timeout) => time::timeout(timeout, read_handshake(stream))
+			.await
+			.map_err(|_| PaperClientError::UnreachableServer)?,
+
+		None => read_handshake(stream).await,
+	}
+}
+
+async fn read_handshake<T: AsyncPaperTransport>(
+	stream: &mut BufStream<T>,
+) -> PaperClientResult<()> {
+	let mut reader = AsyncStreamReader::new(stream);
+
+	let is_ok = reader
+		.read_bool()
 		.await
 		.map_err(|_| PaperClientError::UnreachableServer)?;
 
-	if stream.set_nodelay(true).is_err() {
-		return Err(PaperClientError::Internal);
+	match is_ok {
+		true => Ok(()),
+		false => Err(PaperClientError::from_reader_async(reader).await),
 	}
+}
 
-	Ok(BufStream::new(stream))
+#[cfg(all(test, unix))]
+mod tests {
+	use std::{io::Write, net::TcpStream, os::fd::AsFd};
+
+	use paper_utils::stream::StreamReader;
+
+	use super::AsyncPaperClient;
+	use crate::command::Command;
+
+	#[tokio::test]
+	async fn reconnect_after_fork() {
+		let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+
+		// the parent process's copy of the inherited connection
+		let fd = client.stream.get_ref().as_fd().try_clone_to_owned().unwrap();
+		let mut parent = TcpStream::from(fd);
+
+		client.pid = client.pid.wrapping_add(1);
+
+		assert!(client.ping().await.is_ok());
+		assert_eq!(client.client_stats().reconnects(), 1);
+
+		// the inherited connection was not shut down
+		parent.set_nonblocking(false).unwrap();
+		parent.write_all(&Command::Ping.to_bytes()).unwrap();
+		assert!(StreamReader::new(&mut parent).read_bool().unwrap());
+	}
 }
@@ -5,19 +5,143 @@
  * LICENSE file in the root directory of this source tree.
  */
 
-use std::sync::{
-	Arc,
-	atomic::{AtomicUsize, Ordering},
+use std::{
+	collections::HashMap,
+	fmt::{self, Display},
+	future::Future,
+	ops::{Deref, DerefMut},
+	path::Path,
+	pin::Pin,
+	sync::{
+		Arc,
+		RwLock,
+		atomic::{AtomicBool, AtomicUsize, Ordering},
+	},
+	task::{Context, Poll},
+	time::{Duration, Instant},
 };
 
-use tokio::sync::{Mutex, MutexGuard};
+use futures_core::Stream;
+use tokio::{
+	sync::{Mutex, MutexGuard, Semaphore, mpsc},
+	task::{AbortHandle, JoinSet},
+	time,
+};
+
+use crate::{
+	addr::FromPaperAddr,
+	arg::{AsPaperKey, AsPaperTtl},
+	async_client::AsyncPaperClient,
+	auth::{AuthTokenProvider, StaticTokenProvider},
+	background::{BackgroundTask, TaskRegistry},
+	config::PaperClientConfig,
+	error::{PaperClientError, PaperClientResult},
+	health::{self, Availability, EjectionPolicy, NodeHealth, NodeRole, NodeStatus},
+	metrics::{ConnectionStats, PoolMetrics, PoolMetricsRecorder, PoolStats},
+	monitor::{MonitorState, StatusThresholds},
+	pool::CheckoutOptions,
+	replica::ReadPreference,
+	selection::{ClientSelection, WeightedSelector},
+	settings::PaperSettings,
+	sharder::{ModuloSharder, Sharder},
+	tenant::TenantUsage,
+	ttl::Ttl,
+	value::PaperValue,
+};
+
+// the default maximum number of writes issued by set_nowait which may be
+// waiting for a response
+const DEFAULT_NOWAIT_CAPACITY: usize = 1024;
 
-use crate::{addr::FromPaperAddr, async_client::AsyncPaperClient, error::PaperClientError};
+// the shortest interval at which ejected nodes are probed in the
+// background, so that a zero ejection duration does not spin
+const MIN_PROBE_INTERVAL: Duration = Duration::from_millis(10);
+
+tokio::task_local! {
+	// the worker which the current task was pinned to by pin_to_worker
+	static WORKER: usize;
+}
 
 #[derive(Debug, Clone)]
 pub struct AsyncPaperPool {
-	clients: Arc<Box<[Arc<Mutex<AsyncPaperClient>>]>>,
-	index:   Arc<AtomicUsize>,
+	nodes: Arc<Box<[PoolNode]>>,
+	addrs: Arc<Box<[String]>>,
+	index: Arc<AtomicUsize>,
+
+	replicas:      Arc<Box<[PoolNode]>>,
+	replica_index: Arc<AtomicUsize>,
+
+	sharder:         Arc<dyn Sharder>,
+	ejection:        Option<EjectionPolicy>,
+	read_preference: ReadPreference,
+	selection:       ClientSelection,
+	is_pinned:       bool,
+
+	// the task which probes ejected nodes, replaced whenever the ejection
+	// policy is set
+	failback: Option<AbortHandle>,
+
+	config:        PaperClientConfig,
+	auth_provider: Arc<RwLock<Option<Arc<dyn AuthTokenProvider>>>>,
+	metrics:       Arc<PoolMetricsRecorder>,
+	tasks:         Arc<TaskRegistry>,
+	nowait:        Arc<Semaphore>,
+	is_drained:    Arc<AtomicBool>,
+
+	nowait_capacity: usize,
+}
+
+/// A stream of the values obtained by [`AsyncPaperPool::stream_get`],
+/// yielding each key with the result of its get.
+#[derive(Debug)]
+pub struct AsyncGetStream {
+	receiver: mpsc::Receiver<(String, PaperClientResult<PaperValue>)>,
+}
+
+/// Configures and connects a `AsyncPaperPool`.
+///
+/// # Examples
+/// ```ignore
+/// use paper_client::{AsyncPaperPool, PaperClientConfig};
+///
+/// let pool = AsyncPaperPool::builder()
+///     .addr("paper://127.0.0.1:3145")
+///     .size(4)
+///     .config(PaperClientConfig::default())
+///     .build()
+///     .await
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct AsyncPaperPoolBuilder {
+	addrs:   Vec<PaperClientResult<String>>,
+	size:    usize,
+	sharder: Arc<dyn Sharder>,
+	config:  PaperClientConfig,
+
+	auth_provider: Option<Arc<dyn AuthTokenProvider>>,
+}
+
+#[derive(Debug)]
+struct PoolNode {
+	addr:    String,
+	clients: Box<[Mutex<AsyncPaperClient>]>,
+	stats:   Box<[Arc<ConnectionStats>]>,
+	index:   AtomicUsize,
+	health:  Arc<NodeHealth>,
+
+	selector: WeightedSelector,
+}
+
+/// A `AsyncPaperClient` checked out of a `AsyncPaperPool`. The client is returned to the
+/// pool when the guard is dropped.
+#[derive(Debug)]
+pub struct AsyncPaperPoolGuard<'a> {
+	client:  MutexGuard<'a, AsyncPaperClient>,
+	metrics: &'a PoolMetricsRecorder,
+
+	// set if the client's options must be restored when it is returned
+	is_overridden: bool,
 }
 
 impl AsyncPaperPool {
@@ -35,24 +159,388 @@ impl AsyncPaperPool {
 		paper_addr: impl FromPaperAddr,
 		size: usize,
 	) -> Result<Self, PaperClientError> {
-		assert!(size > 0);
+		AsyncPaperPool::builder()
+			.addr(paper_addr)
+			.size(size)
+			.build()
+			.await
+	}
 
-		let mut clients = Vec::new();
+	/// Creates a new instance of a sharded pool with `size` clients per
+	/// supplied address. Keys are mapped to addresses using the supplied
+	/// `Sharder`. If a connection could not be established to any of the
+	/// clients, a `PaperClientError` is returned.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::{AsyncPaperPool, RendezvousSharder};
+	///
+	/// let pool = AsyncPaperPool::new_sharded(
+	///     &["paper://127.0.0.1:3145", "paper://127.0.0.1:3145"],
+	///     2,
+	///     RendezvousSharder,
+	/// ).await.unwrap();
+	/// ```
+	pub async fn new_sharded(
+		paper_addrs: &[impl FromPaperAddr],
+		size: usize,
+		sharder: impl Sharder + 'static,
+	) -> Result<Self, PaperClientError> {
+		paper_addrs
+			.iter()
+			.fold(AsyncPaperPool::builder(), |builder, paper_addr| {
+				builder.addr(paper_addr.clone())
+			})
+			.size(size)
+			.sharder(sharder)
+			.build()
+			.await
+	}
 
-		for _ in 0..size {
-			let client = AsyncPaperClient::new(paper_addr.clone()).await?;
-			clients.push(Arc::new(Mutex::new(client)));
-		}
+	/// Creates a new instance of the pool using the settings read from the
+	/// environment (see [`PaperSettings`]), and authorizes the pool's clients if
+	/// an auth token was supplied.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// match AsyncPaperPool::from_env().await {
+	///     Ok(_) => println!("connected"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn from_env() -> Result<Self, PaperClientError> {
+		AsyncPaperPool::from_settings(PaperSettings::from_env()?).await
+	}
 
-		let pool = AsyncPaperPool {
-			clients: Arc::new(clients.into_boxed_slice()),
-			index:   Arc::new(AtomicUsize::default()),
+	/// Creates a new instance of the pool using the settings read from the
+	/// TOML file at the supplied path (see [`PaperSettings`]), and authorizes
+	/// the pool's clients if an auth token was supplied.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// match AsyncPaperPool::from_config("paper.toml").await {
+	///     Ok(_) => println!("connected"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn from_config(path: impl AsRef<Path>) -> Result<Self, PaperClientError> {
+		AsyncPaperPool::from_settings(PaperSettings::from_file(path)?).await
+	}
+
+	/// Creates a new instance of the pool using the supplied settings, and
+	/// authorizes the pool's clients if an auth token was supplied.
+	pub async fn from_settings(settings: PaperSettings) -> Result<Self, PaperClientError> {
+		let builder = AsyncPaperPool::builder()
+			.addr(settings.addr())
+			.size(settings.pool_size())
+			.config(settings.config().clone());
+
+		let builder = match settings.auth_token() {
+			Some(token) => builder.auth(token),
+			None => builder,
 		};
 
-		Ok(pool)
+		builder.build().await
+	}
+
+	/// Returns a builder used to configure and connect a pool.
+	#[must_use]
+	pub fn builder() -> AsyncPaperPoolBuilder {
+		AsyncPaperPoolBuilder::default()
+	}
+
+	/// Connects the pool to the supplied read replicas, using the same
+	/// number of clients per replica as per primary node. Reads obtained
+	/// through [`AsyncPaperPool::read_client`] and
+	/// [`AsyncPaperPool::read_client_for`] are routed according to the
+	/// supplied `ReadPreference`, while all other clients are connected to
+	/// the primary nodes. Each replica is expected to hold a full copy of
+	/// the cache.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::{AsyncPaperPool, ReadPreference};
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4)
+	///     .await
+	///     .unwrap()
+	///     .with_replicas(&["paper://127.0.0.1:3145"], ReadPreference::PreferReplica)
+	///     .await
+	///     .unwrap();
+	/// ```
+	pub async fn with_replicas(
+		mut self,
+		paper_addrs: &[impl FromPaperAddr],
+		preference: ReadPreference,
+	) -> Result<Self, PaperClientError> {
+		let mut replicas = Vec::with_capacity(paper_addrs.len());
+
+		for paper_addr in paper_addrs {
+			let addr = paper_addr.to_addr()?;
+			let replica = PoolNode::connect(addr, self.node_size(), &self.config).await?;
+
+			if let Some(provider) = self.auth_provider() {
+				replica.auth_with(&provider).await?;
+			}
+
+			replicas.push(replica);
+		}
+
+		self.replicas = Arc::new(replicas.into_boxed_slice());
+		self.read_preference = preference;
+
+		Ok(self)
+	}
+
+	/// Sets how the clients of each node are handed out. Defaults to
+	/// `ClientSelection::RoundRobin`.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::{AsyncPaperPool, ClientSelection};
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4)
+	///     .await
+	///     .unwrap()
+	///     .with_client_selection(ClientSelection::LatencyWeighted);
+	/// ```
+	#[must_use]
+	pub fn with_client_selection(mut self, selection: ClientSelection) -> Self {
+		self.selection = selection;
+		self
+	}
+
+	/// Pins each runtime worker to a single client of each node. A task
+	/// run through [`pin_to_worker`] is always handed its worker's client
+	/// (the worker modulo the node size), so tasks on different workers do
+	/// not contend for the same client's mutex. Tasks which are not pinned
+	/// are handed clients as determined by the pool's `ClientSelection`.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::{AsyncPaperPool, pin_to_worker};
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4)
+	///     .await
+	///     .unwrap()
+	///     .with_worker_pinning();
+	///
+	/// for worker in 0..4 {
+	///     let pool = pool.clone();
+	///
+	///     tokio::spawn(pin_to_worker(worker, async move {
+	///         let _ = pool.client().await.ping().await;
+	///     }));
+	/// }
+	/// ```
+	#[must_use]
+	pub fn with_worker_pinning(mut self) -> Self {
+		self.is_pinned = true;
+		self
+	}
+
+	/// Enables ejection of unhealthy nodes from routing. Once a node has
+	/// been ejected, it is probed once per ejection duration by a
+	/// background task (see [`AsyncPaperPool::background_tasks`]), as well
+	/// as when a checkout is routed past it, and restored when it has
+	/// responded to the policy's number of consecutive probes. Each
+	/// ejection and restoration is reported to the observer as a
+	/// `FailoverEvent`. The task covers the nodes and any replicas
+	/// connected before this is called, and stops once the pool has been
+	/// dropped. Setting the policy again replaces the task.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::{AsyncPaperPool, EjectionPolicy};
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4)
+	///     .await
+	///     .unwrap()
+	///     .with_ejection_policy(EjectionPolicy::default());
+	/// ```
+	#[must_use]
+	pub fn with_ejection_policy(mut self, policy: EjectionPolicy) -> Self {
+		self.ejection = Some(policy);
+
+		let nodes = Arc::downgrade(&self.nodes);
+		let replicas = Arc::downgrade(&self.replicas);
+		let config = self.config.clone();
+
+		let interval = policy.ejection_duration().max(MIN_PROBE_INTERVAL);
+
+		if let Some(failback) = self.failback.take() {
+			failback.abort();
+		}
+
+		let failback = self.tasks.spawn("paper-client::failback", async move {
+			loop {
+				time::sleep(interval).await;
+
+				let Some(nodes) = nodes.upgrade() else {
+					break;
+				};
+
+				let replicas = replicas.upgrade();
+
+				let monitored = nodes
+					.iter()
+					.chain(replicas.iter().flat_map(|replicas| replicas.iter()));
+
+				for node in monitored {
+					node.is_available(Some(&policy), &config).await;
+				}
+			}
+		});
+
+		self.failback = Some(failback);
+		self
+	}
+
+	/// Keeps the pool's connections alive by pinging each client which has
+	/// been idle for at least the supplied interval from a background task
+	/// (see [`AsyncPaperPool::background_tasks`]), so that idle connections
+	/// are not expired by NATs or firewalls and dead connections are
+	/// re-established before they are next used. Clients which are checked
+	/// out are skipped. The task covers the nodes and any replicas connected
+	/// before this is called, and stops once the pool has been dropped.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use std::time::Duration;
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4)
+	///     .await
+	///     .unwrap()
+	///     .with_keep_alive(Duration::from_secs(60));
+	/// ```
+	#[must_use]
+	pub fn with_keep_alive(self, interval: Duration) -> Self {
+		let nodes = Arc::downgrade(&self.nodes);
+		let replicas = Arc::downgrade(&self.replicas);
+
+		self.tasks.spawn("paper-client::keep_alive", async move {
+			loop {
+				time::sleep(interval).await;
+
+				let Some(nodes) = nodes.upgrade() else {
+					break;
+				};
+
+				// the replicas are replaced if more are connected later
+				let replicas = replicas.upgrade();
+
+				let clients = nodes
+					.iter()
+					.chain(replicas.iter().flat_map(|replicas| replicas.iter()))
+					.flat_map(|node| node.clients.iter());
+
+				for client in clients {
+					if let Ok(mut client) = client.try_lock() {
+						let _ = client.ping_if_idle(interval).await;
+					}
+				}
+			}
+		});
+
+		self
+	}
+
+	/// Sets the maximum number of writes issued by
+	/// [`AsyncPaperPool::set_nowait`] which may be waiting for a response,
+	/// after which further writes return `WriteQueueFull`. Defaults to
+	/// 1024.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4)
+	///     .await
+	///     .unwrap()
+	///     .with_nowait_capacity(256);
+	/// ```
+	#[must_use]
+	pub fn with_nowait_capacity(mut self, capacity: usize) -> Self {
+		self.nowait = Arc::new(Semaphore::new(capacity));
+		self.nowait_capacity = capacity;
+		self
+	}
+
+	/// Monitors the status of each node (and any replicas connected before
+	/// this is called) from a background task (see
+	/// [`AsyncPaperPool::background_tasks`]), which polls the status at the
+	/// supplied interval and emits a `PaperEvent::Threshold` to the pool's
+	/// observer when a node's miss ratio or used size first exceeds the
+	/// supplied thresholds and again when it recovers. A
+	/// `PaperEvent::PolicyChange` is emitted when a node's active policy
+	/// differs from the one in its previous status (e.g., when a server
+	/// configured to `PaperPolicy::Auto` switches policies). Polls which
+	/// fail are skipped. The task stops once the pool has been dropped.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use std::time::Duration;
+	/// use paper_client::{AsyncPaperPool, StatusThresholds};
+	///
+	/// let thresholds = StatusThresholds::default().miss_ratio(0.2);
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4)
+	///     .await
+	///     .unwrap()
+	///     .with_status_monitor(Duration::from_secs(10), thresholds);
+	/// ```
+	#[must_use]
+	pub fn with_status_monitor(self, interval: Duration, thresholds: StatusThresholds) -> Self {
+		let nodes = Arc::downgrade(&self.nodes);
+		let replicas = Arc::downgrade(&self.replicas);
+		let observer = self.config.observer.clone();
+
+		self.tasks.spawn("paper-client::status_monitor", async move {
+			let mut states = HashMap::<String, MonitorState>::new();
+
+			loop {
+				time::sleep(interval).await;
+
+				let Some(nodes) = nodes.upgrade() else {
+					break;
+				};
+
+				let replicas = replicas.upgrade();
+
+				let monitored = nodes
+					.iter()
+					.chain(replicas.iter().flat_map(|replicas| replicas.iter()));
+
+				for node in monitored {
+					let Ok(status) = node.clients[0].lock().await.status().await else {
+						continue;
+					};
+
+					let events = states
+						.entry(node.addr.clone())
+						.or_default()
+						.update(&node.addr, &thresholds, &status);
+
+					if let Some(observer) = &observer {
+						for event in events {
+							observer.on_event(&event);
+						}
+					}
+				}
+			}
+		});
+
+		self
 	}
 
-	/// Attempts to authorize each client with the supplied auth token.
+	/// Attempts to authorize each client with the supplied auth token. The
+	/// token is stored by the pool and used to authorize any clients which
+	/// are connected later (e.g., to replicas).
 	///
 	/// # Examples
 	/// ```ignore
@@ -65,13 +553,81 @@ impl AsyncPaperPool {
 	/// };
 	/// ```
 	pub async fn auth(&self, token: &str) -> Result<(), PaperClientError> {
-		for client in self.clients.iter() {
-			client.lock().await.auth(token).await?;
+		self.auth_with(StaticTokenProvider::new(token)).await
+	}
+
+	/// Attempts to authorize each client with the token supplied by the
+	/// supplied `AuthTokenProvider`. The provider is stored by the pool and
+	/// used to authorize any clients which are connected later.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::{AsyncPaperPool, EnvTokenProvider};
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	///
+	/// if let Err(err) = pool.auth_with(EnvTokenProvider::new("PAPER_AUTH_TOKEN")).await {
+	///     println!("{err:?}");
+	/// };
+	/// ```
+	pub async fn auth_with(
+		&self,
+		provider: impl AuthTokenProvider + 'static,
+	) -> Result<(), PaperClientError> {
+		let provider: Arc<dyn AuthTokenProvider> = Arc::new(provider);
+
+		*self
+			.auth_provider
+			.write()
+			.expect("Could not obtain auth provider.") = Some(provider.clone());
+
+		for node in self.nodes.iter().chain(self.replicas.iter()) {
+			node.auth_with(&provider).await?;
+		}
+
+		Ok(())
+	}
+
+	/// Reauthorizes each client's existing connection with the token
+	/// currently supplied by the pool's `AuthTokenProvider`, without
+	/// reconnecting. Does nothing if the pool has not been authorized.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	///
+	/// if let Err(err) = pool.reauth().await {
+	///     println!("{err:?}");
+	/// };
+	/// ```
+	pub async fn reauth(&self) -> Result<(), PaperClientError> {
+		for node in self.nodes.iter().chain(self.replicas.iter()) {
+			node.reauth().await?;
 		}
 
 		Ok(())
 	}
 
+	/// Replaces the pool's auth token with the supplied token and
+	/// reauthorizes each client's existing connection, without
+	/// reconnecting.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	///
+	/// if let Err(err) = pool.rotate_token("my_new_token").await {
+	///     println!("{err:?}");
+	/// };
+	/// ```
+	pub async fn rotate_token(&self, token: &str) -> Result<(), PaperClientError> {
+		self.auth(token).await
+	}
+
 	/// Obtains a guarded `PaperClient`. Use this client, then drop the
 	/// reference (or allow it to go out of scope). Do not hold a reference
 	/// to this client, otherwise the client will be unusable by other
@@ -83,19 +639,972 @@ impl AsyncPaperPool {
 	///
 	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
 	///
-	/// match pool.client().ping().await {
+	/// match pool.client().await.ping().await {
 	///     Ok(value) => println!("{value:?}"),
 	///     Err(err) => println!("{err:?}"),
 	/// };
 	/// ```
-	pub async fn client(&self) -> MutexGuard<'_, AsyncPaperClient> {
-		self.clients[self.get_index()].lock().await
+	pub async fn client(&self) -> AsyncPaperPoolGuard<'_> {
+		let worker = self.worker();
+		let index = worker.unwrap_or_else(|| self.get_index()) % self.num_clients();
+		let node = &self.nodes[self.available_node(index / self.node_size()).await];
+
+		let client = match (worker, self.selection) {
+			(Some(_), _) | (None, ClientSelection::RoundRobin) => index % self.node_size(),
+			(None, ClientSelection::LatencyWeighted) => node.next_client(self.selection),
+		};
+
+		self.checkout(node, client).await
 	}
 
-	fn get_index(&self) -> usize {
-		let index = self.index.load(Ordering::Relaxed);
-		self.index
-			.store((index + 1) % self.clients.len(), Ordering::Relaxed);
-		index
+	/// Obtains a guarded `AsyncPaperClient` in the same way as
+	/// [`AsyncPaperPool::client`], with the supplied options applied until
+	/// the guard is dropped. See `PaperPool::client_with`.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use std::time::Duration;
+	/// use paper_client::{AsyncPaperPool, CheckoutOptions};
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	/// let options = CheckoutOptions::default().timeout(Duration::from_millis(20));
+	///
+	/// match pool.client_with(options).await.get("key").await {
+	///     Ok(value) => println!("{value:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// };
+	/// ```
+	pub async fn client_with(&self, options: CheckoutOptions) -> AsyncPaperPoolGuard<'_> {
+		let mut client = self.client().await;
+		client.apply(options);
+		client
+	}
+
+	/// Obtains a guarded `AsyncPaperClient` in the same way as
+	/// [`AsyncPaperPool::client`], but returns `PoolTimeout` if the client
+	/// could not be obtained within the supplied timeout, or `PoolDrained`
+	/// if the pool has been drained.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use std::time::Duration;
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	///
+	/// match pool.try_client(Duration::from_millis(100)).await {
+	///     Ok(mut client) => println!("{:?}", client.ping().await),
+	///     Err(err) => println!("{err:?}"),
+	/// };
+	/// ```
+	pub async fn try_client(
+		&self,
+		timeout: Duration,
+	) -> Result<AsyncPaperPoolGuard<'_>, PaperClientError> {
+		if self.is_drained() {
+			return Err(PaperClientError::PoolDrained);
+		}
+
+		let start = Instant::now();
+
+		let index = self.worker().unwrap_or_else(|| self.get_index()) % self.num_clients();
+		let node = &self.nodes[self.available_node(index / self.node_size()).await];
+
+		let client = index % self.node_size();
+
+		match time::timeout(timeout, node.clients[client].lock()).await {
+			Ok(mut guard) => {
+				node.stats[client].record_checkout();
+				self.replace_if_exhausted(&mut guard).await;
+				Ok(AsyncPaperPoolGuard::new(guard, &self.metrics, start.elapsed()))
+			},
+
+			Err(_) => {
+				self.metrics.timeout();
+				Err(PaperClientError::PoolTimeout)
+			},
+		}
+	}
+
+	/// Obtains a guarded `AsyncPaperClient` for reads, connected to either
+	/// a primary node or a replica depending on the pool's
+	/// `ReadPreference`. The same usage rules as [`AsyncPaperPool::client`]
+	/// apply.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	///
+	/// match pool.read_client().await.status().await {
+	///     Ok(status) => println!("{status:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// };
+	/// ```
+	pub async fn read_client(&self) -> AsyncPaperPoolGuard<'_> {
+		let node = self.read_node(None).await;
+		self.checkout(node, self.select_client(node)).await
+	}
+
+	/// Obtains a guarded `AsyncPaperClient` for reading the supplied key,
+	/// connected to either the primary node responsible for the key or a
+	/// replica depending on the pool's `ReadPreference`. The same usage
+	/// rules as [`AsyncPaperPool::client`] apply.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	///
+	/// match pool.read_client_for("key").await.get("key").await {
+	///     Ok(value) => println!("{value:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// };
+	/// ```
+	pub async fn read_client_for(&self, key: impl AsPaperKey) -> AsyncPaperPoolGuard<'_> {
+		let node = self.read_node(Some(key.as_paper_key())).await;
+		self.checkout(node, self.select_client(node)).await
+	}
+
+	/// Obtains a guarded `AsyncPaperClient` connected to the address
+	/// responsible for the supplied key, as determined by the pool's
+	/// `Sharder`. The same usage rules as [`AsyncPaperPool::client`] apply.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	///
+	/// match pool.client_for("key").await.get("key").await {
+	///     Ok(value) => println!("{value:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// };
+	/// ```
+	pub async fn client_for(&self, key: impl AsPaperKey) -> AsyncPaperPoolGuard<'_> {
+		let node = &self.nodes[self.shard(key.as_paper_key()).await];
+		self.checkout(node, self.select_client(node)).await
+	}
+
+	/// Gets the values of the supplied keys, with at most `concurrency` gets
+	/// in flight at once across the pool's clients (a `concurrency` of 0 is
+	/// treated as 1). Each key is read from the client returned by
+	/// [`AsyncPaperPool::read_client_for`]. The values are yielded in the
+	/// order in which they are received rather than the order of the keys.
+	/// Dropping the stream cancels any remaining gets.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use futures_util::StreamExt;
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	/// let mut values = pool.stream_get(["key1", "key2", "key3"], 2);
+	///
+	/// while let Some((key, value)) = values.next().await {
+	///     println!("{key}: {value:?}");
+	/// }
+	/// ```
+	pub fn stream_get<K>(
+		&self,
+		keys: impl IntoIterator<Item = K>,
+		concurrency: usize,
+	) -> AsyncGetStream
+	where
+		K: Into<String>,
+	{
+		let keys = keys
+			.into_iter()
+			.map(Into::into)
+			.collect::<Vec<String>>();
+
+		let concurrency = concurrency.max(1);
+		let (sender, receiver) = mpsc::channel(concurrency);
+
+		let pool = self.clone();
+
+		self.tasks.spawn("paper-client::stream_get", async move {
+			let mut keys = keys.into_iter();
+			let mut gets = JoinSet::new();
+
+			loop {
+				while gets.len() < concurrency
+					&& let Some(key) = keys.next()
+				{
+					let pool = pool.clone();
+
+					gets.spawn(async move {
+						let value = pool
+							.read_client_for(&key)
+							.await
+							.get(&key)
+							.await;
+
+						(key, value)
+					});
+				}
+
+				// dropping the set when the stream has been dropped aborts
+				// any gets which are still in flight
+				match gets.join_next().await {
+					Some(Ok(item)) => {
+						if sender.send(item).await.is_err() {
+							break;
+						}
+					},
+
+					Some(Err(_)) => {},
+					None => break,
+				}
+			}
+		});
+
+		AsyncGetStream {
+			receiver,
+		}
+	}
+
+	/// Sets the supplied key, value, and ttl without waiting for the
+	/// server's response, for writes whose occasional loss is acceptable
+	/// but whose latency matters (e.g., caching telemetry). The write is
+	/// issued to the client returned by [`AsyncPaperPool::client_for`] from
+	/// a background task (see [`AsyncPaperPool::background_tasks`]), and
+	/// its failure is only counted in the pool's `PoolMetrics::nowait_errors`.
+	///
+	/// If too many earlier writes are still waiting for a response (see
+	/// [`AsyncPaperPool::with_nowait_capacity`]), the write is not issued and
+	/// `WriteQueueFull` is returned, so that the caller can shed or retry it
+	/// rather than letting the backlog grow without bound. Must be called
+	/// from within a tokio runtime.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	///
+	/// if let Err(err) = pool.set_nowait("key", "value", None) {
+	///     println!("{err:?}");
+	/// }
+	/// ```
+	pub fn set_nowait(
+		&self,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		if self.is_drained() {
+			return Err(PaperClientError::PoolDrained);
+		}
+
+		let value: PaperValue = value
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidValue)?;
+
+		let Ok(permit) = self.nowait.clone().try_acquire_owned() else {
+			return Err(PaperClientError::WriteQueueFull);
+		};
+
+		let key = key.as_paper_key().to_owned();
+		let ttl = ttl.as_paper_ttl()?;
+
+		let pool = self.clone();
+
+		self.tasks.spawn("paper-client::set_nowait", async move {
+			let result = pool
+				.client_for(&key)
+				.await
+				.set(&key, value, Ttl::from_secs(ttl))
+				.await;
+
+			if result.is_err() {
+				pool.metrics.nowait_error();
+			}
+
+			drop(permit);
+		});
+
+		Ok(())
+	}
+
+	/// Returns the addresses of the pool's nodes.
+	#[must_use]
+	pub fn addrs(&self) -> &[String] {
+		&self.addrs
+	}
+
+	/// Returns a snapshot of the health of each of the pool's nodes.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	///
+	/// for node in pool.cluster_status() {
+	///     println!("{}: {:?}", node.addr(), node.state());
+	/// }
+	/// ```
+	#[must_use]
+	pub fn cluster_status(&self) -> Vec<NodeStatus> {
+		let primaries = self
+			.nodes
+			.iter()
+			.map(|node| node.health.status(&node.addr, NodeRole::Primary));
+
+		let replicas = self
+			.replicas
+			.iter()
+			.map(|node| node.health.status(&node.addr, NodeRole::Replica));
+
+		primaries.chain(replicas).collect()
+	}
+
+	/// Returns a snapshot of the pool's checkout and connection metrics.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	/// let metrics = pool.metrics();
+	///
+	/// println!("{} in use, {} idle", metrics.in_use(), metrics.idle());
+	/// ```
+	#[must_use]
+	pub fn metrics(&self) -> PoolMetrics {
+		let stats = self
+			.nodes
+			.iter()
+			.chain(self.replicas.iter())
+			.flat_map(|node| node.stats.iter())
+			.map(|stats| &**stats)
+			.collect::<Vec<_>>();
+
+		self.metrics.snapshot(&stats)
+	}
+
+	/// Returns a snapshot of the commands sent over each of the pool's
+	/// connections (see `PaperClient::client_stats`), with totals across
+	/// the pool, without checking out any clients.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	/// let stats = pool.aggregate_stats();
+	///
+	/// for connection in stats.connections() {
+	///     println!("{}: {} commands", connection.addr(), connection.commands());
+	/// }
+	/// ```
+	#[must_use]
+	pub fn aggregate_stats(&self) -> PoolStats {
+		self.nodes
+			.iter()
+			.chain(self.replicas.iter())
+			.flat_map(|node| node.stats.iter().map(|stats| stats.snapshot(&node.addr)))
+			.collect()
+	}
+
+	/// Returns a snapshot of the usage of every tenant of the pool's
+	/// clients (see `AsyncPaperClient::tenant`), ordered by tenant.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	///
+	/// for usage in pool.tenant_usage() {
+	///     println!("{}: {} bytes read", usage.tenant(), usage.bytes_read());
+	/// }
+	/// ```
+	#[must_use]
+	pub fn tenant_usage(&self) -> Vec<TenantUsage> {
+		self.config.tenants.usage()
+	}
+
+	/// Returns the background tasks spawned by the pool which are still
+	/// running.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	///
+	/// for task in pool.background_tasks() {
+	///     println!("{} ({})", task.name(), task.id());
+	/// }
+	/// ```
+	#[must_use]
+	pub fn background_tasks(&self) -> Vec<BackgroundTask> {
+		self.tasks.tasks()
+	}
+
+	/// Aborts all of the background tasks spawned by the pool (e.g., on
+	/// shutdown), returning the number of tasks which were aborted. Any
+	/// `AsyncGetStream` whose task was aborted ends early.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	/// pool.abort_background_tasks();
+	/// ```
+	pub fn abort_background_tasks(&self) -> usize {
+		self.tasks.abort()
+	}
+
+	/// Drains the pool: no more clients are handed out by
+	/// [`AsyncPaperPool::try_client`], and each client's connection is
+	/// closed once its outstanding guard has been dropped. Clients obtained
+	/// from the pool afterwards in any other way are closed and return
+	/// `Disconnected`. The writes already issued by
+	/// [`AsyncPaperPool::set_nowait`] are waited for before any connection
+	/// is closed. If any such write or guard is still outstanding after the
+	/// supplied timeout, the connections it holds are left open and
+	/// `PoolTimeout` is returned.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use std::time::Duration;
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4).await.unwrap();
+	///
+	/// match pool.drain(Duration::from_secs(5)).await {
+	///     Ok(_) => println!("drained"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub async fn drain(&self, timeout: Duration) -> PaperClientResult<()> {
+		let start = Instant::now();
+		self.is_drained.store(true, Ordering::Relaxed);
+
+		// every permit is only available once each nowait write which was
+		// accepted before the drain has received its response
+		let capacity = self.nowait_capacity.min(u32::MAX as usize) as u32;
+		let nowait = time::timeout(timeout, self.nowait.acquire_many(capacity)).await;
+
+		let mut is_closed = matches!(nowait, Ok(Ok(_)));
+
+		let clients = self
+			.nodes
+			.iter()
+			.chain(self.replicas.iter())
+			.flat_map(|node| node.clients.iter());
+
+		for client in clients {
+			let remaining = timeout.saturating_sub(start.elapsed());
+
+			match time::timeout(remaining, client.lock()).await {
+				Ok(mut client) => client.close().await,
+				Err(_) => is_closed = false,
+			}
+		}
+
+		match is_closed {
+			true => Ok(()),
+			false => Err(PaperClientError::PoolTimeout),
+		}
+	}
+
+	/// Returns `true` if the pool has been drained.
+	#[must_use]
+	pub fn is_drained(&self) -> bool {
+		self.is_drained.load(Ordering::Relaxed)
+	}
+
+	async fn checkout<'a>(&'a self, node: &'a PoolNode, index: usize) -> AsyncPaperPoolGuard<'a> {
+		let start = Instant::now();
+
+		node.stats[index].record_checkout();
+		let mut client = node.clients[index].lock().await;
+
+		self.replace_if_exhausted(&mut client).await;
+
+		AsyncPaperPoolGuard::new(client, &self.metrics, start.elapsed())
+	}
+
+	// a client whose reconnects were exhausted would otherwise fail every
+	// time it is handed out, so its connection is replaced first
+	async fn replace_if_exhausted(&self, client: &mut AsyncPaperClient) {
+		if client.replace_if_exhausted().await {
+			self.metrics.replacement();
+		}
+	}
+
+	async fn read_node(&self, key: Option<&str>) -> &PoolNode {
+		if self.read_preference == ReadPreference::PreferReplica
+			&& let Some(replica) = self.available_replica().await
+		{
+			return replica;
+		}
+
+		let primary = match key {
+			Some(key) => &self.nodes[self.shard(key).await],
+			None => &self.nodes[self.available_node(self.get_index() / self.node_size()).await],
+		};
+
+		if self.read_preference != ReadPreference::NearestByLatency {
+			return primary;
+		}
+
+		let mut nearest = primary;
+
+		for replica in self.replicas.iter() {
+			if replica.health.latency() < nearest.health.latency()
+				&& self.is_available(replica).await
+			{
+				nearest = replica;
+			}
+		}
+
+		nearest
+	}
+
+	async fn available_replica(&self) -> Option<&PoolNode> {
+		if self.replicas.is_empty() {
+			return None;
+		}
+
+		let index = self
+			.replica_index
+			.fetch_add(1, Ordering::Relaxed);
+
+		for offset in 0..self.replicas.len() {
+			let replica = &self.replicas[(index + offset) % self.replicas.len()];
+
+			if self.is_available(replica).await {
+				return Some(replica);
+			}
+		}
+
+		None
+	}
+
+	async fn shard(&self, key: &str) -> usize {
+		if self.nodes.len() == 1 {
+			return 0;
+		}
+
+		let available = self.available_nodes().await;
+
+		if available.is_empty() || available.len() == self.nodes.len() {
+			return self.sharder.shard(key, &self.addrs);
+		}
+
+		let addrs = available
+			.iter()
+			.map(|index| self.addrs[*index].clone())
+			.collect::<Vec<_>>();
+
+		available[self.sharder.shard(key, &addrs)]
+	}
+
+	async fn available_nodes(&self) -> Vec<usize> {
+		let mut available = Vec::with_capacity(self.nodes.len());
+
+		for index in 0..self.nodes.len() {
+			if self.is_available(&self.nodes[index]).await {
+				available.push(index);
+			}
+		}
+
+		available
+	}
+
+	async fn available_node(&self, index: usize) -> usize {
+		for offset in 0..self.nodes.len() {
+			let candidate = (index + offset) % self.nodes.len();
+
+			if self.is_available(&self.nodes[candidate]).await {
+				return candidate;
+			}
+		}
+
+		index
+	}
+
+	async fn is_available(&self, node: &PoolNode) -> bool {
+		node.is_available(self.ejection.as_ref(), &self.config)
+			.await
+	}
+
+	fn auth_provider(&self) -> Option<Arc<dyn AuthTokenProvider>> {
+		self.auth_provider
+			.read()
+			.expect("Could not obtain auth provider.")
+			.clone()
+	}
+
+	fn node_size(&self) -> usize {
+		self.nodes[0].clients.len()
+	}
+
+	// each caller is handed the next index, even under contention, which
+	// wraps around along with the counter
+	fn get_index(&self) -> usize {
+		self.index.fetch_add(1, Ordering::Relaxed) % self.num_clients()
+	}
+
+	fn num_clients(&self) -> usize {
+		self.nodes.len() * self.node_size()
+	}
+
+	// the worker which the current task was pinned to, if the pool pins
+	// workers to clients
+	fn worker(&self) -> Option<usize> {
+		match self.is_pinned {
+			true => WORKER.try_with(|worker| *worker).ok(),
+			false => None,
+		}
+	}
+
+	fn select_client(&self, node: &PoolNode) -> usize {
+		match self.worker() {
+			Some(worker) => worker % node.clients.len(),
+			None => node.next_client(self.selection),
+		}
+	}
+}
+
+/// Runs the supplied future as the supplied runtime worker, so that any
+/// `AsyncPaperPool` with worker pinning enabled hands it the worker's
+/// clients. See [`AsyncPaperPool::with_worker_pinning`].
+///
+/// # Examples
+/// ```ignore
+/// use paper_client::{AsyncPaperPool, pin_to_worker};
+///
+/// let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 4)
+///     .await
+///     .unwrap()
+///     .with_worker_pinning();
+///
+/// pin_to_worker(1, async {
+///     let _ = pool.client().await.ping().await;
+/// }).await;
+/// ```
+pub async fn pin_to_worker<F: Future>(worker: usize, future: F) -> F::Output {
+	WORKER.scope(worker, future).await
+}
+
+impl PoolNode {
+	async fn connect(
+		addr: String,
+		size: usize,
+		config: &PaperClientConfig,
+	) -> Result<Self, PaperClientError> {
+		let health = Arc::new(NodeHealth::default());
+		let mut clients = Vec::with_capacity(size);
+		let mut stats = Vec::with_capacity(size);
+
+		for _ in 0..size {
+			let mut client = AsyncPaperClient::connect(addr.clone(), config.clone()).await?;
+			client.set_health(health.clone());
+
+			stats.push(client.stats());
+			clients.push(Mutex::new(client));
+		}
+
+		let node = PoolNode {
+			addr,
+			clients: clients.into_boxed_slice(),
+			stats: stats.into_boxed_slice(),
+			index: AtomicUsize::default(),
+			health,
+
+			selector: WeightedSelector::new(size),
+		};
+
+		Ok(node)
+	}
+
+	async fn auth_with(
+		&self,
+		provider: &Arc<dyn AuthTokenProvider>,
+	) -> Result<(), PaperClientError> {
+		for client in self.clients.iter() {
+			let mut client = client.lock().await;
+
+			client.set_auth_provider(provider.clone());
+			client.reauth().await?;
+		}
+
+		Ok(())
+	}
+
+	async fn reauth(&self) -> Result<(), PaperClientError> {
+		for client in self.clients.iter() {
+			client.lock().await.reauth().await?;
+		}
+
+		Ok(())
+	}
+
+	fn next_client(&self, selection: ClientSelection) -> usize {
+		match selection {
+			ClientSelection::RoundRobin => self.get_index(),
+			ClientSelection::LatencyWeighted => self.selector.select(&self.stats),
+		}
+	}
+
+	// probes the node if it is due, reporting its ejection or restoration
+	// to the observer
+	async fn is_available(
+		&self,
+		policy: Option<&EjectionPolicy>,
+		config: &PaperClientConfig,
+	) -> bool {
+		match self.health.availability(policy, config.clock.now()) {
+			Availability::Available => true,
+			Availability::Ejected => false,
+
+			Availability::Ejecting => {
+				health::notify_failover(config, &self.addr, true);
+				false
+			},
+
+			Availability::Probe => {
+				// a probe which found every client checked out says nothing
+				// of the node's health
+				let Some(is_healthy) = self.probe().await else {
+					self.health.skip_probe();
+					return false;
+				};
+
+				let is_restored = self
+					.health
+					.finish_probe(is_healthy, policy, config.clock.now());
+
+				if is_restored {
+					health::notify_failover(config, &self.addr, false);
+				}
+
+				is_restored
+			},
+		}
+	}
+
+	async fn probe(&self) -> Option<bool> {
+		let mut is_healthy = None;
+
+		for client in self.clients.iter() {
+			let Ok(mut client) = client.try_lock() else {
+				continue;
+			};
+
+			match client.probe().await {
+				Ok(_) => is_healthy = Some(true),
+				Err(_) => return Some(false),
+			}
+		}
+
+		is_healthy
+	}
+
+	fn get_index(&self) -> usize {
+		self.index.fetch_add(1, Ordering::Relaxed) % self.clients.len()
+	}
+}
+
+impl Display for AsyncPaperPool {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"AsyncPaperPool({} node(s), {} replica(s), {} client(s) per node)",
+			self.nodes.len(),
+			self.replicas.len(),
+			self.node_size(),
+		)
+	}
+}
+
+impl Stream for AsyncGetStream {
+	type Item = (String, PaperClientResult<PaperValue>);
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		self.receiver.poll_recv(cx)
+	}
+}
+
+impl<'a> AsyncPaperPoolGuard<'a> {
+	fn new(
+		client: MutexGuard<'a, AsyncPaperClient>,
+		metrics: &'a PoolMetricsRecorder,
+		wait_time: Duration,
+	) -> Self {
+		metrics.checkout(wait_time);
+
+		AsyncPaperPoolGuard {
+			client,
+			metrics,
+
+			is_overridden: false,
+		}
+	}
+
+	fn apply(&mut self, options: CheckoutOptions) {
+		if let Some(timeout) = options.timeout {
+			self.client.set_command_timeout(Some(timeout));
+			self.is_overridden = true;
+		}
+	}
+}
+
+impl Deref for AsyncPaperPoolGuard<'_> {
+	type Target = AsyncPaperClient;
+
+	fn deref(&self) -> &Self::Target {
+		&self.client
+	}
+}
+
+impl DerefMut for AsyncPaperPoolGuard<'_> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.client
+	}
+}
+
+impl Drop for AsyncPaperPoolGuard<'_> {
+	fn drop(&mut self) {
+		if self.is_overridden {
+			self.client.reset_command_timeout();
+		}
+
+		self.metrics.checkin();
+	}
+}
+
+impl AsyncPaperPoolBuilder {
+	/// Adds a node at the supplied address to the pool. Adding more than
+	/// one address creates a sharded pool.
+	#[must_use]
+	pub fn addr(mut self, paper_addr: impl FromPaperAddr) -> Self {
+		self.addrs.push(paper_addr.to_addr());
+		self
+	}
+
+	/// Sets the number of clients connected to each node. Defaults to 1.
+	#[must_use]
+	pub fn size(mut self, size: usize) -> Self {
+		self.size = size;
+		self
+	}
+
+	/// Sets the `Sharder` used to map keys to nodes. Defaults to
+	/// `ModuloSharder`.
+	#[must_use]
+	pub fn sharder(mut self, sharder: impl Sharder + 'static) -> Self {
+		self.sharder = Arc::new(sharder);
+		self
+	}
+
+	/// Sets the configuration used by each of the pool's clients.
+	#[must_use]
+	pub fn config(mut self, config: PaperClientConfig) -> Self {
+		self.config = config;
+		self
+	}
+
+	/// Sets the auth token with which each of the pool's clients is
+	/// authorized as soon as it is connected, so that building the pool
+	/// fails if the token is rejected. The token is stored by the pool and
+	/// used to authorize any clients which are connected later.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::AsyncPaperPool;
+	///
+	/// let pool = AsyncPaperPool::builder()
+	///     .addr("paper://127.0.0.1:3145")
+	///     .auth("auth_token")
+	///     .build()
+	///     .await
+	///     .unwrap();
+	/// ```
+	#[must_use]
+	pub fn auth(self, token: &str) -> Self {
+		self.auth_with(StaticTokenProvider::new(token))
+	}
+
+	/// Sets the `AuthTokenProvider` which supplies the token with which
+	/// each of the pool's clients is authorized as soon as it is connected
+	/// (see `auth`).
+	#[must_use]
+	pub fn auth_with(mut self, provider: impl AuthTokenProvider + 'static) -> Self {
+		self.auth_provider = Some(Arc::new(provider));
+		self
+	}
+
+	/// Connects the pool's clients, authorizing them if an auth token was
+	/// supplied. If a connection could not be established to any of the
+	/// clients or the auth token was rejected, a `PaperClientError` is
+	/// returned.
+	pub async fn build(self) -> Result<AsyncPaperPool, PaperClientError> {
+		assert!(!self.addrs.is_empty());
+		assert!(self.size > 0);
+
+		let mut nodes = Vec::with_capacity(self.addrs.len());
+		let mut addrs = Vec::with_capacity(self.addrs.len());
+
+		for addr in self.addrs {
+			let node = PoolNode::connect(addr?, self.size, &self.config).await?;
+
+			if let Some(provider) = &self.auth_provider {
+				node.auth_with(provider).await?;
+			}
+
+			addrs.push(node.addr.clone());
+			nodes.push(node);
+		}
+
+		let pool = AsyncPaperPool {
+			nodes: Arc::new(nodes.into_boxed_slice()),
+			addrs: Arc::new(addrs.into_boxed_slice()),
+			index: Arc::new(AtomicUsize::default()),
+
+			replicas:      Arc::new(Box::default()),
+			replica_index: Arc::new(AtomicUsize::default()),
+
+			sharder:         self.sharder,
+			ejection:        None,
+			read_preference: ReadPreference::default(),
+			selection:       ClientSelection::default(),
+			is_pinned:       false,
+
+			failback: None,
+
+			config:        self.config,
+			auth_provider: Arc::new(RwLock::new(self.auth_provider)),
+			metrics:       Arc::default(),
+			tasks:         Arc::default(),
+			nowait:        Arc::new(Semaphore::new(DEFAULT_NOWAIT_CAPACITY)),
+			is_drained:    Arc::default(),
+
+			nowait_capacity: DEFAULT_NOWAIT_CAPACITY,
+		};
+
+		Ok(pool)
+	}
+}
+
+impl Default for AsyncPaperPoolBuilder {
+	fn default() -> Self {
+		AsyncPaperPoolBuilder {
+			addrs:   Vec::new(),
+			size:    1,
+			sharder: Arc::new(ModuloSharder),
+			config:  PaperClientConfig::default(),
+
+			auth_provider: None,
+		}
 	}
 }
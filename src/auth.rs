@@ -0,0 +1,198 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	env,
+	fmt::{self, Debug},
+	fs,
+	path::PathBuf,
+	sync::Mutex,
+	time::SystemTime,
+};
+
+use crate::error::{PaperClientError, PaperClientResult};
+
+/// Supplies the auth token used to authorize a client's connection. The
+/// token is requested each time the connection is (re)authorized, so a
+/// provider may return a different token after it has been rotated.
+pub trait AuthTokenProvider: Debug + Send + Sync {
+	/// Returns the current auth token, or `AuthTokenUnavailable` if the
+	/// token could not be obtained.
+	fn token(&self) -> PaperClientResult<String>;
+}
+
+/// Supplies a fixed auth token.
+#[derive(Clone)]
+pub struct StaticTokenProvider {
+	token: String,
+}
+
+/// Supplies the auth token stored in an environment variable.
+#[derive(Debug, Clone)]
+pub struct EnvTokenProvider {
+	var: String,
+}
+
+/// Supplies the auth token returned by a callback.
+pub struct CallbackTokenProvider<F> {
+	callback: F,
+}
+
+/// Supplies the auth token stored in a file. The file is re-read whenever
+/// its modification time changes, so rotating the token only requires
+/// rewriting the file.
+pub struct FileTokenProvider {
+	path:   PathBuf,
+	cached: Mutex<Option<(SystemTime, String)>>,
+}
+
+impl StaticTokenProvider {
+	/// Creates a provider which always supplies the supplied token.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::StaticTokenProvider;
+	///
+	/// let provider = StaticTokenProvider::new("my_token");
+	/// ```
+	#[must_use]
+	pub fn new(token: impl Into<String>) -> Self {
+		StaticTokenProvider {
+			token: token.into(),
+		}
+	}
+}
+
+impl AuthTokenProvider for StaticTokenProvider {
+	fn token(&self) -> PaperClientResult<String> {
+		Ok(self.token.clone())
+	}
+}
+
+impl Debug for StaticTokenProvider {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("StaticTokenProvider")
+			.field("token", &"<redacted>")
+			.finish()
+	}
+}
+
+impl EnvTokenProvider {
+	/// Creates a provider which supplies the value of the supplied
+	/// environment variable.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::EnvTokenProvider;
+	///
+	/// let provider = EnvTokenProvider::new("PAPER_AUTH_TOKEN");
+	/// ```
+	#[must_use]
+	pub fn new(var: impl Into<String>) -> Self {
+		EnvTokenProvider {
+			var: var.into(),
+		}
+	}
+}
+
+impl AuthTokenProvider for EnvTokenProvider {
+	fn token(&self) -> PaperClientResult<String> {
+		env::var(&self.var).map_err(|_| PaperClientError::AuthTokenUnavailable)
+	}
+}
+
+impl<F> CallbackTokenProvider<F>
+where
+	F: Fn() -> PaperClientResult<String> + Send + Sync,
+{
+	/// Creates a provider which supplies the token returned by the
+	/// supplied callback.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::CallbackTokenProvider;
+	///
+	/// let provider = CallbackTokenProvider::new(|| Ok("my_token".to_owned()));
+	/// ```
+	#[must_use]
+	pub fn new(callback: F) -> Self {
+		CallbackTokenProvider {
+			callback,
+		}
+	}
+}
+
+impl<F> AuthTokenProvider for CallbackTokenProvider<F>
+where
+	F: Fn() -> PaperClientResult<String> + Send + Sync,
+{
+	fn token(&self) -> PaperClientResult<String> {
+		(self.callback)()
+	}
+}
+
+impl<F> Debug for CallbackTokenProvider<F> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("CallbackTokenProvider")
+			.finish_non_exhaustive()
+	}
+}
+
+impl FileTokenProvider {
+	/// Creates a provider which supplies the contents of the supplied file,
+	/// with surrounding whitespace removed.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::FileTokenProvider;
+	///
+	/// let provider = FileTokenProvider::new("/run/secrets/paper_token");
+	/// ```
+	#[must_use]
+	pub fn new(path: impl Into<PathBuf>) -> Self {
+		FileTokenProvider {
+			path:   path.into(),
+			cached: Mutex::new(None),
+		}
+	}
+}
+
+impl AuthTokenProvider for FileTokenProvider {
+	fn token(&self) -> PaperClientResult<String> {
+		let modified = fs::metadata(&self.path)
+			.and_then(|metadata| metadata.modified())
+			.map_err(|_| PaperClientError::AuthTokenUnavailable)?;
+
+		let mut cached = self
+			.cached
+			.lock()
+			.expect("Could not obtain cached token.");
+
+		if let Some((cached_modified, token)) = cached.as_ref()
+			&& *cached_modified == modified
+		{
+			return Ok(token.clone());
+		}
+
+		let token = fs::read_to_string(&self.path)
+			.map_err(|_| PaperClientError::AuthTokenUnavailable)?
+			.trim()
+			.to_owned();
+
+		*cached = Some((modified, token.clone()));
+
+		Ok(token)
+	}
+}
+
+impl Debug for FileTokenProvider {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("FileTokenProvider")
+			.field("path", &self.path)
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,110 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	future::Future,
+	sync::{Mutex, MutexGuard},
+};
+
+use tokio::task::{AbortHandle, Id};
+
+// the number of registered tasks at which finished tasks are first pruned
+const MIN_PRUNE_LEN: usize = 64;
+
+/// A background task spawned by an `AsyncPaperPool` (e.g., the task which
+/// drives an `AsyncGetStream`). The task's ID matches the ID shown for it by
+/// tools such as tokio-console.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackgroundTask {
+	name: &'static str,
+	id:   Id,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct TaskRegistry {
+	tasks: Mutex<RegisteredTasks>,
+}
+
+// finished tasks are only pruned once the number of registered tasks has
+// doubled since they were last pruned, so that spawning a task (e.g., for
+// each set_nowait) does not scan every registered task
+#[derive(Debug, Default)]
+struct RegisteredTasks {
+	tasks:    Vec<(BackgroundTask, AbortHandle)>,
+	prune_at: usize,
+}
+
+impl BackgroundTask {
+	/// Returns the name of the task (e.g., `paper-client::stream_get`).
+	#[must_use]
+	pub fn name(&self) -> &'static str {
+		self.name
+	}
+
+	/// Returns the tokio ID of the task.
+	#[must_use]
+	pub fn id(&self) -> Id {
+		self.id
+	}
+}
+
+impl TaskRegistry {
+	// spawns and registers the task, returning a handle with which it can
+	// be aborted on its own
+	pub(crate) fn spawn<F>(&self, name: &'static str, future: F) -> AbortHandle
+	where
+		F: Future<Output = ()> + Send + 'static,
+	{
+		let handle = tokio::spawn(future);
+
+		let task = BackgroundTask {
+			name,
+			id: handle.id(),
+		};
+
+		let mut registered = self.lock();
+
+		if registered.tasks.len() >= registered.prune_at {
+			registered.tasks.retain(|(_, handle)| !handle.is_finished());
+			registered.prune_at = (registered.tasks.len() * 2).max(MIN_PRUNE_LEN);
+		}
+
+		let abort_handle = handle.abort_handle();
+		registered.tasks.push((task, abort_handle.clone()));
+
+		abort_handle
+	}
+
+	pub(crate) fn tasks(&self) -> Vec<BackgroundTask> {
+		self.lock()
+			.tasks
+			.iter()
+			.filter(|(_, handle)| !handle.is_finished())
+			.map(|(task, _)| task.clone())
+			.collect()
+	}
+
+	pub(crate) fn abort(&self) -> usize {
+		let mut registered = self.lock();
+		let mut aborted = 0;
+
+		for (_, handle) in registered.tasks.drain(..) {
+			if !handle.is_finished() {
+				handle.abort();
+				aborted += 1;
+			}
+		}
+
+		aborted
+	}
+
+	fn lock(&self) -> MutexGuard<'_, RegisteredTasks> {
+		self.tasks
+			.lock()
+			.expect("Could not obtain background tasks.")
+	}
+}
@@ -0,0 +1,351 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{borrow::Cow, io::Read};
+
+#[cfg(feature = "tokio")]
+use tokio::io::AsyncRead;
+
+use crate::{
+	arg::{AsPaperKey, AsPaperTtl},
+	codec::ValueCodec,
+	config::PaperClientConfig,
+	command::{Command, ResponseShape},
+	error::{PaperClientError, PaperClientResult},
+	value::PaperValue,
+};
+
+/// A set of key commands which are sent to the server together, so that
+/// the whole batch costs a single round trip. A batch is built inside the
+/// closure passed to `PaperClient::batch` (or `AsyncPaperClient::batch`)
+/// and is sent when the closure returns.
+///
+/// # Examples
+/// ```
+/// use paper_client::PaperClient;
+///
+/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+///
+/// let responses = client.batch(|batch| {
+///     batch.set("a", "value", None);
+///     batch.get("a");
+///     batch.del("a");
+/// });
+/// ```
+#[derive(Debug, Default)]
+pub struct Batch {
+	commands: Vec<PaperClientResult<BatchCommand>>,
+}
+
+/// The response to a single command of a batch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchResponse {
+	/// The command succeeded and returned no data (`set`, `del`, `ttl`).
+	Ok,
+	/// The value returned by `get` or `peek`.
+	Value(PaperValue),
+	/// The result of `has`.
+	Has(bool),
+	/// The result of `size`.
+	Size(u32),
+}
+
+#[derive(Debug)]
+enum BatchCommand {
+	Get(String),
+	Set(String, PaperValue, u32),
+	Del(String),
+
+	Has(String),
+	Peek(String),
+	Ttl(String, u32),
+	Size(String),
+}
+
+impl Batch {
+	/// Adds a `get` of the supplied key to the batch.
+	pub fn get(&mut self, key: impl AsPaperKey) -> &mut Self {
+		self.push(BatchCommand::Get(key.as_paper_key().to_owned()))
+	}
+
+	/// Adds a `set` of the supplied key, value, and ttl to the batch. If
+	/// the value cannot be converted, the command is not sent and its
+	/// response is `InvalidValue`. If the ttl is invalid, the command is
+	/// not sent and its response is `InvalidTtl`.
+	pub fn set(
+		&mut self,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> &mut Self {
+		let command = value
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidValue)
+			.and_then(|value| {
+				let ttl = ttl.as_paper_ttl()?;
+				Ok(BatchCommand::Set(key.as_paper_key().to_owned(), value, ttl))
+			});
+
+		self.commands.push(command);
+		self
+	}
+
+	/// Adds a `del` of the supplied key to the batch.
+	pub fn del(&mut self, key: impl AsPaperKey) -> &mut Self {
+		self.push(BatchCommand::Del(key.as_paper_key().to_owned()))
+	}
+
+	/// Adds a `has` of the supplied key to the batch.
+	pub fn has(&mut self, key: impl AsPaperKey) -> &mut Self {
+		self.push(BatchCommand::Has(key.as_paper_key().to_owned()))
+	}
+
+	/// Adds a `peek` of the supplied key to the batch.
+	pub fn peek(&mut self, key: impl AsPaperKey) -> &mut Self {
+		self.push(BatchCommand::Peek(key.as_paper_key().to_owned()))
+	}
+
+	/// Adds a `ttl` of the supplied key to the batch. If the ttl is
+	/// invalid, the command is not sent and its response is `InvalidTtl`.
+	pub fn ttl(&mut self, key: impl AsPaperKey, ttl: impl AsPaperTtl) -> &mut Self {
+		let command = ttl
+			.as_paper_ttl()
+			.map(|ttl| BatchCommand::Ttl(key.as_paper_key().to_owned(), ttl));
+
+		self.commands.push(command);
+		self
+	}
+
+	/// Adds a `size` of the supplied key to the batch.
+	pub fn size(&mut self, key: impl AsPaperKey) -> &mut Self {
+		self.push(BatchCommand::Size(key.as_paper_key().to_owned()))
+	}
+
+	/// Returns the number of commands in the batch.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.commands.len()
+	}
+
+	/// Returns `true` if the batch has no commands.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.commands.is_empty()
+	}
+
+	/// Encodes the value of each `set` with the supplied codec. A value
+	/// which cannot be encoded is not sent and its response is
+	/// `InvalidValue`.
+	pub(crate) fn encode_values(&mut self, codec: &dyn ValueCodec) {
+		for command in &mut self.commands {
+			if let Ok(BatchCommand::Set(_, value, _)) = command {
+				match codec.encode(value) {
+					Ok(encoded) => *value = PaperValue::from(encoded),
+					Err(_) => *command = Err(PaperClientError::InvalidValue),
+				}
+			}
+		}
+	}
+
+	/// Replaces each key with the key which is sent to the server (see
+	/// `PaperClientConfig::max_key_length`).
+	pub(crate) fn hash_keys(&mut self, config: &PaperClientConfig) {
+		for command in self.commands.iter_mut().flatten() {
+			let key = command.key_mut();
+
+			if let Cow::Owned(hashed) = config.paper_key(key) {
+				*key = hashed;
+			}
+		}
+	}
+
+	/// Returns the batch's commands which can be sent, in order.
+	pub(crate) fn commands(&self) -> Vec<Command<'_>> {
+		self.commands
+			.iter()
+			.filter_map(|command| command.as_ref().ok())
+			.map(BatchCommand::to_command)
+			.collect()
+	}
+
+	/// Reads the response to each of the supplied commands (as returned by
+	/// `commands`) from the reader, in order. A command which could not be
+	/// sent receives its error without reading from the reader. A stream
+	/// error fails the whole batch, as the remaining responses can no
+	/// longer be read.
+	pub(crate) fn receive(
+		&self,
+		commands: &[Command<'_>],
+		reader: &mut impl Read,
+		max_size: Option<u32>,
+	) -> PaperClientResult<Vec<PaperClientResult<BatchResponse>>> {
+		let mut commands = commands.iter();
+		let mut responses = Vec::with_capacity(self.commands.len());
+
+		for command in &self.commands {
+			// a command which could not be sent has no response to read
+			let command = command
+				.as_ref()
+				.map(|_| commands.next());
+
+			let response = match command {
+				Ok(Some(command)) => match receive_response(command, reader, max_size) {
+					Err(
+						err @ (PaperClientError::InvalidResponse | PaperClientError::DeadlineExceeded),
+					) => return Err(err),
+
+					response => response,
+				},
+
+				Ok(None) => return Err(PaperClientError::Internal),
+				Err(PaperClientError::InvalidTtl) => Err(PaperClientError::InvalidTtl),
+				Err(_) => Err(PaperClientError::InvalidValue),
+			};
+
+			responses.push(response);
+		}
+
+		Ok(responses)
+	}
+
+	/// The async counterpart of `receive`.
+	#[cfg(feature = "tokio")]
+	pub(crate) async fn receive_async<R>(
+		&self,
+		commands: &[Command<'_>],
+		reader: &mut R,
+		max_size: Option<u32>,
+	) -> PaperClientResult<Vec<PaperClientResult<BatchResponse>>>
+	where
+		R: AsyncRead + Unpin,
+	{
+		let mut commands = commands.iter();
+		let mut responses = Vec::with_capacity(self.commands.len());
+
+		for command in &self.commands {
+			// a command which could not be sent has no response to read
+			let command = command
+				.as_ref()
+				.map(|_| commands.next());
+
+			let response = match command {
+				Ok(Some(command)) => {
+					match receive_response_async(command, reader, max_size).await {
+						Err(
+							err @ (PaperClientError::InvalidResponse
+							| PaperClientError::DeadlineExceeded),
+						) => return Err(err),
+
+						response => response,
+					}
+				},
+
+				Ok(None) => return Err(PaperClientError::Internal),
+				Err(PaperClientError::InvalidTtl) => Err(PaperClientError::InvalidTtl),
+				Err(_) => Err(PaperClientError::InvalidValue),
+			};
+
+			responses.push(response);
+		}
+
+		Ok(responses)
+	}
+
+	fn push(&mut self, command: BatchCommand) -> &mut Self {
+		self.commands.push(Ok(command));
+		self
+	}
+}
+
+impl BatchCommand {
+	fn key_mut(&mut self) -> &mut String {
+		match self {
+			BatchCommand::Get(key)
+			| BatchCommand::Set(key, _, _)
+			| BatchCommand::Del(key)
+			| BatchCommand::Has(key)
+			| BatchCommand::Peek(key)
+			| BatchCommand::Ttl(key, _)
+			| BatchCommand::Size(key) => key,
+		}
+	}
+
+	fn to_command(&self) -> Command<'_> {
+		match self {
+			BatchCommand::Get(key) => Command::Get(key),
+			BatchCommand::Set(key, value, ttl) => Command::Set(key, value.clone(), *ttl),
+			BatchCommand::Del(key) => Command::Del(key),
+
+			BatchCommand::Has(key) => Command::Has(key),
+			BatchCommand::Peek(key) => Command::Peek(key),
+			BatchCommand::Ttl(key, ttl) => Command::Ttl(key, *ttl),
+			BatchCommand::Size(key) => Command::Size(key),
+		}
+	}
+}
+
+fn receive_response(
+	command: &Command<'_>,
+	reader: &mut impl Read,
+	max_size: Option<u32>,
+) -> PaperClientResult<BatchResponse> {
+	match command.spec().response {
+		ResponseShape::Ok => command
+			.parse_reader(reader)
+			.map(|_| BatchResponse::Ok),
+
+		ResponseShape::Buf => command
+			.parse_buf_reader(reader, max_size)
+			.map(BatchResponse::Value),
+
+		ResponseShape::Bool => command
+			.parse_has_reader(reader)
+			.map(BatchResponse::Has),
+
+		ResponseShape::U32 => command
+			.parse_size_reader(reader)
+			.map(BatchResponse::Size),
+
+		// no batched command responds with a status
+		ResponseShape::Status => Err(PaperClientError::InvalidCommand),
+	}
+}
+
+#[cfg(feature = "tokio")]
+async fn receive_response_async<R>(
+	command: &Command<'_>,
+	reader: &mut R,
+	max_size: Option<u32>,
+) -> PaperClientResult<BatchResponse>
+where
+	R: AsyncRead + Unpin,
+{
+	match command.spec().response {
+		ResponseShape::Ok => command
+			.parse_reader_async(reader)
+			.await
+			.map(|_| BatchResponse::Ok),
+
+		ResponseShape::Buf => command
+			.parse_buf_reader_async(reader, max_size)
+			.await
+			.map(BatchResponse::Value),
+
+		ResponseShape::Bool => command
+			.parse_has_reader_async(reader)
+			.await
+			.map(BatchResponse::Has),
+
+		ResponseShape::U32 => command
+			.parse_size_reader_async(reader)
+			.await
+			.map(BatchResponse::Size),
+
+		// no batched command responds with a status
+		ResponseShape::Status => Err(PaperClientError::InvalidCommand),
+	}
+}
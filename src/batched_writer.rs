@@ -0,0 +1,426 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	fmt::{self, Debug},
+	sync::{
+		Arc,
+		atomic::{AtomicU64, Ordering},
+	},
+	time::Duration,
+};
+
+use tokio::{
+	sync::{mpsc, oneshot},
+	time::{self, Instant},
+};
+
+use crate::{
+	arg::{AsPaperKey, AsPaperTtl},
+	async_client::AsyncPaperClient,
+	batch::{Batch, BatchResponse},
+	error::{PaperCacheError, PaperClientError, PaperClientResult},
+	ttl::Ttl,
+	value::PaperValue,
+};
+
+const DEFAULT_MAX_COMMANDS: usize = 128;
+const DEFAULT_MAX_BYTES: usize = 64 * 1024;
+const DEFAULT_MAX_DELAY: Duration = Duration::from_millis(5);
+
+// the maximum number of commands waiting to be batched, after which set and
+// del wait for the queue to drain
+const BATCHED_WRITER_QUEUE_CAPACITY: usize = 4096;
+
+/// A writer which queues `set`s and `del`s and sends them to the server in
+/// batches from a background task, so that a write-heavy workload costs a
+/// single round trip per batch rather than per command. The queued commands
+/// are flushed once the first of the writer's `BatchFlushPolicy` limits is
+/// reached, so a write only becomes visible to readers within the
+/// policy's `max_delay` of being queued.
+///
+/// The commands are sent in the order in which they were queued. A queued
+/// command which fails (other than a `del` of a key which was not found) is
+/// counted in the writer's metrics rather than returned to the caller. The
+/// writer can be cloned to share its queue between tasks, and the queued
+/// commands are still flushed once every clone has been dropped.
+///
+/// # Examples
+/// ```ignore
+/// use std::time::Duration;
+/// use paper_client::{AsyncPaperClient, BatchFlushPolicy, BatchedWriter};
+///
+/// let client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+///
+/// let policy = BatchFlushPolicy::default()
+///     .max_commands(256)
+///     .max_delay(Duration::from_millis(5));
+///
+/// let writer = BatchedWriter::new(client, policy);
+///
+/// let _ = writer.set("key", "value", None).await;
+/// let _ = writer.del("other").await;
+///
+/// writer.flush().await;
+/// println!("{:?}", writer.metrics());
+/// ```
+#[derive(Debug, Clone)]
+pub struct BatchedWriter {
+	sender:  mpsc::Sender<WriterCommand>,
+	metrics: Arc<BatchedWriterMetricsRecorder>,
+}
+
+/// The limits which determine when a `BatchedWriter` flushes its queued
+/// commands. The commands are flushed once any of the limits is reached.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use paper_client::BatchFlushPolicy;
+///
+/// let policy = BatchFlushPolicy::default()
+///     .max_commands(64)
+///     .max_bytes(16 * 1024)
+///     .max_delay(Duration::from_millis(2));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchFlushPolicy {
+	max_commands: usize,
+	max_bytes:    usize,
+	max_delay:    Duration,
+}
+
+/// A snapshot of the commands sent by a `BatchedWriter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchedWriterMetrics {
+	queued:  u64,
+	written: u64,
+	flushes: u64,
+	errors:  u64,
+}
+
+#[derive(Debug, Default)]
+struct BatchedWriterMetricsRecorder {
+	queued:  AtomicU64,
+	written: AtomicU64,
+	flushes: AtomicU64,
+	errors:  AtomicU64,
+}
+
+enum WriterCommand {
+	Set(String, PaperValue, u32),
+	Del(String),
+
+	// notifies the caller once all previously queued commands are sent
+	Flush(oneshot::Sender<()>),
+}
+
+// the commands waiting to be flushed
+struct PendingBatch {
+	commands: Vec<WriterCommand>,
+	bytes:    usize,
+	deadline: Instant,
+}
+
+impl BatchedWriter {
+	/// Creates a writer which sends its commands with the supplied client,
+	/// which should already be authorized if the server requires it. Must
+	/// be called from within a tokio runtime.
+	#[must_use]
+	pub fn new(client: AsyncPaperClient, policy: BatchFlushPolicy) -> Self {
+		let (sender, receiver) = mpsc::channel(BATCHED_WRITER_QUEUE_CAPACITY);
+
+		let metrics = Arc::new(BatchedWriterMetricsRecorder::default());
+		let worker_metrics = metrics.clone();
+
+		// the task stops once every clone of the writer (and so every
+		// sender) is dropped
+		tokio::spawn(run(client, policy, receiver, worker_metrics));
+
+		BatchedWriter {
+			sender,
+			metrics,
+		}
+	}
+
+	/// Queues a `set` of the supplied key, value, and ttl. Waits only if
+	/// too many commands are already queued. See `AsyncPaperClient::set`.
+	pub async fn set(
+		&self,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let value: PaperValue = value
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidValue)?;
+
+		let command = WriterCommand::Set(key.as_paper_key().to_owned(), value, ttl.as_paper_ttl()?);
+		self.queue(command).await
+	}
+
+	/// Queues a `del` of the supplied key. Waits only if too many commands
+	/// are already queued. See `AsyncPaperClient::del`.
+	pub async fn del(&self, key: impl AsPaperKey) -> PaperClientResult<()> {
+		let command = WriterCommand::Del(key.as_paper_key().to_owned());
+		self.queue(command).await
+	}
+
+	/// Flushes the queued commands without waiting for the flush policy,
+	/// and waits until they have been sent and their responses received.
+	pub async fn flush(&self) {
+		let (sender, receiver) = oneshot::channel();
+
+		if self.sender.send(WriterCommand::Flush(sender)).await.is_ok() {
+			let _ = receiver.await;
+		}
+	}
+
+	/// Returns a snapshot of the commands sent so far.
+	#[must_use]
+	pub fn metrics(&self) -> BatchedWriterMetrics {
+		self.metrics.snapshot()
+	}
+
+	async fn queue(&self, command: WriterCommand) -> PaperClientResult<()> {
+		self.sender
+			.send(command)
+			.await
+			.map_err(|_| PaperClientError::Disconnected)?;
+
+		self.metrics.queued.fetch_add(1, Ordering::Relaxed);
+		Ok(())
+	}
+}
+
+impl BatchFlushPolicy {
+	/// Sets the number of queued commands at which they are flushed.
+	/// Defaults to 128.
+	#[must_use]
+	pub fn max_commands(mut self, commands: usize) -> Self {
+		self.max_commands = commands.max(1);
+		self
+	}
+
+	/// Sets the total size (in bytes) of the queued keys and values at
+	/// which they are flushed. Defaults to 64 KiB.
+	#[must_use]
+	pub fn max_bytes(mut self, bytes: usize) -> Self {
+		self.max_bytes = bytes;
+		self
+	}
+
+	/// Sets the maximum time a command is queued before it is flushed,
+	/// which bounds how stale a read of the command's key can be. Defaults
+	/// to 5 milliseconds.
+	#[must_use]
+	pub fn max_delay(mut self, delay: Duration) -> Self {
+		self.max_delay = delay;
+		self
+	}
+}
+
+impl Default for BatchFlushPolicy {
+	fn default() -> Self {
+		BatchFlushPolicy {
+			max_commands: DEFAULT_MAX_COMMANDS,
+			max_bytes:    DEFAULT_MAX_BYTES,
+			max_delay:    DEFAULT_MAX_DELAY,
+		}
+	}
+}
+
+impl BatchedWriterMetrics {
+	/// Returns the number of commands queued.
+	#[must_use]
+	pub fn queued(&self) -> u64 {
+		self.queued
+	}
+
+	/// Returns the number of queued commands which have been sent
+	/// (including those which failed).
+	#[must_use]
+	pub fn written(&self) -> u64 {
+		self.written
+	}
+
+	/// Returns the number of batches sent.
+	#[must_use]
+	pub fn flushes(&self) -> u64 {
+		self.flushes
+	}
+
+	/// Returns the number of queued commands which failed.
+	#[must_use]
+	pub fn errors(&self) -> u64 {
+		self.errors
+	}
+}
+
+impl BatchedWriterMetricsRecorder {
+	fn record(
+		&self,
+		commands: usize,
+		result: PaperClientResult<Vec<PaperClientResult<BatchResponse>>>,
+	) {
+		self.flushes.fetch_add(1, Ordering::Relaxed);
+
+		let errors = match result {
+			Ok(responses) => responses
+				.into_iter()
+				.filter(|response| !is_applied(response))
+				.count(),
+
+			// the responses to the batch could not be read
+			Err(_) => commands,
+		};
+
+		self.written.fetch_add(commands as u64, Ordering::Relaxed);
+		self.errors.fetch_add(errors as u64, Ordering::Relaxed);
+	}
+
+	fn snapshot(&self) -> BatchedWriterMetrics {
+		BatchedWriterMetrics {
+			queued:  self.queued.load(Ordering::Relaxed),
+			written: self.written.load(Ordering::Relaxed),
+			flushes: self.flushes.load(Ordering::Relaxed),
+			errors:  self.errors.load(Ordering::Relaxed),
+		}
+	}
+}
+
+impl PendingBatch {
+	fn new() -> Self {
+		PendingBatch {
+			commands: Vec::new(),
+			bytes:    0,
+			deadline: Instant::now(),
+		}
+	}
+
+	fn push(&mut self, command: WriterCommand, policy: &BatchFlushPolicy) {
+		if self.commands.is_empty() {
+			self.deadline = Instant::now() + policy.max_delay;
+		}
+
+		self.bytes += command.size();
+		self.commands.push(command);
+	}
+
+	fn is_full(&self, policy: &BatchFlushPolicy) -> bool {
+		self.commands.len() >= policy.max_commands || self.bytes >= policy.max_bytes
+	}
+
+	async fn flush(
+		&mut self,
+		client: &mut AsyncPaperClient,
+		metrics: &BatchedWriterMetricsRecorder,
+	) {
+		if self.commands.is_empty() {
+			return;
+		}
+
+		let commands = std::mem::take(&mut self.commands);
+		self.bytes = 0;
+
+		let result = client
+			.batch(|batch| {
+				for command in &commands {
+					command.add_to(batch);
+				}
+			})
+			.await;
+
+		metrics.record(commands.len(), result);
+	}
+}
+
+impl WriterCommand {
+	fn size(&self) -> usize {
+		match self {
+			WriterCommand::Set(key, value, _) => key.len() + value.len(),
+			WriterCommand::Del(key) => key.len(),
+			WriterCommand::Flush(_) => 0,
+		}
+	}
+
+	fn add_to(&self, batch: &mut Batch) {
+		match self {
+			WriterCommand::Set(key, value, ttl) => {
+				batch.set(key, value.clone(), Ttl::from_secs(*ttl));
+			},
+
+			WriterCommand::Del(key) => {
+				batch.del(key);
+			},
+
+			WriterCommand::Flush(_) => {},
+		}
+	}
+}
+
+impl Debug for WriterCommand {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			WriterCommand::Set(key, _, ttl) => write!(f, "Set({key:?}, {ttl})"),
+			WriterCommand::Del(key) => write!(f, "Del({key:?})"),
+			WriterCommand::Flush(_) => write!(f, "Flush"),
+		}
+	}
+}
+
+async fn run(
+	mut client: AsyncPaperClient,
+	policy: BatchFlushPolicy,
+	mut receiver: mpsc::Receiver<WriterCommand>,
+	metrics: Arc<BatchedWriterMetricsRecorder>,
+) {
+	let mut pending = PendingBatch::new();
+
+	loop {
+		let command = match pending.commands.is_empty() {
+			true => receiver.recv().await,
+
+			false => match time::timeout_at(pending.deadline, receiver.recv()).await {
+				Ok(command) => command,
+
+				Err(_) => {
+					pending.flush(&mut client, &metrics).await;
+					continue;
+				},
+			},
+		};
+
+		match command {
+			Some(WriterCommand::Flush(notify)) => {
+				pending.flush(&mut client, &metrics).await;
+				let _ = notify.send(());
+			},
+
+			Some(command) => {
+				pending.push(command, &policy);
+
+				if pending.is_full(&policy) {
+					pending.flush(&mut client, &metrics).await;
+				}
+			},
+
+			None => {
+				pending.flush(&mut client, &metrics).await;
+				break;
+			},
+		}
+	}
+}
+
+// a del of a key which was not found has the intended effect
+fn is_applied(response: &PaperClientResult<BatchResponse>) -> bool {
+	matches!(
+		response,
+		Ok(_) | Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)),
+	)
+}
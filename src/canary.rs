@@ -0,0 +1,228 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::time::Duration;
+
+#[cfg(feature = "tokio")]
+use tokio::time;
+
+#[cfg(feature = "tokio")]
+use crate::{async_client::AsyncPaperClient, transport::AsyncPaperTransport};
+use crate::{
+	client::PaperClient,
+	error::PaperClientResult,
+	policy::PaperPolicy,
+	status::Status,
+	transport::PaperTransport,
+};
+
+// the reason recorded in the audit events of the experiment's policy changes
+const CANARY_REASON: &str = "policy canary experiment";
+
+/// The result of a policy canary experiment, comparing the miss ratio of
+/// the canary (running the candidate policy) with that of the baseline
+/// over the experiment's window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyComparison {
+	policy:          PaperPolicy,
+	baseline_policy: PaperPolicy,
+
+	baseline: WindowStats,
+	canary:   WindowStats,
+}
+
+/// The gets and misses of a cache over the window of an experiment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowStats {
+	gets:   u64,
+	misses: u64,
+}
+
+impl PolicyComparison {
+	/// Returns the candidate policy applied to the canary.
+	#[must_use]
+	pub fn policy(&self) -> &PaperPolicy {
+		&self.policy
+	}
+
+	/// Returns the policy of the baseline (`PaperPolicy::Auto` if the
+	/// baseline selects its policy automatically).
+	#[must_use]
+	pub fn baseline_policy(&self) -> &PaperPolicy {
+		&self.baseline_policy
+	}
+
+	/// Returns the baseline's gets and misses over the window.
+	#[must_use]
+	pub fn baseline(&self) -> WindowStats {
+		self.baseline
+	}
+
+	/// Returns the canary's gets and misses over the window.
+	#[must_use]
+	pub fn canary(&self) -> WindowStats {
+		self.canary
+	}
+
+	/// Returns the canary's miss ratio minus the baseline's, or `None` if
+	/// either received no gets over the window. A negative delta means the
+	/// candidate policy missed less often than the baseline's policy.
+	#[must_use]
+	pub fn miss_ratio_delta(&self) -> Option<f64> {
+		Some(self.canary.miss_ratio()? - self.baseline.miss_ratio()?)
+	}
+}
+
+impl WindowStats {
+	fn between(start: &Status, end: &Status) -> Self {
+		WindowStats {
+			gets:   end.total_gets().saturating_sub(start.total_gets()),
+			misses: misses(end).saturating_sub(misses(start)),
+		}
+	}
+
+	/// Returns the number of gets.
+	#[must_use]
+	pub fn gets(&self) -> u64 {
+		self.gets
+	}
+
+	/// Returns the number of gets which missed.
+	#[must_use]
+	pub fn misses(&self) -> u64 {
+		self.misses
+	}
+
+	/// Returns the ratio of misses to gets, or `None` if there were no
+	/// gets.
+	#[must_use]
+	pub fn miss_ratio(&self) -> Option<f64> {
+		match self.gets {
+			0 => None,
+			gets => Some(self.misses as f64 / gets as f64),
+		}
+	}
+}
+
+/// Runs a policy canary experiment: applies the supplied candidate policy
+/// to the canary, samples the status of both caches at the start and end
+/// of the supplied window, and compares their miss ratios over the window.
+/// The canary's previous policy is restored once the window has passed.
+///
+/// The baseline and canary are expected to serve comparable traffic (e.g.,
+/// two instances behind the same sharder), and the window to be long
+/// enough for the candidate policy to warm up.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use paper_client::{PaperClient, PaperPolicy, run_policy_canary};
+///
+/// let mut baseline = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+/// let mut canary = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+///
+/// let window = Duration::from_millis(100);
+///
+/// match run_policy_canary(&mut baseline, &mut canary, PaperPolicy::Lru, window) {
+///     Ok(comparison) => println!("{:?}", comparison.miss_ratio_delta()),
+///     Err(err) => println!("{err:?}"),
+/// }
+/// ```
+pub fn run_policy_canary<B, C>(
+	baseline: &mut PaperClient<B>,
+	canary: &mut PaperClient<C>,
+	policy: PaperPolicy,
+	window: Duration,
+) -> PaperClientResult<PolicyComparison>
+where
+	B: PaperTransport,
+	C: PaperTransport,
+{
+	let previous = current_policy(&canary.status()?);
+	canary.policy_with_reason(policy.clone(), CANARY_REASON)?;
+
+	let baseline_start = baseline.status()?;
+	let canary_start = canary.status()?;
+
+	canary.config().clock.sleep(window);
+
+	let baseline_end = baseline.status()?;
+	let canary_end = canary.status()?;
+
+	canary.policy_with_reason(previous, CANARY_REASON)?;
+
+	Ok(PolicyComparison {
+		policy,
+		baseline_policy: current_policy(&baseline_end),
+
+		baseline: WindowStats::between(&baseline_start, &baseline_end),
+		canary:   WindowStats::between(&canary_start, &canary_end),
+	})
+}
+
+/// The async counterpart of [`run_policy_canary`].
+///
+/// # Examples
+/// ```ignore
+/// use std::time::Duration;
+/// use paper_client::{AsyncPaperClient, PaperPolicy, run_policy_canary_async};
+///
+/// let mut baseline = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+/// let mut canary = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+///
+/// let window = Duration::from_secs(600);
+///
+/// match run_policy_canary_async(&mut baseline, &mut canary, PaperPolicy::Lru, window).await {
+///     Ok(comparison) => println!("{:?}", comparison.miss_ratio_delta()),
+///     Err(err) => println!("{err:?}"),
+/// }
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn run_policy_canary_async<B, C>(
+	baseline: &mut AsyncPaperClient<B>,
+	canary: &mut AsyncPaperClient<C>,
+	policy: PaperPolicy,
+	window: Duration,
+) -> PaperClientResult<PolicyComparison>
+where
+	B: AsyncPaperTransport,
+	C: AsyncPaperTransport,
+{
+	let previous = current_policy(&canary.status().await?);
+	canary.policy_with_reason(policy.clone(), CANARY_REASON).await?;
+
+	let baseline_start = baseline.status().await?;
+	let canary_start = canary.status().await?;
+
+	time::sleep(window).await;
+
+	let baseline_end = baseline.status().await?;
+	let canary_end = canary.status().await?;
+
+	canary.policy_with_reason(previous, CANARY_REASON).await?;
+
+	Ok(PolicyComparison {
+		policy,
+		baseline_policy: current_policy(&baseline_end),
+
+		baseline: WindowStats::between(&baseline_start, &baseline_end),
+		canary:   WindowStats::between(&canary_start, &canary_end),
+	})
+}
+
+fn current_policy(status: &Status) -> PaperPolicy {
+	match status.is_auto_policy() {
+		true => PaperPolicy::Auto,
+		false => status.policy().clone(),
+	}
+}
+
+// the status only reports the miss ratio, so the number of misses is
+// recovered from it
+fn misses(status: &Status) -> u64 {
+	(status.miss_ratio() * status.total_gets() as f64).round() as u64
+}
@@ -0,0 +1,232 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+#[cfg(feature = "tokio")]
+use std::{
+	pin::Pin,
+	task::{Context, Poll},
+};
+use std::{
+	fmt::{self, Debug, Display},
+	io::{self, Read, Write},
+	sync::Mutex,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// The direction of a captured frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureDirection {
+	Sent,
+	Received,
+}
+
+/// A capture of the raw bytes exchanged with the server, for reporting
+/// protocol-level issues with an exact byte trace.
+///
+/// Each write of a command and each read of its response is written to the
+/// supplied writer as a line consisting of the Unix timestamp (with
+/// microsecond precision), the direction (`>>` for sent and `<<` for
+/// received), the server's address, and the bytes in hex:
+///
+/// ```text
+/// 1760000000.000000 >> 127.0.0.1:3145 1000000003000000...
+/// ```
+///
+/// The handshake which precedes the first command is not captured. Keys are
+/// shown in the sent frames as the client's `KeyDisplay` requires (see
+/// `PaperClientConfig::key_display`), in which case each command is
+/// captured as a whole rather than as it is written. The token of an auth
+/// command is always captured as `<redacted>`, whatever the key display.
+/// Failures to write to the capture's writer are ignored.
+///
+/// # Examples
+/// ```
+/// use std::io;
+/// use paper_client::{PaperClient, PaperClientConfig, ProtocolCapture};
+///
+/// let capture = ProtocolCapture::new(io::stderr());
+/// let config = PaperClientConfig::default().capture(capture);
+///
+/// let mut client = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+/// let _ = client.ping();
+/// ```
+pub struct ProtocolCapture {
+	writer: Mutex<Box<dyn Write + Send>>,
+}
+
+/// A stream which writes the bytes read from and written to the underlying
+/// stream to a `ProtocolCapture`, if any.
+pub(crate) struct CaptureStream<'a, S> {
+	stream:  &'a mut S,
+	capture: Option<&'a ProtocolCapture>,
+	addr:    &'a str,
+
+	is_sent_captured: bool,
+}
+
+impl ProtocolCapture {
+	/// Creates a new capture which writes to the supplied writer.
+	#[must_use]
+	pub fn new(writer: impl Write + Send + 'static) -> Self {
+		ProtocolCapture {
+			writer: Mutex::new(Box::new(writer)),
+		}
+	}
+
+	pub(crate) fn record(&self, addr: &str, direction: CaptureDirection, bytes: &[u8]) {
+		if bytes.is_empty() {
+			return;
+		}
+
+		let timestamp = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.unwrap_or_default();
+
+		let hex = bytes
+			.iter()
+			.map(|byte| format!("{byte:02x}"))
+			.collect::<String>();
+
+		let Ok(mut writer) = self.writer.lock() else {
+			return;
+		};
+
+		let _ = writeln!(
+			writer,
+			"{}.{:06} {direction} {addr} {hex}",
+			timestamp.as_secs(),
+			timestamp.subsec_micros(),
+		);
+
+		let _ = writer.flush();
+	}
+}
+
+impl<'a, S> CaptureStream<'a, S> {
+	pub(crate) fn new(
+		stream: &'a mut S,
+		capture: Option<&'a ProtocolCapture>,
+		addr: &'a str,
+	) -> Self {
+		CaptureStream {
+			stream,
+			capture,
+			addr,
+
+			is_sent_captured: true,
+		}
+	}
+
+	/// Stops the stream from recording the bytes written to it, for when
+	/// the sent bytes have already been recorded in another form (e.g.,
+	/// with their keys redacted).
+	pub(crate) fn without_sent(mut self) -> Self {
+		self.is_sent_captured = false;
+		self
+	}
+
+	pub(crate) fn record_sent(&self, bytes: &[u8]) {
+		self.record(CaptureDirection::Sent, bytes);
+	}
+
+	fn record(&self, direction: CaptureDirection, bytes: &[u8]) {
+		if let Some(capture) = self.capture {
+			capture.record(self.addr, direction, bytes);
+		}
+	}
+}
+
+impl<S: Read> Read for CaptureStream<'_, S> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let size = self.stream.read(buf)?;
+		self.record(CaptureDirection::Received, &buf[..size]);
+
+		Ok(size)
+	}
+}
+
+impl<S: Write> Write for CaptureStream<'_, S> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let size = self.stream.write(buf)?;
+
+		if self.is_sent_captured {
+			self.record(CaptureDirection::Sent, &buf[..size]);
+		}
+
+		Ok(size)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.stream.flush()
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl<S: AsyncRead + Unpin> AsyncRead for CaptureStream<'_, S> {
+	fn poll_read(
+		self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &mut ReadBuf<'_>,
+	) -> Poll<io::Result<()>> {
+		let this = self.get_mut();
+		let filled = buf.filled().len();
+
+		let result = Pin::new(&mut *this.stream).poll_read(cx, buf);
+
+		if let Poll::Ready(Ok(())) = result {
+			this.record(CaptureDirection::Received, &buf.filled()[filled..]);
+		}
+
+		result
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl<S: AsyncWrite + Unpin> AsyncWrite for CaptureStream<'_, S> {
+	fn poll_write(
+		self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &[u8],
+	) -> Poll<io::Result<usize>> {
+		let this = self.get_mut();
+		let result = Pin::new(&mut *this.stream).poll_write(cx, buf);
+
+		if let Poll::Ready(Ok(size)) = result
+			&& this.is_sent_captured
+		{
+			this.record(CaptureDirection::Sent, &buf[..size]);
+		}
+
+		result
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Pin::new(&mut *self.get_mut().stream).poll_flush(cx)
+	}
+
+	fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Pin::new(&mut *self.get_mut().stream).poll_shutdown(cx)
+	}
+}
+
+impl Display for CaptureDirection {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			CaptureDirection::Sent => write!(f, ">>"),
+			CaptureDirection::Received => write!(f, "<<"),
+		}
+	}
+}
+
+impl Debug for ProtocolCapture {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ProtocolCapture").finish_non_exhaustive()
+	}
+}
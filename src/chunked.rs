@@ -0,0 +1,385 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	hash::{BuildHasher, RandomState},
+	net::TcpStream,
+	time::SystemTime,
+};
+
+#[cfg(feature = "tokio")]
+use crate::async_client::AsyncPaperClient;
+#[cfg(feature = "tokio")]
+use crate::transport::AsyncPaperTransport;
+use crate::{
+	arg::{AsPaperKey, AsPaperTtl},
+	client::PaperClient,
+	error::{PaperCacheError, PaperClientError, PaperClientResult},
+	transport::PaperTransport,
+	ttl::Ttl,
+	value::PaperValue,
+};
+
+/// The suffix appended to a key to form the keys of its chunks, followed
+/// by the identifier of the value and the index of the chunk.
+pub const CHUNK_KEY_SUFFIX: &str = "#chunk";
+
+// the prefix of a manifest, which begins with a NUL byte so that it is
+// unlikely to be confused with a value set by a caller
+const MANIFEST_PREFIX: &[u8] = b"\0paper-chunks\0";
+const MANIFEST_LEN: usize = MANIFEST_PREFIX.len() + 8 + 4 + 8;
+
+/// A client which splits each value larger than the supplied chunk size
+/// into chunks, each set under its own key, so that rare large values do
+/// not fail with `ExceedingValueSize`. The value's key then holds a
+/// manifest of the chunks, from which `get` reassembles the value and with
+/// which `del` removes the chunks. Values which fit in a single chunk are
+/// set as is.
+///
+/// The chunks are set before their manifest, and every value is chunked
+/// under a new identifier, so a `get` never combines the chunks of two
+/// different values. The chunks share their value's ttl. If any chunk has
+/// been evicted, the value is treated as missing and `get` fails with
+/// `KeyNotFound`. Chunked values must only be read and written through a
+/// chunked client, as any other client sees the manifest instead.
+///
+/// # Examples
+/// ```
+/// use paper_client::{ChunkedClient, PaperClient};
+///
+/// let client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+/// let mut client = ChunkedClient::new(client, 1024 * 1024);
+///
+/// let _ = client.set("key", vec![0u8; 4 * 1024 * 1024], None);
+/// let _ = client.get("key");
+/// ```
+#[derive(Debug)]
+pub struct ChunkedClient<T = TcpStream> {
+	client:     PaperClient<T>,
+	chunk_size: usize,
+}
+
+/// The async counterpart of `ChunkedClient`.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncChunkedClient<T = tokio::net::TcpStream> {
+	client:     AsyncPaperClient<T>,
+	chunk_size: usize,
+}
+
+// the chunks of a single value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Manifest {
+	id:    u64,
+	count: u32,
+	len:   u64,
+}
+
+impl<T: PaperTransport> ChunkedClient<T> {
+	/// Wraps the supplied client, splitting values larger than the supplied
+	/// chunk size (in bytes, at least 1) into chunks.
+	#[must_use]
+	pub fn new(client: PaperClient<T>, chunk_size: usize) -> Self {
+		ChunkedClient {
+			client,
+			chunk_size: chunk_size.max(1),
+		}
+	}
+
+	/// Gets the value of the supplied key from the cache, reassembling it
+	/// from its chunks if it was chunked. See `PaperClient::get`.
+	pub fn get(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = key.as_paper_key();
+		let value = self.client.get(key)?;
+
+		let Some(manifest) = Manifest::decode(&value) else {
+			return Ok(value);
+		};
+
+		let mut buf = Vec::with_capacity(manifest.len as usize);
+
+		for index in 0..manifest.count {
+			let chunk = self.client.get(manifest.chunk_key(key, index))?;
+			buf.extend_from_slice(&chunk);
+		}
+
+		manifest.reassemble(buf)
+	}
+
+	/// Sets the supplied key, value, and ttl to the cache, chunking the
+	/// value if it is larger than the chunk size. The chunks of the key's
+	/// previous value, if any, are deleted once the new value has been
+	/// set. See `PaperClient::set`.
+	pub fn set(
+		&mut self,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let ttl = ttl.as_paper_ttl()?;
+
+		let value: PaperValue = value
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidValue)?;
+
+		let previous = self.manifest(key)?;
+
+		match value.len() > self.chunk_size {
+			true => {
+				let manifest = Manifest::new(key, value.len(), self.chunk_size);
+
+				for (index, chunk) in value.chunks(self.chunk_size).enumerate() {
+					let chunk_key = manifest.chunk_key(key, index as u32);
+					self.client.set(chunk_key, chunk, Ttl::from_secs(ttl))?;
+				}
+
+				self.client.set(key, manifest.encode(), Ttl::from_secs(ttl))?;
+			},
+
+			false => self.client.set(key, value, Ttl::from_secs(ttl))?,
+		}
+
+		match previous {
+			Some(previous) => self.del_chunks(key, previous),
+			None => Ok(()),
+		}
+	}
+
+	/// Deletes the value of the supplied key from the cache, along with its
+	/// chunks if it was chunked. See `PaperClient::del`.
+	pub fn del(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let manifest = self.manifest(key)?;
+
+		self.client.del(key)?;
+
+		match manifest {
+			Some(manifest) => self.del_chunks(key, manifest),
+			None => Ok(()),
+		}
+	}
+
+	/// Returns a reference to the underlying client.
+	#[must_use]
+	pub fn inner(&self) -> &PaperClient<T> {
+		&self.client
+	}
+
+	/// Returns a mutable reference to the underlying client. Commands
+	/// issued through it are not chunked.
+	#[must_use]
+	pub fn inner_mut(&mut self) -> &mut PaperClient<T> {
+		&mut self.client
+	}
+
+	/// Unwraps the underlying client.
+	#[must_use]
+	pub fn into_inner(self) -> PaperClient<T> {
+		self.client
+	}
+
+	// the manifest held by the supplied key, which is peeked so that
+	// checking for it does not affect the key's eviction
+	fn manifest(&mut self, key: &str) -> PaperClientResult<Option<Manifest>> {
+		match self.client.peek(key) {
+			Ok(value) => Ok(Manifest::decode(&value)),
+			Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => Ok(None),
+			Err(err) => Err(err),
+		}
+	}
+
+	fn del_chunks(&mut self, key: &str, manifest: Manifest) -> PaperClientResult<()> {
+		for index in 0..manifest.count {
+			match self.client.del(manifest.chunk_key(key, index)) {
+				Ok(_) | Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => {},
+				Err(err) => return Err(err),
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl<T: AsyncPaperTransport> AsyncChunkedClient<T> {
+	/// Wraps the supplied client, splitting values larger than the supplied
+	/// chunk size (in bytes, at least 1) into chunks.
+	#[must_use]
+	pub fn new(client: AsyncPaperClient<T>, chunk_size: usize) -> Self {
+		AsyncChunkedClient {
+			client,
+			chunk_size: chunk_size.max(1),
+		}
+	}
+
+	/// Gets the value of the supplied key from the cache, reassembling it
+	/// from its chunks if it was chunked. See `AsyncPaperClient::get`.
+	pub async fn get(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = key.as_paper_key();
+		let value = self.client.get(key).await?;
+
+		let Some(manifest) = Manifest::decode(&value) else {
+			return Ok(value);
+		};
+
+		let mut buf = Vec::with_capacity(manifest.len as usize);
+
+		for index in 0..manifest.count {
+			let chunk = self.client.get(manifest.chunk_key(key, index)).await?;
+			buf.extend_from_slice(&chunk);
+		}
+
+		manifest.reassemble(buf)
+	}
+
+	/// Sets the supplied key, value, and ttl to the cache, chunking the
+	/// value if it is larger than the chunk size. See `ChunkedClient::set`.
+	pub async fn set(
+		&mut self,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let ttl = ttl.as_paper_ttl()?;
+
+		let value: PaperValue = value
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidValue)?;
+
+		let previous = self.manifest(key).await?;
+
+		match value.len() > self.chunk_size {
+			true => {
+				let manifest = Manifest::new(key, value.len(), self.chunk_size);
+
+				for (index, chunk) in value.chunks(self.chunk_size).enumerate() {
+					let chunk_key = manifest.chunk_key(key, index as u32);
+					self.client.set(chunk_key, chunk, Ttl::from_secs(ttl)).await?;
+				}
+
+				self.client.set(key, manifest.encode(), Ttl::from_secs(ttl)).await?;
+			},
+
+			false => self.client.set(key, value, Ttl::from_secs(ttl)).await?,
+		}
+
+		match previous {
+			Some(previous) => self.del_chunks(key, previous).await,
+			None => Ok(()),
+		}
+	}
+
+	/// Deletes the value of the supplied key from the cache, along with its
+	/// chunks if it was chunked. See `AsyncPaperClient::del`.
+	pub async fn del(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let manifest = self.manifest(key).await?;
+
+		self.client.del(key).await?;
+
+		match manifest {
+			Some(manifest) => self.del_chunks(key, manifest).await,
+			None => Ok(()),
+		}
+	}
+
+	/// Returns a reference to the underlying client.
+	#[must_use]
+	pub fn inner(&self) -> &AsyncPaperClient<T> {
+		&self.client
+	}
+
+	/// Returns a mutable reference to the underlying client. Commands
+	/// issued through it are not chunked.
+	#[must_use]
+	pub fn inner_mut(&mut self) -> &mut AsyncPaperClient<T> {
+		&mut self.client
+	}
+
+	/// Unwraps the underlying client.
+	#[must_use]
+	pub fn into_inner(self) -> AsyncPaperClient<T> {
+		self.client
+	}
+
+	async fn manifest(&mut self, key: &str) -> PaperClientResult<Option<Manifest>> {
+		match self.client.peek(key).await {
+			Ok(value) => Ok(Manifest::decode(&value)),
+			Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => Ok(None),
+			Err(err) => Err(err),
+		}
+	}
+
+	async fn del_chunks(&mut self, key: &str, manifest: Manifest) -> PaperClientResult<()> {
+		for index in 0..manifest.count {
+			match self.client.del(manifest.chunk_key(key, index)).await {
+				Ok(_) | Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => {},
+				Err(err) => return Err(err),
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl Manifest {
+	fn new(key: &str, len: usize, chunk_size: usize) -> Self {
+		// distinguishes the chunks of this value from those of any other
+		// value set under the same key
+		let id = RandomState::new().hash_one((key, SystemTime::now()));
+
+		Manifest {
+			id,
+			count: len.div_ceil(chunk_size) as u32,
+			len: len as u64,
+		}
+	}
+
+	fn chunk_key(&self, key: &str, index: u32) -> String {
+		format!("{key}{CHUNK_KEY_SUFFIX}-{:016x}-{index}", self.id)
+	}
+
+	// a chunk evicted and then set again by another value cannot be
+	// reassembled, so a value of the wrong length is treated as missing
+	fn reassemble(&self, buf: Vec<u8>) -> PaperClientResult<PaperValue> {
+		match buf.len() as u64 == self.len {
+			true => Ok(buf.into()),
+			false => Err(PaperCacheError::KeyNotFound.into()),
+		}
+	}
+
+	fn encode(&self) -> Vec<u8> {
+		let mut buf = Vec::with_capacity(MANIFEST_LEN);
+
+		buf.extend_from_slice(MANIFEST_PREFIX);
+		buf.extend_from_slice(&self.id.to_le_bytes());
+		buf.extend_from_slice(&self.count.to_le_bytes());
+		buf.extend_from_slice(&self.len.to_le_bytes());
+
+		buf
+	}
+
+	fn decode(value: &[u8]) -> Option<Self> {
+		if value.len() != MANIFEST_LEN {
+			return None;
+		}
+
+		let fields = value.strip_prefix(MANIFEST_PREFIX)?;
+
+		let (id, fields) = fields.split_first_chunk::<8>()?;
+		let (count, fields) = fields.split_first_chunk::<4>()?;
+		let (len, _) = fields.split_first_chunk::<8>()?;
+
+		Some(Manifest {
+			id:    u64::from_le_bytes(*id),
+			count: u32::from_le_bytes(*count),
+			len:   u64::from_le_bytes(*len),
+		})
+	}
+}
@@ -5,30 +5,91 @@
  * LICENSE file in the root directory of this source tree.
  */
 
-use std::net::TcpStream;
+use std::{
+	fmt::{self, Debug, Display},
+	io::{self, Write},
+	net::TcpStream,
+	path::Path,
+	process,
+	slice,
+	sync::Arc,
+	time::{Duration, Instant},
+};
 
+use paper_utils::stream;
 pub use paper_utils::stream::{StreamError, StreamReader};
 
 use crate::{
-	addr::FromPaperAddr,
-	arg::{AsPaperAuthToken, AsPaperKey},
+	addr::{DisplayAddr, FromPaperAddr},
+	arg::{AsPaperAuthToken, AsPaperKey, AsPaperTtl},
+	auth::{AuthTokenProvider, StaticTokenProvider},
+	batch::{Batch, BatchResponse},
+	capture::CaptureStream,
 	command::Command,
-	error::{PaperClientError, PaperClientResult},
+	config::PaperClientConfig,
+	connection_info::{ConnectionInfo, PeerAddr},
+	deadline,
+	decode::DecodePaperResponse,
+	error::{PaperCacheError, PaperClientError, PaperClientResult},
+	get_result::GetResult,
+	health::NodeHealth,
+	key_display::KeyDisplay,
+	metrics::{ClientStats, ConnectionStats},
+	numeric::NumericValue,
+	observer::{AuditCommand, AuditEvent, PaperEvent},
 	policy::PaperPolicy,
+	range::RangeWriter,
+	response::{Ack, Custom, DecodeResponse, Flag, IntoSlice, IntoVec, Size, StatusFrame, ToWriter, Value},
+	sequence::{self, SequenceToken, Sequencer},
+	settings::PaperSettings,
+	singleflight::{self, LOCK_POLL_INTERVAL, LOCK_TTL},
+	stale::{self, StaleResult, STALE_MARKER_VALUE},
 	status::Status,
+	tenant::{Tenant, TenantUsage},
+	transport::PaperTransport,
 	value::PaperValue,
 };
 
 const RECONNECT_MAX_ATTEMPTS: u8 = 3;
+const MIN_READ_TIMEOUT: Duration = Duration::from_millis(1);
+
+// the maximum delay between polls of wait_for, as a multiple of the poll interval
+const MAX_WAIT_BACKOFF_FACTOR: u32 = 8;
 
-#[derive(Debug)]
-pub struct PaperClient {
-	addr: String,
+// the maximum number of keys deleted by a single batch of del_prefix
+const DEL_PREFIX_BATCH_SIZE: usize = 256;
 
-	auth_token:         Option<String>,
+pub struct PaperClient<T = TcpStream> {
+	addr:   String,
+	config: PaperClientConfig,
+
+	auth_provider:      Option<Arc<dyn AuthTokenProvider>>,
 	reconnect_attempts: u8,
+	is_closed:          bool,
+
+	// bounds each command, defaulting to the config's command timeout
+	command_timeout: Option<Duration>,
+
+	// set while a command's response has not been fully read, in which
+	// case the connection must be re-established before the next command
+	is_stale: bool,
+
+	// the token with which a reconnected connection is authorized, which
+	// is sent along with the next command rather than on its own
+	pending_auth: Option<String>,
+
+	// the process which opened the connection, which differs from the
+	// current process once the client has crossed a fork
+	pid: u32,
+
+	has_read_timeout: bool,
 
-	stream: TcpStream,
+	sequencer: Arc<Sequencer>,
+
+	health: Option<Arc<NodeHealth>>,
+	stats:  Arc<ConnectionStats>,
+
+	stream: T,
 }
 
 impl PaperClient {
@@ -43,21 +104,187 @@ impl PaperClient {
 	/// let client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
 	/// ```
 	pub fn new(paper_addr: impl FromPaperAddr) -> PaperClientResult<Self> {
+		PaperClient::with_config(paper_addr, PaperClientConfig::default())
+	}
+
+	/// Creates a new instance of the client with the supplied configuration
+	/// and connects to the server. If a connection could not be established,
+	/// a `PaperClientError` is returned.
+	///
+	/// # Examples
+	/// ```
+	/// use std::time::Duration;
+	/// use paper_client::{PaperClient, PaperClientConfig};
+	///
+	/// let config = PaperClientConfig::default()
+	///     .handshake_timeout(Some(Duration::from_secs(1)))
+	///     .connect_retries(3);
+	///
+	/// let client = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+	/// ```
+	pub fn with_config(
+		paper_addr: impl FromPaperAddr,
+		config: PaperClientConfig,
+	) -> PaperClientResult<Self> {
+		let addr = paper_addr.to_addr()?;
+		PaperClient::connect(addr, config)
+	}
+
+	/// Creates a new instance of the client using the settings read from the
+	/// environment (see [`PaperSettings`]), and authorizes the client if
+	/// an auth token was supplied.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// match PaperClient::from_env() {
+	///     Ok(_) => println!("connected"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn from_env() -> PaperClientResult<Self> {
+		PaperClient::from_settings(PaperSettings::from_env()?)
+	}
+
+	/// Creates a new instance of the client using the settings read from the
+	/// TOML file at the supplied path (see [`PaperSettings`]), and authorizes
+	/// the client if an auth token was supplied.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// match PaperClient::from_config("paper.toml") {
+	///     Ok(_) => println!("connected"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn from_config(path: impl AsRef<Path>) -> PaperClientResult<Self> {
+		PaperClient::from_settings(PaperSettings::from_file(path)?)
+	}
+
+	/// Creates a new instance of the client using the supplied settings, and
+	/// authorizes the client if an auth token was supplied.
+	pub fn from_settings(settings: PaperSettings) -> PaperClientResult<Self> {
+		let mut client = PaperClient::with_config(settings.addr(), settings.config().clone())?;
+
+		if let Some(token) = settings.auth_token() {
+			client.auth(token)?;
+		}
+
+		Ok(client)
+	}
+}
+
+impl<T: PaperTransport> PaperClient<T> {
+	/// Creates a new instance of the client with the supplied configuration
+	/// and connects to the server over the transport `T`. If a connection
+	/// could not be established, a `PaperClientError` is returned.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use std::os::unix::net::UnixStream;
+	/// use paper_client::{PaperClient, PaperClientConfig};
+	///
+	/// let client = PaperClient::<UnixStream>::with_transport(
+	///     "paper:///tmp/paper.sock",
+	///     PaperClientConfig::default(),
+	/// )
+	/// .unwrap();
+	/// ```
+	pub fn with_transport(
+		paper_addr: impl FromPaperAddr,
+		config: PaperClientConfig,
+	) -> PaperClientResult<Self> {
+		let addr = paper_addr.to_addr()?;
+		PaperClient::connect(addr, config)
+	}
+
+	/// Creates a new instance of the client over an already established
+	/// transport, and waits for the server's handshake. The supplied
+	/// address is used if the client needs to reconnect.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{PaperClient, ScriptedResponder};
+	///
+	/// let responder = ScriptedResponder::new();
+	///
+	/// let client = PaperClient::from_transport(
+	///     "paper://loopback",
+	///     responder.transport(),
+	/// )
+	/// .unwrap();
+	/// ```
+	pub fn from_transport(
+		paper_addr: impl FromPaperAddr,
+		transport: T,
+	) -> PaperClientResult<Self> {
+		let config = PaperClientConfig::default();
+		PaperClient::from_transport_with_config(paper_addr, transport, config)
+	}
+
+	/// Creates a new instance of the client over an already established
+	/// transport with the supplied configuration, and waits for the
+	/// server's handshake.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{PaperClient, PaperClientConfig, ScriptedResponder};
+	///
+	/// let responder = ScriptedResponder::new();
+	///
+	/// let client = PaperClient::from_transport_with_config(
+	///     "paper://loopback",
+	///     responder.transport(),
+	///     PaperClientConfig::default(),
+	/// )
+	/// .unwrap();
+	/// ```
+	pub fn from_transport_with_config(
+		paper_addr: impl FromPaperAddr,
+		mut transport: T,
+		config: PaperClientConfig,
+	) -> PaperClientResult<Self> {
 		let addr = paper_addr.to_addr()?;
-		let stream = init_stream(&addr)?;
 
-		let mut client = PaperClient {
+		handshake(&mut transport, config.handshake_timeout)?;
+
+		Ok(PaperClient::from_stream(addr, config, transport))
+	}
+
+	pub(crate) fn connect(addr: String, config: PaperClientConfig) -> PaperClientResult<Self> {
+		let stream = init_stream(&addr, &config)?;
+
+		Ok(PaperClient::from_stream(addr, config, stream))
+	}
+
+	fn from_stream(addr: String, config: PaperClientConfig, stream: T) -> Self {
+		let command_timeout = config.command_timeout;
+
+		PaperClient {
 			addr,
+			config,
 
-			auth_token: None,
+			auth_provider: None,
 			reconnect_attempts: 0,
+			is_closed: false,
 
-			stream,
-		};
+			command_timeout,
 
-		client.handshake()?;
+			is_stale: false,
+			pending_auth: None,
+			pid: process::id(),
+			has_read_timeout: false,
 
-		Ok(client)
+			sequencer: Arc::default(),
+
+			health: None,
+			stats: Arc::default(),
+
+			stream,
+		}
 	}
 
 	/// Pings the server.
@@ -74,7 +301,7 @@ impl PaperClient {
 	/// }
 	/// ```
 	pub fn ping(&mut self) -> PaperClientResult<PaperValue> {
-		self.process_value(&Command::Ping)
+		self.process(&Command::Ping, Value(self.config.max_response_size))
 	}
 
 	/// Gets the cache version.
@@ -91,7 +318,7 @@ impl PaperClient {
 	/// }
 	/// ```
 	pub fn version(&mut self) -> PaperClientResult<PaperValue> {
-		self.process_value(&Command::Version)
+		self.process(&Command::Version, Value(self.config.max_response_size))
 	}
 
 	/// Attempts to authorize the connection with the supplied auth token. This
@@ -110,14 +337,73 @@ impl PaperClient {
 	/// }
 	/// ```
 	pub fn auth(&mut self, token: impl AsPaperAuthToken) -> PaperClientResult<()> {
-		let auth_token = token.as_paper_auth_token();
+		let provider = StaticTokenProvider::new(token.as_paper_auth_token());
+		self.auth_with(provider)
+	}
+
+	/// Attempts to authorize the connection with the token supplied by the
+	/// supplied `AuthTokenProvider`. The provider is queried again each time
+	/// the connection is reauthorized or reconnected.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{PaperClient, EnvTokenProvider};
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.auth_with(EnvTokenProvider::new("PAPER_AUTH_TOKEN")) {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn auth_with(
+		&mut self,
+		provider: impl AuthTokenProvider + 'static,
+	) -> PaperClientResult<()> {
+		self.set_auth_provider(Arc::new(provider));
+		self.reauth()
+	}
 
-		let command = Command::Auth(auth_token);
-		let result = self.process(&command);
+	/// Reauthorizes the existing connection with the token currently
+	/// supplied by the client's `AuthTokenProvider`, without reconnecting.
+	/// Does nothing if the client has not been authorized.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.reauth() {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn reauth(&mut self) -> PaperClientResult<()> {
+		let Some(provider) = self.auth_provider.clone() else {
+			return Ok(());
+		};
 
-		self.auth_token = Some(auth_token.to_owned());
+		let token = provider.token()?;
+		self.process(&Command::Auth(&token), Ack)
+	}
 
-		result
+	/// Replaces the client's auth token with the supplied token and
+	/// reauthorizes the existing connection, without reconnecting.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.rotate_token("my_new_token") {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn rotate_token(&mut self, token: impl AsPaperAuthToken) -> PaperClientResult<()> {
+		self.auth(token)
 	}
 
 	/// Gets the value of the supplied key from the cache.
@@ -134,11 +420,15 @@ impl PaperClient {
 	/// }
 	/// ```
 	pub fn get(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
-		let command = Command::Get(key.as_paper_key());
-		self.process_value(&command)
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Get(&key);
+		let value = self.process(&command, Value(self.config.max_response_size))?;
+
+		self.config.decode_value(value)
 	}
 
-	/// Sets the supplied key, value, and ttl to the cache.
+	/// Gets the value of the supplied key from the cache, reporting a
+	/// missing key as a miss rather than as a `KeyNotFound` error.
 	///
 	/// # Examples
 	/// ```
@@ -146,27 +436,73 @@ impl PaperClient {
 	///
 	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
 	///
-	/// match client.set("key", "value", None) {
-	///     Ok(_) => println!("done"),
+	/// match client.get_result("key") {
+	///     Ok(result) if result.is_hit() => println!("hit: {:?}", result.size()),
+	///     Ok(_) => println!("miss"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub fn set(
+	pub fn get_result(&mut self, key: impl AsPaperKey) -> PaperClientResult<GetResult> {
+		match self.get(key) {
+			Ok(value) => Ok(GetResult::hit(value)),
+			Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => Ok(GetResult::miss()),
+			Err(err) => Err(err),
+		}
+	}
+
+	/// Waits for the supplied key to appear in the cache and returns its
+	/// value, polling with `get`. The delay between polls starts at the
+	/// supplied interval and doubles after each poll, up to eight times the
+	/// interval. If the key does not appear within the supplied timeout,
+	/// `WaitTimeout` is returned. Any error other than `KeyNotFound` is
+	/// returned immediately.
+	///
+	/// # Examples
+	/// ```
+	/// use std::time::Duration;
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.wait_for("job:42", Duration::from_secs(5), Duration::from_millis(10)) {
+	///     Ok(value) => println!("{value:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn wait_for(
 		&mut self,
 		key: impl AsPaperKey,
-		value: impl TryInto<PaperValue>,
-		ttl: Option<u32>,
-	) -> PaperClientResult<()> {
-		let value: PaperValue = value
-			.try_into()
-			.map_err(|_| PaperClientError::InvalidValue)?;
+		timeout: Duration,
+		poll_interval: Duration,
+	) -> PaperClientResult<PaperValue> {
+		let key = key.as_paper_key();
+		let clock = self.config.clock.clone();
+
+		let start = clock.now();
+		let mut interval = poll_interval;
+
+		loop {
+			match self.get(key) {
+				Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => {},
+				result => return result,
+			}
 
-		let command = Command::Set(key.as_paper_key(), value, ttl.unwrap_or(0));
+			let remaining = timeout.saturating_sub(clock.now() - start);
 
-		self.process(&command)
+			if remaining.is_zero() {
+				return Err(PaperClientError::WaitTimeout);
+			}
+
+			clock.sleep(interval.min(remaining));
+			interval = (interval * 2).min(poll_interval * MAX_WAIT_BACKOFF_FACTOR);
+		}
 	}
 
-	/// Deletes the value of the supplied key from the cache.
+	/// Marks the value of the supplied key as stale without deleting it,
+	/// by setting its stale marker (the key followed by
+	/// `STALE_MARKER_SUFFIX`). The next `get_allow_stale` of the key
+	/// reports the value as stale, so that a single reader refreshes it
+	/// while others continue to be served the stale value.
 	///
 	/// # Examples
 	/// ```
@@ -174,18 +510,24 @@ impl PaperClient {
 	///
 	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
 	///
-	/// match client.del("key") {
+	/// match client.invalidate_soft("key") {
 	///     Ok(_) => println!("done"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub fn del(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
-		let command = Command::Del(key.as_paper_key());
-		self.process(&command)
+	pub fn invalidate_soft(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
+		self.set(stale::marker_key(key.as_paper_key()), STALE_MARKER_VALUE, None)
 	}
 
-	/// Checks if the cache contains an object with the supplied key
-	/// without altering the eviction order of the objects.
+	/// Gets the value of the supplied key from the cache, along with
+	/// whether it was marked stale by `invalidate_soft`. Reading a stale
+	/// value consumes its marker, so exactly one reader observes each soft
+	/// invalidation and is responsible for refreshing the value. The value
+	/// and marker are read in a single round trip.
+	///
+	/// A marker which is set while the key is missing is consumed by the
+	/// next read of the key, in which case the value is reported as stale
+	/// even if it was set after the invalidation.
 	///
 	/// # Examples
 	/// ```
@@ -193,18 +535,49 @@ impl PaperClient {
 	///
 	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
 	///
-	/// match client.has("key") {
-	///     Ok(has) => println!("{has}"),
+	/// match client.get_allow_stale("key") {
+	///     Ok(result) if result.is_stale() => println!("refresh {:?}", result.value()),
+	///     Ok(result) => println!("{:?}", result.value()),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub fn has(&mut self, key: impl AsPaperKey) -> PaperClientResult<bool> {
-		let command = Command::Has(key.as_paper_key());
-		self.process_has(&command)
+	pub fn get_allow_stale(&mut self, key: impl AsPaperKey) -> PaperClientResult<StaleResult> {
+		let key = key.as_paper_key();
+		let marker = stale::marker_key(key);
+
+		let mut responses = self
+			.batch(|batch| {
+				batch.get(key).del(&marker);
+			})?
+			.into_iter();
+
+		let value = match responses.next() {
+			Some(Ok(BatchResponse::Value(value))) => value,
+			Some(Err(err)) => return Err(err),
+			_ => return Err(PaperClientError::Internal),
+		};
+
+		let is_stale = match responses.next() {
+			Some(Ok(_)) => true,
+			Some(Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound))) => false,
+			Some(Err(err)) => return Err(err),
+			None => return Err(PaperClientError::Internal),
+		};
+
+		Ok(StaleResult::new(value, is_stale))
 	}
 
-	/// Gets (peeks) the value of the supplied key from the cache without
-	/// altering the eviction order of the objects.
+	/// Gets the value of the supplied key from the cache or, if it is
+	/// missing, computes it with the supplied function and sets it with
+	/// the supplied ttl. Only one process computes a missing value: the
+	/// others wait (for up to `LOCK_TTL`) for it to be set, rather than
+	/// computing it themselves.
+	///
+	/// The computing process is elected with a lock key (the key followed
+	/// by `LOCK_KEY_SUFFIX`). As the server has no atomic set-if-absent
+	/// command, processes which miss the key at the same instant may both
+	/// acquire the lock and compute the value, so this reduces dogpiles
+	/// rather than strictly preventing them.
 	///
 	/// # Examples
 	/// ```
@@ -212,53 +585,152 @@ impl PaperClient {
 	///
 	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
 	///
-	/// match client.peek("key") {
+	/// match client.get_or_compute_coordinated("key", None, || "value") {
 	///     Ok(value) => println!("{value:?}"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub fn peek(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
-		let command = Command::Peek(key.as_paper_key());
-		self.process_value(&command)
+	pub fn get_or_compute_coordinated<V: TryInto<PaperValue>>(
+		&mut self,
+		key: impl AsPaperKey,
+		ttl: impl AsPaperTtl,
+		f: impl FnOnce() -> V,
+	) -> PaperClientResult<PaperValue> {
+		let key = key.as_paper_key();
+
+		match self.get(key) {
+			Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => {},
+			result => return result,
+		}
+
+		let lock = singleflight::lock_key(key);
+		let is_locked = self.try_lock(&lock)?;
+
+		if !is_locked {
+			match self.wait_for(key, LOCK_TTL, LOCK_POLL_INTERVAL) {
+				// the lock holder failed to set the value in time
+				Err(PaperClientError::WaitTimeout) => {},
+				result => return result,
+			}
+		}
+
+		let value: PaperValue = f()
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidValue)?;
+
+		let result = self.set(key, value.clone(), ttl);
+
+		if is_locked {
+			let _ = self.del(&lock);
+		}
+
+		result.map(|_| value)
 	}
 
-	/// Sets the TTL associated with the supplied key.
+	/// Gets the value of the supplied key from the cache and reads it into
+	/// the supplied buffer, replacing its contents. Reusing a buffer across
+	/// calls avoids allocating for each value. Returns the size of the value.
 	///
 	/// # Examples
 	/// ```
 	/// use paper_client::PaperClient;
 	///
 	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	/// let mut buf = Vec::new();
 	///
-	/// match client.ttl("key", Some(5)) {
-	///     Ok(_) => println!("done"),
+	/// match client.get_into("key", &mut buf) {
+	///     Ok(size) => println!("{:?}", &buf[..size]),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub fn ttl(&mut self, key: impl AsPaperKey, ttl: Option<u32>) -> PaperClientResult<()> {
-		let command = Command::Ttl(key.as_paper_key(), ttl.unwrap_or(0));
-		self.process(&command)
+	pub fn get_into(&mut self, key: impl AsPaperKey, buf: &mut Vec<u8>) -> PaperClientResult<usize> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Get(&key);
+		let size = self.process(&command, IntoVec(buf, self.config.max_response_size))?;
+
+		self.config.decode_vec(buf, size)
 	}
 
-	/// Gets the size of the value of the supplied key from the cache in bytes.
+	/// Gets the value of the supplied key from the cache and reads it into
+	/// the start of the supplied slice. Returns the size of the value. If the
+	/// value does not fit in the slice, it is discarded and `ResponseTooLarge`
+	/// is returned.
 	///
 	/// # Examples
 	/// ```
 	/// use paper_client::PaperClient;
 	///
 	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	/// let mut buf = [0u8; 1024];
 	///
-	/// match client.size("key") {
+	/// match client.get_into_slice("key", &mut buf) {
+	///     Ok(size) => println!("{:?}", &buf[..size]),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn get_into_slice(
+		&mut self,
+		key: impl AsPaperKey,
+		buf: &mut [u8],
+	) -> PaperClientResult<usize> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Get(&key);
+		let size = self.process(&command, IntoSlice(buf))?;
+
+		self.config.decode_slice(buf, size)
+	}
+
+	/// Gets the value of the supplied key from the cache and writes it to
+	/// the supplied writer in chunks, without buffering the whole value in
+	/// memory. Returns the size of the value. Unlike `get`, the value is not
+	/// limited by the configured maximum response size. If the writer fails,
+	/// the rest of the value is still read and `InvalidWriter` is returned.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	/// let mut value = Vec::new();
+	///
+	/// match client.get_to_writer("key", &mut value) {
 	///     Ok(size) => println!("{size}"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub fn size(&mut self, key: impl AsPaperKey) -> PaperClientResult<u32> {
-		let command = Command::Size(key.as_paper_key());
-		self.process_size(&command)
+	pub fn get_to_writer(
+		&mut self,
+		key: impl AsPaperKey,
+		writer: &mut impl Write,
+	) -> PaperClientResult<u64> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Get(&key);
+
+		let Some(codec) = self.config.value_codec.clone() else {
+			return self.process(&command, ToWriter(writer));
+		};
+
+		// the whole value is needed to decode it
+		let mut buf = Vec::new();
+		self.process(&command, ToWriter(&mut buf))?;
+
+		let value = codec.decode(&buf)?;
+
+		writer
+			.write_all(&value)
+			.map_err(|_| PaperClientError::InvalidWriter)?;
+
+		Ok(value.len() as u64)
 	}
 
-	/// Wipes the contents of the cache.
+	/// Gets the bytes of the value of the supplied key from the supplied
+	/// offset, up to the supplied length (e.g., to read the header of a
+	/// large cached artifact). If the value is shorter than the range, the
+	/// bytes up to its end are returned.
+	///
+	/// The server cannot send part of a value, so the whole value is still
+	/// transferred, but only the range is kept in memory. Unlike `get`, the
+	/// value is not limited by the configured maximum response size.
 	///
 	/// # Examples
 	/// ```
@@ -266,16 +738,28 @@ impl PaperClient {
 	///
 	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
 	///
-	/// match client.wipe() {
-	///     Ok(_) => println!("done"),
+	/// match client.get_range("key", 0, 16) {
+	///     Ok(header) => println!("{header:?}"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub fn wipe(&mut self) -> PaperClientResult<()> {
-		self.process(&Command::Wipe)
+	pub fn get_range(
+		&mut self,
+		key: impl AsPaperKey,
+		offset: u64,
+		len: usize,
+	) -> PaperClientResult<PaperValue> {
+		let mut writer = RangeWriter::new(offset, len);
+		self.get_to_writer(key, &mut writer)?;
+
+		Ok(PaperValue::from(writer.into_inner()))
 	}
 
-	/// Resizes the cache to the supplied size.
+	/// Sets the supplied key, value, and ttl to the cache. The ttl is
+	/// either a number of seconds (`None` for no expiry), a `Duration`,
+	/// which is rounded up to the nearest second, or a `Ttl`. A ttl which
+	/// would expire the key immediately (e.g., `Some(0)`) is rejected with
+	/// `InvalidTtl`.
 	///
 	/// # Examples
 	/// ```
@@ -283,35 +767,56 @@ impl PaperClient {
 	///
 	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
 	///
-	/// match client.resize(10) {
+	/// match client.set("key", "value", None) {
 	///     Ok(_) => println!("done"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub fn resize(&mut self, size: u64) -> PaperClientResult<()> {
-		let command = Command::Resize(size);
-		self.process(&command)
+	pub fn set(
+		&mut self,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let value: PaperValue = value
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidValue)?;
+
+		let value = self.config.encode_value(value)?;
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Set(&key, value, ttl.as_paper_ttl()?);
+
+		self.process(&command, Ack)
 	}
 
-	/// Sets the cache's eviction policy.
+	/// Sets the supplied key to the supplied number, stored as its decimal
+	/// text (see [`NumericValue`]) so that clients in other languages can
+	/// read it. A float which is not finite is rejected with
+	/// `InvalidValue`.
 	///
 	/// # Examples
 	/// ```
-	/// use paper_client::{PaperClient, PaperPolicy};
+	/// use paper_client::PaperClient;
 	///
 	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
 	///
-	/// match client.policy(PaperPolicy::Lru) {
+	/// match client.set_num("hits", 42u64, None) {
 	///     Ok(_) => println!("done"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub fn policy(&mut self, policy: PaperPolicy) -> PaperClientResult<()> {
-		let command = Command::Policy(policy);
-		self.process(&command)
+	pub fn set_num(
+		&mut self,
+		key: impl AsPaperKey,
+		value: impl NumericValue,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		self.set(key, value.to_paper_value()?, ttl)
 	}
 
-	/// Gets the cache's status.
+	/// Gets the number stored at the supplied key (see [`NumericValue`]).
+	/// If the value is not a number of the requested type, `InvalidNumber`
+	/// is returned.
 	///
 	/// # Examples
 	/// ```
@@ -319,179 +824,1161 @@ impl PaperClient {
 	///
 	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
 	///
-	/// match client.status() {
-	///     Ok(status) => println!("{status:?}"),
+	/// match client.get_num::<u64>("hits") {
+	///     Ok(hits) => println!("{hits}"),
 	///     Err(err) => println!("{err:?}"),
 	/// }
 	/// ```
-	pub fn status(&mut self) -> PaperClientResult<Status> {
-		self.process_status(&Command::Status)
+	pub fn get_num<N: NumericValue>(&mut self, key: impl AsPaperKey) -> PaperClientResult<N> {
+		N::from_paper_value(&self.get(key)?)
 	}
 
-	fn process(&mut self, command: &Command<'_>) -> PaperClientResult<()> {
-		match self
-			.send(command)
-			.and_then(|_| self.receive(command))
-		{
-			Ok(response) => {
-				self.reconnect_attempts = 0;
-				Ok(response)
-			},
-
-			Err(PaperClientError::InvalidResponse) => {
-				self.reconnect_attempts += 1;
-				self.reconnect()?;
-				self.process(command)
-			},
+	/// Deletes the value of the supplied key from the cache.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.del("key") {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn del(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Del(&key);
+		self.process(&command, Ack)
+	}
+
+	/// Deletes each of the supplied keys which starts with the supplied
+	/// prefix, and returns the number of keys which were deleted. The
+	/// server cannot enumerate its keys, so the candidate keys are supplied
+	/// by the caller (e.g., from the application's own key registry). The
+	/// deletes are pipelined in batches.
+	///
+	/// Keys which are not found are skipped. If any other error occurs, it
+	/// is returned, in which case some of the keys may have been deleted.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.del_prefix("user:", ["user:1", "user:2", "item:1"]) {
+	///     Ok(deleted) => println!("{deleted} deleted"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn del_prefix<K: AsPaperKey>(
+		&mut self,
+		prefix: &str,
+		keys: impl IntoIterator<Item = K>,
+	) -> PaperClientResult<u64> {
+		self.count_prefix(prefix, keys, |batch, key| {
+			batch.del(key);
+		})
+	}
+
+	/// Returns the number of the supplied keys which `del_prefix` would
+	/// delete (i.e., which start with the supplied prefix and are in the
+	/// cache), without deleting them or altering their eviction order.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.del_prefix_dry_run("user:", ["user:1", "user:2", "item:1"]) {
+	///     Ok(count) => println!("{count} would be deleted"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn del_prefix_dry_run<K: AsPaperKey>(
+		&mut self,
+		prefix: &str,
+		keys: impl IntoIterator<Item = K>,
+	) -> PaperClientResult<u64> {
+		self.count_prefix(prefix, keys, |batch, key| {
+			batch.has(key);
+		})
+	}
+
+	/// Checks if the cache contains an object with the supplied key
+	/// without altering the eviction order of the objects.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.has("key") {
+	///     Ok(has) => println!("{has}"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn has(&mut self, key: impl AsPaperKey) -> PaperClientResult<bool> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Has(&key);
+		self.process(&command, Flag)
+	}
+
+	/// Gets (peeks) the value of the supplied key from the cache without
+	/// altering the eviction order of the objects.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.peek("key") {
+	///     Ok(value) => println!("{value:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn peek(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Peek(&key);
+		let value = self.process(&command, Value(self.config.max_response_size))?;
+
+		self.config.decode_value(value)
+	}
+
+	/// Sets the TTL associated with the supplied key. The ttl is either a
+	/// number of seconds (`None` for no expiry), a `Duration`, which is
+	/// rounded up to the nearest second, or a `Ttl`. A ttl which would
+	/// expire the key immediately is rejected with `InvalidTtl`.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.ttl("key", Some(5)) {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn ttl(&mut self, key: impl AsPaperKey, ttl: impl AsPaperTtl) -> PaperClientResult<()> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Ttl(&key, ttl.as_paper_ttl()?);
+		self.process(&command, Ack)
+	}
+
+	/// Gets the size of the value of the supplied key from the cache in bytes.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.size("key") {
+	///     Ok(size) => println!("{size}"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn size(&mut self, key: impl AsPaperKey) -> PaperClientResult<u32> {
+		let key = self.config.paper_key(key.as_paper_key());
+		let command = Command::Size(&key);
+		self.process(&command, Size)
+	}
+
+	/// Wipes the contents of the cache.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.wipe() {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn wipe(&mut self) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Wipe, None, false)
+	}
+
+	/// Wipes the contents of the cache in the same way as `wipe`, recording
+	/// the supplied reason in the `AuditEvent` sent to the observer.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.wipe_with_reason("rebuilding the catalog") {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn wipe_with_reason(&mut self, reason: &str) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Wipe, Some(reason), false)
+	}
+
+	/// Resizes the cache to the supplied size.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.resize(10) {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn resize(&mut self, size: u64) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Resize(size), None, false)
+	}
+
+	/// Resizes the cache in the same way as `resize`, recording the supplied
+	/// reason in the `AuditEvent` sent to the observer.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.resize_with_reason(10, "scaling down for maintenance") {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn resize_with_reason(&mut self, size: u64, reason: &str) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Resize(size), Some(reason), false)
+	}
+
+	/// Resizes the cache in the same way as `resize_with_reason`, bypassing
+	/// the client's `Guardrails`. The `AuditEvent` sent to the observer is
+	/// marked as forced.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.force_resize(10, "emergency memory reclaim") {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn force_resize(&mut self, size: u64, reason: &str) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Resize(size), Some(reason), true)
+	}
+
+	/// Sets the cache's eviction policy.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{PaperClient, PaperPolicy};
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.policy(PaperPolicy::Lru) {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn policy(&mut self, policy: PaperPolicy) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Policy(policy), None, false)
+	}
+
+	/// Sets the cache's eviction policy in the same way as `policy`,
+	/// recording the supplied reason in the `AuditEvent` sent to the
+	/// observer.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{PaperClient, PaperPolicy};
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.policy_with_reason(PaperPolicy::Lru, "lfu thrashing") {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn policy_with_reason(
+		&mut self,
+		policy: PaperPolicy,
+		reason: &str,
+	) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Policy(policy), Some(reason), false)
+	}
+
+	/// Sets the cache's eviction policy in the same way as
+	/// `policy_with_reason`, bypassing the client's `Guardrails`. The
+	/// `AuditEvent` sent to the observer is marked as forced.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{PaperClient, PaperPolicy};
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.force_policy(PaperPolicy::Lru, "rolling back lfu") {
+	///     Ok(_) => println!("done"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn force_policy(
+		&mut self,
+		policy: PaperPolicy,
+		reason: &str,
+	) -> PaperClientResult<()> {
+		self.audit(AuditCommand::Policy(policy), Some(reason), true)
+	}
+
+	/// Gets the cache's status.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.status() {
+	///     Ok(status) => println!("{status:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn status(&mut self) -> PaperClientResult<Status> {
+		self.process(&Command::Status, StatusFrame)
+	}
+
+	/// Returns a token for the most recent mutation issued by the client,
+	/// which can be waited on (e.g., by another thread before it reads from
+	/// another pooled connection) until the server has acknowledged it. See
+	/// [`SequenceToken`].
+	///
+	/// # Examples
+	/// ```
+	/// use std::time::Duration;
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	/// let _ = client.set("key", "value", None);
+	///
+	/// let token = client.sequence();
+	/// assert!(token.wait(Duration::from_secs(1)).is_ok());
+	/// ```
+	#[must_use]
+	pub fn sequence(&self) -> SequenceToken {
+		self.sequencer.token()
+	}
+
+	/// Returns a view of the client for the supplied tenant, whose keys are
+	/// namespaced by the tenant's name and whose usage is accounted for
+	/// separately. See [`Tenant`].
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	/// let mut tenant = client.tenant("team-a");
+	///
+	/// match tenant.set("key", "value", None) {
+	///     Ok(_) => println!("{} bytes written", tenant.usage().bytes_written()),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn tenant(&mut self, tenant: &str) -> Tenant<'_, T> {
+		let recorder = self.config.tenants.recorder(tenant);
+		Tenant::new(self, tenant, recorder)
+	}
+
+	/// Returns a snapshot of the usage of every tenant of the client (and
+	/// of every other client constructed from the same configuration),
+	/// ordered by tenant.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// for usage in client.tenant_usage() {
+	///     println!("{}: {} requests", usage.tenant(), usage.requests());
+	/// }
+	/// ```
+	#[must_use]
+	pub fn tenant_usage(&self) -> Vec<TenantUsage> {
+		self.config.tenants.usage()
+	}
+
+	/// Executes the supplied command and decodes its response with the
+	/// supplied `DecodePaperResponse`, so that a command's response can be
+	/// parsed into a user-defined type. The command is processed like any
+	/// of the client's own commands (e.g., it is retried after a broken
+	/// connection, and subject to the client's `CommandPolicy`), except
+	/// that it is sent as supplied, so its key is not transformed by the
+	/// client's configuration (e.g., its key hashing).
+	///
+	/// # Examples
+	/// ```
+	/// use std::io::Read;
+	/// use paper_client::{Command, DecodePaperResponse, PaperClient, PaperClientError, StreamReader};
+	/// use paper_client::error::PaperClientResult;
+	///
+	/// struct Kibibytes;
+	///
+	/// impl DecodePaperResponse for Kibibytes {
+	///     type Output = f64;
+	///
+	///     fn decode<R: Read>(&mut self, reader: &mut StreamReader<'_, R>) -> PaperClientResult<f64> {
+	///         let size = reader
+	///             .read_u32()
+	///             .map_err(|_| PaperClientError::InvalidResponse)?;
+	///
+	///         Ok(f64::from(size) / 1024.0)
+	///     }
+	/// }
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// match client.execute(Command::Size("key"), Kibibytes) {
+	///     Ok(size) => println!("{size} KiB"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn execute<D: DecodePaperResponse>(
+		&mut self,
+		command: Command<'_>,
+		decoder: D,
+	) -> PaperClientResult<D::Output> {
+		self.process(&command, Custom(decoder))
+	}
+
+	/// Sends every command added to the batch by the supplied closure in a
+	/// single write once the closure returns, and then reads all of their
+	/// responses, so that the batch costs a single round trip. Returns the
+	/// response to each command in the order in which it was added.
+	///
+	/// A cache error (e.g., `KeyNotFound`) only fails its own command. If
+	/// the connection fails partway through, the batch is not retried and
+	/// the error is returned, in which case some of its commands may have
+	/// been executed.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// let responses = client.batch(|batch| {
+	///     batch.get("a");
+	///     batch.set("b", "value", None);
+	/// });
+	///
+	/// match responses {
+	///     Ok(responses) => println!("{responses:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn batch(
+		&mut self,
+		f: impl FnOnce(&mut Batch),
+	) -> PaperClientResult<Vec<PaperClientResult<BatchResponse>>> {
+		let mut batch = Batch::default();
+		f(&mut batch);
 
-			err => err,
+		batch.hash_keys(&self.config);
+
+		if let Some(codec) = &self.config.value_codec {
+			batch.encode_values(codec.as_ref());
 		}
+
+		let responses = self.process_batch(&batch)?;
+
+		let responses = responses
+			.into_iter()
+			.map(|response| match response {
+				Ok(BatchResponse::Value(value)) => self
+					.config
+					.decode_value(value)
+					.map(BatchResponse::Value),
+
+				response => response,
+			})
+			.collect();
+
+		Ok(responses)
 	}
 
-	fn process_value(&mut self, command: &Command<'_>) -> PaperClientResult<PaperValue> {
-		match self
-			.send(command)
-			.and_then(|_| self.receive_value(command))
-		{
-			Ok(response) => {
-				self.reconnect_attempts = 0;
-				Ok(response)
-			},
+	/// Returns the details of the client's current connection (e.g., the
+	/// address of the server it landed on, which may differ between
+	/// reconnects if the server's hostname resolves to several addresses).
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// if let Some(tls) = client.connection_info().tls() {
+	///     println!("{} with {}", tls.protocol(), tls.cipher());
+	/// }
+	/// ```
+	#[must_use]
+	pub fn connection_info(&self) -> ConnectionInfo {
+		self.stream.connection_info()
+	}
 
-			Err(PaperClientError::InvalidResponse) => {
-				self.reconnect_attempts += 1;
-				self.reconnect()?;
-				self.process_value(command)
-			},
+	/// Returns the address of the server the client is currently connected
+	/// to, if its transport reports it. See `connection_info`.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// if let Some(addr) = client.peer_addr() {
+	///     println!("connected to {addr}");
+	/// }
+	/// ```
+	#[must_use]
+	pub fn peer_addr(&self) -> Option<PeerAddr> {
+		self.connection_info().peer_addr().cloned()
+	}
 
-			err => err,
+	/// Returns the number of times the most recent command reconnected to
+	/// the server in order to be retried. This is reset to 0 whenever a
+	/// command succeeds.
+	#[must_use]
+	pub fn reconnect_attempts(&self) -> u8 {
+		self.reconnect_attempts
+	}
+
+	/// Returns a snapshot of the commands the client has sent.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	/// let _ = client.ping();
+	///
+	/// let stats = client.client_stats();
+	/// println!("{} commands, {} errors", stats.commands(), stats.errors());
+	/// ```
+	#[must_use]
+	pub fn client_stats(&self) -> ClientStats {
+		self.stats.snapshot(&self.addr)
+	}
+
+	/// Sets the maximum time each of the client's commands may take,
+	/// overriding the configured `command_timeout`. If `None`, commands are
+	/// only bounded by the deadline, if any.
+	///
+	/// # Examples
+	/// ```
+	/// use std::time::Duration;
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	/// client.set_command_timeout(Some(Duration::from_millis(50)));
+	/// ```
+	pub fn set_command_timeout(&mut self, timeout: Option<Duration>) {
+		self.command_timeout = timeout;
+	}
+
+	/// Returns the maximum time each of the client's commands may take.
+	#[must_use]
+	pub fn command_timeout(&self) -> Option<Duration> {
+		self.command_timeout
+	}
+
+	pub(crate) fn reset_command_timeout(&mut self) {
+		self.command_timeout = self.config.command_timeout;
+	}
+
+	fn process<D: DecodeResponse>(
+		&mut self,
+		command: &Command<'_>,
+		decoder: D,
+	) -> PaperClientResult<D::Output> {
+		let is_mutation = command.kind().is_mutation();
+
+		if is_mutation {
+			self.sequencer.issue();
+		}
+
+		let result = self.within_timeout(|client| client.attempt(command, decoder));
+
+		if is_mutation && sequence::is_response(&result) {
+			self.sequencer.acknowledge();
 		}
+
+		result
 	}
 
-	fn process_has(&mut self, command: &Command<'_>) -> PaperClientResult<bool> {
-		match self
-			.send(command)
-			.and_then(|_| self.receive_has(command))
-		{
+	fn attempt<D: DecodeResponse>(
+		&mut self,
+		command: &Command<'_>,
+		mut decoder: D,
+	) -> PaperClientResult<D::Output> {
+		let start = Instant::now();
+		let mut is_partially_read = false;
+
+		let result = self.send(command).and_then(|_| {
+			let mut stream = self.capture_stream();
+
+			decoder
+				.decode(command, &mut stream)
+				.inspect_err(|err| is_partially_read = D::is_partially_read(err))
+		});
+
+		self.record(&result, start);
+
+		if is_partially_read {
+			self.is_stale = true;
+		}
+
+		match result {
 			Ok(response) => {
 				self.reconnect_attempts = 0;
 				Ok(response)
 			},
 
-			Err(PaperClientError::InvalidResponse) => {
-				self.reconnect_attempts += 1;
-				self.reconnect()?;
-				self.process_has(command)
+			Err(err) if D::IS_RETRYABLE && self.config.is_retryable(&err) => {
+				self.retry(err)?;
+				self.attempt(command, decoder)
 			},
 
 			err => err,
 		}
 	}
 
-	fn process_size(&mut self, command: &Command<'_>) -> PaperClientResult<u32> {
-		match self
-			.send(command)
-			.and_then(|_| self.receive_size(command))
-		{
-			Ok(response) => {
+	fn process_batch(
+		&mut self,
+		batch: &Batch,
+	) -> PaperClientResult<Vec<PaperClientResult<BatchResponse>>> {
+		let commands = batch.commands();
+
+		if commands.is_empty() {
+			return batch.receive(&commands, &mut io::empty(), None);
+		}
+
+		let start = Instant::now();
+
+		let max_response_size = self.config.max_response_size;
+
+		let mutations = commands
+			.iter()
+			.filter(|command| command.kind().is_mutation())
+			.count();
+
+		for _ in 0..mutations {
+			self.sequencer.issue();
+		}
+
+		let result = self.within_timeout(|client| {
+			client.send_all(&commands).and_then(|_| {
+				let mut stream = client.capture_stream();
+				batch.receive(&commands, &mut stream, max_response_size)
+			})
+		});
+
+		self.record(&result, start);
+
+		if result.is_ok() {
+			self.reconnect_attempts = 0;
+
+			// every response of the batch has been received
+			if mutations > 0 {
+				self.sequencer.acknowledge();
+			}
+		}
+
+		result
+	}
+
+	// bounds the supplied exchange with the server by the command timeout
+	fn within_timeout<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+		match self.command_timeout {
+			Some(timeout) => deadline::with_deadline(Instant::now() + timeout, || f(self)),
+			None => f(self),
+		}
+	}
+
+	pub(crate) fn config(&self) -> &PaperClientConfig {
+		&self.config
+	}
+
+	pub(crate) fn stats(&self) -> Arc<ConnectionStats> {
+		self.stats.clone()
+	}
+
+	pub(crate) fn set_auth_provider(&mut self, provider: Arc<dyn AuthTokenProvider>) {
+		self.auth_provider = Some(provider);
+	}
+
+	pub(crate) fn set_health(&mut self, health: Arc<NodeHealth>) {
+		self.health = Some(health);
+	}
+
+	pub(crate) fn close(&mut self) {
+		// the client is never reconnected once closed
+		self.is_closed = true;
+
+		// shutting down an inherited connection would also close it for
+		// the parent process
+		if self.pid == process::id() {
+			let _ = self.stream.shutdown();
+		}
+	}
+
+	// re-establishes the connection of a client whose most recent command
+	// exhausted its reconnects, or of a strict client whose connection
+	// failed (e.g., before it is handed out by a pool), returning whether
+	// the connection was replaced
+	pub(crate) fn replace_if_exhausted(&mut self) -> bool {
+		let is_exhausted = self.reconnect_attempts >= RECONNECT_MAX_ATTEMPTS
+			|| (self.config.strict && self.is_stale);
+
+		if self.is_closed || !is_exhausted {
+			return false;
+		}
+
+		match self.reconnect() {
+			Ok(_) => {
 				self.reconnect_attempts = 0;
-				Ok(response)
+				true
 			},
 
-			Err(PaperClientError::InvalidResponse) => {
-				self.reconnect_attempts += 1;
-				self.reconnect()?;
-				self.process_size(command)
-			},
+			Err(_) => false,
+		}
+	}
 
-			err => err,
+	pub(crate) fn probe(&mut self) -> PaperClientResult<()> {
+		self.reconnect_attempts = 0;
+
+		if self.ping().is_ok() {
+			return Ok(());
 		}
+
+		self.reconnect()?;
+		self.ping().map(|_| ())
 	}
 
-	fn process_status(&mut self, command: &Command<'_>) -> PaperClientResult<Status> {
-		match self
-			.send(command)
-			.and_then(|_| self.receive_status(command))
+	fn audit(
+		&mut self,
+		command: AuditCommand,
+		reason: Option<&str>,
+		is_forced: bool,
+	) -> PaperClientResult<()> {
+		if !is_forced {
+			self.check_guardrails(&command)?;
+		}
+
+		let result = self.process(&command.to_command(), Ack);
+
+		if result.is_ok()
+			&& matches!(command, AuditCommand::Policy(_))
+			&& let Some(guardrails) = &self.config.guardrails
 		{
-			Ok(response) => {
-				self.reconnect_attempts = 0;
-				Ok(response)
-			},
+			guardrails.record_policy(self.config.clock.now());
+		}
+
+		if let Some(observer) = &self.config.observer {
+			let event = AuditEvent::new(&self.addr, command, reason, result.is_ok(), is_forced);
+			observer.on_event(&PaperEvent::Audit(event));
+		}
+
+		result
+	}
+
+	fn check_guardrails(&mut self, command: &AuditCommand) -> PaperClientResult<()> {
+		let Some(guardrails) = self.config.guardrails.clone() else {
+			return Ok(());
+		};
 
-			Err(PaperClientError::InvalidResponse) => {
-				self.reconnect_attempts += 1;
-				self.reconnect()?;
-				self.process_status(command)
+		match command {
+			AuditCommand::Resize(size) if guardrails.limits_resize() => {
+				let status = self.status()?;
+				guardrails.check_resize(status.max_size(), *size)
 			},
 
-			err => err,
+			AuditCommand::Policy(_) => guardrails.check_policy(self.config.clock.now()),
+
+			_ => Ok(()),
 		}
 	}
 
-	fn send(&mut self, command: &Command<'_>) -> PaperClientResult<()> {
-		command
-			.write(&mut self.stream)
-			.map_err(|err| match err {
-				StreamError::InvalidStream => PaperClientError::Disconnected,
-				_ => PaperClientError::InvalidCommand,
-			})
+	// acquires the supplied lock key unless it is already held, returning
+	// whether it was acquired (i.e., whether this client's token survived
+	// any concurrent acquisitions)
+	fn try_lock(&mut self, lock: &str) -> PaperClientResult<bool> {
+		if self.has(lock)? {
+			return Ok(false);
+		}
+
+		let token = singleflight::lock_token();
+		self.set(lock, token.as_str(), LOCK_TTL)?;
+
+		match self.get(lock) {
+			Ok(value) => Ok(value == token.as_str()),
+			Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => Ok(false),
+			Err(err) => Err(err),
+		}
+	}
+
+	// adds a command for each of the supplied keys which starts with the
+	// prefix to batches, and counts the commands which found their key
+	fn count_prefix<K: AsPaperKey>(
+		&mut self,
+		prefix: &str,
+		keys: impl IntoIterator<Item = K>,
+		command: impl Fn(&mut Batch, &str),
+	) -> PaperClientResult<u64> {
+		let keys = keys
+			.into_iter()
+			.filter(|key| key.as_paper_key().starts_with(prefix))
+			.collect::<Vec<_>>();
+
+		let mut count = 0;
+
+		for chunk in keys.chunks(DEL_PREFIX_BATCH_SIZE) {
+			let responses = self.batch(|batch| {
+				for key in chunk {
+					command(batch, key.as_paper_key());
+				}
+			})?;
+
+			for response in responses {
+				match response {
+					Ok(BatchResponse::Ok | BatchResponse::Has(true)) => count += 1,
+					Ok(_) => {},
+					Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => {},
+					Err(err) => return Err(err),
+				}
+			}
+		}
+
+		Ok(count)
 	}
 
-	fn receive(&mut self, command: &Command<'_>) -> PaperClientResult<()> {
-		command.parse_reader(&mut self.stream)
+	fn record<R>(&mut self, result: &PaperClientResult<R>, start: Instant) {
+		let latency = start.elapsed();
+
+		if !result.as_ref().is_err_and(is_stream_error) {
+			self.is_stale = false;
+		}
+
+		// only a command's first attempt contributes to the retry budget
+		if self.reconnect_attempts == 0
+			&& let Some(budget) = &self.config.retry_budget
+		{
+			budget.deposit();
+		}
+
+		let is_error = result
+			.as_ref()
+			.is_err_and(|err| !matches!(err, PaperClientError::CacheError(_)));
+
+		self.stats.record_command(is_error);
+
+		if result.is_ok() {
+			self.stats.record_latency(latency);
+		}
+
+		if let Some(health) = &self.health {
+			health.record(result, latency);
+		}
 	}
 
-	fn receive_value(&mut self, command: &Command<'_>) -> PaperClientResult<PaperValue> {
-		command.parse_buf_reader(&mut self.stream)
+	fn send(&mut self, command: &Command<'_>) -> PaperClientResult<()> {
+		self.send_all(slice::from_ref(command))
 	}
 
-	fn receive_has(&mut self, command: &Command<'_>) -> PaperClientResult<bool> {
-		command.parse_has_reader(&mut self.stream)
+	fn send_all(&mut self, commands: &[Command<'_>]) -> PaperClientResult<()> {
+		if deadline::is_expired() {
+			return Err(PaperClientError::DeadlineExceeded);
+		}
+
+		// a connection inherited across a fork is shared with the parent
+		// process, so a new one is opened rather than interleaving writes
+		if self.pid != process::id() {
+			self.is_stale = true;
+		}
+
+		if self.is_stale {
+			// a strict client is never reconnected on behalf of a command
+			if self.config.strict {
+				return Err(PaperClientError::Disconnected);
+			}
+
+			self.reconnect()?;
+		}
+
+		// checked once the connection is known to be in sync, as a denied
+		// command is not a stream error
+		if !commands
+			.iter()
+			.all(|command| self.config.command_policy.is_allowed(command.kind()))
+		{
+			return Err(PaperClientError::CommandDenied);
+		}
+
+		for command in commands {
+			command.validate()?;
+		}
+
+		self.set_deadline_timeout()?;
+		self.is_stale = true;
+
+		let pending_auth = self.pending_auth.take();
+		let auth = pending_auth.as_deref().map(Command::Auth);
+
+		let key_display = self.config.key_display;
+		let is_redacted = self.config.capture.is_some()
+			&& (key_display != KeyDisplay::Full
+				|| auth.is_some()
+				|| commands.iter().any(|command| matches!(command, Command::Auth(_))));
+
+		let mut stream = self.capture_stream();
+
+		// the commands are captured with their keys (and any auth token)
+		// redacted rather than as they are written
+		if is_redacted {
+			for command in auth.iter().chain(commands) {
+				stream.record_sent(&command.to_display_bytes(key_display));
+			}
+
+			stream = stream.without_sent();
+		}
+
+		let result = match (&auth, commands) {
+			(None, [command]) => command.write(&mut stream),
+
+			// the commands are written together so that a batch is sent in
+			// as few packets as possible, along with the pending auth, if any
+			(auth, commands) => {
+				let buf = auth
+					.iter()
+					.chain(commands)
+					.flat_map(Command::to_bytes)
+					.collect::<Vec<_>>();
+
+				stream::write_buf(&mut stream, &buf)
+			},
+		};
+
+		result.map_err(|err| match err {
+			StreamError::InvalidStream => PaperClientError::Disconnected,
+			_ => PaperClientError::InvalidCommand,
+		})?;
+
+		// the auth's response precedes the commands'. If the token was
+		// rejected, the commands fail as unauthorized and their responses
+		// are read as usual
+		if let Some(auth) = &auth
+			&& let Err(err) = auth.parse_reader(&mut stream)
+			&& is_stream_error(&err)
+		{
+			return Err(err);
+		}
+
+		Ok(())
 	}
 
-	fn receive_size(&mut self, command: &Command<'_>) -> PaperClientResult<u32> {
-		command.parse_size_reader(&mut self.stream)
+	fn capture_stream(&mut self) -> CaptureStream<'_, T> {
+		CaptureStream::new(&mut self.stream, self.config.capture.as_deref(), &self.addr)
 	}
 
-	fn receive_status(&mut self, command: &Command<'_>) -> PaperClientResult<Status> {
-		command.parse_status_reader(&mut self.stream)
+	// bounds the command's reads by the time remaining until the deadline,
+	// in which case a read which times out is retried and then returned as
+	// DeadlineExceeded
+	fn set_deadline_timeout(&mut self) -> PaperClientResult<()> {
+		match deadline::remaining() {
+			Some(remaining) => {
+				let timeout = remaining.max(MIN_READ_TIMEOUT);
+
+				self.stream.set_read_timeout(Some(timeout))?;
+				self.has_read_timeout = true;
+			},
+
+			None if self.has_read_timeout => {
+				self.stream.set_read_timeout(None)?;
+				self.has_read_timeout = false;
+			},
+
+			None => {},
+		}
+
+		Ok(())
 	}
 
-	fn handshake(&mut self) -> PaperClientResult<()> {
-		let mut reader = StreamReader::new(&mut self.stream);
+	fn retry(&mut self, err: PaperClientError) -> PaperClientResult<()> {
+		if self.reconnect_attempts >= RECONNECT_MAX_ATTEMPTS {
+			return Err(PaperClientError::ReconnectExhausted {
+				attempts:   self.reconnect_attempts,
+				last_error: Box::new(err),
+			});
+		}
+
+		if deadline::is_expired() {
+			return Err(PaperClientError::DeadlineExceeded);
+		}
+
+		if let Some(budget) = &self.config.retry_budget
+			&& !budget.withdraw()
+		{
+			return Err(PaperClientError::RetryBudgetExhausted);
+		}
+
+		self.reconnect_attempts += 1;
+		self.reconnect()
+	}
 
-		let is_ok = reader
-			.read_bool()
-			.map_err(|_| PaperClientError::UnreachableServer)?;
+	fn backoff(&self) {
+		let delay = self.config.reconnect_delay(self.reconnect_attempts);
+		let delay = deadline::remaining().map_or(delay, |remaining| delay.min(remaining));
 
-		match is_ok {
-			true => Ok(()),
-			false => Err(PaperClientError::from_reader(reader)),
+		if !delay.is_zero() {
+			self.config.clock.sleep(delay);
 		}
 	}
 
 	fn reconnect(&mut self) -> PaperClientResult<()> {
-		if self.reconnect_attempts > RECONNECT_MAX_ATTEMPTS {
+		if self.is_closed {
 			return Err(PaperClientError::Disconnected);
 		}
 
-		self.stream = init_stream(&self.addr)?;
-		self.handshake()?;
+		self.backoff();
+		self.stats.reconnect();
 
-		if let Some(token) = self.auth_token.clone() {
-			self.auth(token)?;
-		}
+		self.stream = init_stream(&self.addr, &self.config)?;
+
+		self.is_stale = false;
+		self.has_read_timeout = false;
+		self.pid = process::id();
+
+		// the connection is authorized along with the next command, so that
+		// traffic resumes without waiting a round trip for the auth alone
+		self.pending_auth = match &self.auth_provider {
+			Some(provider) => Some(provider.token()?),
+			None => None,
+		};
 
 		Ok(())
 	}
 }
 
-fn init_stream(addr: &str) -> PaperClientResult<TcpStream> {
-	let stream = TcpStream::connect(addr).map_err(|_| PaperClientError::UnreachableServer)?;
+impl<T> Debug for PaperClient<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("PaperClient")
+			.field("addr", &format_args!("{}", DisplayAddr(&self.addr)))
+			.field("config", &self.config)
+			.field("auth_token", &self.auth_provider.as_ref().map(|_| "<redacted>"))
+			.field("reconnect_attempts", &self.reconnect_attempts)
+			.finish_non_exhaustive()
+	}
+}
+
+impl<T> Display for PaperClient<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.auth_provider.is_some() {
+			true => write!(f, "PaperClient({}, authed)", DisplayAddr(&self.addr)),
+			false => write!(f, "PaperClient({})", DisplayAddr(&self.addr)),
+		}
+	}
+}
+
+fn init_stream<T: PaperTransport>(
+	addr: &str,
+	config: &PaperClientConfig,
+) -> PaperClientResult<T> {
+	let mut attempt = 0;
 
-	if stream.set_nodelay(true).is_err() {
-		return Err(PaperClientError::Internal);
+	loop {
+		match connect(addr, config) {
+			Err(PaperClientError::UnreachableServer) if attempt < config.connect_retries => {
+				config.clock.sleep(config.connect_delay(attempt));
+				attempt += 1;
+			},
+
+			result => return result,
+		}
 	}
+}
+
+fn connect<T: PaperTransport>(addr: &str, config: &PaperClientConfig) -> PaperClientResult<T> {
+	let mut stream = T::open_with(addr, config)?;
+
+	handshake(&mut stream, config.handshake_timeout)?;
 
 	Ok(stream)
 }
+
+fn is_stream_error(err: &PaperClientError) -> bool {
+	matches!(
+		err,
+		PaperClientError::InvalidCommand
+			| PaperClientError::InvalidResponse
+			| PaperClientError::Disconnected
+			| PaperClientError::DeadlineExceeded
+	)
+}
+
+fn handshake<T: PaperTransport>(
+	stream: &mut T,
+	timeout: Option<Duration>,
+) -> PaperClientResult<()> {
+	stream.set_read_timeout(timeout)?;
+
+	let mut reader = StreamReader::new(&mut *stream);
+
+	let result = match reader.read_bool() {
+		Ok(true) => Ok(()),
+		Ok(false) => Err(PaperClientError::from_reader(reader)),
+		Err(_) => Err(PaperClientError::UnreachableServer),
+	};
+
+	stream.set_read_timeout(None)?;
+
+	result
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+	use std::{io::Write, net::TcpStream, os::fd::AsFd};
+
+	use paper_utils::stream::StreamReader;
+
+	use super::PaperClient;
+	use crate::command::Command;
+
+	#[test]
+	fn reconnect_after_fork() {
+		let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+
+		// the parent process's copy of the inherited connection
+		let fd = client.stream.as_fd().try_clone_to_owned().unwrap();
+		let mut parent = TcpStream::from(fd);
+
+		client.pid = client.pid.wrapping_add(1);
+
+		assert!(client.ping().is_ok());
+		assert_eq!(client.client_stats().reconnects(), 1);
+
+		// the inherited connection was not shut down
+		parent.write_all(&Command::Ping.to_bytes()).unwrap();
+		assert!(StreamReader::new(&mut parent).read_bool().unwrap());
+	}
+}
@@ -0,0 +1,108 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	fmt::Debug,
+	sync::{Arc, Mutex},
+	thread,
+	time::{Duration, Instant},
+};
+
+/// The source of time for a client's time-based logic: the delays between
+/// connection and reconnection attempts of a `PaperClient`, how long a
+/// pool's node stays ejected, and the interval between policy changes
+/// allowed by `Guardrails`. Replacing the clock with a `MockClock` lets
+/// tests of this logic run instantly rather than sleeping.
+///
+/// The async client and pool sleep with tokio's timer, which can be paused
+/// and advanced in tests with `tokio::time::pause`.
+///
+/// The clock is monotonic, so it does not affect a `Ttl::At`, which is
+/// always converted against the system's wall clock.
+pub trait Clock: Debug + Send + Sync {
+	/// Returns the current instant.
+	fn now(&self) -> Instant;
+
+	/// Blocks the current thread for the supplied duration.
+	fn sleep(&self, duration: Duration);
+}
+
+/// The system's clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+/// A clock which only advances when told to. Sleeping on the clock advances
+/// it by the slept duration without blocking. Clones of the clock share its
+/// time.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use paper_client::{Clock, MockClock, PaperClientConfig};
+///
+/// let clock = MockClock::new();
+/// let config = PaperClientConfig::default().clock(clock.clone());
+///
+/// let start = clock.now();
+/// clock.advance(Duration::from_secs(60));
+///
+/// assert_eq!(clock.now() - start, Duration::from_secs(60));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockClock {
+	start:   Instant,
+	elapsed: Arc<Mutex<Duration>>,
+}
+
+impl Clock for SystemClock {
+	fn now(&self) -> Instant {
+		Instant::now()
+	}
+
+	fn sleep(&self, duration: Duration) {
+		thread::sleep(duration);
+	}
+}
+
+impl MockClock {
+	/// Creates a new mock clock, starting at the current instant.
+	#[must_use]
+	pub fn new() -> Self {
+		MockClock {
+			start:   Instant::now(),
+			elapsed: Arc::default(),
+		}
+	}
+
+	/// Advances the clock by the supplied duration.
+	pub fn advance(&self, duration: Duration) {
+		*self
+			.elapsed
+			.lock()
+			.expect("Could not obtain clock.") += duration;
+	}
+}
+
+impl Clock for MockClock {
+	fn now(&self) -> Instant {
+		self.start
+			+ *self
+				.elapsed
+				.lock()
+				.expect("Could not obtain clock.")
+	}
+
+	fn sleep(&self, duration: Duration) {
+		self.advance(duration);
+	}
+}
+
+impl Default for MockClock {
+	fn default() -> Self {
+		MockClock::new()
+	}
+}
@@ -0,0 +1,60 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::fmt::Debug;
+
+use crate::error::PaperClientResult;
+
+/// Transforms values on their way to and from the cache (e.g., to compress
+/// or encrypt them). A codec is configured once with
+/// `PaperClientConfig::value_codec` and is then applied to every value the
+/// client sets and gets, including those of batches.
+///
+/// An encoding failure should be reported as `InvalidValue` and a decoding
+/// failure as `InvalidEncoding`.
+///
+/// # Examples
+/// ```
+/// use paper_client::{PaperClientConfig, ValueCodec, error::PaperClientResult};
+///
+/// // reverses the bytes of every value
+/// #[derive(Debug)]
+/// struct ReverseCodec;
+///
+/// impl ValueCodec for ReverseCodec {
+///     fn encode(&self, value: &[u8]) -> PaperClientResult<Vec<u8>> {
+///         Ok(value.iter().rev().copied().collect())
+///     }
+///
+///     fn decode(&self, value: &[u8]) -> PaperClientResult<Vec<u8>> {
+///         Ok(value.iter().rev().copied().collect())
+///     }
+/// }
+///
+/// let config = PaperClientConfig::default().value_codec(ReverseCodec);
+/// ```
+pub trait ValueCodec: Debug + Send + Sync {
+	/// Encodes a value before it is sent to the cache.
+	fn encode(&self, value: &[u8]) -> PaperClientResult<Vec<u8>>;
+
+	/// Decodes a value after it is received from the cache.
+	fn decode(&self, value: &[u8]) -> PaperClientResult<Vec<u8>>;
+}
+
+/// A codec which leaves values unchanged.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IdentityCodec;
+
+impl ValueCodec for IdentityCodec {
+	fn encode(&self, value: &[u8]) -> PaperClientResult<Vec<u8>> {
+		Ok(value.to_vec())
+	}
+
+	fn decode(&self, value: &[u8]) -> PaperClientResult<Vec<u8>> {
+		Ok(value.to_vec())
+	}
+}
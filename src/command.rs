@@ -6,8 +6,9 @@
  */
 
 use std::{
-	io::{Read, Write},
-	str::FromStr,
+	fmt::{self, Display, Write as _},
+	io::{self, Read, Write},
+	str,
 };
 
 #[cfg(feature = "tokio")]
@@ -15,18 +16,26 @@ use paper_utils::stream::AsyncStreamReader;
 use paper_utils::{
 	command::CommandByte,
 	sheet::{Sheet, SheetBuilder},
-	stream::{StreamError, StreamReader},
+	stream::{self, StreamError, StreamReader},
 };
 #[cfg(feature = "tokio")]
-use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 use crate::{
-	error::{PaperClientError, PaperClientResult},
+	error::{CommandError, PaperClientError, PaperClientResult},
+	key_display::KeyDisplay,
 	policy::PaperPolicy,
 	status::Status,
 	value::PaperValue,
 };
 
+const COPY_CHUNK_SIZE: usize = 8192;
+
+// the maximum size of a key, value, or token, whose size is sent as a u32
+const MAX_ARG_SIZE: usize = u32::MAX as usize;
+
+/// A command sent to the server.
+#[derive(Debug)]
 pub enum Command<'a> {
 	Ping,
 	Version,
@@ -50,20 +59,239 @@ pub enum Command<'a> {
 	Status,
 }
 
+/// The kind of a command, without its arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum CommandKind {
+	Ping    = CommandByte::PING,
+	Version = CommandByte::VERSION,
+
+	Auth = CommandByte::AUTH,
+
+	Get = CommandByte::GET,
+	Set = CommandByte::SET,
+	Del = CommandByte::DEL,
+
+	Has  = CommandByte::HAS,
+	Peek = CommandByte::PEEK,
+	Ttl  = CommandByte::TTL,
+	Size = CommandByte::SIZE,
+
+	Wipe = CommandByte::WIPE,
+
+	Resize = CommandByte::RESIZE,
+	Policy = CommandByte::POLICY,
+
+	Status = CommandByte::STATUS,
+}
+
+/// The shape of the response the server sends for a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ResponseShape {
+	/// Only the success flag (or an error).
+	Ok,
+	/// A length-prefixed buffer.
+	Buf,
+	/// A single boolean.
+	Bool,
+	/// A single `u32`.
+	U32,
+	/// A full `Status` frame.
+	Status,
+}
+
+/// A field of a command's frame, sent after the command byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Field {
+	/// The auth token, as a length-prefixed string.
+	Token,
+	/// A key, as a length-prefixed string.
+	Key,
+	/// A value, as a length-prefixed buffer.
+	Value,
+	/// A TTL, as a `u32`.
+	Ttl,
+	/// A cache size, as a `u64`.
+	Size,
+	/// An eviction policy, as a length-prefixed string.
+	Policy,
+}
+
+/// The value of a command's field, as it is encoded on the wire.
+enum Arg<'a> {
+	Str(&'a str),
+	Buf(&'a [u8]),
+	U32(u32),
+	U64(u64),
+	Policy(&'a PaperPolicy),
+}
+
+/// The static description of a command: its name, its byte on the wire,
+/// the layout of its fields, and the shape of its response.
+#[derive(Debug)]
+pub(crate) struct CommandSpec {
+	pub(crate) name:     &'static str,
+	pub(crate) byte:     u8,
+	pub(crate) fields:   &'static [Field],
+	pub(crate) response: ResponseShape,
+}
+
+/// The command table, indexed by command byte. Adding a command to the
+/// protocol starts here; the entries are checked against their bytes at
+/// compile time, and each command is validated and encoded from its
+/// field layout.
+const COMMANDS: [CommandSpec; 14] = [
+	CommandSpec::new("ping", CommandByte::PING, &[], ResponseShape::Buf),
+	CommandSpec::new("version", CommandByte::VERSION, &[], ResponseShape::Buf),
+
+	CommandSpec::new("auth", CommandByte::AUTH, &[Field::Token], ResponseShape::Ok),
+
+	CommandSpec::new("get", CommandByte::GET, &[Field::Key], ResponseShape::Buf),
+	CommandSpec::new(
+		"set",
+		CommandByte::SET,
+		&[Field::Key, Field::Value, Field::Ttl],
+		ResponseShape::Ok,
+	),
+	CommandSpec::new("del", CommandByte::DEL, &[Field::Key], ResponseShape::Ok),
+
+	CommandSpec::new("has", CommandByte::HAS, &[Field::Key], ResponseShape::Bool),
+	CommandSpec::new("peek", CommandByte::PEEK, &[Field::Key], ResponseShape::Buf),
+	CommandSpec::new("ttl", CommandByte::TTL, &[Field::Key, Field::Ttl], ResponseShape::Ok),
+	CommandSpec::new("size", CommandByte::SIZE, &[Field::Key], ResponseShape::U32),
+
+	CommandSpec::new("wipe", CommandByte::WIPE, &[], ResponseShape::Ok),
+
+	CommandSpec::new("resize", CommandByte::RESIZE, &[Field::Size], ResponseShape::Ok),
+	CommandSpec::new("policy", CommandByte::POLICY, &[Field::Policy], ResponseShape::Ok),
+
+	CommandSpec::new("status", CommandByte::STATUS, &[], ResponseShape::Status),
+];
+
+const _: () = {
+	let mut index = 0;
+
+	while index < COMMANDS.len() {
+		assert!(COMMANDS[index].byte as usize == index, "command table out of order");
+		index += 1;
+	}
+};
+
+impl CommandKind {
+	/// Returns the name of the command.
+	#[must_use]
+	pub const fn name(self) -> &'static str {
+		COMMANDS[self as usize].name
+	}
+
+	/// Returns `true` if the command modifies the cache.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::CommandKind;
+	///
+	/// assert!(CommandKind::Set.is_mutation());
+	/// assert!(!CommandKind::Peek.is_mutation());
+	/// ```
+	#[must_use]
+	pub const fn is_mutation(self) -> bool {
+		matches!(
+			self,
+			CommandKind::Set
+				| CommandKind::Del
+				| CommandKind::Ttl
+				| CommandKind::Wipe
+				| CommandKind::Resize
+				| CommandKind::Policy
+		)
+	}
+}
+
+impl Display for CommandKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.name())
+	}
+}
+
+impl CommandSpec {
+	const fn new(
+		name: &'static str,
+		byte: u8,
+		fields: &'static [Field],
+		response: ResponseShape,
+	) -> Self {
+		CommandSpec {
+			name,
+			byte,
+			fields,
+			response,
+		}
+	}
+}
+
+impl Field {
+	/// Returns the error for an argument in this field which is too large
+	/// to be sent.
+	fn too_large(self, size: usize) -> CommandError {
+		match self {
+			Field::Token => CommandError::TokenTooLarge {
+				size,
+				max: MAX_ARG_SIZE,
+			},
+
+			Field::Key => CommandError::KeyTooLarge {
+				size,
+				max: MAX_ARG_SIZE,
+			},
+
+			_ => CommandError::ValueTooLarge {
+				size,
+				max: MAX_ARG_SIZE,
+			},
+		}
+	}
+}
+
+impl Arg<'_> {
+	/// Returns the number of bytes the argument occupies on the wire.
+	fn encoded_len(&self) -> usize {
+		match self {
+			Arg::Str(value) => buf_len(value.len()),
+			Arg::Buf(value) => buf_len(value.len()),
+			Arg::U32(_) => 4,
+			Arg::U64(_) => 8,
+			Arg::Policy(policy) => buf_len(formatted_len(policy)),
+		}
+	}
+}
+
 impl Command<'_> {
-	pub fn write(&self, writer: &mut impl Write) -> Result<(), StreamError> {
+	pub(crate) fn write(&self, writer: &mut impl Write) -> Result<(), StreamError> {
 		self.to_sheet().write(writer)
 	}
 
+	pub(crate) fn to_bytes(&self) -> Vec<u8> {
+		self.to_sheet().serialize().to_vec()
+	}
+
+	/// Returns the command's bytes with each key shown as the supplied key
+	/// display requires and the auth token redacted, for recording the
+	/// command in a capture.
+	pub(crate) fn to_display_bytes(&self, key_display: KeyDisplay) -> Vec<u8> {
+		self.to_sheet_with(key_display).serialize().to_vec()
+	}
+
 	#[cfg(feature = "tokio")]
-	pub async fn write_async<W>(&self, writer: &mut W) -> Result<(), StreamError>
+	pub(crate) async fn write_async<W>(&self, writer: &mut W) -> Result<(), StreamError>
 	where
 		W: AsyncWrite + Unpin,
 	{
 		self.to_sheet().write_async(writer).await
 	}
 
-	pub fn parse_reader(&self, reader: &mut impl Read) -> PaperClientResult<()> {
+	pub(crate) fn parse_reader(&self, reader: &mut impl Read) -> PaperClientResult<()> {
+		debug_assert_eq!(self.spec().response, ResponseShape::Ok);
+
 		let mut reader = StreamReader::new(reader);
 
 		let is_ok = reader
@@ -77,10 +305,12 @@ impl Command<'_> {
 	}
 
 	#[cfg(feature = "tokio")]
-	pub async fn parse_reader_async<R>(&self, reader: &mut R) -> PaperClientResult<()>
+	pub(crate) async fn parse_reader_async<R>(&self, reader: &mut R) -> PaperClientResult<()>
 	where
 		R: AsyncRead + Unpin,
 	{
+		debug_assert_eq!(self.spec().response, ResponseShape::Ok);
+
 		let mut reader = AsyncStreamReader::new(reader);
 
 		let is_ok = reader
@@ -94,7 +324,172 @@ impl Command<'_> {
 		}
 	}
 
-	pub fn parse_buf_reader(&self, reader: &mut impl Read) -> PaperClientResult<PaperValue> {
+	pub(crate) fn parse_buf_reader(
+		&self,
+		reader: &mut impl Read,
+		max_size: Option<u32>,
+	) -> PaperClientResult<PaperValue> {
+		let size = self.parse_buf_size(reader)?;
+
+		if max_size.is_some_and(|max_size| size > max_size) {
+			// the value is drained so that the connection stays in sync
+			copy_buf(reader, &mut io::sink(), size)?;
+			return Err(PaperClientError::ResponseTooLarge);
+		}
+
+		let buf = stream::read_buf(reader, size as usize)
+			.map_err(|_| PaperClientError::InvalidResponse)?;
+
+		Ok(buf.into())
+	}
+
+	#[cfg(feature = "tokio")]
+	pub(crate) async fn parse_buf_reader_async<R>(
+		&self,
+		reader: &mut R,
+		max_size: Option<u32>,
+	) -> PaperClientResult<PaperValue>
+	where
+		R: AsyncRead + Unpin,
+	{
+		let size = self.parse_buf_size_async(reader).await?;
+
+		if max_size.is_some_and(|max_size| size > max_size) {
+			copy_buf_async(reader, &mut tokio::io::sink(), size).await?;
+			return Err(PaperClientError::ResponseTooLarge);
+		}
+
+		let buf = stream::read_buf_async(reader, size as usize)
+			.await
+			.map_err(|_| PaperClientError::InvalidResponse)?;
+
+		Ok(buf.into())
+	}
+
+	pub(crate) fn parse_buf_vec(
+		&self,
+		reader: &mut impl Read,
+		buf: &mut Vec<u8>,
+		max_size: Option<u32>,
+	) -> PaperClientResult<usize> {
+		let size = self.parse_buf_size(reader)?;
+
+		if max_size.is_some_and(|max_size| size > max_size) {
+			copy_buf(reader, &mut io::sink(), size)?;
+			return Err(PaperClientError::ResponseTooLarge);
+		}
+
+		buf.clear();
+		buf.resize(size as usize, 0);
+
+		reader
+			.read_exact(buf)
+			.map_err(|_| PaperClientError::InvalidResponse)?;
+
+		Ok(buf.len())
+	}
+
+	#[cfg(feature = "tokio")]
+	pub(crate) async fn parse_buf_vec_async<R>(
+		&self,
+		reader: &mut R,
+		buf: &mut Vec<u8>,
+		max_size: Option<u32>,
+	) -> PaperClientResult<usize>
+	where
+		R: AsyncRead + Unpin,
+	{
+		let size = self.parse_buf_size_async(reader).await?;
+
+		if max_size.is_some_and(|max_size| size > max_size) {
+			copy_buf_async(reader, &mut tokio::io::sink(), size).await?;
+			return Err(PaperClientError::ResponseTooLarge);
+		}
+
+		buf.clear();
+		buf.resize(size as usize, 0);
+
+		reader
+			.read_exact(buf)
+			.await
+			.map_err(|_| PaperClientError::InvalidResponse)?;
+
+		Ok(buf.len())
+	}
+
+	pub(crate) fn parse_buf_slice(
+		&self,
+		reader: &mut impl Read,
+		buf: &mut [u8],
+	) -> PaperClientResult<usize> {
+		let size = self.parse_buf_size(reader)?;
+
+		if size as usize > buf.len() {
+			copy_buf(reader, &mut io::sink(), size)?;
+			return Err(PaperClientError::ResponseTooLarge);
+		}
+
+		reader
+			.read_exact(&mut buf[..size as usize])
+			.map_err(|_| PaperClientError::InvalidResponse)?;
+
+		Ok(size as usize)
+	}
+
+	#[cfg(feature = "tokio")]
+	pub(crate) async fn parse_buf_slice_async<R>(
+		&self,
+		reader: &mut R,
+		buf: &mut [u8],
+	) -> PaperClientResult<usize>
+	where
+		R: AsyncRead + Unpin,
+	{
+		let size = self.parse_buf_size_async(reader).await?;
+
+		if size as usize > buf.len() {
+			copy_buf_async(reader, &mut tokio::io::sink(), size).await?;
+			return Err(PaperClientError::ResponseTooLarge);
+		}
+
+		reader
+			.read_exact(&mut buf[..size as usize])
+			.await
+			.map_err(|_| PaperClientError::InvalidResponse)?;
+
+		Ok(size as usize)
+	}
+
+	pub(crate) fn parse_buf_writer(
+		&self,
+		reader: &mut impl Read,
+		writer: &mut impl Write,
+	) -> PaperClientResult<u64> {
+		let size = self.parse_buf_size(reader)?;
+		copy_buf(reader, writer, size)?;
+
+		Ok(size.into())
+	}
+
+	#[cfg(feature = "tokio")]
+	pub(crate) async fn parse_buf_writer_async<R, W>(
+		&self,
+		reader: &mut R,
+		writer: &mut W,
+	) -> PaperClientResult<u64>
+	where
+		R: AsyncRead + Unpin,
+		W: AsyncWrite + Unpin,
+	{
+		let size = self.parse_buf_size_async(reader).await?;
+		copy_buf_async(reader, writer, size).await?;
+
+		Ok(size.into())
+	}
+
+	fn parse_buf_size(&self, reader: &mut impl Read) -> PaperClientResult<u32> {
+		debug_assert_eq!(self.spec().response, ResponseShape::Buf);
+
 		let mut reader = StreamReader::new(reader);
 
 		let is_ok = reader
@@ -102,23 +497,21 @@ impl Command<'_> {
 			.map_err(|_| PaperClientError::InvalidResponse)?;
 
 		match is_ok {
-			true => {
-				let buf = reader
-					.read_buf()
-					.map_err(|_| PaperClientError::InvalidResponse)?;
-
-				Ok(buf.into())
-			},
+			true => reader
+				.read_u32()
+				.map_err(|_| PaperClientError::InvalidResponse),
 
 			false => Err(PaperClientError::from_reader(reader)),
 		}
 	}
 
 	#[cfg(feature = "tokio")]
-	pub async fn parse_buf_reader_async<R>(&self, reader: &mut R) -> PaperClientResult<PaperValue>
+	async fn parse_buf_size_async<R>(&self, reader: &mut R) -> PaperClientResult<u32>
 	where
 		R: AsyncRead + Unpin,
 	{
+		debug_assert_eq!(self.spec().response, ResponseShape::Buf);
+
 		let mut reader = AsyncStreamReader::new(reader);
 
 		let is_ok = reader
@@ -127,20 +520,18 @@ impl Command<'_> {
 			.map_err(|_| PaperClientError::InvalidResponse)?;
 
 		match is_ok {
-			true => {
-				let buf = reader
-					.read_buf()
-					.await
-					.map_err(|_| PaperClientError::InvalidResponse)?;
-
-				Ok(buf.into())
-			},
+			true => reader
+				.read_u32()
+				.await
+				.map_err(|_| PaperClientError::InvalidResponse),
 
 			false => Err(PaperClientError::from_reader_async(reader).await),
 		}
 	}
 
-	pub fn parse_has_reader(&self, reader: &mut impl Read) -> PaperClientResult<bool> {
+	pub(crate) fn parse_has_reader(&self, reader: &mut impl Read) -> PaperClientResult<bool> {
+		debug_assert_eq!(self.spec().response, ResponseShape::Bool);
+
 		let mut reader = StreamReader::new(reader);
 
 		let is_ok = reader
@@ -161,10 +552,12 @@ impl Command<'_> {
 	}
 
 	#[cfg(feature = "tokio")]
-	pub async fn parse_has_reader_async<R>(&self, reader: &mut R) -> PaperClientResult<bool>
+	pub(crate) async fn parse_has_reader_async<R>(&self, reader: &mut R) -> PaperClientResult<bool>
 	where
 		R: AsyncRead + Unpin,
 	{
+		debug_assert_eq!(self.spec().response, ResponseShape::Bool);
+
 		let mut reader = AsyncStreamReader::new(reader);
 
 		let is_ok = reader
@@ -186,7 +579,9 @@ impl Command<'_> {
 		}
 	}
 
-	pub fn parse_size_reader(&self, reader: &mut impl Read) -> PaperClientResult<u32> {
+	pub(crate) fn parse_size_reader(&self, reader: &mut impl Read) -> PaperClientResult<u32> {
+		debug_assert_eq!(self.spec().response, ResponseShape::U32);
+
 		let mut reader = StreamReader::new(reader);
 
 		let is_ok = reader
@@ -207,10 +602,12 @@ impl Command<'_> {
 	}
 
 	#[cfg(feature = "tokio")]
-	pub async fn parse_size_reader_async<R>(&self, reader: &mut R) -> PaperClientResult<u32>
+	pub(crate) async fn parse_size_reader_async<R>(&self, reader: &mut R) -> PaperClientResult<u32>
 	where
 		R: AsyncRead + Unpin,
 	{
+		debug_assert_eq!(self.spec().response, ResponseShape::U32);
+
 		let mut reader = AsyncStreamReader::new(reader);
 
 		let is_ok = reader
@@ -232,7 +629,9 @@ impl Command<'_> {
 		}
 	}
 
-	pub fn parse_status_reader(&self, reader: &mut impl Read) -> PaperClientResult<Status> {
+	pub(crate) fn parse_status_reader(&self, reader: &mut impl Read) -> PaperClientResult<Status> {
+		debug_assert_eq!(self.spec().response, ResponseShape::Status);
+
 		let mut reader = StreamReader::new(reader);
 
 		let is_ok = reader
@@ -275,26 +674,30 @@ impl Command<'_> {
 				.read_f64()
 				.map_err(|_| PaperClientError::InvalidResponse)?;
 
+			// a policy which is not valid UTF-8 does not desynchronize the
+			// stream, so the remainder of the frame is still read
+			let mut is_malformed = false;
+
 			let mut policies = Vec::<PaperPolicy>::new();
 			let num_policies = reader
 				.read_u32()
 				.map_err(|_| PaperClientError::InvalidResponse)?;
 
 			for _ in 0..num_policies {
-				let policy_str = reader
-					.read_string()
-					.map_err(|_| PaperClientError::InvalidResponse)?;
-				let policy = PaperPolicy::from_str(&policy_str)
+				let policy_buf = reader
+					.read_buf()
 					.map_err(|_| PaperClientError::InvalidResponse)?;
 
-				policies.push(policy);
+				match parse_policy(&policy_buf) {
+					Some(policy) => policies.push(policy),
+					None => is_malformed = true,
+				}
 			}
 
-			let policy_str = reader
-				.read_string()
-				.map_err(|_| PaperClientError::InvalidResponse)?;
-			let policy = PaperPolicy::from_str(&policy_str)
+			let policy_buf = reader
+				.read_buf()
 				.map_err(|_| PaperClientError::InvalidResponse)?;
+			let policy = parse_policy(&policy_buf);
 			let is_auto_policy = reader
 				.read_bool()
 				.map_err(|_| PaperClientError::InvalidResponse)?;
@@ -303,6 +706,10 @@ impl Command<'_> {
 				.read_u64()
 				.map_err(|_| PaperClientError::InvalidResponse)?;
 
+			let (false, Some(policy)) = (is_malformed, policy) else {
+				return Err(PaperClientError::MalformedResponse);
+			};
+
 			let status = Status::new(
 				pid,
 				max_size,
@@ -327,10 +734,12 @@ impl Command<'_> {
 	}
 
 	#[cfg(feature = "tokio")]
-	pub async fn parse_status_reader_async<R>(&self, reader: &mut R) -> PaperClientResult<Status>
+	pub(crate) async fn parse_status_reader_async<R>(&self, reader: &mut R) -> PaperClientResult<Status>
 	where
 		R: AsyncRead + Unpin,
 	{
+		debug_assert_eq!(self.spec().response, ResponseShape::Status);
+
 		let mut reader = AsyncStreamReader::new(reader);
 
 		let is_ok = reader
@@ -384,6 +793,10 @@ impl Command<'_> {
 				.await
 				.map_err(|_| PaperClientError::InvalidResponse)?;
 
+			// a policy which is not valid UTF-8 does not desynchronize the
+			// stream, so the remainder of the frame is still read
+			let mut is_malformed = false;
+
 			let mut policies = Vec::<PaperPolicy>::new();
 			let num_policies = reader
 				.read_u32()
@@ -391,22 +804,22 @@ impl Command<'_> {
 				.map_err(|_| PaperClientError::InvalidResponse)?;
 
 			for _ in 0..num_policies {
-				let policy_str = reader
-					.read_string()
+				let policy_buf = reader
+					.read_buf()
 					.await
 					.map_err(|_| PaperClientError::InvalidResponse)?;
-				let policy = PaperPolicy::from_str(&policy_str)
-					.map_err(|_| PaperClientError::InvalidResponse)?;
 
-				policies.push(policy);
+				match parse_policy(&policy_buf) {
+					Some(policy) => policies.push(policy),
+					None => is_malformed = true,
+				}
 			}
 
-			let policy_str = reader
-				.read_string()
+			let policy_buf = reader
+				.read_buf()
 				.await
 				.map_err(|_| PaperClientError::InvalidResponse)?;
-			let policy = PaperPolicy::from_str(&policy_str)
-				.map_err(|_| PaperClientError::InvalidResponse)?;
+			let policy = parse_policy(&policy_buf);
 			let is_auto_policy = reader
 				.read_bool()
 				.await
@@ -417,6 +830,10 @@ impl Command<'_> {
 				.await
 				.map_err(|_| PaperClientError::InvalidResponse)?;
 
+			let (false, Some(policy)) = (is_malformed, policy) else {
+				return Err(PaperClientError::MalformedResponse);
+			};
+
 			let status = Status::new(
 				pid,
 				max_size,
@@ -440,76 +857,317 @@ impl Command<'_> {
 		}
 	}
 
-	fn to_sheet(&self) -> Sheet {
+	/// Returns the kind of the command.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{Command, CommandKind};
+	///
+	/// assert_eq!(Command::Get("key").kind(), CommandKind::Get);
+	/// ```
+	#[must_use]
+	pub const fn kind(&self) -> CommandKind {
 		match self {
-			Command::Ping => SheetBuilder::new()
-				.write_u8(CommandByte::PING)
-				.into_sheet(),
-
-			Command::Version => SheetBuilder::new()
-				.write_u8(CommandByte::VERSION)
-				.into_sheet(),
-
-			Command::Auth(token) => SheetBuilder::new()
-				.write_u8(CommandByte::AUTH)
-				.write_str(token)
-				.into_sheet(),
-
-			Command::Get(key) => SheetBuilder::new()
-				.write_u8(CommandByte::GET)
-				.write_str(key)
-				.into_sheet(),
-
-			Command::Set(key, value, ttl) => SheetBuilder::new()
-				.write_u8(CommandByte::SET)
-				.write_str(key)
-				.write_buf(value.into())
-				.write_u32(*ttl)
-				.into_sheet(),
-
-			Command::Del(key) => SheetBuilder::new()
-				.write_u8(CommandByte::DEL)
-				.write_str(key)
-				.into_sheet(),
-
-			Command::Has(key) => SheetBuilder::new()
-				.write_u8(CommandByte::HAS)
-				.write_str(key)
-				.into_sheet(),
-
-			Command::Peek(key) => SheetBuilder::new()
-				.write_u8(CommandByte::PEEK)
-				.write_str(key)
-				.into_sheet(),
-
-			Command::Ttl(key, ttl) => SheetBuilder::new()
-				.write_u8(CommandByte::TTL)
-				.write_str(key)
-				.write_u32(*ttl)
-				.into_sheet(),
-
-			Command::Size(key) => SheetBuilder::new()
-				.write_u8(CommandByte::SIZE)
-				.write_str(key)
-				.into_sheet(),
-
-			Command::Wipe => SheetBuilder::new()
-				.write_u8(CommandByte::WIPE)
-				.into_sheet(),
-
-			Command::Resize(size) => SheetBuilder::new()
-				.write_u8(CommandByte::RESIZE)
-				.write_u64(*size)
-				.into_sheet(),
-
-			Command::Policy(policy) => SheetBuilder::new()
-				.write_u8(CommandByte::POLICY)
-				.write_str(policy.to_string())
-				.into_sheet(),
-
-			Command::Status => SheetBuilder::new()
-				.write_u8(CommandByte::STATUS)
-				.into_sheet(),
+			Command::Ping => CommandKind::Ping,
+			Command::Version => CommandKind::Version,
+
+			Command::Auth(_) => CommandKind::Auth,
+
+			Command::Get(_) => CommandKind::Get,
+			Command::Set(..) => CommandKind::Set,
+			Command::Del(_) => CommandKind::Del,
+
+			Command::Has(_) => CommandKind::Has,
+			Command::Peek(_) => CommandKind::Peek,
+			Command::Ttl(..) => CommandKind::Ttl,
+			Command::Size(_) => CommandKind::Size,
+
+			Command::Wipe => CommandKind::Wipe,
+
+			Command::Resize(_) => CommandKind::Resize,
+			Command::Policy(_) => CommandKind::Policy,
+
+			Command::Status => CommandKind::Status,
+		}
+	}
+
+	/// Returns the command's byte, as sent on the wire.
+	pub(crate) const fn byte(&self) -> u8 {
+		self.kind() as u8
+	}
+
+	/// Returns the command's entry in the command table.
+	pub(crate) const fn spec(&self) -> &'static CommandSpec {
+		&COMMANDS[self.byte() as usize]
+	}
+
+	/// Returns the name of the command.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::Command;
+	///
+	/// assert_eq!(Command::Get("key").name(), "get");
+	/// ```
+	#[must_use]
+	pub const fn name(&self) -> &'static str {
+		self.spec().name
+	}
+
+	/// Returns the number of bytes the command occupies on the wire.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::Command;
+	///
+	/// assert_eq!(Command::Ping.encoded_len(), 1);
+	/// assert_eq!(Command::Get("key").encoded_len(), 8);
+	/// ```
+	#[must_use]
+	pub fn encoded_len(&self) -> usize {
+		let args: usize = self
+			.spec()
+			.fields
+			.iter()
+			.map(|field| self.arg(*field).encoded_len())
+			.sum();
+
+		1 + args
+	}
+
+	/// Writes the command into the supplied buffer exactly as it is sent to
+	/// the server, without allocating, and returns the number of bytes
+	/// written. This allows a single buffer to be reused for every command
+	/// (e.g., in constrained environments which write to the connection
+	/// themselves). If the buffer is smaller than `encoded_len`,
+	/// `BufferTooSmall` is returned and the buffer is left unchanged.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::Command;
+	///
+	/// let mut buf = [0u8; 64];
+	///
+	/// let len = Command::Get("key").encode_into(&mut buf).unwrap();
+	/// assert_eq!(&buf[..len], b"\x03\x03\x00\x00\x00key");
+	/// ```
+	pub fn encode_into(&self, buf: &mut [u8]) -> PaperClientResult<usize> {
+		self.validate()?;
+
+		let required = self.encoded_len();
+
+		if buf.len() < required {
+			return Err(PaperClientError::BufferTooSmall { required });
+		}
+
+		let mut writer = SliceWriter {
+			buf,
+			len: 0,
+		};
+
+		writer.write_bytes(&[self.byte()]);
+
+		for field in self.spec().fields {
+			match self.arg(*field) {
+				Arg::Str(value) => writer.write_buf(value.as_bytes()),
+				Arg::Buf(value) => writer.write_buf(value),
+				Arg::U32(value) => writer.write_bytes(&value.to_le_bytes()),
+				Arg::U64(value) => writer.write_bytes(&value.to_le_bytes()),
+
+				Arg::Policy(policy) => {
+					let len = formatted_len(policy) as u32;
+
+					writer.write_bytes(&len.to_le_bytes());
+					let _ = write!(writer, "{policy}");
+				},
+			}
+		}
+
+		Ok(writer.len)
+	}
+
+	/// Checks that each of the command's arguments fits in the protocol,
+	/// so that a command which cannot be encoded is reported before any of
+	/// it is sent.
+	pub(crate) fn validate(&self) -> Result<(), CommandError> {
+		for field in self.spec().fields {
+			let size = match self.arg(*field) {
+				Arg::Str(value) => value.len(),
+				Arg::Buf(value) => value.len(),
+				_ => continue,
+			};
+
+			if size > MAX_ARG_SIZE {
+				return Err(field.too_large(size));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Returns the command's argument in the supplied field of its layout.
+	fn arg(&self, field: Field) -> Arg<'_> {
+		match (field, self) {
+			(Field::Token, Command::Auth(token)) => Arg::Str(token),
+
+			(
+				Field::Key,
+				Command::Get(key)
+				| Command::Set(key, ..)
+				| Command::Del(key)
+				| Command::Has(key)
+				| Command::Peek(key)
+				| Command::Ttl(key, _)
+				| Command::Size(key),
+			) => Arg::Str(key),
+
+			(Field::Value, Command::Set(_, value, _)) => Arg::Buf(value),
+			(Field::Ttl, Command::Set(.., ttl) | Command::Ttl(_, ttl)) => Arg::U32(*ttl),
+			(Field::Size, Command::Resize(size)) => Arg::U64(*size),
+			(Field::Policy, Command::Policy(policy)) => Arg::Policy(policy),
+
+			_ => unreachable!("the {} command has no {field:?} field", self.name()),
 		}
 	}
+
+	fn to_sheet(&self) -> Sheet {
+		self.build_sheet(None)
+	}
+
+	fn to_sheet_with(&self, key_display: KeyDisplay) -> Sheet {
+		self.build_sheet(Some(key_display))
+	}
+
+	// builds the command's frame from its field layout; with a key display,
+	// each key is shown as it requires and the auth token is redacted, which
+	// is only fit for diagnostics and must never be sent to the server
+	fn build_sheet(&self, key_display: Option<KeyDisplay>) -> Sheet {
+		let mut builder = SheetBuilder::new().write_u8(self.byte());
+
+		for field in self.spec().fields {
+			builder = match (field, self.arg(*field), key_display) {
+				(Field::Token, _, Some(_)) => builder.write_str("<redacted>"),
+
+				(Field::Key, Arg::Str(key), Some(key_display)) => {
+					builder.write_str(key_display.display(key))
+				},
+
+				(_, Arg::Str(value), _) => builder.write_str(value),
+				(_, Arg::Buf(value), _) => builder.write_buf(value),
+				(_, Arg::U32(value), _) => builder.write_u32(value),
+				(_, Arg::U64(value), _) => builder.write_u64(value),
+				(_, Arg::Policy(policy), _) => builder.write_str(policy.to_string()),
+			};
+		}
+
+		builder.into_sheet()
+	}
+}
+
+/// Counts the bytes written to it, so that a value's formatted length is
+/// known without allocating.
+struct ByteCounter(usize);
+
+/// Writes into a slice which is known to be large enough.
+struct SliceWriter<'a> {
+	buf: &'a mut [u8],
+	len: usize,
+}
+
+impl fmt::Write for ByteCounter {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		self.0 += s.len();
+		Ok(())
+	}
+}
+
+impl SliceWriter<'_> {
+	fn write_bytes(&mut self, bytes: &[u8]) {
+		self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+		self.len += bytes.len();
+	}
+
+	fn write_buf(&mut self, bytes: &[u8]) {
+		self.write_bytes(&(bytes.len() as u32).to_le_bytes());
+		self.write_bytes(bytes);
+	}
+}
+
+impl fmt::Write for SliceWriter<'_> {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		self.write_bytes(s.as_bytes());
+		Ok(())
+	}
+}
+
+/// Returns the length of the supplied value's formatted string.
+fn formatted_len(value: &impl Display) -> usize {
+	let mut counter = ByteCounter(0);
+	let _ = write!(counter, "{value}");
+
+	counter.0
+}
+
+/// Returns the number of bytes a length-prefixed buffer of the supplied
+/// length occupies on the wire.
+const fn buf_len(len: usize) -> usize {
+	4 + len
+}
+
+/// Copies a buffer of the supplied size from the reader to the writer in
+/// chunks. The whole buffer is always read, even if writing fails, so that
+/// the connection stays in sync.
+fn copy_buf(reader: &mut impl Read, writer: &mut impl Write, size: u32) -> PaperClientResult<()> {
+	let mut chunk = [0u8; COPY_CHUNK_SIZE];
+	let mut remaining = size as usize;
+	let mut is_written = true;
+
+	while remaining > 0 {
+		let len = remaining.min(COPY_CHUNK_SIZE);
+
+		reader
+			.read_exact(&mut chunk[..len])
+			.map_err(|_| PaperClientError::InvalidResponse)?;
+
+		is_written = is_written && writer.write_all(&chunk[..len]).is_ok();
+		remaining -= len;
+	}
+
+	match is_written {
+		true => Ok(()),
+		false => Err(PaperClientError::InvalidWriter),
+	}
+}
+
+#[cfg(feature = "tokio")]
+async fn copy_buf_async<R, W>(reader: &mut R, writer: &mut W, size: u32) -> PaperClientResult<()>
+where
+	R: AsyncRead + Unpin,
+	W: AsyncWrite + Unpin,
+{
+	let mut chunk = [0u8; COPY_CHUNK_SIZE];
+	let mut remaining = size as usize;
+	let mut is_written = true;
+
+	while remaining > 0 {
+		let len = remaining.min(COPY_CHUNK_SIZE);
+
+		reader
+			.read_exact(&mut chunk[..len])
+			.await
+			.map_err(|_| PaperClientError::InvalidResponse)?;
+
+		is_written = is_written && writer.write_all(&chunk[..len]).await.is_ok();
+		remaining -= len;
+	}
+
+	match is_written {
+		true => Ok(()),
+		false => Err(PaperClientError::InvalidWriter),
+	}
+}
+
+fn parse_policy(buf: &[u8]) -> Option<PaperPolicy> {
+	let policy_str = str::from_utf8(buf).ok()?;
+	Some(PaperPolicy::from_server(policy_str))
 }
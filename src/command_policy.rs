@@ -0,0 +1,88 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::command::CommandKind;
+
+// the commands the client issues itself to maintain its connection
+const CONNECTION_COMMANDS: [CommandKind; 3] = [
+	CommandKind::Ping,
+	CommandKind::Version,
+	CommandKind::Auth,
+];
+
+/// Configures which commands a client may issue. A denied command fails
+/// with `CommandDenied` before it is sent to the server.
+///
+/// `ping`, `version`, and `auth` are always allowed, as the client issues
+/// them itself to maintain its connection.
+///
+/// # Examples
+/// ```
+/// use paper_client::{CommandKind, CommandPolicy, PaperClientConfig};
+///
+/// // an application build which cannot modify the cache's configuration
+/// let config = PaperClientConfig::default().command_policy(
+///     CommandPolicy::allow_all()
+///         .deny(CommandKind::Wipe)
+///         .deny(CommandKind::Resize)
+///         .deny(CommandKind::Policy),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandPolicy {
+	// one bit per command byte
+	allowed: u32,
+}
+
+impl CommandPolicy {
+	/// Creates a policy which allows every command.
+	#[must_use]
+	pub fn allow_all() -> Self {
+		CommandPolicy {
+			allowed: u32::MAX,
+		}
+	}
+
+	/// Creates a policy which denies every command except those which
+	/// are always allowed. Commands can then be allowed individually.
+	#[must_use]
+	pub fn deny_all() -> Self {
+		CommandPolicy {
+			allowed: 0,
+		}
+	}
+
+	/// Allows the supplied command.
+	#[must_use]
+	pub fn allow(mut self, kind: CommandKind) -> Self {
+		self.allowed |= bit(kind);
+		self
+	}
+
+	/// Denies the supplied command.
+	#[must_use]
+	pub fn deny(mut self, kind: CommandKind) -> Self {
+		self.allowed &= !bit(kind);
+		self
+	}
+
+	/// Returns `true` if the policy allows the supplied command.
+	#[must_use]
+	pub fn is_allowed(&self, kind: CommandKind) -> bool {
+		CONNECTION_COMMANDS.contains(&kind) || self.allowed & bit(kind) != 0
+	}
+}
+
+impl Default for CommandPolicy {
+	fn default() -> Self {
+		CommandPolicy::allow_all()
+	}
+}
+
+fn bit(kind: CommandKind) -> u32 {
+	1 << kind as u8
+}
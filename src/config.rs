@@ -0,0 +1,409 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	borrow::Cow,
+	fmt::Write,
+	hash::{BuildHasher, RandomState},
+	sync::Arc,
+	time::Duration,
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+	capture::ProtocolCapture,
+	clock::{Clock, SystemClock},
+	codec::ValueCodec,
+	command_policy::CommandPolicy,
+	error::{PaperClientError, PaperClientResult},
+	guardrails::Guardrails,
+	key_display::KeyDisplay,
+	observer::PaperObserver,
+	retry::{RetryBudget, RetryableError},
+	tenant::TenantRegistry,
+	transport::PaperConnector,
+	value::PaperValue,
+};
+
+const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_CONNECT_BACKOFF: Duration = Duration::from_millis(100);
+const DEFAULT_RECONNECT_BACKOFF: Duration = Duration::from_millis(50);
+
+// the prefix of a hashed key
+const HASHED_KEY_PREFIX: &str = "sha256:";
+
+/// Configures how a client connects to and communicates with the server.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use paper_client::PaperClientConfig;
+///
+/// let config = PaperClientConfig::default()
+///     .handshake_timeout(Some(Duration::from_secs(1)))
+///     .connect_retries(3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PaperClientConfig {
+	pub(crate) handshake_timeout: Option<Duration>,
+	pub(crate) command_timeout:   Option<Duration>,
+	pub(crate) connect_retries:   u32,
+	pub(crate) connect_backoff:   Duration,
+	pub(crate) reconnect_backoff: Duration,
+	pub(crate) resync:            bool,
+	pub(crate) strict:            bool,
+	pub(crate) max_response_size: Option<u32>,
+	pub(crate) observer:          Option<Arc<dyn PaperObserver>>,
+	pub(crate) retry_budget:      Option<Arc<RetryBudget>>,
+	pub(crate) retry_on:          Vec<RetryableError>,
+	pub(crate) command_policy:    CommandPolicy,
+	pub(crate) value_codec:       Option<Arc<dyn ValueCodec>>,
+	pub(crate) max_key_length:    Option<usize>,
+	pub(crate) clock:             Arc<dyn Clock>,
+	pub(crate) capture:           Option<Arc<ProtocolCapture>>,
+	pub(crate) key_display:       KeyDisplay,
+	pub(crate) connector:         Option<Arc<dyn PaperConnector>>,
+	pub(crate) tenants:           Arc<TenantRegistry>,
+	pub(crate) guardrails:        Option<Arc<Guardrails>>,
+}
+
+impl PaperClientConfig {
+	/// Sets the maximum time to wait for the server's handshake after the
+	/// connection is established. If `None`, the client waits indefinitely.
+	/// Defaults to 5 seconds.
+	#[must_use]
+	pub fn handshake_timeout(mut self, timeout: Option<Duration>) -> Self {
+		self.handshake_timeout = timeout;
+		self
+	}
+
+	/// Sets the maximum time each command may take (including any
+	/// reconnects and retries), after which it fails with
+	/// `DeadlineExceeded`. An earlier deadline set with `with_deadline`
+	/// still applies. A pool's clients share this default, which a single
+	/// checkout can override (see `CheckoutOptions`). If `None`, commands
+	/// are only bounded by the deadline, if any. Defaults to `None`.
+	#[must_use]
+	pub fn command_timeout(mut self, timeout: Option<Duration>) -> Self {
+		self.command_timeout = timeout;
+		self
+	}
+
+	/// Sets the connector which opens the connections of a client over a
+	/// `BoxedTransport` (e.g., to connect over TLS). The connector is
+	/// ignored by clients over any other transport. Defaults to no
+	/// connector, in which case a `BoxedTransport` connects over TCP or a
+	/// Unix socket depending on the address.
+	#[must_use]
+	pub fn connector(mut self, connector: impl PaperConnector + 'static) -> Self {
+		self.connector = Some(Arc::new(connector));
+		self
+	}
+
+	/// Sets the number of times a failed connection attempt (including the
+	/// handshake) is retried before `UnreachableServer` is returned.
+	/// Defaults to 0.
+	#[must_use]
+	pub fn connect_retries(mut self, retries: u32) -> Self {
+		self.connect_retries = retries;
+		self
+	}
+
+	/// Sets the delay before the first connection retry. The delay doubles
+	/// with each subsequent retry. Defaults to 100 milliseconds.
+	#[must_use]
+	pub fn connect_backoff(mut self, backoff: Duration) -> Self {
+		self.connect_backoff = backoff;
+		self
+	}
+
+	/// Sets the delay between successive attempts to reconnect and retry a
+	/// failed command. The first attempt is immediate, after which the
+	/// delay doubles with each attempt. Up to half of each delay is
+	/// randomized so that clients which lost their connections together do
+	/// not reconnect in lockstep. The delay never extends past the current
+	/// deadline. Defaults to 50 milliseconds.
+	#[must_use]
+	pub fn reconnect_backoff(mut self, backoff: Duration) -> Self {
+		self.reconnect_backoff = backoff;
+		self
+	}
+
+	/// Sets whether a malformed response which leaves the connection in a
+	/// known state (e.g., a status containing a policy name which is not
+	/// valid UTF-8) is drained and returned as `MalformedResponse`. If
+	/// `false`, the client instead reconnects as it would for any other
+	/// invalid response. Defaults to `true`.
+	#[must_use]
+	pub fn resync(mut self, resync: bool) -> Self {
+		self.resync = resync;
+		self
+	}
+
+	/// Sets whether the client is strict, in which case each command makes
+	/// at most one attempt over the network. A failed command is never
+	/// retried (regardless of `retry_on`) and its error is returned as is,
+	/// and a client whose connection failed is not reconnected, so every
+	/// later command fails with `Disconnected`. A pool replaces such a
+	/// client the next time it is checked out. Defaults to `false`.
+	#[must_use]
+	pub fn strict(mut self, strict: bool) -> Self {
+		self.strict = strict;
+		self
+	}
+
+	/// Sets the maximum size (in bytes) of a value which is read into
+	/// memory. A larger value is drained from the connection and
+	/// `ResponseTooLarge` is returned, in which case the value can still be
+	/// read with the client's `get_to_writer`, which streams it without
+	/// buffering. If `None`, values of any size are read. Defaults to `None`.
+	#[must_use]
+	pub fn max_response_size(mut self, size: Option<u32>) -> Self {
+		self.max_response_size = size;
+		self
+	}
+
+	/// Sets the observer which receives the client's events (e.g., an
+	/// `AuditEvent` for each `wipe`, `resize`, or `policy` command). When
+	/// used to configure a pool, the observer is shared by all of its
+	/// clients. Defaults to no observer.
+	#[must_use]
+	pub fn observer(mut self, observer: impl PaperObserver + 'static) -> Self {
+		self.observer = Some(Arc::new(observer));
+		self
+	}
+
+	/// Sets the budget which limits how often a failed command is retried
+	/// after reconnecting. The budget is shared by every client constructed
+	/// from this configuration (including clones of it), such as all of a
+	/// pool's clients. Defaults to no budget, in which case retries are
+	/// only limited per command.
+	#[must_use]
+	pub fn retry_budget(mut self, budget: RetryBudget) -> Self {
+		self.retry_budget = Some(Arc::new(budget));
+		self
+	}
+
+	/// Sets the classes of error after which a command is retried once the
+	/// connection has been re-established. Any other error is returned to
+	/// the caller. Defaults to `InvalidResponse` only.
+	#[must_use]
+	pub fn retry_on(mut self, errors: impl IntoIterator<Item = RetryableError>) -> Self {
+		self.retry_on = errors.into_iter().collect();
+		self
+	}
+
+	/// Sets the policy which determines which commands the client may
+	/// issue. A denied command fails with `CommandDenied` without being
+	/// sent. Defaults to allowing every command.
+	#[must_use]
+	pub fn command_policy(mut self, policy: CommandPolicy) -> Self {
+		self.command_policy = policy;
+		self
+	}
+
+	/// Sets the guardrails which limit the client's `resize` and `policy`
+	/// commands. The guardrails are shared by every client constructed from
+	/// this configuration (including clones of it), such as all of a pool's
+	/// clients. Defaults to no guardrails.
+	#[must_use]
+	pub fn guardrails(mut self, guardrails: Guardrails) -> Self {
+		self.guardrails = Some(Arc::new(guardrails));
+		self
+	}
+
+	/// Sets the codec which is applied to every value the client sets and
+	/// gets. When used to configure a pool, the codec is shared by all of
+	/// its clients. With a codec, `get_to_writer` reads the whole value
+	/// into memory in order to decode it, and the slice supplied to
+	/// `get_into_slice` must fit the encoded value. Defaults to no codec,
+	/// in which case values are sent and received unchanged.
+	#[must_use]
+	pub fn value_codec(mut self, codec: impl ValueCodec + 'static) -> Self {
+		self.value_codec = Some(Arc::new(codec));
+		self
+	}
+
+	/// Sets the maximum length (in bytes) of a key which is sent to the
+	/// server as is. A longer key is replaced by `sha256:` followed by the
+	/// hex-encoded SHA-256 digest of the key, so that keys built from URLs
+	/// or queries do not exceed the server's limits. A key which already
+	/// starts with `sha256:` is always hashed, so a hashed key never
+	/// collides with one which was not. If `None`, keys are never hashed.
+	/// Defaults to `None`.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClientConfig;
+	///
+	/// let config = PaperClientConfig::default().max_key_length(Some(256));
+	/// ```
+	#[must_use]
+	pub fn max_key_length(mut self, length: Option<usize>) -> Self {
+		self.max_key_length = length;
+		self
+	}
+
+	/// Sets the clock used for the client's time-based logic (see
+	/// [`Clock`]). When used to configure a pool, the clock is also used to
+	/// time node ejections. Defaults to `SystemClock`.
+	#[must_use]
+	pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+		self.clock = Arc::new(clock);
+		self
+	}
+
+	/// Sets the capture to which the raw bytes of each command and its
+	/// response are written (see [`ProtocolCapture`]). This is intended for
+	/// debugging, as every frame is hex-encoded and written synchronously.
+	/// When used to configure a pool, the capture is shared by all of its
+	/// clients. Defaults to no capture.
+	#[must_use]
+	pub fn capture(mut self, capture: ProtocolCapture) -> Self {
+		self.capture = Some(Arc::new(capture));
+		self
+	}
+
+	/// Sets how keys are shown in the client's diagnostic output, such as
+	/// the frames recorded by its capture, so that keys which contain
+	/// personal data are not leaked into logs (see [`KeyDisplay`]). Keys
+	/// are always sent to the server as is. Defaults to
+	/// `KeyDisplay::Full`.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{KeyDisplay, PaperClientConfig};
+	///
+	/// let config = PaperClientConfig::default().key_display(KeyDisplay::Hashed);
+	/// ```
+	#[must_use]
+	pub fn key_display(mut self, key_display: KeyDisplay) -> Self {
+		self.key_display = key_display;
+		self
+	}
+
+	/// Returns the key which is sent to the server for the supplied key.
+	pub(crate) fn paper_key<'a>(&self, key: &'a str) -> Cow<'a, str> {
+		let Some(max_key_length) = self.max_key_length else {
+			return Cow::Borrowed(key);
+		};
+
+		if key.len() <= max_key_length && !key.starts_with(HASHED_KEY_PREFIX) {
+			return Cow::Borrowed(key);
+		}
+
+		let digest = Sha256::digest(key.as_bytes());
+		let mut hashed = String::with_capacity(HASHED_KEY_PREFIX.len() + digest.len() * 2);
+
+		hashed.push_str(HASHED_KEY_PREFIX);
+
+		for byte in digest {
+			let _ = write!(hashed, "{byte:02x}");
+		}
+
+		Cow::Owned(hashed)
+	}
+
+	pub(crate) fn encode_value(&self, value: PaperValue) -> PaperClientResult<PaperValue> {
+		match &self.value_codec {
+			Some(codec) => codec.encode(&value).map(PaperValue::from),
+			None => Ok(value),
+		}
+	}
+
+	pub(crate) fn decode_value(&self, value: PaperValue) -> PaperClientResult<PaperValue> {
+		match &self.value_codec {
+			Some(codec) => codec.decode(&value).map(PaperValue::from),
+			None => Ok(value),
+		}
+	}
+
+	/// Decodes the first `size` bytes of the buffer in place, returning the
+	/// size of the decoded value.
+	pub(crate) fn decode_vec(&self, buf: &mut Vec<u8>, size: usize) -> PaperClientResult<usize> {
+		let Some(codec) = &self.value_codec else {
+			return Ok(size);
+		};
+
+		*buf = codec.decode(&buf[..size])?;
+		Ok(buf.len())
+	}
+
+	/// Decodes the first `size` bytes of the slice in place, returning the
+	/// size of the decoded value. If the decoded value does not fit in the
+	/// slice, `ResponseTooLarge` is returned.
+	pub(crate) fn decode_slice(&self, buf: &mut [u8], size: usize) -> PaperClientResult<usize> {
+		let Some(codec) = &self.value_codec else {
+			return Ok(size);
+		};
+
+		let value = codec.decode(&buf[..size])?;
+
+		let Some(dest) = buf.get_mut(..value.len()) else {
+			return Err(PaperClientError::ResponseTooLarge);
+		};
+
+		dest.copy_from_slice(&value);
+		Ok(value.len())
+	}
+
+	pub(crate) fn reconnect_delay(&self, attempt: u8) -> Duration {
+		let Some(exponent) = attempt.checked_sub(2) else {
+			return Duration::ZERO;
+		};
+
+		let delay = self
+			.reconnect_backoff
+			.saturating_mul(2u32.saturating_pow(exponent.into()));
+
+		let half = delay / 2;
+		let jitter = RandomState::new().hash_one(attempt) % (half.as_nanos() as u64).max(1);
+
+		half + Duration::from_nanos(jitter)
+	}
+
+	pub(crate) fn is_retryable(&self, err: &PaperClientError) -> bool {
+		!self.strict
+			&& self
+				.retry_on
+				.iter()
+				.any(|class| class.matches(err))
+	}
+
+	pub(crate) fn connect_delay(&self, attempt: u32) -> Duration {
+		self.connect_backoff
+			.saturating_mul(2u32.saturating_pow(attempt))
+	}
+}
+
+impl Default for PaperClientConfig {
+	fn default() -> Self {
+		PaperClientConfig {
+			handshake_timeout: Some(DEFAULT_HANDSHAKE_TIMEOUT),
+			command_timeout:   None,
+			connect_retries:   0,
+			connect_backoff:   DEFAULT_CONNECT_BACKOFF,
+			reconnect_backoff: DEFAULT_RECONNECT_BACKOFF,
+			resync:            true,
+			strict:            false,
+			max_response_size: None,
+			observer:          None,
+			retry_budget:      None,
+			retry_on:          vec![RetryableError::InvalidResponse],
+			command_policy:    CommandPolicy::default(),
+			value_codec:       None,
+			max_key_length:    None,
+			clock:             Arc::new(SystemClock),
+			capture:           None,
+			key_display:       KeyDisplay::Full,
+			connector:         None,
+			tenants:           Arc::default(),
+			guardrails:        None,
+		}
+	}
+}
@@ -0,0 +1,130 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	fmt::{self, Display},
+	net::SocketAddr,
+	path::PathBuf,
+};
+
+/// The details of a client's current connection, as reported by its
+/// transport (see `PaperTransport::connection_info`). A transport which
+/// does not report its details (e.g., a `LoopbackTransport`) has no peer
+/// address.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConnectionInfo {
+	peer_addr: Option<PeerAddr>,
+	tls:       Option<TlsInfo>,
+}
+
+/// The address of the server a connection was established with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeerAddr {
+	/// The socket address a TCP connection landed on, which identifies the
+	/// server a hostname resolved to.
+	Tcp(SocketAddr),
+
+	/// The path of the server's Unix socket.
+	Unix(PathBuf),
+}
+
+/// The parameters negotiated by a TLS connection, as reported by the
+/// `PaperConnector` which opened it.
+///
+/// # Examples
+/// ```
+/// use paper_client::TlsInfo;
+///
+/// let tls = TlsInfo::new("TLSv1.3", "TLS13_AES_256_GCM_SHA384").with_alpn(b"paper");
+/// assert_eq!(tls.alpn(), Some(b"paper".as_slice()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsInfo {
+	protocol: String,
+	cipher:   String,
+	alpn:     Option<Vec<u8>>,
+}
+
+impl ConnectionInfo {
+	/// Creates the details of a connection to the supplied peer.
+	#[must_use]
+	pub fn new(peer_addr: Option<PeerAddr>) -> Self {
+		ConnectionInfo {
+			peer_addr,
+			tls: None,
+		}
+	}
+
+	/// Sets the parameters negotiated by the connection's TLS session.
+	#[must_use]
+	pub fn with_tls(mut self, tls: TlsInfo) -> Self {
+		self.tls = Some(tls);
+		self
+	}
+
+	/// Returns the address of the server the connection was established
+	/// with, if the transport reports it.
+	#[must_use]
+	pub fn peer_addr(&self) -> Option<&PeerAddr> {
+		self.peer_addr.as_ref()
+	}
+
+	/// Returns the parameters negotiated by the connection's TLS session,
+	/// or `None` if the connection is not encrypted (or its transport does
+	/// not report them).
+	#[must_use]
+	pub fn tls(&self) -> Option<&TlsInfo> {
+		self.tls.as_ref()
+	}
+}
+
+impl TlsInfo {
+	/// Creates the parameters of a TLS session with the supplied protocol
+	/// version and cipher suite.
+	#[must_use]
+	pub fn new(protocol: impl Into<String>, cipher: impl Into<String>) -> Self {
+		TlsInfo {
+			protocol: protocol.into(),
+			cipher:   cipher.into(),
+			alpn:     None,
+		}
+	}
+
+	/// Sets the application protocol negotiated with ALPN.
+	#[must_use]
+	pub fn with_alpn(mut self, alpn: impl Into<Vec<u8>>) -> Self {
+		self.alpn = Some(alpn.into());
+		self
+	}
+
+	/// Returns the negotiated protocol version (e.g., `TLSv1.3`).
+	#[must_use]
+	pub fn protocol(&self) -> &str {
+		&self.protocol
+	}
+
+	/// Returns the negotiated cipher suite.
+	#[must_use]
+	pub fn cipher(&self) -> &str {
+		&self.cipher
+	}
+
+	/// Returns the application protocol negotiated with ALPN, if any.
+	#[must_use]
+	pub fn alpn(&self) -> Option<&[u8]> {
+		self.alpn.as_deref()
+	}
+}
+
+impl Display for PeerAddr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			PeerAddr::Tcp(addr) => write!(f, "{addr}"),
+			PeerAddr::Unix(path) => write!(f, "{}", path.display()),
+		}
+	}
+}
@@ -0,0 +1,111 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+#[cfg(feature = "tokio")]
+use std::future::Future;
+use std::{
+	cell::Cell,
+	time::{Duration, Instant},
+};
+
+thread_local! {
+	static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+#[cfg(feature = "tokio")]
+tokio::task_local! {
+	static TASK_DEADLINE: Instant;
+}
+
+// restores the previous deadline when a scope exits, including by panic
+struct DeadlineGuard {
+	previous: Option<Instant>,
+}
+
+/// Runs the supplied closure with a deadline which every client command
+/// issued on the current thread within the closure observes. A command
+/// issued after the deadline has passed, or which is still waiting for its
+/// response when the deadline passes, fails with `DeadlineExceeded`.
+///
+/// Deadlines can be nested, in which case the earliest deadline applies.
+///
+/// # Examples
+/// ```
+/// use std::time::{Duration, Instant};
+/// use paper_client::{PaperClient, with_deadline};
+///
+/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+/// let deadline = Instant::now() + Duration::from_millis(200);
+///
+/// let value = with_deadline(deadline, || client.get("key"));
+/// ```
+pub fn with_deadline<R>(deadline: Instant, f: impl FnOnce() -> R) -> R {
+	let previous = DEADLINE.get();
+	DEADLINE.set(Some(earliest(previous, deadline)));
+
+	let _guard = DeadlineGuard {
+		previous,
+	};
+
+	f()
+}
+
+/// Runs the supplied future with a deadline which every client command
+/// issued within the future observes, even if the task moves between
+/// threads. A command issued after the deadline has passed, or which is
+/// still waiting for its response when the deadline passes, fails with
+/// `DeadlineExceeded`.
+///
+/// Deadlines can be nested, in which case the earliest deadline applies.
+///
+/// # Examples
+/// ```ignore
+/// use std::time::{Duration, Instant};
+/// use paper_client::{AsyncPaperClient, with_deadline_async};
+///
+/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+/// let deadline = Instant::now() + Duration::from_millis(200);
+///
+/// let value = with_deadline_async(deadline, client.get("key")).await;
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn with_deadline_async<F: Future>(deadline: Instant, future: F) -> F::Output {
+	let deadline = earliest(current_deadline(), deadline);
+	TASK_DEADLINE.scope(deadline, future).await
+}
+
+/// Returns the deadline of the current scope, if any.
+#[must_use]
+pub fn current_deadline() -> Option<Instant> {
+	#[cfg(feature = "tokio")]
+	if let Ok(deadline) = TASK_DEADLINE.try_with(|deadline| *deadline) {
+		return Some(earliest(DEADLINE.get(), deadline));
+	}
+
+	DEADLINE.get()
+}
+
+/// Returns the time remaining until the current scope's deadline, or
+/// `None` if there is no deadline. Returns `Some(Duration::ZERO)` if the
+/// deadline has passed.
+pub(crate) fn remaining() -> Option<Duration> {
+	current_deadline().map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
+
+pub(crate) fn is_expired() -> bool {
+	remaining().is_some_and(|remaining| remaining.is_zero())
+}
+
+fn earliest(deadline: Option<Instant>, other: Instant) -> Instant {
+	deadline.map_or(other, |deadline| deadline.min(other))
+}
+
+impl Drop for DeadlineGuard {
+	fn drop(&mut self) {
+		DEADLINE.set(self.previous);
+	}
+}
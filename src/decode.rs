@@ -0,0 +1,115 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::io::Read;
+
+#[cfg(feature = "tokio")]
+use paper_utils::stream::AsyncStreamReader;
+use paper_utils::stream::StreamReader;
+#[cfg(feature = "tokio")]
+use tokio::io::AsyncRead;
+
+use crate::error::PaperClientResult;
+
+/// Decodes the payload of a command's response, allowing a command to be
+/// executed with a user-defined response type through
+/// `PaperClient::execute`, with the same retries, reconnects, metrics, and
+/// capture as the client's own commands.
+///
+/// The client reads the response's leading success flag (and the error, if
+/// the command failed), so the decoder is only handed the payload which
+/// follows a successful response. A read of the payload which fails should
+/// be reported as `PaperClientError::InvalidResponse`, so that the command
+/// is retried if the decoder is retryable. As a decoder which fails may not
+/// have read the whole payload, the connection is re-established before
+/// the next command.
+///
+/// # Examples
+/// ```
+/// use std::io::Read;
+/// use paper_client::{DecodePaperResponse, PaperClientError, StreamReader};
+/// use paper_client::error::PaperClientResult;
+///
+/// // decodes a size response as a number of kibibytes
+/// struct Kibibytes;
+///
+/// impl DecodePaperResponse for Kibibytes {
+///     type Output = f64;
+///
+///     fn decode<R: Read>(&mut self, reader: &mut StreamReader<'_, R>) -> PaperClientResult<f64> {
+///         let size = reader
+///             .read_u32()
+///             .map_err(|_| PaperClientError::InvalidResponse)?;
+///
+///         Ok(f64::from(size) / 1024.0)
+///     }
+/// }
+/// ```
+pub trait DecodePaperResponse {
+	/// The decoded response.
+	type Output;
+
+	/// Whether the command can be sent again after the response could not
+	/// be decoded. Decoders which hand part of the response to the caller
+	/// before it has been fully read must not be retried. Defaults to
+	/// `true`.
+	const IS_RETRYABLE: bool = true;
+
+	/// Decodes the payload of a successful response from the reader.
+	fn decode<R: Read>(
+		&mut self,
+		reader: &mut StreamReader<'_, R>,
+	) -> PaperClientResult<Self::Output>;
+}
+
+/// The async counterpart of [`DecodePaperResponse`], used by
+/// `AsyncPaperClient::execute`.
+///
+/// # Examples
+/// ```ignore
+/// use paper_client::{AsyncStreamReader, DecodePaperResponseAsync, PaperClientError};
+/// use paper_client::error::PaperClientResult;
+/// use tokio::io::AsyncRead;
+///
+/// struct Kibibytes;
+///
+/// impl DecodePaperResponseAsync for Kibibytes {
+///     type Output = f64;
+///
+///     async fn decode_async<R>(
+///         &mut self,
+///         reader: &mut AsyncStreamReader<'_, R>,
+///     ) -> PaperClientResult<f64>
+///     where
+///         R: AsyncRead + Unpin + Send,
+///     {
+///         let size = reader
+///             .read_u32()
+///             .await
+///             .map_err(|_| PaperClientError::InvalidResponse)?;
+///
+///         Ok(f64::from(size) / 1024.0)
+///     }
+/// }
+/// ```
+#[cfg(feature = "tokio")]
+pub trait DecodePaperResponseAsync: Send {
+	/// The decoded response.
+	type Output;
+
+	/// Whether the command can be sent again after the response could not
+	/// be decoded. Defaults to `true`.
+	const IS_RETRYABLE: bool = true;
+
+	/// Decodes the payload of a successful response from the reader.
+	fn decode_async<R>(
+		&mut self,
+		reader: &mut AsyncStreamReader<'_, R>,
+	) -> impl Future<Output = PaperClientResult<Self::Output>> + Send
+	where
+		R: AsyncRead + Unpin + Send;
+}
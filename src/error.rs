@@ -16,6 +16,49 @@ use tokio::io::AsyncRead;
 
 pub type PaperClientResult<T> = Result<T, PaperClientError>;
 
+/// Defines an error whose variants are each sent by the server as the
+/// supplied code, generating the mapping in both directions. A code which
+/// has no variant (e.g., one added by a newer server) is preserved as
+/// `Unknown` so that it can still be diagnosed. A code which is assigned to
+/// more than one variant is rejected as an unreachable pattern.
+macro_rules! error_codes {
+	(
+		pub enum $name:ident {
+			$(
+				#[error($message:literal)]
+				$variant:ident = $code:literal,
+			)*
+		}
+	) => {
+		#[derive(Debug, PartialEq, Error)]
+		pub enum $name {
+			$(
+				#[error($message)]
+				$variant,
+			)*
+
+			#[error("an unknown error occurred (code {0})")]
+			Unknown(u8),
+		}
+
+		impl $name {
+			fn from_code(code: u8) -> Self {
+				match code {
+					$($code => $name::$variant,)*
+					code => $name::Unknown(code),
+				}
+			}
+
+			pub(crate) fn code(&self) -> u8 {
+				match self {
+					$($name::$variant => $code,)*
+					$name::Unknown(code) => *code,
+				}
+			}
+		}
+	};
+}
+
 #[derive(Debug, PartialEq, Error)]
 pub enum PaperClientError {
 	#[error(transparent)]
@@ -24,62 +67,168 @@ pub enum PaperClientError {
 	#[error(transparent)]
 	CacheError(#[from] PaperCacheError),
 
+	#[error(transparent)]
+	CommandError(#[from] CommandError),
+
 	#[error("invalid PaperCache address")]
 	InvalidAddress,
 
+	#[error("invalid client configuration")]
+	InvalidConfig,
+
 	#[error("could not connect to PaperServer")]
 	UnreachableServer,
 
 	#[error("could not send command to PaperServer")]
 	InvalidCommand,
 
+	#[error("the command is not allowed by the client's command policy")]
+	CommandDenied,
+
 	#[error("could not parse supplied value as PaperValue")]
 	InvalidValue,
 
+	#[error("the ttl must be in the future")]
+	InvalidTtl,
+
+	#[error("could not decode value with the configured codec")]
+	InvalidEncoding,
+
+	#[error("the value is not a number of the requested type")]
+	InvalidNumber,
+
+	#[error("the value could not be converted to the requested type")]
+	InvalidConversion,
+
+	#[error("could not write value to the supplied writer")]
+	InvalidWriter,
+
+	#[error("could not read or write the command journal")]
+	JournalUnavailable,
+
+	#[error("refused to shrink the cache by more than the guardrails allow")]
+	ResizeRefused,
+
+	#[error("refused to change the policy again within the guardrails' interval")]
+	PolicyRefused,
+
+	#[error("the namespace to swap out is not the current namespace")]
+	NamespaceMismatch,
+
+	#[error("could not parse the supplied JSON")]
+	InvalidJson,
+
 	#[error("could not receive response from PaperServer")]
 	InvalidResponse,
 
+	#[error("received a malformed response from PaperServer")]
+	MalformedResponse,
+
+	#[error("the response exceeded the maximum response size")]
+	ResponseTooLarge,
+
 	#[error("an internal error occurred")]
 	Internal,
 
 	#[error("disconnected from PaperServer")]
 	Disconnected,
+
+	#[error("gave up after {attempts} reconnect attempts: {last_error}")]
+	ReconnectExhausted {
+		attempts:   u8,
+		#[source]
+		last_error: Box<PaperClientError>,
+	},
+
+	#[error("could not obtain auth token")]
+	AuthTokenUnavailable,
+
+	#[error("timed out waiting for a pooled client")]
+	PoolTimeout,
+
+	#[error("timed out waiting for the key")]
+	WaitTimeout,
+
+	#[error("timed out waiting for the sequence to be acknowledged")]
+	SequenceTimeout,
+
+	#[error("the pool has been drained")]
+	PoolDrained,
+
+	#[error("too many writes are waiting for a response")]
+	WriteQueueFull,
+
+	#[error("the retry budget was exhausted")]
+	RetryBudgetExhausted,
+
+	#[error("the deadline was exceeded")]
+	DeadlineExceeded,
+
+	#[error("the buffer is too small for the command ({required} bytes are required)")]
+	BufferTooSmall {
+		required: usize,
+	},
 }
 
-#[derive(Debug, PartialEq, Error)]
-pub enum PaperCacheError {
-	#[error("an internal error occurred")]
-	Internal,
+error_codes! {
+	pub enum PaperCacheError {
+		#[error("an internal error occurred")]
+		Internal = 0,
 
-	#[error("the key was not found in the cache")]
-	KeyNotFound,
+		#[error("the key was not found in the cache")]
+		KeyNotFound = 1,
 
-	#[error("the value size cannot be zero")]
-	ZeroValueSize,
+		#[error("the value size cannot be zero")]
+		ZeroValueSize = 2,
 
-	#[error("the value size cannot exceed the cache size")]
-	ExceedingValueSize,
+		#[error("the value size cannot exceed the cache size")]
+		ExceedingValueSize = 3,
 
-	#[error("the cache size cannot be zero")]
-	ZeroCacheSize,
+		#[error("the cache size cannot be zero")]
+		ZeroCacheSize = 4,
 
-	#[error("unconfigured policy")]
-	UnconfiguredPolicy,
+		#[error("unconfigured policy")]
+		UnconfiguredPolicy = 5,
 
-	#[error("invalid policy")]
-	InvalidPolicy,
+		#[error("invalid policy")]
+		InvalidPolicy = 6,
+	}
 }
 
+/// A command which could not be encoded, and so was not sent to the
+/// server.
 #[derive(Debug, PartialEq, Error)]
-pub enum PaperServerError {
-	#[error("an internal error occurred")]
-	Internal,
+pub enum CommandError {
+	#[error("the key is {size} bytes, which exceeds the maximum of {max} bytes")]
+	KeyTooLarge {
+		size: usize,
+		max:  usize,
+	},
+
+	#[error("the value is {size} bytes, which exceeds the maximum of {max} bytes")]
+	ValueTooLarge {
+		size: usize,
+		max:  usize,
+	},
+
+	#[error("the auth token is {size} bytes, which exceeds the maximum of {max} bytes")]
+	TokenTooLarge {
+		size: usize,
+		max:  usize,
+	},
+}
+
+error_codes! {
+	pub enum PaperServerError {
+		#[error("an internal error occurred")]
+		Internal = 1,
 
-	#[error("the maximum number of connections was exceeded")]
-	MaxConnectionsExceeded,
+		#[error("the maximum number of connections was exceeded")]
+		MaxConnectionsExceeded = 2,
 
-	#[error("unauthorized")]
-	Unauthorized,
+		#[error("unauthorized")]
+		Unauthorized = 3,
+	}
 }
 
 impl PaperClientError {
@@ -128,32 +277,3 @@ impl PaperClientError {
 		PaperClientError::ServerError(server_error)
 	}
 }
-
-impl PaperServerError {
-	fn from_code(code: u8) -> Self {
-		match code {
-			2 => PaperServerError::MaxConnectionsExceeded,
-			3 => PaperServerError::Unauthorized,
-
-			_ => PaperServerError::Internal,
-		}
-	}
-}
-
-impl PaperCacheError {
-	fn from_code(code: u8) -> Self {
-		match code {
-			1 => PaperCacheError::KeyNotFound,
-
-			2 => PaperCacheError::ZeroValueSize,
-			3 => PaperCacheError::ExceedingValueSize,
-
-			4 => PaperCacheError::ZeroCacheSize,
-
-			5 => PaperCacheError::UnconfiguredPolicy,
-			6 => PaperCacheError::InvalidPolicy,
-
-			_ => PaperCacheError::Internal,
-		}
-	}
-}
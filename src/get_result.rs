@@ -0,0 +1,60 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::value::PaperValue;
+
+/// The result of a `get_result` command, which reports whether the get was
+/// a hit along with the value's size, so that hit rates can be recorded
+/// per call site.
+#[derive(Debug)]
+pub struct GetResult {
+	value: Option<PaperValue>,
+}
+
+impl GetResult {
+	pub(crate) fn hit(value: PaperValue) -> Self {
+		GetResult {
+			value: Some(value),
+		}
+	}
+
+	pub(crate) fn miss() -> Self {
+		GetResult {
+			value: None,
+		}
+	}
+
+	/// Returns `true` if the key was found in the cache.
+	#[must_use]
+	pub fn is_hit(&self) -> bool {
+		self.value.is_some()
+	}
+
+	/// Returns `true` if the key was not found in the cache.
+	#[must_use]
+	pub fn is_miss(&self) -> bool {
+		self.value.is_none()
+	}
+
+	/// Returns the size of the value in bytes, or `None` on a miss.
+	#[must_use]
+	pub fn size(&self) -> Option<usize> {
+		self.value.as_ref().map(PaperValue::len)
+	}
+
+	/// Returns the value, or `None` on a miss.
+	#[must_use]
+	pub fn value(&self) -> Option<&PaperValue> {
+		self.value.as_ref()
+	}
+
+	/// Consumes the result, returning the value, or `None` on a miss.
+	#[must_use]
+	pub fn into_value(self) -> Option<PaperValue> {
+		self.value
+	}
+}
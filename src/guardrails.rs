@@ -0,0 +1,105 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	sync::Mutex,
+	time::{Duration, Instant},
+};
+
+use crate::error::{PaperClientError, PaperClientResult};
+
+/// Limits on the administrative commands a client issues, protecting the
+/// cache from mistaken `resize` and `policy` commands. A command which
+/// exceeds a limit fails with `ResizeRefused` or `PolicyRefused` without
+/// being sent, unless it is issued with the client's `force_resize` or
+/// `force_policy`.
+///
+/// The guardrails are shared by every client constructed from the same
+/// configuration (including clones of it), such as all of a pool's
+/// clients, so a policy change made by one client limits the others.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use paper_client::{Guardrails, PaperClientConfig};
+///
+/// let guardrails = Guardrails::default()
+///     .max_shrink(0.25)
+///     .min_policy_interval(Duration::from_secs(3600));
+///
+/// let config = PaperClientConfig::default().guardrails(guardrails);
+/// ```
+#[derive(Debug, Default)]
+pub struct Guardrails {
+	max_shrink:          Option<f64>,
+	min_policy_interval: Option<Duration>,
+
+	last_policy_change: Mutex<Option<Instant>>,
+}
+
+impl Guardrails {
+	/// Sets the largest fraction (from 0 to 1) by which a `resize` may
+	/// shrink the cache, relative to its current maximum size. A guarded
+	/// `resize` first gets the cache's status in order to compare the
+	/// sizes. Defaults to no limit.
+	#[must_use]
+	pub fn max_shrink(mut self, fraction: f64) -> Self {
+		self.max_shrink = Some(fraction.clamp(0.0, 1.0));
+		self
+	}
+
+	/// Sets the minimum time between two `policy` commands, as measured by
+	/// the client's `Clock`. Defaults to no limit.
+	#[must_use]
+	pub fn min_policy_interval(mut self, interval: Duration) -> Self {
+		self.min_policy_interval = Some(interval);
+		self
+	}
+
+	pub(crate) fn limits_resize(&self) -> bool {
+		self.max_shrink.is_some()
+	}
+
+	pub(crate) fn check_resize(&self, current: u64, size: u64) -> PaperClientResult<()> {
+		let Some(max_shrink) = self.max_shrink else {
+			return Ok(());
+		};
+
+		let shrink = current.saturating_sub(size) as f64 / current.max(1) as f64;
+
+		match shrink > max_shrink {
+			true => Err(PaperClientError::ResizeRefused),
+			false => Ok(()),
+		}
+	}
+
+	pub(crate) fn check_policy(&self, now: Instant) -> PaperClientResult<()> {
+		let Some(interval) = self.min_policy_interval else {
+			return Ok(());
+		};
+
+		let last_policy_change = *self
+			.last_policy_change
+			.lock()
+			.expect("Could not obtain policy change.");
+
+		match last_policy_change {
+			Some(last) if now.saturating_duration_since(last) < interval => {
+				Err(PaperClientError::PolicyRefused)
+			},
+
+			_ => Ok(()),
+		}
+	}
+
+	pub(crate) fn record_policy(&self, now: Instant) {
+		*self
+			.last_policy_change
+			.lock()
+			.expect("Could not obtain policy change.") = Some(now);
+	}
+}
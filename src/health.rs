@@ -0,0 +1,336 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	sync::{
+		Mutex,
+		atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+	},
+	time::{Duration, Instant},
+};
+
+use crate::{
+	config::PaperClientConfig,
+	error::{PaperClientError, PaperClientResult},
+	metrics::LatencyAverage,
+	observer::{FailoverEvent, PaperEvent},
+};
+
+/// Configures when a pool's node is ejected from routing and for how long
+/// it stays ejected before being probed.
+#[derive(Debug, Clone, Copy)]
+pub struct EjectionPolicy {
+	max_consecutive_failures: u32,
+	ejection_duration:        Duration,
+	healthy_probes:           u32,
+}
+
+/// The routing state of a pool's node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeState {
+	Healthy,
+	Ejected,
+}
+
+/// The role of a pool's node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeRole {
+	Primary,
+	Replica,
+}
+
+/// A snapshot of the health of one of a pool's nodes.
+#[derive(Debug, Clone)]
+pub struct NodeStatus {
+	addr:  String,
+	role:  NodeRole,
+	state: NodeState,
+
+	requests:             u64,
+	errors:               u64,
+	consecutive_failures: u32,
+
+	latency: Duration,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct NodeHealth {
+	requests:             AtomicU64,
+	errors:               AtomicU64,
+	consecutive_failures: AtomicU32,
+
+	latency: LatencyAverage,
+
+	ejected_at:     Mutex<Option<Instant>>,
+	probing:        AtomicBool,
+	healthy_probes: AtomicU32,
+}
+
+pub(crate) enum Availability {
+	Available,
+	Ejected,
+
+	// the node has just been ejected by this check
+	Ejecting,
+	Probe,
+}
+
+impl EjectionPolicy {
+	/// Creates a new ejection policy. A node is ejected after
+	/// `max_consecutive_failures` consecutive connection-level failures
+	/// and is probed again after `ejection_duration` has elapsed.
+	///
+	/// # Examples
+	/// ```
+	/// use std::time::Duration;
+	/// use paper_client::EjectionPolicy;
+	///
+	/// let policy = EjectionPolicy::new(5, Duration::from_secs(10));
+	/// ```
+	#[must_use]
+	pub fn new(max_consecutive_failures: u32, ejection_duration: Duration) -> Self {
+		assert!(max_consecutive_failures > 0);
+
+		EjectionPolicy {
+			max_consecutive_failures,
+			ejection_duration,
+			healthy_probes: 1,
+		}
+	}
+
+	/// Sets the number of consecutive successful probes an ejected node
+	/// must respond to before it is restored to routing (at least 1). The
+	/// node is probed at most once per `ejection_duration`, so a flapping
+	/// node is only restored once it has stayed healthy for that many
+	/// probes. Defaults to 1.
+	///
+	/// # Examples
+	/// ```
+	/// use std::time::Duration;
+	/// use paper_client::EjectionPolicy;
+	///
+	/// let policy = EjectionPolicy::new(5, Duration::from_secs(10)).healthy_probes(3);
+	/// ```
+	#[must_use]
+	pub fn healthy_probes(mut self, healthy_probes: u32) -> Self {
+		self.healthy_probes = healthy_probes.max(1);
+		self
+	}
+
+	#[cfg(feature = "tokio")]
+	pub(crate) fn ejection_duration(&self) -> Duration {
+		self.ejection_duration
+	}
+}
+
+impl Default for EjectionPolicy {
+	fn default() -> Self {
+		EjectionPolicy::new(5, Duration::from_secs(10))
+	}
+}
+
+impl NodeStatus {
+	/// Returns the node's address.
+	#[must_use]
+	pub fn addr(&self) -> &str {
+		&self.addr
+	}
+
+	/// Returns the node's role.
+	#[must_use]
+	pub fn role(&self) -> NodeRole {
+		self.role
+	}
+
+	/// Returns the node's routing state.
+	#[must_use]
+	pub fn state(&self) -> NodeState {
+		self.state
+	}
+
+	/// Returns the total number of requests sent to the node.
+	#[must_use]
+	pub fn requests(&self) -> u64 {
+		self.requests
+	}
+
+	/// Returns the total number of connection-level errors encountered
+	/// while communicating with the node.
+	#[must_use]
+	pub fn errors(&self) -> u64 {
+		self.errors
+	}
+
+	/// Returns the node's error rate in the range `[0, 1]`.
+	#[must_use]
+	pub fn error_rate(&self) -> f64 {
+		if self.requests == 0 {
+			return 0.0;
+		}
+
+		self.errors as f64 / self.requests as f64
+	}
+
+	/// Returns the number of connection-level errors encountered since the
+	/// node's last successful response.
+	#[must_use]
+	pub fn consecutive_failures(&self) -> u32 {
+		self.consecutive_failures
+	}
+
+	/// Returns the moving average of the node's successful request
+	/// latencies.
+	#[must_use]
+	pub fn latency(&self) -> Duration {
+		self.latency
+	}
+}
+
+impl NodeHealth {
+	pub fn record<T>(&self, result: &PaperClientResult<T>, latency: Duration) {
+		match result {
+			Err(err) => self.record_error(err),
+
+			Ok(_) => {
+				self.requests.fetch_add(1, Ordering::Relaxed);
+				self.consecutive_failures.store(0, Ordering::Relaxed);
+				self.latency.record(latency);
+			},
+		}
+	}
+
+	pub fn record_error(&self, err: &PaperClientError) {
+		self.requests.fetch_add(1, Ordering::Relaxed);
+
+		match is_connection_error(err) {
+			true => {
+				self.errors.fetch_add(1, Ordering::Relaxed);
+				self.consecutive_failures
+					.fetch_add(1, Ordering::Relaxed);
+			},
+
+			false => self.consecutive_failures.store(0, Ordering::Relaxed),
+		}
+	}
+
+	pub fn latency(&self) -> Duration {
+		self.latency.get()
+	}
+
+	pub fn availability(&self, policy: Option<&EjectionPolicy>, now: Instant) -> Availability {
+		let Some(policy) = policy else {
+			return Availability::Available;
+		};
+
+		let mut ejected_at = self
+			.ejected_at
+			.lock()
+			.expect("Could not obtain node health.");
+
+		match *ejected_at {
+			Some(instant) if now.saturating_duration_since(instant) < policy.ejection_duration => {
+				Availability::Ejected
+			},
+
+			Some(_) => match self.probing.swap(true, Ordering::AcqRel) {
+				true => Availability::Ejected,
+				false => Availability::Probe,
+			},
+
+			None if self.consecutive_failures.load(Ordering::Relaxed)
+				>= policy.max_consecutive_failures =>
+			{
+				*ejected_at = Some(now);
+				Availability::Ejecting
+			},
+
+			None => Availability::Available,
+		}
+	}
+
+	// returns true if the node has been restored to routing, which only
+	// happens once it has responded to enough consecutive probes
+	pub fn finish_probe(
+		&self,
+		is_healthy: bool,
+		policy: Option<&EjectionPolicy>,
+		now: Instant,
+	) -> bool {
+		let mut ejected_at = self
+			.ejected_at
+			.lock()
+			.expect("Could not obtain node health.");
+
+		let healthy_probes = policy.map_or(1, |policy| policy.healthy_probes);
+
+		let is_restored = match is_healthy {
+			true => self.healthy_probes.fetch_add(1, Ordering::Relaxed) + 1 >= healthy_probes,
+
+			false => {
+				self.healthy_probes.store(0, Ordering::Relaxed);
+				false
+			},
+		};
+
+		*ejected_at = match is_restored {
+			true => {
+				self.consecutive_failures.store(0, Ordering::Relaxed);
+				self.healthy_probes.store(0, Ordering::Relaxed);
+				None
+			},
+
+			false => Some(now),
+		};
+
+		self.probing.store(false, Ordering::Release);
+		is_restored
+	}
+
+	// ends a probe which could not be sent (e.g., because every client was
+	// checked out), leaving the node ejected and its healthy probes intact
+	pub fn skip_probe(&self) {
+		self.probing.store(false, Ordering::Release);
+	}
+
+	pub fn status(&self, addr: &str, role: NodeRole) -> NodeStatus {
+		let is_ejected = self
+			.ejected_at
+			.lock()
+			.expect("Could not obtain node health.")
+			.is_some();
+
+		NodeStatus {
+			addr: addr.to_owned(),
+			role,
+			state: if is_ejected { NodeState::Ejected } else { NodeState::Healthy },
+
+			requests:             self.requests.load(Ordering::Relaxed),
+			errors:               self.errors.load(Ordering::Relaxed),
+			consecutive_failures: self.consecutive_failures.load(Ordering::Relaxed),
+
+			latency: self.latency(),
+		}
+	}
+}
+
+pub(crate) fn notify_failover(config: &PaperClientConfig, addr: &str, is_ejected: bool) {
+	if let Some(observer) = &config.observer {
+		let event = FailoverEvent::new(addr, is_ejected);
+		observer.on_event(&PaperEvent::Failover(event));
+	}
+}
+
+fn is_connection_error(err: &PaperClientError) -> bool {
+	matches!(
+		err,
+		PaperClientError::UnreachableServer
+			| PaperClientError::InvalidCommand
+			| PaperClientError::InvalidResponse
+			| PaperClientError::Disconnected
+			| PaperClientError::ReconnectExhausted { .. }
+	)
+}
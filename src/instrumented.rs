@@ -0,0 +1,561 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	collections::HashMap,
+	net::TcpStream,
+	time::{Duration, Instant},
+};
+
+#[cfg(feature = "tokio")]
+use crate::async_client::AsyncPaperClient;
+#[cfg(feature = "tokio")]
+use crate::transport::AsyncPaperTransport;
+use crate::{
+	arg::{AsPaperKey, AsPaperTtl},
+	client::PaperClient,
+	error::{PaperCacheError, PaperClientError, PaperClientResult},
+	transport::PaperTransport,
+	value::PaperValue,
+};
+
+const DEFAULT_DELIMITER: char = ':';
+const DEFAULT_MAX_PREFIXES: usize = 64;
+
+/// The prefix under which keys are recorded once the maximum number of
+/// prefixes has been reached.
+pub const OTHER_PREFIX: &str = "<other>";
+
+/// A client which records hits, misses, errors, and latencies for each
+/// key prefix (the part of the key before the first delimiter, e.g., `user`
+/// for `user:42`), so that the hottest and coldest namespaces of the cache
+/// can be found. A key without a delimiter is recorded under the empty
+/// prefix.
+///
+/// The number of prefixes recorded is capped, after which keys with new
+/// prefixes are recorded under `OTHER_PREFIX`. Only the key commands (`get`,
+/// `set`, `del`, `has`, `peek`, `ttl`, and `size`) are recorded; the
+/// underlying client is available through `inner_mut` for all others.
+///
+/// # Examples
+/// ```
+/// use paper_client::{InstrumentedClient, PaperClient};
+///
+/// let client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+/// let mut client = InstrumentedClient::new(client).with_max_prefixes(16);
+///
+/// let _ = client.get("user:42");
+///
+/// for prefix in client.snapshot().prefixes() {
+///     println!("{}: {:?}", prefix.prefix(), prefix.hit_ratio());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct InstrumentedClient<T = TcpStream> {
+	client:   PaperClient<T>,
+	recorder: KeyMetricsRecorder,
+}
+
+/// The async counterpart of `InstrumentedClient`.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncInstrumentedClient<T = tokio::net::TcpStream> {
+	client:   AsyncPaperClient<T>,
+	recorder: KeyMetricsRecorder,
+}
+
+/// A snapshot of the metrics recorded by an instrumented client, with
+/// prefixes ordered from most to least requested.
+#[derive(Debug, Clone)]
+pub struct KeyMetrics {
+	prefixes: Vec<PrefixMetrics>,
+}
+
+/// The metrics recorded for a single key prefix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefixMetrics {
+	prefix: String,
+
+	requests: u64,
+	hits:     u64,
+	misses:   u64,
+	errors:   u64,
+
+	total_latency: Duration,
+}
+
+#[derive(Debug)]
+struct KeyMetricsRecorder {
+	delimiter:    char,
+	max_prefixes: usize,
+
+	prefixes: HashMap<String, PrefixMetrics>,
+}
+
+// whether a command looks up a value, in which case a missing key is
+// recorded as a miss rather than as an error
+#[derive(Clone, Copy)]
+enum Lookup {
+	Value,
+	None,
+}
+
+impl<T: PaperTransport> InstrumentedClient<T> {
+	/// Wraps the supplied client, recording metrics for every key command
+	/// issued through the wrapper.
+	#[must_use]
+	pub fn new(client: PaperClient<T>) -> Self {
+		InstrumentedClient {
+			client,
+			recorder: KeyMetricsRecorder::default(),
+		}
+	}
+
+	/// Sets the delimiter which separates a key's prefix from the rest of
+	/// the key. Defaults to `:`.
+	#[must_use]
+	pub fn with_delimiter(mut self, delimiter: char) -> Self {
+		self.recorder.delimiter = delimiter;
+		self
+	}
+
+	/// Sets the maximum number of prefixes which are recorded separately.
+	/// Defaults to 64.
+	#[must_use]
+	pub fn with_max_prefixes(mut self, max_prefixes: usize) -> Self {
+		self.recorder.max_prefixes = max_prefixes;
+		self
+	}
+
+	/// Gets the value of the supplied key from the cache. See
+	/// `PaperClient::get`.
+	pub fn get(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = key.as_paper_key();
+		let start = Instant::now();
+
+		let result = self.client.get(key);
+		self.recorder.record(key, Lookup::Value, &result, start);
+
+		result
+	}
+
+	/// Sets the supplied key, value, and ttl to the cache. See
+	/// `PaperClient::set`.
+	pub fn set(
+		&mut self,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let start = Instant::now();
+
+		let result = self.client.set(key, value, ttl);
+		self.recorder.record(key, Lookup::None, &result, start);
+
+		result
+	}
+
+	/// Deletes the value of the supplied key from the cache. See
+	/// `PaperClient::del`.
+	pub fn del(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let start = Instant::now();
+
+		let result = self.client.del(key);
+		self.recorder.record(key, Lookup::None, &result, start);
+
+		result
+	}
+
+	/// Checks if the cache contains the supplied key. See `PaperClient::has`.
+	pub fn has(&mut self, key: impl AsPaperKey) -> PaperClientResult<bool> {
+		let key = key.as_paper_key();
+		let start = Instant::now();
+
+		let result = self.client.has(key);
+		self.recorder.record(key, Lookup::None, &result, start);
+
+		result
+	}
+
+	/// Peeks the value of the supplied key from the cache. See
+	/// `PaperClient::peek`.
+	pub fn peek(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = key.as_paper_key();
+		let start = Instant::now();
+
+		let result = self.client.peek(key);
+		self.recorder.record(key, Lookup::Value, &result, start);
+
+		result
+	}
+
+	/// Sets the ttl of the supplied key. See `PaperClient::ttl`.
+	pub fn ttl(&mut self, key: impl AsPaperKey, ttl: impl AsPaperTtl) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let start = Instant::now();
+
+		let result = self.client.ttl(key, ttl);
+		self.recorder.record(key, Lookup::None, &result, start);
+
+		result
+	}
+
+	/// Gets the size of the value of the supplied key. See
+	/// `PaperClient::size`.
+	pub fn size(&mut self, key: impl AsPaperKey) -> PaperClientResult<u32> {
+		let key = key.as_paper_key();
+		let start = Instant::now();
+
+		let result = self.client.size(key);
+		self.recorder.record(key, Lookup::None, &result, start);
+
+		result
+	}
+
+	/// Returns a snapshot of the metrics recorded so far.
+	#[must_use]
+	pub fn snapshot(&self) -> KeyMetrics {
+		self.recorder.snapshot()
+	}
+
+	/// Discards the metrics recorded so far.
+	pub fn reset(&mut self) {
+		self.recorder.prefixes.clear();
+	}
+
+	/// Returns a reference to the underlying client.
+	#[must_use]
+	pub fn inner(&self) -> &PaperClient<T> {
+		&self.client
+	}
+
+	/// Returns a mutable reference to the underlying client. Commands
+	/// issued through it are not recorded.
+	#[must_use]
+	pub fn inner_mut(&mut self) -> &mut PaperClient<T> {
+		&mut self.client
+	}
+
+	/// Unwraps the underlying client, discarding the recorded metrics.
+	#[must_use]
+	pub fn into_inner(self) -> PaperClient<T> {
+		self.client
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl<T: AsyncPaperTransport> AsyncInstrumentedClient<T> {
+	/// Wraps the supplied client, recording metrics for every key command
+	/// issued through the wrapper.
+	#[must_use]
+	pub fn new(client: AsyncPaperClient<T>) -> Self {
+		AsyncInstrumentedClient {
+			client,
+			recorder: KeyMetricsRecorder::default(),
+		}
+	}
+
+	/// Sets the delimiter which separates a key's prefix from the rest of
+	/// the key. Defaults to `:`.
+	#[must_use]
+	pub fn with_delimiter(mut self, delimiter: char) -> Self {
+		self.recorder.delimiter = delimiter;
+		self
+	}
+
+	/// Sets the maximum number of prefixes which are recorded separately.
+	/// Defaults to 64.
+	#[must_use]
+	pub fn with_max_prefixes(mut self, max_prefixes: usize) -> Self {
+		self.recorder.max_prefixes = max_prefixes;
+		self
+	}
+
+	/// Gets the value of the supplied key from the cache. See
+	/// `AsyncPaperClient::get`.
+	pub async fn get(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = key.as_paper_key();
+		let start = Instant::now();
+
+		let result = self.client.get(key).await;
+		self.recorder.record(key, Lookup::Value, &result, start);
+
+		result
+	}
+
+	/// Sets the supplied key, value, and ttl to the cache. See
+	/// `AsyncPaperClient::set`.
+	pub async fn set(
+		&mut self,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let start = Instant::now();
+
+		let result = self.client.set(key, value, ttl).await;
+		self.recorder.record(key, Lookup::None, &result, start);
+
+		result
+	}
+
+	/// Deletes the value of the supplied key from the cache. See
+	/// `AsyncPaperClient::del`.
+	pub async fn del(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let start = Instant::now();
+
+		let result = self.client.del(key).await;
+		self.recorder.record(key, Lookup::None, &result, start);
+
+		result
+	}
+
+	/// Checks if the cache contains the supplied key. See
+	/// `AsyncPaperClient::has`.
+	pub async fn has(&mut self, key: impl AsPaperKey) -> PaperClientResult<bool> {
+		let key = key.as_paper_key();
+		let start = Instant::now();
+
+		let result = self.client.has(key).await;
+		self.recorder.record(key, Lookup::None, &result, start);
+
+		result
+	}
+
+	/// Peeks the value of the supplied key from the cache. See
+	/// `AsyncPaperClient::peek`.
+	pub async fn peek(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = key.as_paper_key();
+		let start = Instant::now();
+
+		let result = self.client.peek(key).await;
+		self.recorder.record(key, Lookup::Value, &result, start);
+
+		result
+	}
+
+	/// Sets the ttl of the supplied key. See `AsyncPaperClient::ttl`.
+	pub async fn ttl(&mut self, key: impl AsPaperKey, ttl: impl AsPaperTtl) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let start = Instant::now();
+
+		let result = self.client.ttl(key, ttl).await;
+		self.recorder.record(key, Lookup::None, &result, start);
+
+		result
+	}
+
+	/// Gets the size of the value of the supplied key. See
+	/// `AsyncPaperClient::size`.
+	pub async fn size(&mut self, key: impl AsPaperKey) -> PaperClientResult<u32> {
+		let key = key.as_paper_key();
+		let start = Instant::now();
+
+		let result = self.client.size(key).await;
+		self.recorder.record(key, Lookup::None, &result, start);
+
+		result
+	}
+
+	/// Returns a snapshot of the metrics recorded so far.
+	#[must_use]
+	pub fn snapshot(&self) -> KeyMetrics {
+		self.recorder.snapshot()
+	}
+
+	/// Discards the metrics recorded so far.
+	pub fn reset(&mut self) {
+		self.recorder.prefixes.clear();
+	}
+
+	/// Returns a reference to the underlying client.
+	#[must_use]
+	pub fn inner(&self) -> &AsyncPaperClient<T> {
+		&self.client
+	}
+
+	/// Returns a mutable reference to the underlying client. Commands
+	/// issued through it are not recorded.
+	#[must_use]
+	pub fn inner_mut(&mut self) -> &mut AsyncPaperClient<T> {
+		&mut self.client
+	}
+
+	/// Unwraps the underlying client, discarding the recorded metrics.
+	#[must_use]
+	pub fn into_inner(self) -> AsyncPaperClient<T> {
+		self.client
+	}
+}
+
+impl KeyMetrics {
+	/// Returns the metrics of each prefix, from most to least requested.
+	#[must_use]
+	pub fn prefixes(&self) -> &[PrefixMetrics] {
+		&self.prefixes
+	}
+
+	/// Returns the metrics of the supplied prefix, if any were recorded.
+	#[must_use]
+	pub fn prefix(&self, prefix: &str) -> Option<&PrefixMetrics> {
+		self.prefixes
+			.iter()
+			.find(|metrics| metrics.prefix == prefix)
+	}
+}
+
+impl PrefixMetrics {
+	fn new(prefix: String) -> Self {
+		PrefixMetrics {
+			prefix,
+
+			requests: 0,
+			hits:     0,
+			misses:   0,
+			errors:   0,
+
+			total_latency: Duration::ZERO,
+		}
+	}
+
+	/// Returns the prefix.
+	#[must_use]
+	pub fn prefix(&self) -> &str {
+		&self.prefix
+	}
+
+	/// Returns the total number of commands issued for keys with this
+	/// prefix.
+	#[must_use]
+	pub fn requests(&self) -> u64 {
+		self.requests
+	}
+
+	/// Returns the number of `get` and `peek` commands which found a value.
+	#[must_use]
+	pub fn hits(&self) -> u64 {
+		self.hits
+	}
+
+	/// Returns the number of `get` and `peek` commands which did not find a
+	/// value.
+	#[must_use]
+	pub fn misses(&self) -> u64 {
+		self.misses
+	}
+
+	/// Returns the number of commands which failed (excluding misses).
+	#[must_use]
+	pub fn errors(&self) -> u64 {
+		self.errors
+	}
+
+	/// Returns the ratio of hits to lookups, or `None` if there have been
+	/// no lookups.
+	#[must_use]
+	pub fn hit_ratio(&self) -> Option<f64> {
+		let lookups = self.hits + self.misses;
+
+		match lookups {
+			0 => None,
+			lookups => Some(self.hits as f64 / lookups as f64),
+		}
+	}
+
+	/// Returns the mean latency of the commands issued for keys with this
+	/// prefix.
+	#[must_use]
+	pub fn latency(&self) -> Duration {
+		match self.requests {
+			0 => Duration::ZERO,
+			requests => self.total_latency / requests.try_into().unwrap_or(u32::MAX),
+		}
+	}
+}
+
+impl KeyMetricsRecorder {
+	fn record<R>(
+		&mut self,
+		key: &str,
+		lookup: Lookup,
+		result: &PaperClientResult<R>,
+		start: Instant,
+	) {
+		let latency = start.elapsed();
+		let metrics = self.prefix_metrics(key);
+
+		metrics.requests += 1;
+		metrics.total_latency += latency;
+
+		match (lookup, result) {
+			(Lookup::Value, Ok(_)) => metrics.hits += 1,
+
+			(Lookup::Value, Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound))) => {
+				metrics.misses += 1
+			},
+
+			(_, Err(_)) => metrics.errors += 1,
+			(Lookup::None, Ok(_)) => {},
+		}
+	}
+
+	fn prefix_metrics(&mut self, key: &str) -> &mut PrefixMetrics {
+		let prefix = key
+			.split_once(self.delimiter)
+			.map_or("", |(prefix, _)| prefix);
+
+		let is_recorded = self.prefixes.contains_key(prefix);
+
+		let prefix = match is_recorded || self.prefixes.len() < self.max_prefixes {
+			true => prefix,
+			false => OTHER_PREFIX,
+		};
+
+		// the prefix is only allocated the first time it is recorded
+		if !self.prefixes.contains_key(prefix) {
+			self.prefixes
+				.insert(prefix.to_owned(), PrefixMetrics::new(prefix.to_owned()));
+		}
+
+		self.prefixes
+			.get_mut(prefix)
+			.expect("Could not obtain prefix metrics.")
+	}
+
+	fn snapshot(&self) -> KeyMetrics {
+		let mut prefixes = self
+			.prefixes
+			.values()
+			.cloned()
+			.collect::<Vec<_>>();
+
+		prefixes.sort_by(|a, b| {
+			b.requests
+				.cmp(&a.requests)
+				.then_with(|| a.prefix.cmp(&b.prefix))
+		});
+
+		KeyMetrics {
+			prefixes,
+		}
+	}
+}
+
+impl Default for KeyMetricsRecorder {
+	fn default() -> Self {
+		KeyMetricsRecorder {
+			delimiter:    DEFAULT_DELIMITER,
+			max_prefixes: DEFAULT_MAX_PREFIXES,
+
+			prefixes: HashMap::new(),
+		}
+	}
+}
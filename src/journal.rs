@@ -0,0 +1,598 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	collections::VecDeque,
+	fmt::{self, Debug},
+	fs::{File, OpenOptions},
+	io::{Read, Write},
+	net::TcpStream,
+	path::Path,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "tokio")]
+use crate::async_client::AsyncPaperClient;
+#[cfg(feature = "tokio")]
+use crate::transport::AsyncPaperTransport;
+use crate::{
+	arg::{AsPaperKey, AsPaperTtl},
+	client::PaperClient,
+	error::{PaperCacheError, PaperClientError, PaperClientResult},
+	transport::PaperTransport,
+	ttl::Ttl,
+	value::PaperValue,
+};
+
+const SET_TAG: u8 = 0;
+const DEL_TAG: u8 = 1;
+const TTL_TAG: u8 = 2;
+
+/// A client which journals its writes (`set`, `del`, and `ttl`) to a local
+/// file while the server is unreachable, and replays them once it can be
+/// reached again, so that writes (e.g., those which warm up the cache) are
+/// not lost over an intermittent link.
+///
+/// A write which fails because the server could not be reached
+/// (`UnreachableServer`, `Disconnected`, or `ReconnectExhausted`) is
+/// appended to the journal and reported as successful. Any other result is
+/// returned as is. Before each write, the journaled writes are replayed in
+/// order, so a write is never applied before an earlier one. The journal is
+/// flushed to disk with each write and outlives the client, so writes which
+/// were journaled before a restart are replayed by the next client opened
+/// over the same file.
+///
+/// The ttl of a journaled write is kept as the time at which it expires, so
+/// a replayed write expires when it would have had it been applied
+/// immediately. A write whose ttl has elapsed by the time it is replayed
+/// deletes its key instead, as the key would have expired by then.
+///
+/// # Examples
+/// ```
+/// use paper_client::{JournaledClient, PaperClient};
+///
+/// let client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+/// let path = std::env::temp_dir().join("paper_client_journal_example");
+///
+/// let mut client = JournaledClient::new(client, &path).unwrap();
+///
+/// let _ = client.set("key", "value", None);
+/// println!("{} writes pending", client.pending());
+/// ```
+#[derive(Debug)]
+pub struct JournaledClient<T = TcpStream> {
+	client:  PaperClient<T>,
+	journal: CommandJournal,
+}
+
+/// The async counterpart of `JournaledClient`. The journal is written with
+/// blocking file operations, each of which appends a single write.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncJournaledClient<T = tokio::net::TcpStream> {
+	client:  AsyncPaperClient<T>,
+	journal: CommandJournal,
+}
+
+#[derive(Debug)]
+struct CommandJournal {
+	file:    File,
+	entries: VecDeque<JournalEntry>,
+}
+
+// the ttl of each entry is stored as its expiry (in milliseconds since the
+// Unix epoch), or None if the key does not expire
+#[derive(Clone)]
+enum JournalEntry {
+	Set(String, PaperValue, Option<u64>),
+	Del(String),
+	Ttl(String, Option<u64>),
+}
+
+impl<T: PaperTransport> JournaledClient<T> {
+	/// Wraps the supplied client, journaling its writes to the file at the
+	/// supplied path. The file is created if it does not exist, and any
+	/// writes it already holds are replayed before the client's next write.
+	pub fn new(client: PaperClient<T>, path: impl AsRef<Path>) -> PaperClientResult<Self> {
+		Ok(JournaledClient {
+			client,
+			journal: CommandJournal::open(path.as_ref())?,
+		})
+	}
+
+	/// Sets the supplied key, value, and ttl to the cache, or journals the
+	/// write if the server cannot be reached. See `PaperClient::set`.
+	pub fn set(
+		&mut self,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let ttl = ttl.as_paper_ttl()?;
+
+		let value: PaperValue = value
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidValue)?;
+
+		self.write(JournalEntry::Set(key.to_owned(), value, expiry(ttl)))
+	}
+
+	/// Deletes the value of the supplied key from the cache, or journals
+	/// the write if the server cannot be reached. See `PaperClient::del`.
+	pub fn del(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
+		self.write(JournalEntry::Del(key.as_paper_key().to_owned()))
+	}
+
+	/// Sets the ttl of the supplied key, or journals the write if the
+	/// server cannot be reached. See `PaperClient::ttl`.
+	pub fn ttl(&mut self, key: impl AsPaperKey, ttl: impl AsPaperTtl) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let ttl = ttl.as_paper_ttl()?;
+
+		self.write(JournalEntry::Ttl(key.to_owned(), expiry(ttl)))
+	}
+
+	/// Replays the journaled writes in order, returning the number which
+	/// were replayed. A replayed write which the server rejects (e.g., a
+	/// `del` of a key which has since been evicted) is dropped from the
+	/// journal. If the server cannot be reached, the writes which were not
+	/// yet replayed are kept and the error is returned.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{JournaledClient, PaperClient};
+	///
+	/// let client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	/// let path = std::env::temp_dir().join("paper_client_journal_replay_example");
+	///
+	/// let mut client = JournaledClient::new(client, &path).unwrap();
+	///
+	/// match client.replay() {
+	///     Ok(replayed) => println!("replayed {replayed} writes"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn replay(&mut self) -> PaperClientResult<usize> {
+		let mut replayed = 0;
+		let mut result = Ok(());
+
+		for entry in &self.journal.entries {
+			match entry.clone().apply(&mut self.client) {
+				Err(err) if is_unreachable(&err) => {
+					result = Err(err);
+					break;
+				},
+
+				_ => replayed += 1,
+			}
+		}
+
+		self.journal.remove(replayed)?;
+		result.map(|_| replayed)
+	}
+
+	/// Returns the number of journaled writes waiting to be replayed.
+	#[must_use]
+	pub fn pending(&self) -> usize {
+		self.journal.entries.len()
+	}
+
+	/// Returns a reference to the underlying client.
+	#[must_use]
+	pub fn inner(&self) -> &PaperClient<T> {
+		&self.client
+	}
+
+	/// Returns a mutable reference to the underlying client. Commands
+	/// issued through it are not journaled.
+	#[must_use]
+	pub fn inner_mut(&mut self) -> &mut PaperClient<T> {
+		&mut self.client
+	}
+
+	/// Unwraps the underlying client. Writes which have not yet been
+	/// replayed remain in the journal's file.
+	#[must_use]
+	pub fn into_inner(self) -> PaperClient<T> {
+		self.client
+	}
+
+	fn write(&mut self, entry: JournalEntry) -> PaperClientResult<()> {
+		// the write is journaled behind any earlier writes which still
+		// cannot be replayed
+		if !self.journal.entries.is_empty() {
+			match self.replay() {
+				Ok(_) => {},
+				Err(err) if is_unreachable(&err) => return self.journal.append(entry),
+				Err(err) => return Err(err),
+			}
+		}
+
+		match entry.clone().apply(&mut self.client) {
+			Err(err) if is_unreachable(&err) => self.journal.append(entry),
+			result => result,
+		}
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl<T: AsyncPaperTransport> AsyncJournaledClient<T> {
+	/// Wraps the supplied client, journaling its writes to the file at the
+	/// supplied path. See `JournaledClient::new`.
+	pub fn new(client: AsyncPaperClient<T>, path: impl AsRef<Path>) -> PaperClientResult<Self> {
+		Ok(AsyncJournaledClient {
+			client,
+			journal: CommandJournal::open(path.as_ref())?,
+		})
+	}
+
+	/// Sets the supplied key, value, and ttl to the cache, or journals the
+	/// write if the server cannot be reached. See `AsyncPaperClient::set`.
+	pub async fn set(
+		&mut self,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let ttl = ttl.as_paper_ttl()?;
+
+		let value: PaperValue = value
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidValue)?;
+
+		self.write(JournalEntry::Set(key.to_owned(), value, expiry(ttl)))
+			.await
+	}
+
+	/// Deletes the value of the supplied key from the cache, or journals
+	/// the write if the server cannot be reached. See
+	/// `AsyncPaperClient::del`.
+	pub async fn del(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
+		self.write(JournalEntry::Del(key.as_paper_key().to_owned())).await
+	}
+
+	/// Sets the ttl of the supplied key, or journals the write if the
+	/// server cannot be reached. See `AsyncPaperClient::ttl`.
+	pub async fn ttl(&mut self, key: impl AsPaperKey, ttl: impl AsPaperTtl) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let ttl = ttl.as_paper_ttl()?;
+
+		self.write(JournalEntry::Ttl(key.to_owned(), expiry(ttl)))
+			.await
+	}
+
+	/// Replays the journaled writes in order, returning the number which
+	/// were replayed. See `JournaledClient::replay`.
+	pub async fn replay(&mut self) -> PaperClientResult<usize> {
+		let mut replayed = 0;
+		let mut result = Ok(());
+
+		for entry in &self.journal.entries {
+			match entry.clone().apply_async(&mut self.client).await {
+				Err(err) if is_unreachable(&err) => {
+					result = Err(err);
+					break;
+				},
+
+				_ => replayed += 1,
+			}
+		}
+
+		self.journal.remove(replayed)?;
+		result.map(|_| replayed)
+	}
+
+	/// Returns the number of journaled writes waiting to be replayed.
+	#[must_use]
+	pub fn pending(&self) -> usize {
+		self.journal.entries.len()
+	}
+
+	/// Returns a reference to the underlying client.
+	#[must_use]
+	pub fn inner(&self) -> &AsyncPaperClient<T> {
+		&self.client
+	}
+
+	/// Returns a mutable reference to the underlying client. Commands
+	/// issued through it are not journaled.
+	#[must_use]
+	pub fn inner_mut(&mut self) -> &mut AsyncPaperClient<T> {
+		&mut self.client
+	}
+
+	/// Unwraps the underlying client. Writes which have not yet been
+	/// replayed remain in the journal's file.
+	#[must_use]
+	pub fn into_inner(self) -> AsyncPaperClient<T> {
+		self.client
+	}
+
+	async fn write(&mut self, entry: JournalEntry) -> PaperClientResult<()> {
+		if !self.journal.entries.is_empty() {
+			match self.replay().await {
+				Ok(_) => {},
+				Err(err) if is_unreachable(&err) => return self.journal.append(entry),
+				Err(err) => return Err(err),
+			}
+		}
+
+		match entry.clone().apply_async(&mut self.client).await {
+			Err(err) if is_unreachable(&err) => self.journal.append(entry),
+			result => result,
+		}
+	}
+}
+
+impl CommandJournal {
+	fn open(path: &Path) -> PaperClientResult<Self> {
+		let mut file = OpenOptions::new()
+			.read(true)
+			.append(true)
+			.create(true)
+			.open(path)
+			.map_err(|_| PaperClientError::JournalUnavailable)?;
+
+		let mut buf = Vec::new();
+
+		file.read_to_end(&mut buf)
+			.map_err(|_| PaperClientError::JournalUnavailable)?;
+
+		let mut entries = VecDeque::new();
+		let mut remaining = buf.as_slice();
+
+		// an entry which was only partially written (e.g., by a process
+		// which exited mid-write) ends the journal
+		while let Some(entry) = JournalEntry::decode(&mut remaining) {
+			entries.push_back(entry);
+		}
+
+		let mut journal = CommandJournal {
+			file,
+			entries,
+		};
+
+		// drops any partially written entry so that later entries are
+		// appended after the last complete one
+		if !remaining.is_empty() {
+			journal.rewrite(0)?;
+		}
+
+		Ok(journal)
+	}
+
+	fn append(&mut self, entry: JournalEntry) -> PaperClientResult<()> {
+		let mut buf = Vec::new();
+		entry.encode(&mut buf);
+
+		self.file
+			.write_all(&buf)
+			.and_then(|_| self.file.sync_data())
+			.map_err(|_| PaperClientError::JournalUnavailable)?;
+
+		self.entries.push_back(entry);
+		Ok(())
+	}
+
+	// removes the supplied number of entries from the front of the journal
+	fn remove(&mut self, count: usize) -> PaperClientResult<()> {
+		if count == 0 {
+			return Ok(());
+		}
+
+		// the file is rewritten first so that the replayed entries are only
+		// forgotten once they can no longer be replayed again
+		self.rewrite(count)?;
+		self.entries.drain(..count);
+
+		Ok(())
+	}
+
+	// rewrites the file with every entry but the supplied number from the
+	// front of the journal
+	fn rewrite(&mut self, skip: usize) -> PaperClientResult<()> {
+		let mut buf = Vec::new();
+
+		for entry in self.entries.iter().skip(skip) {
+			entry.encode(&mut buf);
+		}
+
+		// the file is opened in append mode, so the entries are written
+		// from the start once it has been truncated
+		self.file
+			.set_len(0)
+			.and_then(|_| self.file.write_all(&buf))
+			.and_then(|_| self.file.sync_data())
+			.map_err(|_| PaperClientError::JournalUnavailable)
+	}
+}
+
+impl JournalEntry {
+	// an entry whose ttl has elapsed is applied as a deletion, as the key
+	// would have expired by now
+	fn apply<T: PaperTransport>(self, client: &mut PaperClient<T>) -> PaperClientResult<()> {
+		match self {
+			JournalEntry::Set(key, value, expiry) => match remaining(expiry) {
+				Some(ttl) => client.set(&key, value, ttl),
+				None => ignore_missing(client.del(&key)),
+			},
+
+			JournalEntry::Del(key) => client.del(&key),
+
+			JournalEntry::Ttl(key, expiry) => match remaining(expiry) {
+				Some(ttl) => client.ttl(&key, ttl),
+				None => ignore_missing(client.del(&key)),
+			},
+		}
+	}
+
+	#[cfg(feature = "tokio")]
+	async fn apply_async<T: AsyncPaperTransport>(
+		self,
+		client: &mut AsyncPaperClient<T>,
+	) -> PaperClientResult<()> {
+		match self {
+			JournalEntry::Set(key, value, expiry) => match remaining(expiry) {
+				Some(ttl) => client.set(&key, value, ttl).await,
+				None => ignore_missing(client.del(&key).await),
+			},
+
+			JournalEntry::Del(key) => client.del(&key).await,
+
+			JournalEntry::Ttl(key, expiry) => match remaining(expiry) {
+				Some(ttl) => client.ttl(&key, ttl).await,
+				None => ignore_missing(client.del(&key).await),
+			},
+		}
+	}
+
+	// each entry is a tag followed by its length-prefixed key and then its
+	// arguments, with every integer in little-endian order
+	fn encode(&self, buf: &mut Vec<u8>) {
+		match self {
+			JournalEntry::Set(key, value, expiry) => {
+				buf.push(SET_TAG);
+				encode_bytes(buf, key.as_bytes());
+				encode_bytes(buf, value);
+				buf.extend_from_slice(&expiry.unwrap_or(0).to_le_bytes());
+			},
+
+			JournalEntry::Del(key) => {
+				buf.push(DEL_TAG);
+				encode_bytes(buf, key.as_bytes());
+			},
+
+			JournalEntry::Ttl(key, expiry) => {
+				buf.push(TTL_TAG);
+				encode_bytes(buf, key.as_bytes());
+				buf.extend_from_slice(&expiry.unwrap_or(0).to_le_bytes());
+			},
+		}
+	}
+
+	// advances the supplied buffer past the decoded entry, leaving it
+	// unchanged if the buffer does not begin with a complete entry
+	fn decode(buf: &mut &[u8]) -> Option<Self> {
+		let mut remaining = *buf;
+
+		let (&tag, rest) = remaining.split_first()?;
+		remaining = rest;
+
+		let key = String::from_utf8(decode_bytes(&mut remaining)?.to_vec()).ok()?;
+
+		let entry = match tag {
+			SET_TAG => {
+				let value = PaperValue::from(decode_bytes(&mut remaining)?);
+				JournalEntry::Set(key, value, decode_expiry(&mut remaining)?)
+			},
+
+			DEL_TAG => JournalEntry::Del(key),
+			TTL_TAG => JournalEntry::Ttl(key, decode_expiry(&mut remaining)?),
+
+			_ => return None,
+		};
+
+		*buf = remaining;
+		Some(entry)
+	}
+}
+
+impl Debug for JournalEntry {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			JournalEntry::Set(key, _, expiry) => write!(f, "Set({key:?}, {expiry:?})"),
+			JournalEntry::Del(key) => write!(f, "Del({key:?})"),
+			JournalEntry::Ttl(key, expiry) => write!(f, "Ttl({key:?}, {expiry:?})"),
+		}
+	}
+}
+
+fn is_unreachable(err: &PaperClientError) -> bool {
+	matches!(
+		err,
+		PaperClientError::UnreachableServer
+			| PaperClientError::Disconnected
+			| PaperClientError::ReconnectExhausted { .. }
+	)
+}
+
+fn expiry(ttl: u32) -> Option<u64> {
+	match ttl {
+		0 => None,
+		ttl => Some(now_millis() + u64::from(ttl) * 1000),
+	}
+}
+
+// the ttl which remains until the supplied expiry, rounded up to the next
+// second, or None if it has elapsed
+fn remaining(expiry: Option<u64>) -> Option<Ttl> {
+	let Some(expiry) = expiry else {
+		return Some(Ttl::None);
+	};
+
+	let now = now_millis();
+
+	match expiry > now {
+		true => {
+			let secs = (expiry - now).div_ceil(1000).min(u64::from(u32::MAX));
+			Some(Ttl::from_secs(secs as u32))
+		},
+		false => None,
+	}
+}
+
+// an expired key which was never written, or was since deleted, is
+// already in the state the entry would leave it in
+fn ignore_missing(result: PaperClientResult<()>) -> PaperClientResult<()> {
+	match result {
+		Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => Ok(()),
+		result => result,
+	}
+}
+
+fn now_millis() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map_or(0, |duration| duration.as_millis() as u64)
+}
+
+fn encode_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+	buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+	buf.extend_from_slice(bytes);
+}
+
+fn decode_bytes<'a>(buf: &mut &'a [u8]) -> Option<&'a [u8]> {
+	let len = decode_u32(buf)? as usize;
+
+	if buf.len() < len {
+		return None;
+	}
+
+	let (bytes, rest) = buf.split_at(len);
+	*buf = rest;
+
+	Some(bytes)
+}
+
+fn decode_expiry(buf: &mut &[u8]) -> Option<Option<u64>> {
+	let (bytes, rest) = buf.split_first_chunk::<8>()?;
+	*buf = rest;
+
+	match u64::from_le_bytes(*bytes) {
+		0 => Some(None),
+		expiry => Some(Some(expiry)),
+	}
+}
+
+fn decode_u32(buf: &mut &[u8]) -> Option<u32> {
+	let (bytes, rest) = buf.split_first_chunk::<4>()?;
+	*buf = rest;
+
+	Some(u32::from_le_bytes(*bytes))
+}
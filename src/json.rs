@@ -0,0 +1,411 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! A minimal JSON reader and writer for the client's documented schemas,
+//! which avoids a dependency on a serialization framework.
+
+use std::fmt::{Display, Write};
+
+use crate::error::{PaperClientError, PaperClientResult};
+
+// the maximum nesting depth of arrays and objects
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum JsonValue {
+	Null,
+	Bool(bool),
+	// numbers are kept as their text so that integers are not rounded
+	Number(String),
+	String(String),
+	Array(Vec<JsonValue>),
+	Object(Vec<(String, JsonValue)>),
+}
+
+/// Writes a JSON object field by field.
+pub(crate) struct JsonObjectWriter {
+	buf:      String,
+	is_empty: bool,
+}
+
+struct JsonParser<'a> {
+	bytes: &'a [u8],
+	index: usize,
+}
+
+impl JsonValue {
+	pub(crate) fn parse(json: &str) -> PaperClientResult<Self> {
+		let mut parser = JsonParser {
+			bytes: json.as_bytes(),
+			index: 0,
+		};
+
+		let value = parser.parse_value(0)?;
+		parser.skip_whitespace();
+
+		match parser.index == parser.bytes.len() {
+			true => Ok(value),
+			false => Err(PaperClientError::InvalidJson),
+		}
+	}
+
+	/// Returns the value of the supplied field if this is an object.
+	pub(crate) fn field(&self, name: &str) -> Option<&JsonValue> {
+		let JsonValue::Object(fields) = self else {
+			return None;
+		};
+
+		fields
+			.iter()
+			.find(|(field, _)| field == name)
+			.map(|(_, value)| value)
+	}
+
+	pub(crate) fn as_u64(&self) -> Option<u64> {
+		match self {
+			JsonValue::Number(number) => number.parse().ok(),
+			_ => None,
+		}
+	}
+
+	pub(crate) fn as_f64(&self) -> Option<f64> {
+		match self {
+			JsonValue::Number(number) => number.parse().ok(),
+			JsonValue::Null => Some(f64::NAN),
+			_ => None,
+		}
+	}
+
+	pub(crate) fn as_bool(&self) -> Option<bool> {
+		match self {
+			JsonValue::Bool(value) => Some(*value),
+			_ => None,
+		}
+	}
+
+	pub(crate) fn as_str(&self) -> Option<&str> {
+		match self {
+			JsonValue::String(value) => Some(value),
+			_ => None,
+		}
+	}
+
+	pub(crate) fn as_array(&self) -> Option<&[JsonValue]> {
+		match self {
+			JsonValue::Array(values) => Some(values),
+			_ => None,
+		}
+	}
+}
+
+impl JsonObjectWriter {
+	pub(crate) fn new() -> Self {
+		JsonObjectWriter {
+			buf:      String::from("{"),
+			is_empty: true,
+		}
+	}
+
+	pub(crate) fn u64(&mut self, name: &str, value: u64) -> &mut Self {
+		self.key(name);
+		let _ = write!(self.buf, "{value}");
+		self
+	}
+
+	// non-finite numbers cannot be represented in JSON and are written as null
+	pub(crate) fn f64(&mut self, name: &str, value: f64) -> &mut Self {
+		self.key(name);
+
+		match value.is_finite() {
+			true => {
+				let _ = write!(self.buf, "{value}");
+			},
+
+			false => self.buf.push_str("null"),
+		}
+
+		self
+	}
+
+	pub(crate) fn bool(&mut self, name: &str, value: bool) -> &mut Self {
+		self.key(name);
+		let _ = write!(self.buf, "{value}");
+		self
+	}
+
+	pub(crate) fn str(&mut self, name: &str, value: &str) -> &mut Self {
+		self.key(name);
+		write_str(&mut self.buf, value);
+		self
+	}
+
+	// writes an array of the supplied values' string representations
+	pub(crate) fn strs(
+		&mut self,
+		name: &str,
+		values: impl IntoIterator<Item = impl Display>,
+	) -> &mut Self {
+		self.key(name);
+		self.buf.push('[');
+
+		for (index, value) in values.into_iter().enumerate() {
+			if index > 0 {
+				self.buf.push(',');
+			}
+
+			write_str(&mut self.buf, &value.to_string());
+		}
+
+		self.buf.push(']');
+		self
+	}
+
+	pub(crate) fn finish(&mut self) -> String {
+		self.buf.push('}');
+		std::mem::take(&mut self.buf)
+	}
+
+	fn key(&mut self, name: &str) {
+		if !self.is_empty {
+			self.buf.push(',');
+		}
+
+		self.is_empty = false;
+
+		write_str(&mut self.buf, name);
+		self.buf.push(':');
+	}
+}
+
+impl JsonParser<'_> {
+	fn parse_value(&mut self, depth: usize) -> PaperClientResult<JsonValue> {
+		if depth > MAX_DEPTH {
+			return Err(PaperClientError::InvalidJson);
+		}
+
+		self.skip_whitespace();
+
+		match self.peek() {
+			Some(b'n') => self.parse_literal("null", JsonValue::Null),
+			Some(b't') => self.parse_literal("true", JsonValue::Bool(true)),
+			Some(b'f') => self.parse_literal("false", JsonValue::Bool(false)),
+			Some(b'"') => self.parse_string().map(JsonValue::String),
+			Some(b'[') => self.parse_array(depth),
+			Some(b'{') => self.parse_object(depth),
+			Some(b'-' | b'0'..=b'9') => self.parse_number(),
+
+			_ => Err(PaperClientError::InvalidJson),
+		}
+	}
+
+	fn parse_literal(&mut self, literal: &str, value: JsonValue) -> PaperClientResult<JsonValue> {
+		match self.bytes[self.index..].starts_with(literal.as_bytes()) {
+			true => {
+				self.index += literal.len();
+				Ok(value)
+			},
+
+			false => Err(PaperClientError::InvalidJson),
+		}
+	}
+
+	fn parse_number(&mut self) -> PaperClientResult<JsonValue> {
+		let start = self.index;
+
+		while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.peek() {
+			self.index += 1;
+		}
+
+		let number = std::str::from_utf8(&self.bytes[start..self.index])
+			.map_err(|_| PaperClientError::InvalidJson)?;
+
+		match number.parse::<f64>() {
+			Ok(_) => Ok(JsonValue::Number(number.to_owned())),
+			Err(_) => Err(PaperClientError::InvalidJson),
+		}
+	}
+
+	fn parse_string(&mut self) -> PaperClientResult<String> {
+		// skip the opening quote
+		self.index += 1;
+
+		let mut value = Vec::new();
+
+		loop {
+			let Some(byte) = self.next() else {
+				return Err(PaperClientError::InvalidJson);
+			};
+
+			match byte {
+				b'"' => break,
+				b'\\' => self.parse_escape(&mut value)?,
+				byte => value.push(byte),
+			}
+		}
+
+		String::from_utf8(value).map_err(|_| PaperClientError::InvalidJson)
+	}
+
+	fn parse_escape(&mut self, value: &mut Vec<u8>) -> PaperClientResult<()> {
+		let escaped = match self.next() {
+			Some(b'"') => '"',
+			Some(b'\\') => '\\',
+			Some(b'/') => '/',
+			Some(b'b') => '\u{8}',
+			Some(b'f') => '\u{c}',
+			Some(b'n') => '\n',
+			Some(b'r') => '\r',
+			Some(b't') => '\t',
+			Some(b'u') => self.parse_unicode_escape()?,
+
+			_ => return Err(PaperClientError::InvalidJson),
+		};
+
+		let mut buf = [0; 4];
+		value.extend_from_slice(escaped.encode_utf8(&mut buf).as_bytes());
+
+		Ok(())
+	}
+
+	fn parse_unicode_escape(&mut self) -> PaperClientResult<char> {
+		let high = self.parse_hex()?;
+
+		if !(0xd800..0xdc00).contains(&high) {
+			return char::from_u32(high).ok_or(PaperClientError::InvalidJson);
+		}
+
+		// a high surrogate must be followed by an escaped low surrogate
+		if self.next() != Some(b'\\') || self.next() != Some(b'u') {
+			return Err(PaperClientError::InvalidJson);
+		}
+
+		let low = self.parse_hex()?;
+
+		if !(0xdc00..0xe000).contains(&low) {
+			return Err(PaperClientError::InvalidJson);
+		}
+
+		char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+			.ok_or(PaperClientError::InvalidJson)
+	}
+
+	fn parse_hex(&mut self) -> PaperClientResult<u32> {
+		let digits = self
+			.bytes
+			.get(self.index..self.index + 4)
+			.and_then(|digits| std::str::from_utf8(digits).ok())
+			.ok_or(PaperClientError::InvalidJson)?;
+
+		self.index += 4;
+		u32::from_str_radix(digits, 16).map_err(|_| PaperClientError::InvalidJson)
+	}
+
+	fn parse_array(&mut self, depth: usize) -> PaperClientResult<JsonValue> {
+		// skip the opening bracket
+		self.index += 1;
+
+		let mut values = Vec::new();
+		self.skip_whitespace();
+
+		if self.peek() == Some(b']') {
+			self.index += 1;
+			return Ok(JsonValue::Array(values));
+		}
+
+		loop {
+			values.push(self.parse_value(depth + 1)?);
+			self.skip_whitespace();
+
+			match self.next() {
+				Some(b',') => continue,
+				Some(b']') => break,
+
+				_ => return Err(PaperClientError::InvalidJson),
+			}
+		}
+
+		Ok(JsonValue::Array(values))
+	}
+
+	fn parse_object(&mut self, depth: usize) -> PaperClientResult<JsonValue> {
+		// skip the opening brace
+		self.index += 1;
+
+		let mut fields = Vec::new();
+		self.skip_whitespace();
+
+		if self.peek() == Some(b'}') {
+			self.index += 1;
+			return Ok(JsonValue::Object(fields));
+		}
+
+		loop {
+			self.skip_whitespace();
+
+			if self.peek() != Some(b'"') {
+				return Err(PaperClientError::InvalidJson);
+			}
+
+			let name = self.parse_string()?;
+			self.skip_whitespace();
+
+			if self.next() != Some(b':') {
+				return Err(PaperClientError::InvalidJson);
+			}
+
+			fields.push((name, self.parse_value(depth + 1)?));
+			self.skip_whitespace();
+
+			match self.next() {
+				Some(b',') => continue,
+				Some(b'}') => break,
+
+				_ => return Err(PaperClientError::InvalidJson),
+			}
+		}
+
+		Ok(JsonValue::Object(fields))
+	}
+
+	fn skip_whitespace(&mut self) {
+		while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+			self.index += 1;
+		}
+	}
+
+	fn peek(&self) -> Option<u8> {
+		self.bytes.get(self.index).copied()
+	}
+
+	fn next(&mut self) -> Option<u8> {
+		let byte = self.peek()?;
+		self.index += 1;
+
+		Some(byte)
+	}
+}
+
+fn write_str(buf: &mut String, value: &str) {
+	buf.push('"');
+
+	for char in value.chars() {
+		match char {
+			'"' => buf.push_str("\\\""),
+			'\\' => buf.push_str("\\\\"),
+			'\n' => buf.push_str("\\n"),
+			'\r' => buf.push_str("\\r"),
+			'\t' => buf.push_str("\\t"),
+			char if char.is_control() => {
+				let _ = write!(buf, "\\u{:04x}", char as u32);
+			},
+
+			char => buf.push(char),
+		}
+	}
+
+	buf.push('"');
+}
@@ -0,0 +1,66 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{borrow::Cow, fmt::Write};
+
+use sha2::{Digest, Sha256};
+
+// the number of bytes of a key's digest which are shown, enough to tell
+// keys apart in a trace without making the digest easy to reverse by
+// guessing
+const HASHED_KEY_BYTES: usize = 8;
+
+const OMITTED_KEY: &str = "<redacted>";
+
+/// How keys appear in the client's diagnostic output (e.g., the frames
+/// recorded by a `ProtocolCapture`), so that keys which contain personal
+/// data are not leaked into logs. Keys are always sent to the server as
+/// is.
+///
+/// # Examples
+/// ```
+/// use paper_client::KeyDisplay;
+///
+/// assert_eq!(KeyDisplay::Full.display("user:42"), "user:42");
+/// assert_eq!(KeyDisplay::Omitted.display("user:42"), "<redacted>");
+/// assert!(KeyDisplay::Hashed.display("user:42").starts_with("sha256:"));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyDisplay {
+	/// The key is shown as is.
+	#[default]
+	Full,
+	/// The key is replaced by `sha256:` followed by the first 8 bytes of
+	/// its hex-encoded SHA-256 digest, so that occurrences of the same key
+	/// can still be correlated.
+	Hashed,
+	/// The key is replaced by `<redacted>`.
+	Omitted,
+}
+
+impl KeyDisplay {
+	/// Returns the supplied key as it should be shown.
+	#[must_use]
+	pub fn display<'a>(&self, key: &'a str) -> Cow<'a, str> {
+		match self {
+			KeyDisplay::Full => Cow::Borrowed(key),
+
+			KeyDisplay::Hashed => {
+				let digest = Sha256::digest(key.as_bytes());
+				let mut hashed = String::from("sha256:");
+
+				for byte in &digest[..HASHED_KEY_BYTES] {
+					let _ = write!(hashed, "{byte:02x}");
+				}
+
+				Cow::Owned(hashed)
+			},
+
+			KeyDisplay::Omitted => Cow::Borrowed(OMITTED_KEY),
+		}
+	}
+}
@@ -13,6 +13,47 @@ pub mod async_client;
 #[cfg(feature = "tokio")]
 pub use crate::async_client::*;
 
+pub mod auth;
+pub use crate::auth::*;
+
+pub mod batch;
+pub use crate::batch::*;
+
+#[cfg(feature = "tokio")]
+pub mod batched_writer;
+#[cfg(feature = "tokio")]
+pub use crate::batched_writer::*;
+
+pub mod canary;
+pub use crate::canary::*;
+
+pub mod capture;
+pub use crate::capture::*;
+
+pub mod chunked;
+pub use crate::chunked::*;
+
+pub mod clock;
+pub use crate::clock::*;
+
+pub mod codec;
+pub use crate::codec::*;
+
+pub mod command_policy;
+pub use crate::command_policy::*;
+
+pub mod connection_info;
+pub use crate::connection_info::*;
+
+pub mod config;
+pub use crate::config::*;
+
+pub mod deadline;
+pub use crate::deadline::*;
+
+pub mod decode;
+pub use crate::decode::*;
+
 pub mod error;
 pub use error::PaperClientError;
 
@@ -24,15 +65,111 @@ pub mod async_pool;
 #[cfg(feature = "tokio")]
 pub use crate::async_pool::*;
 
+#[cfg(feature = "tokio")]
+pub mod background;
+#[cfg(feature = "tokio")]
+pub use crate::background::*;
+
+pub mod get_result;
+pub use crate::get_result::*;
+
+pub mod guardrails;
+pub use crate::guardrails::*;
+
+pub mod health;
+pub use crate::health::*;
+
+pub mod instrumented;
+pub use crate::instrumented::*;
+
+pub mod journal;
+pub use crate::journal::*;
+
+pub mod key_display;
+pub use crate::key_display::*;
+
+pub mod metrics;
+pub use crate::metrics::*;
+
+pub mod migrate;
+pub use crate::migrate::*;
+
+#[cfg(feature = "tokio")]
+pub mod monitor;
+#[cfg(feature = "tokio")]
+pub use crate::monitor::*;
+
+pub mod namespace;
+pub use crate::namespace::*;
+
+pub mod numeric;
+pub use crate::numeric::*;
+
+pub mod observer;
+pub use crate::observer::*;
+
+pub mod ops;
+pub use crate::ops::*;
+
+pub mod replica;
+pub use crate::replica::*;
+
+pub mod retry;
+pub use crate::retry::*;
+
+pub mod selection;
+pub use crate::selection::*;
+
+pub mod sequence;
+pub use crate::sequence::*;
+
+pub mod settings;
+pub use crate::settings::*;
+
+pub mod shadow;
+pub use crate::shadow::*;
+
+pub mod sharder;
+pub use crate::sharder::*;
+
+pub mod singleflight;
+pub use crate::singleflight::*;
+
+pub mod stale;
+pub use crate::stale::*;
+
 pub mod policy;
 pub use crate::policy::*;
 
 pub mod status;
 pub use crate::status::*;
 
+#[cfg(feature = "tokio")]
+pub mod sync_handle;
+#[cfg(feature = "tokio")]
+pub use crate::sync_handle::*;
+
+pub mod tenant;
+pub use crate::tenant::*;
+
+pub mod loopback;
+pub use crate::loopback::*;
+
+pub mod transport;
+pub use crate::transport::*;
+
+pub mod ttl;
+pub use crate::ttl::*;
+
 mod value;
 pub use crate::value::*;
 
 mod addr;
 mod arg;
 mod command;
+pub use crate::command::{Command, CommandKind};
+
+mod json;
+mod range;
+
+mod response;
@@ -0,0 +1,329 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+#[cfg(feature = "tokio")]
+use std::{
+	pin::Pin,
+	task::{Context, Poll},
+};
+use std::{
+	collections::VecDeque,
+	io::{self, Read, Write},
+	sync::{Arc, Mutex, MutexGuard},
+	time::Duration,
+};
+
+use paper_utils::sheet::SheetBuilder;
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+#[cfg(feature = "tokio")]
+use crate::transport::AsyncPaperTransport;
+use crate::{
+	command::Command,
+	error::{PaperClientError, PaperClientResult},
+	transport::PaperTransport,
+	value::PaperValue,
+};
+
+/// Responds to the commands sent over a `LoopbackTransport` with scripted
+/// replies, allowing code which uses a real client to be tested without a
+/// server. Commands must be sent in the order in which they are expected,
+/// and sending an unexpected command panics.
+///
+/// # Examples
+/// ```
+/// use paper_client::{Command, PaperClient, ScriptedResponder};
+///
+/// let responder = ScriptedResponder::new();
+///
+/// responder
+///     .expect(Command::Get("key"))
+///     .reply("value")
+///     .expect(Command::Del("key"))
+///     .reply_ok();
+///
+/// let mut client = PaperClient::from_transport(
+///     "paper://loopback",
+///     responder.transport(),
+/// ).unwrap();
+///
+/// let value: String = client.get("key").unwrap().try_into().unwrap();
+/// assert_eq!(value, "value");
+/// assert!(client.del("key").is_ok());
+///
+/// responder.verify();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedResponder {
+	script: Arc<Mutex<Script>>,
+}
+
+/// A command expected by a `ScriptedResponder`, awaiting its reply.
+#[derive(Debug)]
+#[must_use]
+pub struct Expectation<'a> {
+	responder: &'a ScriptedResponder,
+	command:   Vec<u8>,
+}
+
+/// An in-memory transport connected to a `ScriptedResponder`.
+#[derive(Debug)]
+pub struct LoopbackTransport {
+	script: Arc<Mutex<Script>>,
+}
+
+#[derive(Debug, Default)]
+struct Script {
+	expectations: VecDeque<(Vec<u8>, Vec<u8>)>,
+
+	written:  Vec<u8>,
+	readable: VecDeque<u8>,
+}
+
+impl ScriptedResponder {
+	/// Creates a new responder with no expected commands.
+	#[must_use]
+	pub fn new() -> Self {
+		ScriptedResponder::default()
+	}
+
+	/// Expects the supplied command to be the next command sent after any
+	/// previously expected commands.
+	pub fn expect(&self, command: Command<'_>) -> Expectation<'_> {
+		Expectation {
+			responder: self,
+			command:   command.to_bytes(),
+		}
+	}
+
+	/// Returns a new transport connected to the responder. The server's
+	/// handshake is sent as soon as the transport is created.
+	#[must_use]
+	pub fn transport(&self) -> LoopbackTransport {
+		self.lock()
+			.readable
+			.push_back(b'!');
+
+		LoopbackTransport {
+			script: self.script.clone(),
+		}
+	}
+
+	/// Returns `true` if every expected command has been sent.
+	#[must_use]
+	pub fn is_done(&self) -> bool {
+		self.lock().expectations.is_empty()
+	}
+
+	/// Panics if any of the expected commands have not been sent.
+	pub fn verify(&self) {
+		let remaining = self.lock().expectations.len();
+		assert!(remaining == 0, "{remaining} expected command(s) were not sent");
+	}
+
+	fn lock(&self) -> MutexGuard<'_, Script> {
+		self.script
+			.lock()
+			.expect("Could not obtain script.")
+	}
+}
+
+impl<'a> Expectation<'a> {
+	/// Replies to the command with the supplied value (e.g., for `Get`,
+	/// `Peek`, `Ping`, or `Version`).
+	pub fn reply(self, value: impl Into<PaperValue>) -> &'a ScriptedResponder {
+		let value: PaperValue = value.into();
+		let value: &[u8] = (&value).into();
+
+		let reply = SheetBuilder::new()
+			.write_bool(true)
+			.write_buf(value)
+			.into_sheet();
+
+		self.reply_bytes(reply.serialize().to_vec())
+	}
+
+	/// Replies to the command with a success response which has no value
+	/// (e.g., for `Set`, `Del`, `Ttl`, or `Wipe`).
+	pub fn reply_ok(self) -> &'a ScriptedResponder {
+		self.reply_bytes(vec![b'!'])
+	}
+
+	/// Replies to a `Has` command.
+	pub fn reply_has(self, has: bool) -> &'a ScriptedResponder {
+		let reply = SheetBuilder::new()
+			.write_bool(true)
+			.write_bool(has)
+			.into_sheet();
+
+		self.reply_bytes(reply.serialize().to_vec())
+	}
+
+	/// Replies to a `Size` command.
+	pub fn reply_size(self, size: u32) -> &'a ScriptedResponder {
+		let reply = SheetBuilder::new()
+			.write_bool(true)
+			.write_u32(size)
+			.into_sheet();
+
+		self.reply_bytes(reply.serialize().to_vec())
+	}
+
+	/// Replies to the command with the supplied error. Only server and
+	/// cache errors can be sent by the server; any other error is replied
+	/// as an internal server error.
+	pub fn reply_error(self, err: PaperClientError) -> &'a ScriptedResponder {
+		let mut reply = vec![b'?'];
+
+		match err {
+			PaperClientError::CacheError(err) => reply.extend([0, err.code()]),
+			PaperClientError::ServerError(err) => reply.push(err.code()),
+			_ => reply.push(1),
+		}
+
+		self.reply_bytes(reply)
+	}
+
+	/// Replies to the command with the supplied raw bytes, which allows
+	/// malformed responses to be scripted.
+	pub fn reply_raw(self, reply: impl Into<Vec<u8>>) -> &'a ScriptedResponder {
+		self.reply_bytes(reply.into())
+	}
+
+	fn reply_bytes(self, reply: Vec<u8>) -> &'a ScriptedResponder {
+		self.responder
+			.lock()
+			.expectations
+			.push_back((self.command, reply));
+
+		self.responder
+	}
+}
+
+impl Script {
+	fn write(&mut self, buf: &[u8]) {
+		self.written.extend_from_slice(buf);
+
+		while !self.written.is_empty() {
+			let Some((command, _)) = self.expectations.front() else {
+				panic!("unexpected command sent: {:?}", self.written);
+			};
+
+			if self.written.len() < command.len() {
+				assert!(
+					command.starts_with(&self.written),
+					"unexpected command sent: {:?}, expected: {command:?}",
+					self.written,
+				);
+
+				return;
+			}
+
+			assert!(
+				self.written.starts_with(command),
+				"unexpected command sent: {:?}, expected: {command:?}",
+				self.written,
+			);
+
+			let (command, reply) = self
+				.expectations
+				.pop_front()
+				.expect("Could not find expectation.");
+
+			self.written.drain(..command.len());
+			self.readable.extend(reply);
+		}
+	}
+
+	fn read(&mut self, buf: &mut [u8]) -> usize {
+		let len = buf.len().min(self.readable.len());
+
+		for (byte, readable) in buf.iter_mut().zip(self.readable.drain(..len)) {
+			*byte = readable;
+		}
+
+		len
+	}
+}
+
+impl LoopbackTransport {
+	fn lock(&self) -> MutexGuard<'_, Script> {
+		self.script
+			.lock()
+			.expect("Could not obtain script.")
+	}
+}
+
+impl Read for LoopbackTransport {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		Ok(self.lock().read(buf))
+	}
+}
+
+impl Write for LoopbackTransport {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.lock().write(buf);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+impl PaperTransport for LoopbackTransport {
+	fn open(_: &str) -> PaperClientResult<Self> {
+		// a loopback transport is only connected to its responder
+		Err(PaperClientError::UnreachableServer)
+	}
+
+	fn set_read_timeout(&mut self, _: Option<Duration>) -> PaperClientResult<()> {
+		Ok(())
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncRead for LoopbackTransport {
+	fn poll_read(
+		self: Pin<&mut Self>,
+		_: &mut Context<'_>,
+		buf: &mut ReadBuf<'_>,
+	) -> Poll<io::Result<()>> {
+		let len = self.lock().read(buf.initialize_unfilled());
+		buf.advance(len);
+
+		Poll::Ready(Ok(()))
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncWrite for LoopbackTransport {
+	fn poll_write(
+		self: Pin<&mut Self>,
+		_: &mut Context<'_>,
+		buf: &[u8],
+	) -> Poll<io::Result<usize>> {
+		self.lock().write(buf);
+		Poll::Ready(Ok(buf.len()))
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Poll::Ready(Ok(()))
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncPaperTransport for LoopbackTransport {
+	async fn open(_: &str) -> PaperClientResult<Self> {
+		Err(PaperClientError::UnreachableServer)
+	}
+}
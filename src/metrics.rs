@@ -0,0 +1,379 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+	time::Duration,
+};
+
+const WAIT_TIME_BOUNDS: [Duration; 5] = [
+	Duration::from_micros(100),
+	Duration::from_millis(1),
+	Duration::from_millis(10),
+	Duration::from_millis(100),
+	Duration::from_secs(1),
+];
+
+/// A snapshot of a pool's checkout and connection metrics.
+#[derive(Debug, Clone)]
+pub struct PoolMetrics {
+	in_use: usize,
+	idle:   usize,
+
+	checkouts:    u64,
+	timeouts:     u64,
+	replacements: u64,
+
+	nowait_errors: u64,
+
+	wait_times:       WaitTimeHistogram,
+	reconnects:       Vec<u64>,
+	client_checkouts: Vec<u64>,
+}
+
+/// A snapshot of the commands sent over a single connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientStats {
+	addr: String,
+
+	commands:   u64,
+	errors:     u64,
+	reconnects: u64,
+	latency:    Duration,
+}
+
+/// A snapshot of the commands sent over each of a pool's connections,
+/// with totals across the pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolStats {
+	connections: Vec<ClientStats>,
+}
+
+/// A histogram of the time spent waiting to check out a pooled client.
+#[derive(Debug, Clone)]
+pub struct WaitTimeHistogram {
+	counts: [u64; WAIT_TIME_BOUNDS.len() + 1],
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct PoolMetricsRecorder {
+	in_use: AtomicUsize,
+
+	checkouts:    AtomicU64,
+	timeouts:     AtomicU64,
+	replacements: AtomicU64,
+
+	nowait_errors: AtomicU64,
+
+	wait_times: [AtomicU64; WAIT_TIME_BOUNDS.len() + 1],
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ConnectionStats {
+	commands:   AtomicU64,
+	errors:     AtomicU64,
+	reconnects: AtomicU64,
+	checkouts:  AtomicU64,
+	latency:    LatencyAverage,
+}
+
+// exponentially weighted moving average of successful request latencies
+// in microseconds
+#[derive(Debug, Default)]
+pub(crate) struct LatencyAverage(AtomicU64);
+
+impl PoolMetrics {
+	/// Returns the number of clients currently checked out of the pool.
+	#[must_use]
+	pub fn in_use(&self) -> usize {
+		self.in_use
+	}
+
+	/// Returns the number of clients not currently checked out of the pool.
+	#[must_use]
+	pub fn idle(&self) -> usize {
+		self.idle
+	}
+
+	/// Returns the total number of successful checkouts.
+	#[must_use]
+	pub fn checkouts(&self) -> u64 {
+		self.checkouts
+	}
+
+	/// Returns the total number of checkouts which timed out before a
+	/// client became available.
+	#[must_use]
+	pub fn timeouts(&self) -> u64 {
+		self.timeouts
+	}
+
+	/// Returns the total number of clients whose connections were replaced
+	/// on checkout after their most recent command exhausted its
+	/// reconnects.
+	#[must_use]
+	pub fn replacements(&self) -> u64 {
+		self.replacements
+	}
+
+	/// Returns the total number of writes issued by the async pool's
+	/// `set_nowait` which failed.
+	#[must_use]
+	pub fn nowait_errors(&self) -> u64 {
+		self.nowait_errors
+	}
+
+	/// Returns the histogram of checkout wait times.
+	#[must_use]
+	pub fn wait_times(&self) -> &WaitTimeHistogram {
+		&self.wait_times
+	}
+
+	/// Returns the number of reconnects performed by each of the pool's
+	/// connections, in pool order.
+	#[must_use]
+	pub fn reconnects(&self) -> &[u64] {
+		&self.reconnects
+	}
+
+	/// Returns the number of checkouts of each of the pool's connections,
+	/// in pool order.
+	#[must_use]
+	pub fn client_checkouts(&self) -> &[u64] {
+		&self.client_checkouts
+	}
+}
+
+impl ClientStats {
+	/// Returns the address of the server the connection is to.
+	#[must_use]
+	pub fn addr(&self) -> &str {
+		&self.addr
+	}
+
+	/// Returns the number of commands sent, counting each retry of a
+	/// command separately.
+	#[must_use]
+	pub fn commands(&self) -> u64 {
+		self.commands
+	}
+
+	/// Returns the number of commands which failed. A cache error (e.g., a
+	/// missing key) is a response rather than a failure, so is not counted.
+	#[must_use]
+	pub fn errors(&self) -> u64 {
+		self.errors
+	}
+
+	/// Returns the number of times the connection was re-established.
+	#[must_use]
+	pub fn reconnects(&self) -> u64 {
+		self.reconnects
+	}
+
+	/// Returns the moving average latency of the successful commands.
+	#[must_use]
+	pub fn latency(&self) -> Duration {
+		self.latency
+	}
+}
+
+impl PoolStats {
+	/// Returns the stats of each of the pool's connections, in pool order
+	/// (the primaries' connections followed by the replicas').
+	#[must_use]
+	pub fn connections(&self) -> &[ClientStats] {
+		&self.connections
+	}
+
+	/// Returns the total number of commands sent across the pool.
+	#[must_use]
+	pub fn commands(&self) -> u64 {
+		self.connections.iter().map(ClientStats::commands).sum()
+	}
+
+	/// Returns the total number of commands which failed across the pool.
+	#[must_use]
+	pub fn errors(&self) -> u64 {
+		self.connections.iter().map(ClientStats::errors).sum()
+	}
+
+	/// Returns the total number of reconnects across the pool.
+	#[must_use]
+	pub fn reconnects(&self) -> u64 {
+		self.connections.iter().map(ClientStats::reconnects).sum()
+	}
+
+	/// Returns the average of the connections' latencies, weighted by the
+	/// number of commands each has sent.
+	#[must_use]
+	pub fn latency(&self) -> Duration {
+		let commands = self.commands();
+
+		if commands == 0 {
+			return Duration::ZERO;
+		}
+
+		let total = self
+			.connections
+			.iter()
+			.map(|stats| stats.latency.as_micros() * u128::from(stats.commands))
+			.sum::<u128>();
+
+		Duration::from_micros((total / u128::from(commands)) as u64)
+	}
+}
+
+impl WaitTimeHistogram {
+	/// Returns the histogram's buckets as `(upper_bound, count)` pairs in
+	/// increasing order. The last bucket has no upper bound.
+	#[must_use]
+	pub fn buckets(&self) -> Vec<(Option<Duration>, u64)> {
+		WAIT_TIME_BOUNDS
+			.iter()
+			.map(|bound| Some(*bound))
+			.chain([None])
+			.zip(self.counts)
+			.collect()
+	}
+
+	/// Returns the total number of recorded wait times.
+	#[must_use]
+	pub fn count(&self) -> u64 {
+		self.counts.iter().sum()
+	}
+}
+
+impl PoolMetricsRecorder {
+	pub fn checkout(&self, wait_time: Duration) {
+		let bucket = WAIT_TIME_BOUNDS
+			.iter()
+			.position(|bound| wait_time <= *bound)
+			.unwrap_or(WAIT_TIME_BOUNDS.len());
+
+		self.in_use.fetch_add(1, Ordering::Relaxed);
+		self.checkouts.fetch_add(1, Ordering::Relaxed);
+		self.wait_times[bucket].fetch_add(1, Ordering::Relaxed);
+	}
+
+	pub fn checkin(&self) {
+		self.in_use.fetch_sub(1, Ordering::Relaxed);
+	}
+
+	pub fn timeout(&self) {
+		self.timeouts.fetch_add(1, Ordering::Relaxed);
+	}
+
+	pub fn replacement(&self) {
+		self.replacements.fetch_add(1, Ordering::Relaxed);
+	}
+
+	#[cfg(feature = "tokio")]
+	pub fn nowait_error(&self) {
+		self.nowait_errors.fetch_add(1, Ordering::Relaxed);
+	}
+
+	pub fn snapshot(&self, stats: &[&ConnectionStats]) -> PoolMetrics {
+		let size = stats.len();
+		let in_use = self.in_use.load(Ordering::Relaxed);
+
+		PoolMetrics {
+			in_use,
+			idle: size.saturating_sub(in_use),
+
+			checkouts: self.checkouts.load(Ordering::Relaxed),
+			timeouts: self.timeouts.load(Ordering::Relaxed),
+			replacements: self.replacements.load(Ordering::Relaxed),
+
+			nowait_errors: self.nowait_errors.load(Ordering::Relaxed),
+
+			wait_times: WaitTimeHistogram {
+				counts: self
+					.wait_times
+					.each_ref()
+					.map(|count| count.load(Ordering::Relaxed)),
+			},
+
+			reconnects:       stats.iter().map(|stats| stats.reconnects()).collect(),
+			client_checkouts: stats.iter().map(|stats| stats.checkouts()).collect(),
+		}
+	}
+}
+
+impl ConnectionStats {
+	pub fn record_command(&self, is_error: bool) {
+		self.commands.fetch_add(1, Ordering::Relaxed);
+
+		if is_error {
+			self.errors.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+
+	pub fn reconnect(&self) {
+		self.reconnects.fetch_add(1, Ordering::Relaxed);
+	}
+
+	pub fn reconnects(&self) -> u64 {
+		self.reconnects.load(Ordering::Relaxed)
+	}
+
+	pub fn record_checkout(&self) {
+		self.checkouts.fetch_add(1, Ordering::Relaxed);
+	}
+
+	pub fn checkouts(&self) -> u64 {
+		self.checkouts.load(Ordering::Relaxed)
+	}
+
+	pub fn record_latency(&self, latency: Duration) {
+		self.latency.record(latency);
+	}
+
+	pub fn latency(&self) -> Duration {
+		self.latency.get()
+	}
+
+	pub fn snapshot(&self, addr: &str) -> ClientStats {
+		ClientStats {
+			addr: addr.to_owned(),
+
+			commands:   self.commands.load(Ordering::Relaxed),
+			errors:     self.errors.load(Ordering::Relaxed),
+			reconnects: self.reconnects(),
+			latency:    self.latency(),
+		}
+	}
+}
+
+impl FromIterator<ClientStats> for PoolStats {
+	fn from_iter<I: IntoIterator<Item = ClientStats>>(iter: I) -> Self {
+		PoolStats {
+			connections: iter.into_iter().collect(),
+		}
+	}
+}
+
+impl LatencyAverage {
+	pub fn record(&self, latency: Duration) {
+		let sample = latency.as_micros().min(u64::MAX as u128) as u64;
+
+		let _ = self
+			.0
+			.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |average| {
+				if average == 0 {
+					return Some(sample.max(1));
+				}
+
+				// weight the new sample at 1/5
+				Some((average * 4 + sample) / 5)
+			});
+	}
+
+	pub fn get(&self) -> Duration {
+		Duration::from_micros(self.0.load(Ordering::Relaxed))
+	}
+}
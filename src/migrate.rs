@@ -0,0 +1,231 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	fmt::{self, Debug},
+	time::{Duration, Instant},
+};
+
+#[cfg(feature = "tokio")]
+use tokio::time;
+
+#[cfg(feature = "tokio")]
+use crate::{async_client::AsyncPaperClient, transport::AsyncPaperTransport};
+use crate::{
+	arg::AsPaperKey,
+	client::PaperClient,
+	error::{PaperCacheError, PaperClientError, PaperClientResult},
+	transport::PaperTransport,
+	value::PaperValue,
+};
+
+type ProgressCallback = Box<dyn FnMut(&MigrateProgress) + Send>;
+
+/// Configures a migration performed by [`migrate`] or `migrate_async`.
+///
+/// # Examples
+/// ```
+/// use paper_client::MigrateOptions;
+///
+/// let options = MigrateOptions::default()
+///     .ttl(Some(3600))
+///     .rate_limit(Some(1000))
+///     .on_progress(|progress| println!("{} copied", progress.copied()));
+/// ```
+#[derive(Default)]
+pub struct MigrateOptions {
+	ttl:        Option<u32>,
+	rate_limit: Option<u32>,
+	progress:   Option<ProgressCallback>,
+}
+
+/// The progress of a migration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MigrateProgress {
+	copied:  u64,
+	missing: u64,
+}
+
+impl MigrateOptions {
+	/// Sets the ttl (in seconds) of the copied keys. The server does not
+	/// report a key's remaining ttl, so the same ttl is applied to every
+	/// key. If `None`, the copied keys do not expire. Defaults to `None`.
+	#[must_use]
+	pub fn ttl(mut self, ttl: Option<u32>) -> Self {
+		self.ttl = ttl;
+		self
+	}
+
+	/// Sets the maximum number of keys copied per second. If `None`, keys
+	/// are copied as fast as possible. Defaults to `None`.
+	#[must_use]
+	pub fn rate_limit(mut self, keys_per_sec: Option<u32>) -> Self {
+		self.rate_limit = keys_per_sec.filter(|rate| *rate > 0);
+		self
+	}
+
+	/// Sets the callback which receives the migration's progress after
+	/// each key.
+	#[must_use]
+	pub fn on_progress(mut self, callback: impl FnMut(&MigrateProgress) + Send + 'static) -> Self {
+		self.progress = Some(Box::new(callback));
+		self
+	}
+
+	// the time at which the key at the supplied index may be copied
+	fn start_time(&self, start: Instant, index: u32) -> Option<Instant> {
+		self.rate_limit
+			.map(|rate| start + Duration::from_secs(1) * index / rate)
+	}
+
+	fn report(&mut self, progress: &MigrateProgress) {
+		if let Some(callback) = &mut self.progress {
+			callback(progress);
+		}
+	}
+}
+
+impl MigrateProgress {
+	/// Returns the number of keys copied to the destination.
+	#[must_use]
+	pub fn copied(&self) -> u64 {
+		self.copied
+	}
+
+	/// Returns the number of keys which were not found in the source (e.g.,
+	/// because they expired or were evicted) and so were not copied.
+	#[must_use]
+	pub fn missing(&self) -> u64 {
+		self.missing
+	}
+
+	fn record(&mut self, result: PaperClientResult<()>) -> PaperClientResult<()> {
+		match result {
+			Ok(_) => self.copied += 1,
+			Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => self.missing += 1,
+			Err(err) => return Err(err),
+		}
+
+		Ok(())
+	}
+}
+
+/// Copies the supplied keys from the source client's cache to the
+/// destination client's cache, as the building block of a live migration
+/// between clusters. The server cannot enumerate its keys, so the keys to
+/// copy are supplied by the caller. Keys are read with `peek` so that the
+/// migration does not affect the source's eviction policy.
+///
+/// Keys which are not found in the source are skipped. The migration stops
+/// at the first other error, which is returned.
+///
+/// # Examples
+/// ```
+/// use paper_client::{MigrateOptions, PaperClient, migrate};
+///
+/// let mut source = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+/// let mut dest = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+///
+/// let options = MigrateOptions::default().rate_limit(Some(1000));
+///
+/// match migrate(&mut source, &mut dest, ["key1", "key2"], options) {
+///     Ok(progress) => println!("{} copied", progress.copied()),
+///     Err(err) => println!("{err:?}"),
+/// }
+/// ```
+pub fn migrate<S, D, K>(
+	source: &mut PaperClient<S>,
+	dest: &mut PaperClient<D>,
+	keys: impl IntoIterator<Item = K>,
+	mut options: MigrateOptions,
+) -> PaperClientResult<MigrateProgress>
+where
+	S: PaperTransport,
+	D: PaperTransport,
+	K: AsPaperKey,
+{
+	let clock = source.config().clock.clone();
+
+	let start = clock.now();
+	let mut progress = MigrateProgress::default();
+
+	for (index, key) in (0u32..).zip(keys) {
+		if let Some(time) = options.start_time(start, index) {
+			clock.sleep(time.saturating_duration_since(clock.now()));
+		}
+
+		let key = key.as_paper_key();
+
+		let result = source
+			.peek(key)
+			.and_then(|value: PaperValue| dest.set(key, value, options.ttl));
+
+		progress.record(result)?;
+		options.report(&progress);
+	}
+
+	Ok(progress)
+}
+
+/// The async counterpart of [`migrate`].
+///
+/// # Examples
+/// ```ignore
+/// use paper_client::{AsyncPaperClient, MigrateOptions, migrate_async};
+///
+/// let mut source = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+/// let mut dest = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+///
+/// let options = MigrateOptions::default().rate_limit(Some(1000));
+///
+/// match migrate_async(&mut source, &mut dest, ["key1", "key2"], options).await {
+///     Ok(progress) => println!("{} copied", progress.copied()),
+///     Err(err) => println!("{err:?}"),
+/// }
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn migrate_async<S, D, K>(
+	source: &mut AsyncPaperClient<S>,
+	dest: &mut AsyncPaperClient<D>,
+	keys: impl IntoIterator<Item = K>,
+	mut options: MigrateOptions,
+) -> PaperClientResult<MigrateProgress>
+where
+	S: AsyncPaperTransport,
+	D: AsyncPaperTransport,
+	K: AsPaperKey,
+{
+	let start = Instant::now();
+	let mut progress = MigrateProgress::default();
+
+	for (index, key) in (0u32..).zip(keys) {
+		if let Some(time) = options.start_time(start, index) {
+			time::sleep_until(time.into()).await;
+		}
+
+		let key = key.as_paper_key();
+
+		let result = match source.peek(key).await {
+			Ok(value) => dest.set(key, value, options.ttl).await,
+			Err(err) => Err(err),
+		};
+
+		progress.record(result)?;
+		options.report(&progress);
+	}
+
+	Ok(progress)
+}
+
+impl Debug for MigrateOptions {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("MigrateOptions")
+			.field("ttl", &self.ttl)
+			.field("rate_limit", &self.rate_limit)
+			.finish_non_exhaustive()
+	}
+}
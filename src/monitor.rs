@@ -0,0 +1,129 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::{
+	observer::{PaperEvent, PolicyChangeEvent, ThresholdEvent, ThresholdMetric},
+	policy::PaperPolicy,
+	status::Status,
+};
+
+/// The thresholds on a cache's status monitored by
+/// `AsyncPaperPool::with_status_monitor`. A metric without a threshold is
+/// not monitored. A change to the cache's active eviction policy is always
+/// reported.
+///
+/// # Examples
+/// ```
+/// use paper_client::StatusThresholds;
+///
+/// let thresholds = StatusThresholds::default()
+///     .miss_ratio(0.2)
+///     .used_size(900 * 1024 * 1024);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StatusThresholds {
+	miss_ratio: Option<f64>,
+	used_size:  Option<u64>,
+}
+
+/// The metrics of a single node which currently exceed their thresholds,
+/// and the node's most recently seen policy.
+#[derive(Debug, Default)]
+pub(crate) struct MonitorState {
+	miss_ratio: bool,
+	used_size:  bool,
+	policy:     Option<PaperPolicy>,
+}
+
+impl StatusThresholds {
+	/// Sets the miss ratio above which the cache is reported.
+	#[must_use]
+	pub fn miss_ratio(mut self, miss_ratio: f64) -> Self {
+		self.miss_ratio = Some(miss_ratio);
+		self
+	}
+
+	/// Sets the used size (in bytes) above which the cache is reported.
+	#[must_use]
+	pub fn used_size(mut self, used_size: u64) -> Self {
+		self.used_size = Some(used_size);
+		self
+	}
+}
+
+impl MonitorState {
+	/// Compares the supplied status against the thresholds and the previous
+	/// status, and returns an event for each metric which has crossed its
+	/// threshold and for a change to the active policy.
+	pub(crate) fn update(
+		&mut self,
+		addr: &str,
+		thresholds: &StatusThresholds,
+		status: &Status,
+	) -> Vec<PaperEvent> {
+		let mut events = Vec::new();
+
+		let current = status.policy();
+
+		// the first status only establishes the policy
+		if let Some(previous) = self.policy.replace(current.clone()) && previous != *current {
+			events.push(PaperEvent::PolicyChange(PolicyChangeEvent::new(
+				addr,
+				previous,
+				current.clone(),
+				status.is_auto_policy(),
+			)));
+		}
+
+		if let Some(threshold) = thresholds.miss_ratio {
+			let value = status.miss_ratio();
+
+			if let Some(event) = crossing(
+				&mut self.miss_ratio,
+				addr,
+				ThresholdMetric::MissRatio,
+				value,
+				threshold,
+			) {
+				events.push(PaperEvent::Threshold(event));
+			}
+		}
+
+		if let Some(threshold) = thresholds.used_size {
+			let value = status.used_size() as f64;
+
+			if let Some(event) = crossing(
+				&mut self.used_size,
+				addr,
+				ThresholdMetric::UsedSize,
+				value,
+				threshold as f64,
+			) {
+				events.push(PaperEvent::Threshold(event));
+			}
+		}
+
+		events
+	}
+}
+
+fn crossing(
+	is_exceeded: &mut bool,
+	addr: &str,
+	metric: ThresholdMetric,
+	value: f64,
+	threshold: f64,
+) -> Option<ThresholdEvent> {
+	let exceeds = value > threshold;
+
+	if exceeds == *is_exceeded {
+		return None;
+	}
+
+	*is_exceeded = exceeds;
+	Some(ThresholdEvent::new(addr, metric, value, threshold, exceeds))
+}
@@ -0,0 +1,373 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	collections::{HashMap, HashSet},
+	net::TcpStream,
+};
+
+#[cfg(feature = "tokio")]
+use crate::{async_client::AsyncPaperClient, transport::AsyncPaperTransport};
+use crate::{
+	arg::{AsPaperKey, AsPaperTtl},
+	client::PaperClient,
+	error::{PaperCacheError, PaperClientError, PaperClientResult},
+	tenant::TENANT_DELIMITER,
+	transport::PaperTransport,
+	value::PaperValue,
+};
+
+/// The suffix of the key which holds the name of an alias's current
+/// namespace, so that the alias `users` is switched by setting
+/// `users#version`.
+pub const NAMESPACE_VERSION_SUFFIX: &str = "#version";
+
+/// The suffix of the key which holds the keys loaded into a namespace,
+/// from which they are deleted once the namespace is swapped out.
+pub const NAMESPACE_KEYS_SUFFIX: &str = "#keys";
+
+/// A client which reads an alias (e.g., `users`) through a version key
+/// naming its current namespace, so that a cache can be rebuilt without
+/// readers seeing it half loaded: the new namespace is loaded alongside the
+/// current one with `load`, and `swap_namespace` then switches the alias to
+/// it with a single `set` of the version key before deleting the old one.
+///
+/// The key `42` of the namespace `v2` of the alias `users` is stored as
+/// `users/v2/42`. Each `get` first reads the version key, and a `get` which
+/// misses because its namespace was swapped out and deleted in the meantime
+/// is retried in the new namespace.
+///
+/// Any number of clients may read an alias, but only one should rebuild it
+/// at a time. The keys loaded by a client are recorded in the cache when it
+/// swaps their namespace in, so that a later swap by any client can delete
+/// them. If that record has been evicted, the namespace's keys are instead
+/// left to expire or be evicted.
+///
+/// # Examples
+/// ```
+/// use paper_client::{NamespaceClient, PaperClient};
+///
+/// let client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+/// let mut client = NamespaceClient::new(client, "users");
+///
+/// let _ = client.load("v2", "42", "value", None);
+/// let _ = client.swap_namespace("v1", "v2");
+///
+/// let _ = client.get("42");
+/// ```
+#[derive(Debug)]
+pub struct NamespaceClient<T = TcpStream> {
+	client: PaperClient<T>,
+	alias:  String,
+
+	loaded: HashMap<String, HashSet<String>>,
+}
+
+/// The async counterpart of `NamespaceClient`.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncNamespaceClient<T = tokio::net::TcpStream> {
+	client: AsyncPaperClient<T>,
+	alias:  String,
+
+	loaded: HashMap<String, HashSet<String>>,
+}
+
+impl<T: PaperTransport> NamespaceClient<T> {
+	/// Wraps the supplied client, reading and rebuilding the supplied alias.
+	#[must_use]
+	pub fn new(client: PaperClient<T>, alias: impl Into<String>) -> Self {
+		NamespaceClient {
+			client,
+			alias: alias.into(),
+
+			loaded: HashMap::new(),
+		}
+	}
+
+	/// Returns the alias's current namespace. If no namespace has been
+	/// swapped in, the response is `KeyNotFound`.
+	pub fn namespace(&mut self) -> PaperClientResult<String> {
+		self.client
+			.get(version_key(&self.alias))?
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidConversion)
+	}
+
+	/// Gets the value of the supplied key from the alias's current
+	/// namespace. See `PaperClient::get`.
+	pub fn get(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = key.as_paper_key();
+		let namespace = self.namespace()?;
+
+		match self.client.get(data_key(&self.alias, &namespace, key)) {
+			Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => {
+				let current = self.namespace()?;
+
+				match current == namespace {
+					true => Err(PaperCacheError::KeyNotFound.into()),
+					false => self.client.get(data_key(&self.alias, &current, key)),
+				}
+			},
+
+			result => result,
+		}
+	}
+
+	/// Sets the supplied key, value, and ttl to the supplied namespace,
+	/// which is not read until it is swapped in. See `PaperClient::set`.
+	pub fn load(
+		&mut self,
+		namespace: &str,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+
+		self.client.set(data_key(&self.alias, namespace, key), value, ttl)?;
+
+		self.loaded
+			.entry(namespace.to_owned())
+			.or_default()
+			.insert(key.to_owned());
+
+		Ok(())
+	}
+
+	/// Switches the alias from the old namespace to the new one, then
+	/// deletes the old namespace's keys. If the alias's current namespace is
+	/// not the old one, the response is `NamespaceMismatch` and nothing is
+	/// changed. If no namespace has been swapped in yet, the new one is
+	/// swapped in regardless.
+	pub fn swap_namespace(&mut self, old_ns: &str, new_ns: &str) -> PaperClientResult<()> {
+		match self.namespace() {
+			Ok(current) if current != old_ns => return Err(PaperClientError::NamespaceMismatch),
+			Ok(_) | Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => {},
+			Err(err) => return Err(err),
+		}
+
+		let mut keys = self.keys(new_ns)?;
+		keys.extend(self.loaded.remove(new_ns).unwrap_or_default());
+
+		self.client.set(keys_key(&self.alias, new_ns), encode_keys(&keys), None)?;
+		self.client.set(version_key(&self.alias), new_ns, None)?;
+
+		if old_ns == new_ns {
+			return Ok(());
+		}
+
+		for key in self.keys(old_ns)? {
+			ignore_missing(self.client.del(data_key(&self.alias, old_ns, &key)))?;
+		}
+
+		ignore_missing(self.client.del(keys_key(&self.alias, old_ns)))
+	}
+
+	/// Returns a reference to the underlying client.
+	#[must_use]
+	pub fn inner(&self) -> &PaperClient<T> {
+		&self.client
+	}
+
+	/// Returns a mutable reference to the underlying client.
+	#[must_use]
+	pub fn inner_mut(&mut self) -> &mut PaperClient<T> {
+		&mut self.client
+	}
+
+	/// Unwraps the underlying client.
+	#[must_use]
+	pub fn into_inner(self) -> PaperClient<T> {
+		self.client
+	}
+
+	// the keys recorded for the supplied namespace when it was swapped in
+	fn keys(&mut self, namespace: &str) -> PaperClientResult<HashSet<String>> {
+		match self.client.get(keys_key(&self.alias, namespace)) {
+			Ok(value) => Ok(decode_keys(&value)),
+			Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => Ok(HashSet::new()),
+			Err(err) => Err(err),
+		}
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl<T: AsyncPaperTransport> AsyncNamespaceClient<T> {
+	/// Wraps the supplied client, reading and rebuilding the supplied alias.
+	#[must_use]
+	pub fn new(client: AsyncPaperClient<T>, alias: impl Into<String>) -> Self {
+		AsyncNamespaceClient {
+			client,
+			alias: alias.into(),
+
+			loaded: HashMap::new(),
+		}
+	}
+
+	/// Returns the alias's current namespace. See
+	/// `NamespaceClient::namespace`.
+	pub async fn namespace(&mut self) -> PaperClientResult<String> {
+		self.client
+			.get(version_key(&self.alias))
+			.await?
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidConversion)
+	}
+
+	/// Gets the value of the supplied key from the alias's current
+	/// namespace. See `NamespaceClient::get`.
+	pub async fn get(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = key.as_paper_key();
+		let namespace = self.namespace().await?;
+
+		match self.client.get(data_key(&self.alias, &namespace, key)).await {
+			Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => {
+				let current = self.namespace().await?;
+
+				match current == namespace {
+					true => Err(PaperCacheError::KeyNotFound.into()),
+					false => self.client.get(data_key(&self.alias, &current, key)).await,
+				}
+			},
+
+			result => result,
+		}
+	}
+
+	/// Sets the supplied key, value, and ttl to the supplied namespace. See
+	/// `NamespaceClient::load`.
+	pub async fn load(
+		&mut self,
+		namespace: &str,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+
+		self.client
+			.set(data_key(&self.alias, namespace, key), value, ttl)
+			.await?;
+
+		self.loaded
+			.entry(namespace.to_owned())
+			.or_default()
+			.insert(key.to_owned());
+
+		Ok(())
+	}
+
+	/// Switches the alias from the old namespace to the new one, then
+	/// deletes the old namespace's keys. See
+	/// `NamespaceClient::swap_namespace`.
+	pub async fn swap_namespace(&mut self, old_ns: &str, new_ns: &str) -> PaperClientResult<()> {
+		match self.namespace().await {
+			Ok(current) if current != old_ns => return Err(PaperClientError::NamespaceMismatch),
+			Ok(_) | Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => {},
+			Err(err) => return Err(err),
+		}
+
+		let mut keys = self.keys(new_ns).await?;
+		keys.extend(self.loaded.remove(new_ns).unwrap_or_default());
+
+		self.client
+			.set(keys_key(&self.alias, new_ns), encode_keys(&keys), None)
+			.await?;
+
+		self.client
+			.set(version_key(&self.alias), new_ns, None)
+			.await?;
+
+		if old_ns == new_ns {
+			return Ok(());
+		}
+
+		for key in self.keys(old_ns).await? {
+			ignore_missing(self.client.del(data_key(&self.alias, old_ns, &key)).await)?;
+		}
+
+		ignore_missing(self.client.del(keys_key(&self.alias, old_ns)).await)
+	}
+
+	/// Returns a reference to the underlying client.
+	#[must_use]
+	pub fn inner(&self) -> &AsyncPaperClient<T> {
+		&self.client
+	}
+
+	/// Returns a mutable reference to the underlying client.
+	#[must_use]
+	pub fn inner_mut(&mut self) -> &mut AsyncPaperClient<T> {
+		&mut self.client
+	}
+
+	/// Unwraps the underlying client.
+	#[must_use]
+	pub fn into_inner(self) -> AsyncPaperClient<T> {
+		self.client
+	}
+
+	async fn keys(&mut self, namespace: &str) -> PaperClientResult<HashSet<String>> {
+		match self.client.get(keys_key(&self.alias, namespace)).await {
+			Ok(value) => Ok(decode_keys(&value)),
+			Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => Ok(HashSet::new()),
+			Err(err) => Err(err),
+		}
+	}
+}
+
+fn version_key(alias: &str) -> String {
+	format!("{alias}{NAMESPACE_VERSION_SUFFIX}")
+}
+
+fn data_key(alias: &str, namespace: &str, key: &str) -> String {
+	format!("{alias}{TENANT_DELIMITER}{namespace}{TENANT_DELIMITER}{key}")
+}
+
+fn keys_key(alias: &str, namespace: &str) -> String {
+	format!("{alias}{TENANT_DELIMITER}{namespace}{NAMESPACE_KEYS_SUFFIX}")
+}
+
+fn ignore_missing(result: PaperClientResult<()>) -> PaperClientResult<()> {
+	match result {
+		Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => Ok(()),
+		result => result,
+	}
+}
+
+// each key is prefixed by its length as a u32 (LE)
+fn encode_keys(keys: &HashSet<String>) -> Vec<u8> {
+	let mut buf = Vec::new();
+
+	for key in keys {
+		buf.extend_from_slice(&(key.len() as u32).to_le_bytes());
+		buf.extend_from_slice(key.as_bytes());
+	}
+
+	buf
+}
+
+fn decode_keys(mut buf: &[u8]) -> HashSet<String> {
+	let mut keys = HashSet::new();
+
+	while let Some((len, rest)) = buf.split_first_chunk::<4>() {
+		let len = u32::from_le_bytes(*len) as usize;
+
+		let Some((key, rest)) = rest.split_at_checked(len) else {
+			break;
+		};
+
+		if let Ok(key) = std::str::from_utf8(key) {
+			keys.insert(key.to_owned());
+		}
+
+		buf = rest;
+	}
+
+	keys
+}
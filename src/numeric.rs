@@ -0,0 +1,83 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::str::{self, FromStr};
+
+use crate::{
+	error::{PaperClientError, PaperClientResult},
+	value::PaperValue,
+};
+
+/// A number which can be stored with a client's `set_num` and read with its
+/// `get_num`. A number is stored as its decimal text (e.g., `42`, `-7`, or
+/// `0.25`) so that clients in other languages can read and write it with
+/// their standard number parsing.
+///
+/// A float is written with the fewest digits which read back as the same
+/// float, and never in exponent notation. A float which is not finite has
+/// no portable text, so it is rejected with `InvalidValue`. A value which
+/// is not a number of the requested type is read as `InvalidNumber`.
+///
+/// # Examples
+/// ```
+/// use paper_client::{NumericValue, PaperValue};
+///
+/// assert_eq!(0.1f64.to_paper_value().unwrap(), "0.1");
+/// assert_eq!(u64::from_paper_value(&PaperValue::from("42")).unwrap(), 42);
+/// ```
+pub trait NumericValue: Sized {
+	/// Returns the number's stored representation.
+	fn to_paper_value(&self) -> PaperClientResult<PaperValue>;
+
+	/// Parses a number from its stored representation.
+	fn from_paper_value(value: &PaperValue) -> PaperClientResult<Self>;
+}
+
+macro_rules! impl_integer {
+	($($ty:ty),*) => {
+		$(
+			impl NumericValue for $ty {
+				fn to_paper_value(&self) -> PaperClientResult<PaperValue> {
+					Ok(PaperValue::from(self.to_string()))
+				}
+
+				fn from_paper_value(value: &PaperValue) -> PaperClientResult<Self> {
+					parse(value)
+				}
+			}
+		)*
+	};
+}
+
+macro_rules! impl_float {
+	($($ty:ty),*) => {
+		$(
+			impl NumericValue for $ty {
+				fn to_paper_value(&self) -> PaperClientResult<PaperValue> {
+					match self.is_finite() {
+						true => Ok(PaperValue::from(self.to_string())),
+						false => Err(PaperClientError::InvalidValue),
+					}
+				}
+
+				fn from_paper_value(value: &PaperValue) -> PaperClientResult<Self> {
+					parse(value)
+				}
+			}
+		)*
+	};
+}
+
+impl_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_float!(f32, f64);
+
+fn parse<T: FromStr>(value: &PaperValue) -> PaperClientResult<T> {
+	str::from_utf8(value)
+		.ok()
+		.and_then(|text| text.parse().ok())
+		.ok_or(PaperClientError::InvalidNumber)
+}
@@ -0,0 +1,440 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	fmt::{self, Debug, Display},
+	time::SystemTime,
+};
+
+use crate::{addr::DisplayAddr, command::Command, policy::PaperPolicy};
+
+/// Receives the events emitted by a client. An observer is attached to a
+/// client (or to every client of a pool) through its `PaperClientConfig`.
+///
+/// Events are delivered synchronously on the thread (or task) which issued
+/// the command, so an observer should not block. Events raised by a pool's
+/// background work are instead delivered on the pool's background task
+/// (e.g., an `AsyncPaperPool`'s failback probes and status monitor) or on
+/// the thread which called `PaperPool::probe_ejected`.
+///
+/// # Examples
+/// ```
+/// use paper_client::{PaperEvent, PaperObserver};
+///
+/// #[derive(Debug)]
+/// struct AuditLog;
+///
+/// impl PaperObserver for AuditLog {
+///     fn on_event(&self, event: &PaperEvent) {
+///         if let PaperEvent::Audit(event) = event {
+///             println!("{event}");
+///         }
+///     }
+/// }
+/// ```
+pub trait PaperObserver: Debug + Send + Sync {
+	/// Called once for each event emitted by the client.
+	fn on_event(&self, event: &PaperEvent);
+}
+
+/// An observer which writes each event to stderr.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogObserver;
+
+/// An event emitted by a client to its observer.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum PaperEvent {
+	/// An administrative command was executed.
+	Audit(AuditEvent),
+
+	/// A monitored status metric crossed its threshold.
+	Threshold(ThresholdEvent),
+
+	/// A monitored cache's active eviction policy changed.
+	PolicyChange(PolicyChangeEvent),
+
+	/// A pool's node was ejected from routing or restored to it.
+	Failover(FailoverEvent),
+}
+
+/// The record of an administrative command (`wipe`, `resize`, or
+/// `policy`), emitted after the server has responded to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEvent {
+	addr:    String,
+	command: AuditCommand,
+	reason:  Option<String>,
+
+	is_ok:     bool,
+	is_forced: bool,
+	timestamp: SystemTime,
+}
+
+/// An administrative command recorded by an `AuditEvent`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuditCommand {
+	Wipe,
+	Resize(u64),
+	Policy(PaperPolicy),
+}
+
+/// The record of a status metric crossing its threshold, emitted by a
+/// pool's status monitor when the metric first exceeds the threshold and
+/// again when it recovers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThresholdEvent {
+	addr:      String,
+	metric:    ThresholdMetric,
+	value:     f64,
+	threshold: f64,
+
+	is_exceeded: bool,
+	timestamp:   SystemTime,
+}
+
+/// The record of a change to a cache's active eviction policy, emitted by a
+/// pool's status monitor. Under `PaperPolicy::Auto`, the server switches
+/// between its configured policies on its own, so this is the only way to
+/// learn that it has done so.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyChangeEvent {
+	addr:     String,
+	previous: PaperPolicy,
+	current:  PaperPolicy,
+
+	is_auto_policy: bool,
+	timestamp:      SystemTime,
+}
+
+/// The record of a pool's node being ejected from routing (after which its
+/// keys fail over to the pool's other nodes) or restored to it once it has
+/// responded to enough consecutive probes, as configured by the pool's
+/// `EjectionPolicy`. The event is emitted on the thread (or task) whose
+/// checkout ejected or probed the node, on the thread which called
+/// `PaperPool::probe_ejected`, or, for an `AsyncPaperPool`, on its
+/// background failback task.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailoverEvent {
+	addr: String,
+
+	is_ejected: bool,
+	timestamp:  SystemTime,
+}
+
+/// A status metric monitored by a `ThresholdEvent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThresholdMetric {
+	MissRatio,
+	UsedSize,
+}
+
+impl PaperObserver for LogObserver {
+	fn on_event(&self, event: &PaperEvent) {
+		eprintln!("{event}");
+	}
+}
+
+impl Display for PaperEvent {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			PaperEvent::Audit(event) => write!(f, "{event}"),
+			PaperEvent::Threshold(event) => write!(f, "{event}"),
+			PaperEvent::PolicyChange(event) => write!(f, "{event}"),
+			PaperEvent::Failover(event) => write!(f, "{event}"),
+		}
+	}
+}
+
+impl AuditEvent {
+	pub(crate) fn new(
+		addr: &str,
+		command: AuditCommand,
+		reason: Option<&str>,
+		is_ok: bool,
+		is_forced: bool,
+	) -> Self {
+		AuditEvent {
+			addr: addr.to_owned(),
+			command,
+			reason: reason.map(str::to_owned),
+
+			is_ok,
+			is_forced,
+			timestamp: SystemTime::now(),
+		}
+	}
+
+	/// Returns the address of the server the command was sent to.
+	#[must_use]
+	pub fn addr(&self) -> &str {
+		&self.addr
+	}
+
+	/// Returns the command which was executed.
+	#[must_use]
+	pub fn command(&self) -> &AuditCommand {
+		&self.command
+	}
+
+	/// Returns the reason supplied by the caller, if any.
+	#[must_use]
+	pub fn reason(&self) -> Option<&str> {
+		self.reason.as_deref()
+	}
+
+	/// Returns `true` if the server executed the command successfully.
+	#[must_use]
+	pub fn is_ok(&self) -> bool {
+		self.is_ok
+	}
+
+	/// Returns `true` if the command was forced past the client's
+	/// `Guardrails` (e.g., with `force_resize`).
+	#[must_use]
+	pub fn is_forced(&self) -> bool {
+		self.is_forced
+	}
+
+	/// Returns the time at which the server responded to the command.
+	#[must_use]
+	pub fn timestamp(&self) -> SystemTime {
+		self.timestamp
+	}
+}
+
+impl Display for AuditEvent {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let outcome = match (self.is_ok, self.is_forced) {
+			(true, false) => "ok",
+			(true, true) => "ok, forced",
+			(false, false) => "failed",
+			(false, true) => "failed, forced",
+		};
+
+		write!(f, "audit: {} on {} ({outcome})", self.command, DisplayAddr(&self.addr))?;
+
+		match &self.reason {
+			Some(reason) => write!(f, ", reason: {reason:?}"),
+			None => Ok(()),
+		}
+	}
+}
+
+impl ThresholdEvent {
+	#[cfg(feature = "tokio")]
+	pub(crate) fn new(
+		addr: &str,
+		metric: ThresholdMetric,
+		value: f64,
+		threshold: f64,
+		is_exceeded: bool,
+	) -> Self {
+		ThresholdEvent {
+			addr: addr.to_owned(),
+			metric,
+			value,
+			threshold,
+
+			is_exceeded,
+			timestamp: SystemTime::now(),
+		}
+	}
+
+	/// Returns the address of the server whose status was monitored.
+	#[must_use]
+	pub fn addr(&self) -> &str {
+		&self.addr
+	}
+
+	/// Returns the metric which crossed its threshold.
+	#[must_use]
+	pub fn metric(&self) -> ThresholdMetric {
+		self.metric
+	}
+
+	/// Returns the metric's value (the miss ratio, or the used size in
+	/// bytes).
+	#[must_use]
+	pub fn value(&self) -> f64 {
+		self.value
+	}
+
+	/// Returns the metric's threshold.
+	#[must_use]
+	pub fn threshold(&self) -> f64 {
+		self.threshold
+	}
+
+	/// Returns `true` if the metric has exceeded its threshold, or `false`
+	/// if it has recovered.
+	#[must_use]
+	pub fn is_exceeded(&self) -> bool {
+		self.is_exceeded
+	}
+
+	/// Returns the time at which the status was received.
+	#[must_use]
+	pub fn timestamp(&self) -> SystemTime {
+		self.timestamp
+	}
+}
+
+impl Display for ThresholdEvent {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let outcome = match self.is_exceeded {
+			true => "exceeded",
+			false => "recovered",
+		};
+
+		write!(
+			f,
+			"threshold: {} on {} {outcome} ({} against {})",
+			self.metric,
+			DisplayAddr(&self.addr),
+			self.value,
+			self.threshold,
+		)
+	}
+}
+
+impl PolicyChangeEvent {
+	#[cfg(feature = "tokio")]
+	pub(crate) fn new(
+		addr: &str,
+		previous: PaperPolicy,
+		current: PaperPolicy,
+		is_auto_policy: bool,
+	) -> Self {
+		PolicyChangeEvent {
+			addr: addr.to_owned(),
+			previous,
+			current,
+
+			is_auto_policy,
+			timestamp: SystemTime::now(),
+		}
+	}
+
+	/// Returns the address of the server whose status was monitored.
+	#[must_use]
+	pub fn addr(&self) -> &str {
+		&self.addr
+	}
+
+	/// Returns the policy which was active before the change.
+	#[must_use]
+	pub fn previous(&self) -> &PaperPolicy {
+		&self.previous
+	}
+
+	/// Returns the policy which is now active.
+	#[must_use]
+	pub fn current(&self) -> &PaperPolicy {
+		&self.current
+	}
+
+	/// Returns `true` if the cache is configured to the `PaperPolicy::Auto`
+	/// eviction policy (i.e., the server changed the policy on its own).
+	#[must_use]
+	pub fn is_auto_policy(&self) -> bool {
+		self.is_auto_policy
+	}
+
+	/// Returns the time at which the status was received.
+	#[must_use]
+	pub fn timestamp(&self) -> SystemTime {
+		self.timestamp
+	}
+}
+
+impl Display for PolicyChangeEvent {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"policy: {} changed from {} to {}",
+			DisplayAddr(&self.addr),
+			self.previous,
+			self.current,
+		)?;
+
+		match self.is_auto_policy {
+			true => write!(f, " (auto)"),
+			false => Ok(()),
+		}
+	}
+}
+
+impl FailoverEvent {
+	pub(crate) fn new(addr: &str, is_ejected: bool) -> Self {
+		FailoverEvent {
+			addr: addr.to_owned(),
+
+			is_ejected,
+			timestamp: SystemTime::now(),
+		}
+	}
+
+	/// Returns the address of the node.
+	#[must_use]
+	pub fn addr(&self) -> &str {
+		&self.addr
+	}
+
+	/// Returns `true` if the node was ejected, or `false` if it was
+	/// restored.
+	#[must_use]
+	pub fn is_ejected(&self) -> bool {
+		self.is_ejected
+	}
+
+	/// Returns the time at which the node was ejected or restored.
+	#[must_use]
+	pub fn timestamp(&self) -> SystemTime {
+		self.timestamp
+	}
+}
+
+impl Display for FailoverEvent {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let outcome = match self.is_ejected {
+			true => "ejected",
+			false => "restored",
+		};
+
+		write!(f, "failover: {} {outcome}", DisplayAddr(&self.addr))
+	}
+}
+
+impl Display for ThresholdMetric {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ThresholdMetric::MissRatio => write!(f, "miss ratio"),
+			ThresholdMetric::UsedSize => write!(f, "used size"),
+		}
+	}
+}
+
+impl AuditCommand {
+	pub(crate) fn to_command(&self) -> Command<'static> {
+		match self {
+			AuditCommand::Wipe => Command::Wipe,
+			AuditCommand::Resize(size) => Command::Resize(*size),
+			AuditCommand::Policy(policy) => Command::Policy(policy.clone()),
+		}
+	}
+}
+
+impl Display for AuditCommand {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			AuditCommand::Wipe => write!(f, "wipe"),
+			AuditCommand::Resize(size) => write!(f, "resize to {size}"),
+			AuditCommand::Policy(policy) => write!(f, "policy to {policy}"),
+		}
+	}
+}
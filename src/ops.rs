@@ -0,0 +1,231 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{fmt::Debug, sync::Arc};
+
+use crate::{
+	auth::AuthTokenProvider,
+	client::PaperClient,
+	config::PaperClientConfig,
+	error::PaperClientResult,
+	instrumented::InstrumentedClient,
+	status::Status,
+	value::PaperValue,
+};
+
+/// The cache operations of a client which can be pooled by a `PaperPool`.
+/// Implementing this trait allows a pool of mock or wrapped clients to be
+/// constructed (e.g., for tests or staging) with the same routing,
+/// sharding, and checkout behavior as a pool of `PaperClient`s.
+///
+/// The pool tracks the health and latency of its nodes through the
+/// underlying `PaperClient` returned by `paper_client`. A client which does
+/// not wrap a `PaperClient` is never ejected and is weighted equally by
+/// `ClientSelection::LatencyWeighted`.
+///
+/// # Examples
+/// ```
+/// use paper_client::{InstrumentedClient, PaperPool, PaperPoolBuilder};
+///
+/// let pool: PaperPool<InstrumentedClient> = PaperPoolBuilder::default()
+///     .addr("paper://127.0.0.1:3145")
+///     .size(4)
+///     .build()
+///     .unwrap();
+///
+/// let _ = pool.client().get("user:42");
+/// println!("{:?}", pool.client().snapshot());
+/// ```
+pub trait PaperCacheOps: Debug + Send + Sized {
+	/// Connects a new client to the supplied address.
+	fn connect(addr: &str, config: &PaperClientConfig) -> PaperClientResult<Self>;
+
+	/// Pings the server.
+	fn ping(&mut self) -> PaperClientResult<PaperValue>;
+
+	/// Stores the supplied `AuthTokenProvider` and authorizes the client
+	/// with its token.
+	fn auth_with_provider(
+		&mut self,
+		provider: Arc<dyn AuthTokenProvider>,
+	) -> PaperClientResult<()>;
+
+	/// Reauthorizes the client with the token currently supplied by its
+	/// `AuthTokenProvider`. Does nothing if the client has not been
+	/// authorized.
+	fn reauth(&mut self) -> PaperClientResult<()>;
+
+	/// Gets the value of the supplied key from the cache.
+	fn get(&mut self, key: &str) -> PaperClientResult<PaperValue>;
+
+	/// Sets the supplied key, value, and ttl to the cache.
+	fn set(
+		&mut self,
+		key: &str,
+		value: impl TryInto<PaperValue>,
+		ttl: Option<u32>,
+	) -> PaperClientResult<()>;
+
+	/// Deletes the value of the supplied key from the cache.
+	fn del(&mut self, key: &str) -> PaperClientResult<()>;
+
+	/// Checks if the cache contains the supplied key.
+	fn has(&mut self, key: &str) -> PaperClientResult<bool>;
+
+	/// Peeks the value of the supplied key from the cache.
+	fn peek(&mut self, key: &str) -> PaperClientResult<PaperValue>;
+
+	/// Sets the ttl of the supplied key.
+	fn ttl(&mut self, key: &str, ttl: Option<u32>) -> PaperClientResult<()>;
+
+	/// Gets the size of the value of the supplied key.
+	fn size(&mut self, key: &str) -> PaperClientResult<u32>;
+
+	/// Wipes the contents of the cache.
+	fn wipe(&mut self) -> PaperClientResult<()>;
+
+	/// Gets the cache's status.
+	fn status(&mut self) -> PaperClientResult<Status>;
+
+	/// Returns the underlying `PaperClient`, if any. Defaults to `None`.
+	fn paper_client(&mut self) -> Option<&mut PaperClient> {
+		None
+	}
+}
+
+impl PaperCacheOps for PaperClient {
+	fn connect(addr: &str, config: &PaperClientConfig) -> PaperClientResult<Self> {
+		PaperClient::connect(addr.to_owned(), config.clone())
+	}
+
+	fn ping(&mut self) -> PaperClientResult<PaperValue> {
+		self.ping()
+	}
+
+	fn auth_with_provider(
+		&mut self,
+		provider: Arc<dyn AuthTokenProvider>,
+	) -> PaperClientResult<()> {
+		self.set_auth_provider(provider);
+		self.reauth()
+	}
+
+	fn reauth(&mut self) -> PaperClientResult<()> {
+		self.reauth()
+	}
+
+	fn get(&mut self, key: &str) -> PaperClientResult<PaperValue> {
+		self.get(key)
+	}
+
+	fn set(
+		&mut self,
+		key: &str,
+		value: impl TryInto<PaperValue>,
+		ttl: Option<u32>,
+	) -> PaperClientResult<()> {
+		self.set(key, value, ttl)
+	}
+
+	fn del(&mut self, key: &str) -> PaperClientResult<()> {
+		self.del(key)
+	}
+
+	fn has(&mut self, key: &str) -> PaperClientResult<bool> {
+		self.has(key)
+	}
+
+	fn peek(&mut self, key: &str) -> PaperClientResult<PaperValue> {
+		self.peek(key)
+	}
+
+	fn ttl(&mut self, key: &str, ttl: Option<u32>) -> PaperClientResult<()> {
+		self.ttl(key, ttl)
+	}
+
+	fn size(&mut self, key: &str) -> PaperClientResult<u32> {
+		self.size(key)
+	}
+
+	fn wipe(&mut self) -> PaperClientResult<()> {
+		self.wipe()
+	}
+
+	fn status(&mut self) -> PaperClientResult<Status> {
+		self.status()
+	}
+
+	fn paper_client(&mut self) -> Option<&mut PaperClient> {
+		Some(self)
+	}
+}
+
+impl PaperCacheOps for InstrumentedClient {
+	fn connect(addr: &str, config: &PaperClientConfig) -> PaperClientResult<Self> {
+		PaperClient::connect(addr.to_owned(), config.clone()).map(InstrumentedClient::new)
+	}
+
+	fn ping(&mut self) -> PaperClientResult<PaperValue> {
+		self.inner_mut().ping()
+	}
+
+	fn auth_with_provider(
+		&mut self,
+		provider: Arc<dyn AuthTokenProvider>,
+	) -> PaperClientResult<()> {
+		PaperCacheOps::auth_with_provider(self.inner_mut(), provider)
+	}
+
+	fn reauth(&mut self) -> PaperClientResult<()> {
+		self.inner_mut().reauth()
+	}
+
+	fn get(&mut self, key: &str) -> PaperClientResult<PaperValue> {
+		self.get(key)
+	}
+
+	fn set(
+		&mut self,
+		key: &str,
+		value: impl TryInto<PaperValue>,
+		ttl: Option<u32>,
+	) -> PaperClientResult<()> {
+		self.set(key, value, ttl)
+	}
+
+	fn del(&mut self, key: &str) -> PaperClientResult<()> {
+		self.del(key)
+	}
+
+	fn has(&mut self, key: &str) -> PaperClientResult<bool> {
+		self.has(key)
+	}
+
+	fn peek(&mut self, key: &str) -> PaperClientResult<PaperValue> {
+		self.peek(key)
+	}
+
+	fn ttl(&mut self, key: &str, ttl: Option<u32>) -> PaperClientResult<()> {
+		self.ttl(key, ttl)
+	}
+
+	fn size(&mut self, key: &str) -> PaperClientResult<u32> {
+		self.size(key)
+	}
+
+	fn wipe(&mut self) -> PaperClientResult<()> {
+		self.inner_mut().wipe()
+	}
+
+	fn status(&mut self) -> PaperClientResult<Status> {
+		self.inner_mut().status()
+	}
+
+	fn paper_client(&mut self) -> Option<&mut PaperClient> {
+		Some(self.inner_mut())
+	}
+}
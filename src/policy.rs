@@ -12,7 +12,7 @@ use std::{
 
 use crate::error::PaperClientError;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum PaperPolicy {
 	Auto,
 	Lfu,
@@ -24,6 +24,10 @@ pub enum PaperPolicy {
 	TwoQ(f64, f64),
 	Arc,
 	SThreeFifo(f64),
+
+	/// A policy reported by a server which is newer than this client, so
+	/// that its status can still be read. Holds the policy's name.
+	Unknown(String),
 }
 
 impl Display for PaperPolicy {
@@ -39,6 +43,7 @@ impl Display for PaperPolicy {
 			PaperPolicy::TwoQ(k_in, k_out) => write!(f, "2q-{k_in}-{k_out}"),
 			PaperPolicy::Arc => write!(f, "arc"),
 			PaperPolicy::SThreeFifo(ratio) => write!(f, "s3-fifo-{ratio}"),
+			PaperPolicy::Unknown(name) => write!(f, "{name}"),
 		}
 	}
 }
@@ -66,6 +71,23 @@ impl FromStr for PaperPolicy {
 	}
 }
 
+impl TryFrom<&str> for PaperPolicy {
+	type Error = PaperClientError;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		PaperPolicy::from_str(value)
+	}
+}
+
+impl PaperPolicy {
+	/// Parses a policy reported by the server, in which case a policy
+	/// this client does not recognize is returned as `Unknown` rather than
+	/// rejected.
+	pub(crate) fn from_server(value: &str) -> Self {
+		PaperPolicy::from_str(value).unwrap_or_else(|_| PaperPolicy::Unknown(value.to_owned()))
+	}
+}
+
 fn parse_two_q(value: &str) -> Result<PaperPolicy, PaperClientError> {
 	// skip the "2q-"
 	let tokens = value[3..].split('-').collect::<Vec<&str>>();
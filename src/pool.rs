@@ -5,19 +5,126 @@
  * LICENSE file in the root directory of this source tree.
  */
 
-use std::sync::{
-	Arc,
-	Mutex,
-	MutexGuard,
-	atomic::{AtomicUsize, Ordering},
+use std::{
+	fmt::{self, Display},
+	iter,
+	marker::PhantomData,
+	ops::{Deref, DerefMut},
+	path::Path,
+	sync::{
+		Arc,
+		Mutex,
+		MutexGuard,
+		RwLock,
+		atomic::{AtomicBool, AtomicUsize, Ordering},
+	},
+	thread,
+	time::{Duration, Instant},
 };
 
-use crate::{addr::FromPaperAddr, client::PaperClient, error::PaperClientError};
+use crate::{
+	addr::FromPaperAddr,
+	arg::AsPaperKey,
+	auth::{AuthTokenProvider, StaticTokenProvider},
+	client::PaperClient,
+	config::PaperClientConfig,
+	error::{PaperClientError, PaperClientResult},
+	health::{self, Availability, EjectionPolicy, NodeHealth, NodeRole, NodeStatus},
+	metrics::{ConnectionStats, PoolMetrics, PoolMetricsRecorder, PoolStats},
+	ops::PaperCacheOps,
+	replica::ReadPreference,
+	selection::{ClientSelection, WeightedSelector},
+	settings::PaperSettings,
+	sharder::{ModuloSharder, Sharder},
+	tenant::TenantUsage,
+};
+
+const TRY_CLIENT_INTERVAL: Duration = Duration::from_millis(1);
+
+/// A pool of clients connected to one or more nodes. The pool is generic
+/// over its clients (see [`PaperCacheOps`]), which default to `PaperClient`.
+#[derive(Debug)]
+pub struct PaperPool<C = PaperClient> {
+	nodes: Arc<Box<[PoolNode<C>]>>,
+	addrs: Arc<Box<[String]>>,
+	index: Arc<AtomicUsize>,
+
+	replicas:      Arc<Box<[PoolNode<C>]>>,
+	replica_index: Arc<AtomicUsize>,
+
+	sharder:         Arc<dyn Sharder>,
+	ejection:        Option<EjectionPolicy>,
+	read_preference: ReadPreference,
+	selection:       ClientSelection,
+
+	config:        PaperClientConfig,
+	auth_provider: Arc<RwLock<Option<Arc<dyn AuthTokenProvider>>>>,
+	metrics:       Arc<PoolMetricsRecorder>,
+	is_drained:    Arc<AtomicBool>,
+}
+
+/// Configures and connects a `PaperPool`. A pool of clients other than
+/// `PaperClient` is configured using `PaperPoolBuilder::<C>::default()`.
+///
+/// # Examples
+/// ```
+/// use paper_client::{PaperPool, PaperClientConfig};
+///
+/// let pool = PaperPool::builder()
+///     .addr("paper://127.0.0.1:3145")
+///     .size(4)
+///     .config(PaperClientConfig::default())
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct PaperPoolBuilder<C = PaperClient> {
+	addrs:   Vec<PaperClientResult<String>>,
+	size:    usize,
+	sharder: Arc<dyn Sharder>,
+	config:  PaperClientConfig,
+
+	auth_provider: Option<Arc<dyn AuthTokenProvider>>,
+
+	clients: PhantomData<C>,
+}
+
+#[derive(Debug)]
+struct PoolNode<C> {
+	addr:    String,
+	clients: Box<[Mutex<C>]>,
+	stats:   Box<[Arc<ConnectionStats>]>,
+	index:   AtomicUsize,
+	health:  Arc<NodeHealth>,
+
+	selector: WeightedSelector,
+}
 
-#[derive(Debug, Clone)]
-pub struct PaperPool {
-	clients: Arc<Box<[Arc<Mutex<PaperClient>>]>>,
-	index:   Arc<AtomicUsize>,
+/// A client checked out of a `PaperPool`. The client is returned to the
+/// pool when the guard is dropped.
+#[derive(Debug)]
+pub struct PaperPoolGuard<'a, C = PaperClient> {
+	client:  MutexGuard<'a, C>,
+	metrics: &'a PoolMetricsRecorder,
+
+	// restores the client's overridden options when it is returned
+	reset: Option<fn(&mut C)>,
+}
+
+/// The options of a single checkout from a pool (see
+/// `PaperPool::client_with`), which override the pool's configuration
+/// until the client is returned to the pool.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use paper_client::CheckoutOptions;
+///
+/// let options = CheckoutOptions::default().timeout(Duration::from_millis(20));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CheckoutOptions {
+	pub(crate) timeout: Option<Duration>,
 }
 
 impl PaperPool {
@@ -32,24 +139,181 @@ impl PaperPool {
 	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4).unwrap();
 	/// ```
 	pub fn new(paper_addr: impl FromPaperAddr, size: usize) -> Result<Self, PaperClientError> {
-		assert!(size > 0);
+		PaperPool::builder()
+			.addr(paper_addr)
+			.size(size)
+			.build()
+	}
 
-		let mut clients = Vec::new();
+	/// Creates a new instance of a sharded pool with `size` clients per
+	/// supplied address. Keys are mapped to addresses using the supplied
+	/// `Sharder`. If a connection could not be established to any of the
+	/// clients, a `PaperClientError` is returned.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{PaperPool, RendezvousSharder};
+	///
+	/// let pool = PaperPool::new_sharded(
+	///     &["paper://127.0.0.1:3145", "paper://127.0.0.1:3145"],
+	///     2,
+	///     RendezvousSharder,
+	/// ).unwrap();
+	/// ```
+	pub fn new_sharded(
+		paper_addrs: &[impl FromPaperAddr],
+		size: usize,
+		sharder: impl Sharder + 'static,
+	) -> Result<Self, PaperClientError> {
+		paper_addrs
+			.iter()
+			.fold(PaperPool::builder(), |builder, paper_addr| {
+				builder.addr(paper_addr.clone())
+			})
+			.size(size)
+			.sharder(sharder)
+			.build()
+	}
 
-		for _ in 0..size {
-			let client = PaperClient::new(paper_addr.clone())?;
-			clients.push(Arc::new(Mutex::new(client)));
-		}
+	/// Creates a new instance of the pool using the settings read from the
+	/// environment (see [`PaperSettings`]), and authorizes the pool's clients if
+	/// an auth token was supplied.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperPool;
+	///
+	/// match PaperPool::from_env() {
+	///     Ok(_) => println!("connected"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn from_env() -> Result<Self, PaperClientError> {
+		PaperPool::from_settings(PaperSettings::from_env()?)
+	}
 
-		let pool = PaperPool {
-			clients: Arc::new(clients.into_boxed_slice()),
-			index:   Arc::new(AtomicUsize::default()),
+	/// Creates a new instance of the pool using the settings read from the
+	/// TOML file at the supplied path (see [`PaperSettings`]), and authorizes
+	/// the pool's clients if an auth token was supplied.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperPool;
+	///
+	/// match PaperPool::from_config("paper.toml") {
+	///     Ok(_) => println!("connected"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn from_config(path: impl AsRef<Path>) -> Result<Self, PaperClientError> {
+		PaperPool::from_settings(PaperSettings::from_file(path)?)
+	}
+
+	/// Creates a new instance of the pool using the supplied settings, and
+	/// authorizes the pool's clients if an auth token was supplied.
+	pub fn from_settings(settings: PaperSettings) -> Result<Self, PaperClientError> {
+		let builder = PaperPool::builder()
+			.addr(settings.addr())
+			.size(settings.pool_size())
+			.config(settings.config().clone());
+
+		let builder = match settings.auth_token() {
+			Some(token) => builder.auth(token),
+			None => builder,
 		};
 
-		Ok(pool)
+		builder.build()
 	}
 
-	/// Attempts to authorize each client with the supplied auth token.
+	/// Returns a builder used to configure and connect a pool.
+	#[must_use]
+	pub fn builder() -> PaperPoolBuilder {
+		PaperPoolBuilder::default()
+	}
+}
+
+impl<C: PaperCacheOps> PaperPool<C> {
+	/// Connects the pool to the supplied read replicas, using the same
+	/// number of clients per replica as per primary node. Reads obtained
+	/// through [`PaperPool::read_client`] and [`PaperPool::read_client_for`]
+	/// are routed according to the supplied `ReadPreference`, while all
+	/// other clients are connected to the primary nodes. Each replica is
+	/// expected to hold a full copy of the cache.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{PaperPool, ReadPreference};
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4)
+	///     .unwrap()
+	///     .with_replicas(&["paper://127.0.0.1:3145"], ReadPreference::PreferReplica)
+	///     .unwrap();
+	/// ```
+	pub fn with_replicas(
+		mut self,
+		paper_addrs: &[impl FromPaperAddr],
+		preference: ReadPreference,
+	) -> Result<Self, PaperClientError> {
+		let mut replicas = Vec::with_capacity(paper_addrs.len());
+
+		for paper_addr in paper_addrs {
+			let addr = paper_addr.to_addr()?;
+			let replica = PoolNode::connect(addr, self.node_size(), &self.config)?;
+
+			if let Some(provider) = self.auth_provider() {
+				replica.auth_with(&provider)?;
+			}
+
+			replicas.push(replica);
+		}
+
+		self.replicas = Arc::new(replicas.into_boxed_slice());
+		self.read_preference = preference;
+
+		Ok(self)
+	}
+
+	/// Sets how the clients of each node are handed out. Defaults to
+	/// `ClientSelection::RoundRobin`.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{ClientSelection, PaperPool};
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4)
+	///     .unwrap()
+	///     .with_client_selection(ClientSelection::LatencyWeighted);
+	/// ```
+	#[must_use]
+	pub fn with_client_selection(mut self, selection: ClientSelection) -> Self {
+		self.selection = selection;
+		self
+	}
+
+	/// Enables ejection of unhealthy nodes from routing. Once a node has
+	/// been ejected, it is probed at most once per ejection duration when a
+	/// checkout is routed past it or [`PaperPool::probe_ejected`] is
+	/// called, and restored when it has responded to the policy's number of
+	/// consecutive probes. Each ejection and restoration is reported to the
+	/// observer as a `FailoverEvent`.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{EjectionPolicy, PaperPool};
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4)
+	///     .unwrap()
+	///     .with_ejection_policy(EjectionPolicy::default());
+	/// ```
+	#[must_use]
+	pub fn with_ejection_policy(mut self, policy: EjectionPolicy) -> Self {
+		self.ejection = Some(policy);
+		self
+	}
+
+	/// Attempts to authorize each client with the supplied auth token. The
+	/// token is stored by the pool and used to authorize any clients which
+	/// are connected later (e.g., to replicas).
 	///
 	/// # Examples
 	/// ```
@@ -62,16 +326,81 @@ impl PaperPool {
 	/// };
 	/// ```
 	pub fn auth(&self, token: &str) -> Result<(), PaperClientError> {
-		for client in self.clients.iter() {
-			client
-				.lock()
-				.expect("Could not obtain client.")
-				.auth(token)?;
+		self.auth_with(StaticTokenProvider::new(token))
+	}
+
+	/// Attempts to authorize each client with the token supplied by the
+	/// supplied `AuthTokenProvider`. The provider is stored by the pool and
+	/// used to authorize any clients which are connected later.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{PaperPool, EnvTokenProvider};
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4).unwrap();
+	///
+	/// if let Err(err) = pool.auth_with(EnvTokenProvider::new("PAPER_AUTH_TOKEN")) {
+	///     println!("{err:?}");
+	/// };
+	/// ```
+	pub fn auth_with(
+		&self,
+		provider: impl AuthTokenProvider + 'static,
+	) -> Result<(), PaperClientError> {
+		let provider: Arc<dyn AuthTokenProvider> = Arc::new(provider);
+
+		*self
+			.auth_provider
+			.write()
+			.expect("Could not obtain auth provider.") = Some(provider.clone());
+
+		for node in self.nodes.iter().chain(self.replicas.iter()) {
+			node.auth_with(&provider)?;
 		}
 
 		Ok(())
 	}
 
+	/// Reauthorizes each client's existing connection with the token
+	/// currently supplied by the pool's `AuthTokenProvider`, without
+	/// reconnecting. Does nothing if the pool has not been authorized.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperPool;
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4).unwrap();
+	///
+	/// if let Err(err) = pool.reauth() {
+	///     println!("{err:?}");
+	/// };
+	/// ```
+	pub fn reauth(&self) -> Result<(), PaperClientError> {
+		for node in self.nodes.iter().chain(self.replicas.iter()) {
+			node.reauth()?;
+		}
+
+		Ok(())
+	}
+
+	/// Replaces the pool's auth token with the supplied token and
+	/// reauthorizes each client's existing connection, without
+	/// reconnecting.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperPool;
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4).unwrap();
+	///
+	/// if let Err(err) = pool.rotate_token("my_new_token") {
+	///     println!("{err:?}");
+	/// };
+	/// ```
+	pub fn rotate_token(&self, token: &str) -> Result<(), PaperClientError> {
+		self.auth(token)
+	}
+
 	/// Obtains a guarded `PaperClient`. Use this client, then drop the
 	/// reference (or allow it to go out of scope). Do not hold a reference
 	/// to this client, otherwise the client will be unusable by other
@@ -88,16 +417,809 @@ impl PaperPool {
 	///     Err(err) => println!("{err:?}"),
 	/// };
 	/// ```
-	pub fn client(&self) -> MutexGuard<'_, PaperClient> {
-		self.clients[self.get_index()]
+	pub fn client(&self) -> PaperPoolGuard<'_, C> {
+		let index = self.get_index();
+		let node = &self.nodes[self.available_node(index / self.node_size())];
+
+		let client = match self.selection {
+			ClientSelection::RoundRobin => index % self.node_size(),
+			ClientSelection::LatencyWeighted => node.next_client(self.selection),
+		};
+
+		self.checkout(node, client)
+	}
+
+	/// Obtains a guarded `PaperClient` in the same way as
+	/// [`PaperPool::client`], with the supplied options applied until the
+	/// guard is dropped (e.g., a short command timeout on a latency
+	/// sensitive path, in a pool whose default suits slower jobs).
+	///
+	/// # Examples
+	/// ```
+	/// use std::time::Duration;
+	/// use paper_client::{CheckoutOptions, PaperPool};
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4).unwrap();
+	/// let options = CheckoutOptions::default().timeout(Duration::from_millis(20));
+	///
+	/// match pool.client_with(options).get("key") {
+	///     Ok(value) => println!("{value:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// };
+	/// ```
+	pub fn client_with(&self, options: CheckoutOptions) -> PaperPoolGuard<'_, C> {
+		let mut client = self.client();
+		client.apply(options);
+		client
+	}
+
+	/// Obtains a guarded `PaperClient` in the same way as
+	/// [`PaperPool::client`], but returns `PoolTimeout` if none of the
+	/// node's clients could be obtained within the supplied timeout, or
+	/// `PoolDrained` if the pool has been drained.
+	///
+	/// # Examples
+	/// ```
+	/// use std::time::Duration;
+	/// use paper_client::PaperPool;
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4).unwrap();
+	///
+	/// match pool.try_client(Duration::from_millis(100)) {
+	///     Ok(mut client) => println!("{:?}", client.ping()),
+	///     Err(err) => println!("{err:?}"),
+	/// };
+	/// ```
+	pub fn try_client(&self, timeout: Duration) -> Result<PaperPoolGuard<'_, C>, PaperClientError> {
+		if self.is_drained() {
+			return Err(PaperClientError::PoolDrained);
+		}
+
+		let start = Instant::now();
+
+		let index = self.get_index();
+		let node = &self.nodes[self.available_node(index / self.node_size())];
+
+		loop {
+			for offset in 0..self.node_size() {
+				let client = (index + offset) % self.node_size();
+
+				if let Ok(mut guard) = node.clients[client].try_lock() {
+					node.stats[client].record_checkout();
+					self.replace_if_exhausted(&mut guard);
+					return Ok(PaperPoolGuard::new(guard, &self.metrics, start.elapsed()));
+				}
+			}
+
+			let elapsed = start.elapsed();
+
+			if elapsed >= timeout {
+				self.metrics.timeout();
+				return Err(PaperClientError::PoolTimeout);
+			}
+
+			thread::sleep(TRY_CLIENT_INTERVAL.min(timeout - elapsed));
+		}
+	}
+
+	/// Obtains a guarded `PaperClient` for reads, connected to either a
+	/// primary node or a replica depending on the pool's `ReadPreference`.
+	/// The same usage rules as [`PaperPool::client`] apply.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperPool;
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4).unwrap();
+	///
+	/// match pool.read_client().status() {
+	///     Ok(status) => println!("{status:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// };
+	/// ```
+	pub fn read_client(&self) -> PaperPoolGuard<'_, C> {
+		let node = self.read_node(None);
+		self.checkout(node, node.next_client(self.selection))
+	}
+
+	/// Obtains a guarded `PaperClient` for reading the supplied key,
+	/// connected to either the primary node responsible for the key or a
+	/// replica depending on the pool's `ReadPreference`. The same usage
+	/// rules as [`PaperPool::client`] apply.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperPool;
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4).unwrap();
+	///
+	/// match pool.read_client_for("key").get("key") {
+	///     Ok(value) => println!("{value:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// };
+	/// ```
+	pub fn read_client_for(&self, key: impl AsPaperKey) -> PaperPoolGuard<'_, C> {
+		let node = self.read_node(Some(key.as_paper_key()));
+		self.checkout(node, node.next_client(self.selection))
+	}
+
+	/// Obtains a guarded `PaperClient` connected to the address responsible
+	/// for the supplied key, as determined by the pool's `Sharder`. The same
+	/// usage rules as [`PaperPool::client`] apply.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperPool;
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4).unwrap();
+	///
+	/// match pool.client_for("key").get("key") {
+	///     Ok(value) => println!("{value:?}"),
+	///     Err(err) => println!("{err:?}"),
+	/// };
+	/// ```
+	pub fn client_for(&self, key: impl AsPaperKey) -> PaperPoolGuard<'_, C> {
+		let node = &self.nodes[self.shard(key.as_paper_key())];
+		self.checkout(node, node.next_client(self.selection))
+	}
+
+	/// Returns the addresses of the pool's nodes.
+	#[must_use]
+	pub fn addrs(&self) -> &[String] {
+		&self.addrs
+	}
+
+	/// Returns a snapshot of the health of each of the pool's nodes.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperPool;
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4).unwrap();
+	///
+	/// for node in pool.cluster_status() {
+	///     println!("{}: {:?}", node.addr(), node.state());
+	/// }
+	/// ```
+	#[must_use]
+	pub fn cluster_status(&self) -> Vec<NodeStatus> {
+		let primaries = self
+			.nodes
+			.iter()
+			.map(|node| node.health.status(&node.addr, NodeRole::Primary));
+
+		let replicas = self
+			.replicas
+			.iter()
+			.map(|node| node.health.status(&node.addr, NodeRole::Replica));
+
+		primaries.chain(replicas).collect()
+	}
+
+	/// Probes each ejected node whose ejection duration has elapsed,
+	/// restoring it once it has responded to the ejection policy's number
+	/// of consecutive probes. Ejected nodes are otherwise only probed when a
+	/// checkout is routed past them, so a node whose keys are not requested
+	/// would stay ejected; calling this periodically (e.g., from a thread,
+	/// once per ejection duration) ensures that a healthy node fails back.
+	/// Does nothing if the pool has no ejection policy.
+	///
+	/// # Examples
+	/// ```
+	/// use std::{thread, time::Duration};
+	/// use paper_client::{EjectionPolicy, PaperPool};
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4)
+	///     .unwrap()
+	///     .with_ejection_policy(EjectionPolicy::default());
+	///
+	/// let heartbeat = pool.clone();
+	///
+	/// thread::spawn(move || loop {
+	///     thread::sleep(Duration::from_secs(10));
+	///     heartbeat.probe_ejected();
+	/// });
+	/// ```
+	pub fn probe_ejected(&self) {
+		for node in self.nodes.iter().chain(self.replicas.iter()) {
+			node.is_available(self.ejection.as_ref(), &self.config);
+		}
+	}
+
+	/// Returns a snapshot of the pool's checkout and connection metrics.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperPool;
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4).unwrap();
+	/// let metrics = pool.metrics();
+	///
+	/// println!("{} in use, {} idle", metrics.in_use(), metrics.idle());
+	/// ```
+	#[must_use]
+	pub fn metrics(&self) -> PoolMetrics {
+		let stats = self
+			.nodes
+			.iter()
+			.chain(self.replicas.iter())
+			.flat_map(|node| node.stats.iter())
+			.map(|stats| &**stats)
+			.collect::<Vec<_>>();
+
+		self.metrics.snapshot(&stats)
+	}
+
+	/// Returns a snapshot of the commands sent over each of the pool's
+	/// connections (see `PaperClient::client_stats`), with totals across
+	/// the pool, without checking out any clients.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperPool;
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4).unwrap();
+	/// let stats = pool.aggregate_stats();
+	///
+	/// for connection in stats.connections() {
+	///     println!("{}: {} commands", connection.addr(), connection.commands());
+	/// }
+	/// ```
+	#[must_use]
+	pub fn aggregate_stats(&self) -> PoolStats {
+		self.nodes
+			.iter()
+			.chain(self.replicas.iter())
+			.flat_map(|node| node.stats.iter().map(|stats| stats.snapshot(&node.addr)))
+			.collect()
+	}
+
+	/// Returns a snapshot of the usage of every tenant of the pool's
+	/// clients (see `PaperClient::tenant`), ordered by tenant.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperPool;
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4).unwrap();
+	///
+	/// for usage in pool.tenant_usage() {
+	///     println!("{}: {} bytes read", usage.tenant(), usage.bytes_read());
+	/// }
+	/// ```
+	#[must_use]
+	pub fn tenant_usage(&self) -> Vec<TenantUsage> {
+		self.config.tenants.usage()
+	}
+
+	/// Drains the pool: no more clients are handed out by
+	/// [`PaperPool::try_client`], and each client's connection is closed
+	/// once its outstanding guard has been dropped. Clients obtained from
+	/// the pool afterwards in any other way are closed and return
+	/// `Disconnected`. If any guard is still outstanding after the supplied
+	/// timeout, its connection is left open and `PoolTimeout` is returned.
+	///
+	/// # Examples
+	/// ```
+	/// use std::time::Duration;
+	/// use paper_client::PaperPool;
+	///
+	/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4).unwrap();
+	///
+	/// match pool.drain(Duration::from_secs(5)) {
+	///     Ok(_) => println!("drained"),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn drain(&self, timeout: Duration) -> PaperClientResult<()> {
+		let start = Instant::now();
+		self.is_drained.store(true, Ordering::Relaxed);
+
+		let mut is_closed = true;
+
+		let clients = self
+			.nodes
+			.iter()
+			.chain(self.replicas.iter())
+			.flat_map(|node| node.clients.iter());
+
+		for client in clients {
+			loop {
+				if let Ok(mut client) = client.try_lock() {
+					if let Some(client) = client.paper_client() {
+						client.close();
+					}
+
+					break;
+				}
+
+				let elapsed = start.elapsed();
+
+				if elapsed >= timeout {
+					is_closed = false;
+					break;
+				}
+
+				thread::sleep(TRY_CLIENT_INTERVAL.min(timeout - elapsed));
+			}
+		}
+
+		match is_closed {
+			true => Ok(()),
+			false => Err(PaperClientError::PoolTimeout),
+		}
+	}
+
+	/// Returns `true` if the pool has been drained.
+	#[must_use]
+	pub fn is_drained(&self) -> bool {
+		self.is_drained.load(Ordering::Relaxed)
+	}
+
+	fn checkout<'a>(&'a self, node: &'a PoolNode<C>, index: usize) -> PaperPoolGuard<'a, C> {
+		let start = Instant::now();
+
+		node.stats[index].record_checkout();
+
+		let mut client = node.clients[index]
 			.lock()
-			.expect("Could not obtain client.")
+			.expect("Could not obtain client.");
+
+		self.replace_if_exhausted(&mut client);
+
+		PaperPoolGuard::new(client, &self.metrics, start.elapsed())
+	}
+
+	// a client whose reconnects were exhausted would otherwise fail every
+	// time it is handed out, so its connection is replaced first
+	fn replace_if_exhausted(&self, client: &mut C) {
+		if let Some(client) = client.paper_client()
+			&& client.replace_if_exhausted()
+		{
+			self.metrics.replacement();
+		}
+	}
+
+	fn read_node(&self, key: Option<&str>) -> &PoolNode<C> {
+		let primary = || match key {
+			Some(key) => &self.nodes[self.shard(key)],
+			None => &self.nodes[self.available_node(self.get_index() / self.node_size())],
+		};
+
+		match self.read_preference {
+			ReadPreference::PrimaryOnly => primary(),
+
+			ReadPreference::PreferReplica => self
+				.available_replica()
+				.unwrap_or_else(primary),
+
+			ReadPreference::NearestByLatency => iter::once(primary())
+				.chain(
+					self.replicas
+						.iter()
+						.filter(|replica| self.is_available(replica)),
+				)
+				.min_by_key(|node| node.health.latency())
+				.expect("Could not find a node."),
+		}
+	}
+
+	fn available_replica(&self) -> Option<&PoolNode<C>> {
+		if self.replicas.is_empty() {
+			return None;
+		}
+
+		let index = self.replica_index.fetch_add(1, Ordering::Relaxed);
+
+		(0..self.replicas.len())
+			.map(|offset| &self.replicas[(index + offset) % self.replicas.len()])
+			.find(|replica| self.is_available(replica))
+	}
+
+	fn shard(&self, key: &str) -> usize {
+		if self.nodes.len() == 1 {
+			return 0;
+		}
+
+		let available = self.available_nodes();
+
+		if available.is_empty() || available.len() == self.nodes.len() {
+			return self.sharder.shard(key, &self.addrs);
+		}
+
+		let addrs = available
+			.iter()
+			.map(|index| self.addrs[*index].clone())
+			.collect::<Vec<_>>();
+
+		available[self.sharder.shard(key, &addrs)]
+	}
+
+	fn available_nodes(&self) -> Vec<usize> {
+		(0..self.nodes.len())
+			.filter(|index| self.is_available(&self.nodes[*index]))
+			.collect()
+	}
+
+	fn available_node(&self, index: usize) -> usize {
+		(0..self.nodes.len())
+			.map(|offset| (index + offset) % self.nodes.len())
+			.find(|index| self.is_available(&self.nodes[*index]))
+			.unwrap_or(index)
+	}
+
+	fn is_available(&self, node: &PoolNode<C>) -> bool {
+		node.is_available(self.ejection.as_ref(), &self.config)
+	}
+
+	fn auth_provider(&self) -> Option<Arc<dyn AuthTokenProvider>> {
+		self.auth_provider
+			.read()
+			.expect("Could not obtain auth provider.")
+			.clone()
+	}
+
+	fn node_size(&self) -> usize {
+		self.nodes[0].clients.len()
+	}
+
+	// each caller is handed the next index, even under contention, which
+	// wraps around along with the counter
+	fn get_index(&self) -> usize {
+		let num_clients = self.nodes.len() * self.node_size();
+		self.index.fetch_add(1, Ordering::Relaxed) % num_clients
+	}
+}
+
+impl<C: PaperCacheOps> PoolNode<C> {
+	fn connect(
+		addr: String,
+		size: usize,
+		config: &PaperClientConfig,
+	) -> Result<Self, PaperClientError> {
+		let health = Arc::new(NodeHealth::default());
+		let mut clients = Vec::with_capacity(size);
+		let mut stats = Vec::with_capacity(size);
+
+		for _ in 0..size {
+			let mut client = C::connect(&addr, config)?;
+
+			let client_stats = match client.paper_client() {
+				Some(client) => {
+					client.set_health(health.clone());
+					client.stats()
+				},
+
+				None => Arc::default(),
+			};
+
+			stats.push(client_stats);
+			clients.push(Mutex::new(client));
+		}
+
+		let node = PoolNode {
+			addr,
+			clients: clients.into_boxed_slice(),
+			stats: stats.into_boxed_slice(),
+			index: AtomicUsize::default(),
+			health,
+
+			selector: WeightedSelector::new(size),
+		};
+
+		Ok(node)
+	}
+
+	fn auth_with(&self, provider: &Arc<dyn AuthTokenProvider>) -> Result<(), PaperClientError> {
+		for client in self.clients.iter() {
+			let mut client = client
+				.lock()
+				.expect("Could not obtain client.");
+
+			client.auth_with_provider(provider.clone())?;
+		}
+
+		Ok(())
+	}
+
+	fn reauth(&self) -> Result<(), PaperClientError> {
+		for client in self.clients.iter() {
+			client
+				.lock()
+				.expect("Could not obtain client.")
+				.reauth()?;
+		}
+
+		Ok(())
+	}
+
+	fn next_client(&self, selection: ClientSelection) -> usize {
+		match selection {
+			ClientSelection::RoundRobin => self.get_index(),
+			ClientSelection::LatencyWeighted => self.selector.select(&self.stats),
+		}
+	}
+
+	// probes the node if it is due, reporting its ejection or restoration
+	// to the observer
+	fn is_available(&self, policy: Option<&EjectionPolicy>, config: &PaperClientConfig) -> bool {
+		match self.health.availability(policy, config.clock.now()) {
+			Availability::Available => true,
+			Availability::Ejected => false,
+
+			Availability::Ejecting => {
+				health::notify_failover(config, &self.addr, true);
+				false
+			},
+
+			Availability::Probe => {
+				// a probe which found every client checked out says nothing
+				// of the node's health
+				let Some(is_healthy) = self.probe() else {
+					self.health.skip_probe();
+					return false;
+				};
+
+				let is_restored = self
+					.health
+					.finish_probe(is_healthy, policy, config.clock.now());
+
+				if is_restored {
+					health::notify_failover(config, &self.addr, false);
+				}
+
+				is_restored
+			},
+		}
+	}
+
+	fn probe(&self) -> Option<bool> {
+		let mut is_healthy = None;
+
+		for client in self.clients.iter() {
+			let Ok(mut client) = client.try_lock() else {
+				continue;
+			};
+
+			let result = match client.paper_client() {
+				Some(client) => client.probe(),
+				None => client.ping().map(|_| ()),
+			};
+
+			match result {
+				Ok(_) => is_healthy = Some(true),
+				Err(_) => return Some(false),
+			}
+		}
+
+		is_healthy
 	}
 
 	fn get_index(&self) -> usize {
-		let index = self.index.load(Ordering::Relaxed);
-		self.index
-			.store((index + 1) % self.clients.len(), Ordering::Relaxed);
-		index
+		self.index.fetch_add(1, Ordering::Relaxed) % self.clients.len()
+	}
+}
+
+impl<C> Clone for PaperPool<C> {
+	fn clone(&self) -> Self {
+		PaperPool {
+			nodes: self.nodes.clone(),
+			addrs: self.addrs.clone(),
+			index: self.index.clone(),
+
+			replicas:      self.replicas.clone(),
+			replica_index: self.replica_index.clone(),
+
+			sharder:         self.sharder.clone(),
+			ejection:        self.ejection,
+			read_preference: self.read_preference,
+			selection:       self.selection,
+
+			config:        self.config.clone(),
+			auth_provider: self.auth_provider.clone(),
+			metrics:       self.metrics.clone(),
+			is_drained:    self.is_drained.clone(),
+		}
+	}
+}
+
+impl<C: PaperCacheOps> Display for PaperPool<C> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"PaperPool({} node(s), {} replica(s), {} client(s) per node)",
+			self.nodes.len(),
+			self.replicas.len(),
+			self.node_size(),
+		)
+	}
+}
+
+impl<'a, C> PaperPoolGuard<'a, C> {
+	fn new(
+		client: MutexGuard<'a, C>,
+		metrics: &'a PoolMetricsRecorder,
+		wait_time: Duration,
+	) -> Self {
+		metrics.checkout(wait_time);
+
+		PaperPoolGuard {
+			client,
+			metrics,
+
+			reset: None,
+		}
+	}
+}
+
+impl<C: PaperCacheOps> PaperPoolGuard<'_, C> {
+	fn apply(&mut self, options: CheckoutOptions) {
+		let Some(timeout) = options.timeout else {
+			return;
+		};
+
+		if let Some(client) = self.client.paper_client() {
+			client.set_command_timeout(Some(timeout));
+
+			self.reset = Some(|client: &mut C| {
+				if let Some(client) = client.paper_client() {
+					client.reset_command_timeout();
+				}
+			});
+		}
+	}
+}
+
+impl<C> Deref for PaperPoolGuard<'_, C> {
+	type Target = C;
+
+	fn deref(&self) -> &Self::Target {
+		&self.client
+	}
+}
+
+impl<C> DerefMut for PaperPoolGuard<'_, C> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.client
+	}
+}
+
+impl<C> Drop for PaperPoolGuard<'_, C> {
+	fn drop(&mut self) {
+		if let Some(reset) = self.reset {
+			reset(&mut self.client);
+		}
+
+		self.metrics.checkin();
+	}
+}
+
+impl CheckoutOptions {
+	/// Sets the maximum time each command issued with the checked out
+	/// client may take, overriding the configured `command_timeout`.
+	#[must_use]
+	pub fn timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = Some(timeout);
+		self
+	}
+}
+
+impl<C: PaperCacheOps> PaperPoolBuilder<C> {
+	/// Adds a node at the supplied address to the pool. Adding more than
+	/// one address creates a sharded pool.
+	#[must_use]
+	pub fn addr(mut self, paper_addr: impl FromPaperAddr) -> Self {
+		self.addrs.push(paper_addr.to_addr());
+		self
+	}
+
+	/// Sets the number of clients connected to each node. Defaults to 1.
+	#[must_use]
+	pub fn size(mut self, size: usize) -> Self {
+		self.size = size;
+		self
+	}
+
+	/// Sets the `Sharder` used to map keys to nodes. Defaults to
+	/// `ModuloSharder`.
+	#[must_use]
+	pub fn sharder(mut self, sharder: impl Sharder + 'static) -> Self {
+		self.sharder = Arc::new(sharder);
+		self
+	}
+
+	/// Sets the configuration used by each of the pool's clients.
+	#[must_use]
+	pub fn config(mut self, config: PaperClientConfig) -> Self {
+		self.config = config;
+		self
+	}
+
+	/// Sets the auth token with which each of the pool's clients is
+	/// authorized as soon as it is connected, so that building the pool
+	/// fails if the token is rejected. The token is stored by the pool and
+	/// used to authorize any clients which are connected later.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperPool;
+	///
+	/// let pool = PaperPool::builder()
+	///     .addr("paper://127.0.0.1:3145")
+	///     .auth("auth_token")
+	///     .build()
+	///     .unwrap();
+	/// ```
+	#[must_use]
+	pub fn auth(self, token: &str) -> Self {
+		self.auth_with(StaticTokenProvider::new(token))
+	}
+
+	/// Sets the `AuthTokenProvider` which supplies the token with which
+	/// each of the pool's clients is authorized as soon as it is connected
+	/// (see `auth`).
+	#[must_use]
+	pub fn auth_with(mut self, provider: impl AuthTokenProvider + 'static) -> Self {
+		self.auth_provider = Some(Arc::new(provider));
+		self
+	}
+
+	/// Connects the pool's clients, authorizing them if an auth token was
+	/// supplied. If a connection could not be established to any of the
+	/// clients or the auth token was rejected, a `PaperClientError` is
+	/// returned.
+	pub fn build(self) -> Result<PaperPool<C>, PaperClientError> {
+		assert!(!self.addrs.is_empty());
+		assert!(self.size > 0);
+
+		let mut nodes = Vec::with_capacity(self.addrs.len());
+		let mut addrs = Vec::with_capacity(self.addrs.len());
+
+		for addr in self.addrs {
+			let node = PoolNode::connect(addr?, self.size, &self.config)?;
+
+			if let Some(provider) = &self.auth_provider {
+				node.auth_with(provider)?;
+			}
+
+			addrs.push(node.addr.clone());
+			nodes.push(node);
+		}
+
+		let pool = PaperPool {
+			nodes: Arc::new(nodes.into_boxed_slice()),
+			addrs: Arc::new(addrs.into_boxed_slice()),
+			index: Arc::new(AtomicUsize::default()),
+
+			replicas:      Arc::new(Box::default()),
+			replica_index: Arc::new(AtomicUsize::default()),
+
+			sharder:         self.sharder,
+			ejection:        None,
+			read_preference: ReadPreference::default(),
+			selection:       ClientSelection::default(),
+
+			config:        self.config,
+			auth_provider: Arc::new(RwLock::new(self.auth_provider)),
+			metrics:       Arc::default(),
+			is_drained:    Arc::default(),
+		};
+
+		Ok(pool)
+	}
+}
+
+impl<C> Default for PaperPoolBuilder<C> {
+	fn default() -> Self {
+		PaperPoolBuilder {
+			addrs:   Vec::new(),
+			size:    1,
+			sharder: Arc::new(ModuloSharder),
+			config:  PaperClientConfig::default(),
+
+			auth_provider: None,
+
+			clients: PhantomData,
+		}
 	}
 }
@@ -0,0 +1,87 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::io::{self, Write};
+#[cfg(feature = "tokio")]
+use std::{
+	pin::Pin,
+	task::{Context, Poll},
+};
+
+#[cfg(feature = "tokio")]
+use tokio::io::AsyncWrite;
+
+/// A writer which keeps only the bytes within a range of the bytes written
+/// to it, so that a range of a value can be read without buffering the
+/// whole value.
+#[derive(Debug)]
+pub(crate) struct RangeWriter {
+	start: u64,
+	end:   u64,
+
+	// the number of bytes written so far
+	position: u64,
+	buf:      Vec<u8>,
+}
+
+impl RangeWriter {
+	pub(crate) fn new(offset: u64, len: usize) -> Self {
+		RangeWriter {
+			start: offset,
+			end:   offset.saturating_add(len as u64),
+
+			position: 0,
+			buf:      Vec::new(),
+		}
+	}
+
+	pub(crate) fn into_inner(self) -> Vec<u8> {
+		self.buf
+	}
+
+	fn keep(&mut self, bytes: &[u8]) {
+		let len = bytes.len() as u64;
+
+		let from = self.start.clamp(self.position, self.position + len) - self.position;
+		let to = self.end.clamp(self.position, self.position + len) - self.position;
+
+		self.buf.extend_from_slice(&bytes[from as usize..to as usize]);
+
+		self.position += len;
+	}
+}
+
+impl Write for RangeWriter {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.keep(buf);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncWrite for RangeWriter {
+	fn poll_write(
+		self: Pin<&mut Self>,
+		_: &mut Context<'_>,
+		buf: &[u8],
+	) -> Poll<io::Result<usize>> {
+		self.get_mut().keep(buf);
+		Poll::Ready(Ok(buf.len()))
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Poll::Ready(Ok(()))
+	}
+}
@@ -0,0 +1,24 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+/// Determines which of a pool's nodes serve reads obtained through
+/// `read_client` and `read_client_for`. Writes are always routed to the
+/// pool's primary nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReadPreference {
+	/// All reads are routed to the primary nodes.
+	#[default]
+	PrimaryOnly,
+
+	/// Reads are spread across the available replicas, falling back to the
+	/// primary nodes if no replica is available.
+	PreferReplica,
+
+	/// Reads are routed to whichever available node (primary or replica)
+	/// has the lowest recent request latency.
+	NearestByLatency,
+}
@@ -0,0 +1,388 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::io::{Read, Write};
+
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncRead, AsyncWrite};
+
+#[cfg(feature = "tokio")]
+use paper_utils::stream::AsyncStreamReader;
+use paper_utils::stream::StreamReader;
+
+#[cfg(feature = "tokio")]
+use crate::decode::DecodePaperResponseAsync;
+use crate::{
+	command::Command,
+	decode::DecodePaperResponse,
+	error::{PaperClientError, PaperClientResult},
+	status::Status,
+	value::PaperValue,
+};
+
+/// Decodes a command's response from the stream. A client processes every
+/// command through a single generic `process`, so a new response kind only
+/// needs a new decoder.
+pub(crate) trait DecodeResponse {
+	type Output;
+
+	/// Whether the command can be sent again after an invalid response.
+	/// Decoders which hand part of the response to the caller before it
+	/// has been fully read must not be retried. Either way, the connection
+	/// is re-established before the next command.
+	const IS_RETRYABLE: bool = true;
+
+	/// Returns `true` if the decoder may have failed with the supplied
+	/// error before reading the whole response, in which case the
+	/// connection is re-established before the next command so that the
+	/// rest of the response is not read as the next command's. The
+	/// client's own decoders always read the whole response unless the
+	/// error is a stream error.
+	fn is_partially_read(_err: &PaperClientError) -> bool {
+		false
+	}
+
+	fn decode(
+		&mut self,
+		command: &Command<'_>,
+		reader: &mut impl Read,
+	) -> PaperClientResult<Self::Output>;
+}
+
+/// The async counterpart of `DecodeResponse`.
+#[cfg(feature = "tokio")]
+pub(crate) trait DecodeResponseAsync {
+	type Output;
+
+	const IS_RETRYABLE: bool = true;
+
+	fn is_partially_read(_err: &PaperClientError) -> bool {
+		false
+	}
+
+	async fn decode_async<R>(
+		&mut self,
+		command: &Command<'_>,
+		reader: &mut R,
+	) -> PaperClientResult<Self::Output>
+	where
+		R: AsyncRead + Unpin + Send;
+}
+
+/// Decodes a response which carries no data.
+pub(crate) struct Ack;
+
+/// Decodes a response which carries a value, rejecting values larger than
+/// the maximum size.
+pub(crate) struct Value(pub(crate) Option<u32>);
+
+/// Decodes a response which carries a boolean.
+pub(crate) struct Flag;
+
+/// Decodes a response which carries a size.
+pub(crate) struct Size;
+
+/// Decodes a status response.
+pub(crate) struct StatusFrame;
+
+/// Decodes a value into a reusable buffer, replacing its contents.
+pub(crate) struct IntoVec<'a>(pub(crate) &'a mut Vec<u8>, pub(crate) Option<u32>);
+
+/// Decodes a value into the start of a slice.
+pub(crate) struct IntoSlice<'a>(pub(crate) &'a mut [u8]);
+
+/// Decodes a value by copying it to a writer in chunks.
+pub(crate) struct ToWriter<'a, W: ?Sized>(pub(crate) &'a mut W);
+
+/// Decodes a response with a user-defined decoder once its success flag
+/// has been read.
+pub(crate) struct Custom<D>(pub(crate) D);
+
+impl DecodeResponse for Ack {
+	type Output = ();
+
+	fn decode(&mut self, command: &Command<'_>, reader: &mut impl Read) -> PaperClientResult<()> {
+		command.parse_reader(reader)
+	}
+}
+
+impl DecodeResponse for Value {
+	type Output = PaperValue;
+
+	fn decode(
+		&mut self,
+		command: &Command<'_>,
+		reader: &mut impl Read,
+	) -> PaperClientResult<PaperValue> {
+		command.parse_buf_reader(reader, self.0)
+	}
+}
+
+impl DecodeResponse for Flag {
+	type Output = bool;
+
+	fn decode(&mut self, command: &Command<'_>, reader: &mut impl Read) -> PaperClientResult<bool> {
+		command.parse_has_reader(reader)
+	}
+}
+
+impl DecodeResponse for Size {
+	type Output = u32;
+
+	fn decode(&mut self, command: &Command<'_>, reader: &mut impl Read) -> PaperClientResult<u32> {
+		command.parse_size_reader(reader)
+	}
+}
+
+impl DecodeResponse for StatusFrame {
+	type Output = Status;
+
+	fn decode(
+		&mut self,
+		command: &Command<'_>,
+		reader: &mut impl Read,
+	) -> PaperClientResult<Status> {
+		command.parse_status_reader(reader)
+	}
+}
+
+impl DecodeResponse for IntoVec<'_> {
+	type Output = usize;
+
+	fn decode(
+		&mut self,
+		command: &Command<'_>,
+		reader: &mut impl Read,
+	) -> PaperClientResult<usize> {
+		command.parse_buf_vec(reader, self.0, self.1)
+	}
+}
+
+impl DecodeResponse for IntoSlice<'_> {
+	type Output = usize;
+
+	fn decode(
+		&mut self,
+		command: &Command<'_>,
+		reader: &mut impl Read,
+	) -> PaperClientResult<usize> {
+		command.parse_buf_slice(reader, self.0)
+	}
+}
+
+impl<W: Write + ?Sized> DecodeResponse for ToWriter<'_, W> {
+	type Output = u64;
+
+	// part of the value may already have been written
+	const IS_RETRYABLE: bool = false;
+
+	fn decode(&mut self, command: &Command<'_>, reader: &mut impl Read) -> PaperClientResult<u64> {
+		command.parse_buf_writer(reader, &mut self.0)
+	}
+}
+
+impl<D: DecodePaperResponse> DecodeResponse for Custom<D> {
+	type Output = D::Output;
+
+	const IS_RETRYABLE: bool = D::IS_RETRYABLE;
+
+	// only an error response is known to have been read in full, as a
+	// user-defined decoder may fail partway through the payload
+	fn is_partially_read(err: &PaperClientError) -> bool {
+		!matches!(
+			err,
+			PaperClientError::ServerError(_) | PaperClientError::CacheError(_)
+		)
+	}
+
+	fn decode(
+		&mut self,
+		_: &Command<'_>,
+		reader: &mut impl Read,
+	) -> PaperClientResult<D::Output> {
+		let mut reader = StreamReader::new(reader);
+
+		let is_ok = reader
+			.read_bool()
+			.map_err(|_| PaperClientError::InvalidResponse)?;
+
+		match is_ok {
+			true => self.0.decode(&mut reader),
+			false => Err(PaperClientError::from_reader(reader)),
+		}
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl DecodeResponseAsync for Ack {
+	type Output = ();
+
+	async fn decode_async<R>(
+		&mut self,
+		command: &Command<'_>,
+		reader: &mut R,
+	) -> PaperClientResult<()>
+	where
+		R: AsyncRead + Unpin + Send,
+	{
+		command.parse_reader_async(reader).await
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl DecodeResponseAsync for Value {
+	type Output = PaperValue;
+
+	async fn decode_async<R>(
+		&mut self,
+		command: &Command<'_>,
+		reader: &mut R,
+	) -> PaperClientResult<PaperValue>
+	where
+		R: AsyncRead + Unpin + Send,
+	{
+		command.parse_buf_reader_async(reader, self.0).await
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl DecodeResponseAsync for Flag {
+	type Output = bool;
+
+	async fn decode_async<R>(
+		&mut self,
+		command: &Command<'_>,
+		reader: &mut R,
+	) -> PaperClientResult<bool>
+	where
+		R: AsyncRead + Unpin + Send,
+	{
+		command.parse_has_reader_async(reader).await
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl DecodeResponseAsync for Size {
+	type Output = u32;
+
+	async fn decode_async<R>(
+		&mut self,
+		command: &Command<'_>,
+		reader: &mut R,
+	) -> PaperClientResult<u32>
+	where
+		R: AsyncRead + Unpin + Send,
+	{
+		command.parse_size_reader_async(reader).await
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl DecodeResponseAsync for StatusFrame {
+	type Output = Status;
+
+	async fn decode_async<R>(
+		&mut self,
+		command: &Command<'_>,
+		reader: &mut R,
+	) -> PaperClientResult<Status>
+	where
+		R: AsyncRead + Unpin + Send,
+	{
+		command.parse_status_reader_async(reader).await
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl DecodeResponseAsync for IntoVec<'_> {
+	type Output = usize;
+
+	async fn decode_async<R>(
+		&mut self,
+		command: &Command<'_>,
+		reader: &mut R,
+	) -> PaperClientResult<usize>
+	where
+		R: AsyncRead + Unpin + Send,
+	{
+		command.parse_buf_vec_async(reader, self.0, self.1).await
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl DecodeResponseAsync for IntoSlice<'_> {
+	type Output = usize;
+
+	async fn decode_async<R>(
+		&mut self,
+		command: &Command<'_>,
+		reader: &mut R,
+	) -> PaperClientResult<usize>
+	where
+		R: AsyncRead + Unpin + Send,
+	{
+		command.parse_buf_slice_async(reader, self.0).await
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl<W> DecodeResponseAsync for ToWriter<'_, W>
+where
+	W: AsyncWrite + Unpin + ?Sized,
+{
+	type Output = u64;
+
+	const IS_RETRYABLE: bool = false;
+
+	async fn decode_async<R>(
+		&mut self,
+		command: &Command<'_>,
+		reader: &mut R,
+	) -> PaperClientResult<u64>
+	where
+		R: AsyncRead + Unpin + Send,
+	{
+		command.parse_buf_writer_async(reader, &mut self.0).await
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl<D: DecodePaperResponseAsync> DecodeResponseAsync for Custom<D> {
+	type Output = D::Output;
+
+	const IS_RETRYABLE: bool = D::IS_RETRYABLE;
+
+	// only an error response is known to have been read in full, as a
+	// user-defined decoder may fail partway through the payload
+	fn is_partially_read(err: &PaperClientError) -> bool {
+		!matches!(
+			err,
+			PaperClientError::ServerError(_) | PaperClientError::CacheError(_)
+		)
+	}
+
+	async fn decode_async<R>(
+		&mut self,
+		_: &Command<'_>,
+		reader: &mut R,
+	) -> PaperClientResult<D::Output>
+	where
+		R: AsyncRead + Unpin + Send,
+	{
+		let mut reader = AsyncStreamReader::new(reader);
+
+		let is_ok = reader
+			.read_bool()
+			.await
+			.map_err(|_| PaperClientError::InvalidResponse)?;
+
+		match is_ok {
+			true => self.0.decode_async(&mut reader).await,
+			false => Err(PaperClientError::from_reader_async(reader).await),
+		}
+	}
+}
@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::error::PaperClientError;
+
+// the balance is stored in thousandths of a retry so that fractional
+// ratios can be deposited without floating point atomics
+const RETRY_COST: u64 = 1000;
+
+const DEFAULT_RATIO: f64 = 0.1;
+const DEFAULT_BURST: u32 = 10;
+
+/// Limits the number of retries relative to the number of requests, so
+/// that a fleet of clients does not amplify the load on a struggling
+/// server with synchronized retries.
+///
+/// Each request deposits `ratio` of a retry into the budget and each retry
+/// withdraws one retry. The budget starts with, and never holds more than,
+/// `burst` retries. When the budget is exhausted, the command fails with
+/// `RetryBudgetExhausted` rather than being retried.
+///
+/// A budget is attached to a client or pool through its
+/// `PaperClientConfig`, and is shared by every client constructed from
+/// that configuration.
+///
+/// # Examples
+/// ```
+/// use paper_client::{PaperClientConfig, RetryBudget};
+///
+/// // allow retries for at most 10% of requests, with bursts of up to 10
+/// let config = PaperClientConfig::default()
+///     .retry_budget(RetryBudget::new(0.1, 10));
+/// ```
+#[derive(Debug)]
+pub struct RetryBudget {
+	deposit:     u64,
+	max_balance: u64,
+	balance:     AtomicU64,
+}
+
+/// A class of error after which a command is retried once the connection
+/// has been re-established. Every command in the protocol is idempotent,
+/// so a command which may or may not have reached the server can safely be
+/// sent again.
+///
+/// # Examples
+/// ```
+/// use paper_client::{PaperClientConfig, RetryableError};
+///
+/// // survive a server restart between commands
+/// let config = PaperClientConfig::default()
+///     .retry_on([RetryableError::InvalidResponse, RetryableError::Disconnected]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryableError {
+	/// The response could not be read (`InvalidResponse`).
+	InvalidResponse,
+
+	/// The command could not be sent because the connection was closed
+	/// (`Disconnected`).
+	Disconnected,
+}
+
+impl RetryBudget {
+	/// Creates a new retry budget which allows retries for `ratio` of
+	/// requests (e.g., 0.1 for 10%), with bursts of up to `burst` retries.
+	#[must_use]
+	pub fn new(ratio: f64, burst: u32) -> Self {
+		let deposit = (ratio.clamp(0.0, 1.0) * RETRY_COST as f64).round() as u64;
+		let max_balance = u64::from(burst) * RETRY_COST;
+
+		RetryBudget {
+			deposit,
+			max_balance,
+			balance: AtomicU64::new(max_balance),
+		}
+	}
+
+	/// Returns the number of retries currently available.
+	#[must_use]
+	pub fn available(&self) -> u32 {
+		let available = self.balance.load(Ordering::Relaxed) / RETRY_COST;
+		available.try_into().unwrap_or(u32::MAX)
+	}
+
+	pub(crate) fn deposit(&self) {
+		let _ = self
+			.balance
+			.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |balance| {
+				Some((balance + self.deposit).min(self.max_balance))
+			});
+	}
+
+	pub(crate) fn withdraw(&self) -> bool {
+		self.balance
+			.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |balance| {
+				balance.checked_sub(RETRY_COST)
+			})
+			.is_ok()
+	}
+}
+
+impl RetryableError {
+	pub(crate) fn matches(&self, err: &PaperClientError) -> bool {
+		matches!(
+			(self, err),
+			(RetryableError::InvalidResponse, PaperClientError::InvalidResponse)
+				| (RetryableError::Disconnected, PaperClientError::Disconnected)
+		)
+	}
+}
+
+impl Default for RetryBudget {
+	fn default() -> Self {
+		RetryBudget::new(DEFAULT_RATIO, DEFAULT_BURST)
+	}
+}
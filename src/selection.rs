@@ -0,0 +1,92 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::sync::{Arc, Mutex};
+
+use crate::metrics::ConnectionStats;
+
+// the weight of a connection is this value divided by its average latency
+// in microseconds, so a connection with twice the latency is selected half
+// as often
+const LATENCY_WEIGHT_SCALE: u128 = 1_000_000_000;
+const MAX_WEIGHT: i64 = i64::MAX / 1024;
+
+/// Determines which of a node's clients a pool hands out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClientSelection {
+	/// Clients are handed out in turn.
+	#[default]
+	RoundRobin,
+
+	/// Clients are handed out in a weighted round-robin, where each
+	/// client's weight is inversely proportional to its recent request
+	/// latency. Connections which traverse a degraded network path are
+	/// therefore selected less often. A client without any recorded
+	/// requests is weighted as the fastest client.
+	LatencyWeighted,
+}
+
+// a smooth weighted round-robin (as used by nginx), which interleaves the
+// selections rather than selecting the heaviest client repeatedly
+#[derive(Debug)]
+pub(crate) struct WeightedSelector {
+	current: Mutex<Box<[i64]>>,
+}
+
+impl WeightedSelector {
+	pub fn new(size: usize) -> Self {
+		WeightedSelector {
+			current: Mutex::new(vec![0; size].into_boxed_slice()),
+		}
+	}
+
+	pub fn select(&self, stats: &[Arc<ConnectionStats>]) -> usize {
+		let latencies = stats
+			.iter()
+			.map(|stats| stats.latency().as_micros())
+			.collect::<Vec<_>>();
+
+		let fastest = latencies
+			.iter()
+			.copied()
+			.filter(|latency| *latency > 0)
+			.min()
+			.unwrap_or(1);
+
+		let weights = latencies
+			.into_iter()
+			.map(|latency| match latency {
+				0 => fastest,
+				latency => latency,
+			})
+			.map(|latency| {
+				let weight = LATENCY_WEIGHT_SCALE / latency;
+				weight.clamp(1, MAX_WEIGHT as u128) as i64
+			});
+
+		let mut current = self
+			.current
+			.lock()
+			.expect("Could not obtain selector.");
+
+		let mut total: i64 = 0;
+		let mut selected = 0;
+
+		for (index, weight) in weights.enumerate() {
+			current[index] = current[index].saturating_add(weight);
+			total = total.saturating_add(weight);
+
+			if current[index] > current[selected] {
+				selected = index;
+			}
+		}
+
+		current[selected] = current[selected].saturating_sub(total);
+
+		selected
+	}
+}
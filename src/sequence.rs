@@ -0,0 +1,214 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	fmt::{self, Debug},
+	sync::{Arc, Condvar, Mutex},
+	time::Duration,
+};
+
+#[cfg(feature = "tokio")]
+use tokio::{sync::Notify, time};
+
+use crate::error::{PaperClientError, PaperClientResult};
+
+/// A position in the sequence of mutations (`set`, `del`, `ttl`, `wipe`,
+/// `resize`, and `policy`) issued by a client, returned by the client's
+/// `sequence`.
+///
+/// The server executes the commands sent on a connection in the order in
+/// which they were sent, and a mutation's response is only sent once it has
+/// been applied. A token is acknowledged once the response to its mutation,
+/// or to any later mutation issued by the same client, has been received,
+/// from which point the mutation is visible to every other connection to
+/// the same server. Waiting on a token before reading from another pooled
+/// connection therefore provides read-your-writes consistency across the
+/// pool's connections, including when the mutation and the token are
+/// handed between threads or tasks.
+///
+/// A mutation which failed because the server responded with an error
+/// (e.g., `KeyNotFound`) is still acknowledged, whereas a mutation whose
+/// response was never received (e.g., because the connection failed) is
+/// only acknowledged by a later mutation.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use paper_client::PaperClient;
+///
+/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+/// let _ = client.set("key", "value", None);
+///
+/// let token = client.sequence();
+///
+/// // e.g., on another thread, before reading from another connection
+/// if let Err(err) = token.wait(Duration::from_secs(1)) {
+///     println!("{err:?}");
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SequenceToken {
+	sequencer: Arc<Sequencer>,
+	position:  u64,
+}
+
+/// The sequence of a client's mutations, shared with its tokens.
+#[derive(Default)]
+pub(crate) struct Sequencer {
+	issued: Mutex<u64>,
+
+	acknowledged: Mutex<u64>,
+	condvar:      Condvar,
+
+	#[cfg(feature = "tokio")]
+	notify: Notify,
+}
+
+impl SequenceToken {
+	/// Returns the token's position in its client's sequence of mutations,
+	/// or 0 if the client had not issued any mutations.
+	#[must_use]
+	pub fn position(&self) -> u64 {
+		self.position
+	}
+
+	/// Returns `true` if the token's mutation (and every earlier mutation of
+	/// its client) has been acknowledged by the server.
+	#[must_use]
+	pub fn is_acknowledged(&self) -> bool {
+		self.sequencer.acknowledged() >= self.position
+	}
+
+	/// Blocks until the token has been acknowledged. If it has not been
+	/// acknowledged within the supplied timeout, `SequenceTimeout` is
+	/// returned.
+	pub fn wait(&self, timeout: Duration) -> PaperClientResult<()> {
+		let acknowledged = self
+			.sequencer
+			.acknowledged
+			.lock()
+			.map_err(|_| PaperClientError::Internal)?;
+
+		let (_acknowledged, result) = self
+			.sequencer
+			.condvar
+			.wait_timeout_while(acknowledged, timeout, |acknowledged| {
+				*acknowledged < self.position
+			})
+			.map_err(|_| PaperClientError::Internal)?;
+
+		match result.timed_out() {
+			true => Err(PaperClientError::SequenceTimeout),
+			false => Ok(()),
+		}
+	}
+
+	/// The async counterpart of [`SequenceToken::wait`].
+	///
+	/// # Examples
+	/// ```ignore
+	/// use std::time::Duration;
+	/// use paper_client::AsyncPaperClient;
+	///
+	/// let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	/// let _ = client.set("key", "value", None).await;
+	///
+	/// let token = client.sequence();
+	///
+	/// if let Err(err) = token.wait_async(Duration::from_secs(1)).await {
+	///     println!("{err:?}");
+	/// }
+	/// ```
+	#[cfg(feature = "tokio")]
+	pub async fn wait_async(&self, timeout: Duration) -> PaperClientResult<()> {
+		let deadline = time::Instant::now() + timeout;
+
+		loop {
+			let notified = self.sequencer.notify.notified();
+			tokio::pin!(notified);
+
+			// registers the waiter before the position is checked, so that
+			// an acknowledgement in between is not missed
+			notified.as_mut().enable();
+
+			if self.is_acknowledged() {
+				return Ok(());
+			}
+
+			if time::timeout_at(deadline, notified).await.is_err() {
+				return Err(PaperClientError::SequenceTimeout);
+			}
+		}
+	}
+}
+
+impl Sequencer {
+	/// Returns a token for the most recently issued mutation.
+	pub(crate) fn token(self: &Arc<Self>) -> SequenceToken {
+		SequenceToken {
+			sequencer: self.clone(),
+			position:  *self.issued.lock().expect("Could not obtain sequence."),
+		}
+	}
+
+	/// Issues the next position in the sequence to a mutation.
+	pub(crate) fn issue(&self) {
+		*self.issued.lock().expect("Could not obtain sequence.") += 1;
+	}
+
+	/// Acknowledges every issued mutation, once the server has responded to
+	/// the most recent one.
+	pub(crate) fn acknowledge(&self) {
+		let issued = *self.issued.lock().expect("Could not obtain sequence.");
+
+		{
+			let mut acknowledged = self
+				.acknowledged
+				.lock()
+				.expect("Could not obtain sequence.");
+
+			if issued == *acknowledged {
+				return;
+			}
+
+			*acknowledged = issued;
+		}
+
+		self.condvar.notify_all();
+
+		#[cfg(feature = "tokio")]
+		self.notify.notify_waiters();
+	}
+
+	fn acknowledged(&self) -> u64 {
+		*self.acknowledged.lock().expect("Could not obtain sequence.")
+	}
+}
+
+/// Returns `true` if the server responded to the command (even if it
+/// responded with an error).
+pub(crate) fn is_response<T>(result: &PaperClientResult<T>) -> bool {
+	matches!(
+		result,
+		Ok(_) | Err(PaperClientError::CacheError(_) | PaperClientError::ServerError(_)),
+	)
+}
+
+impl Debug for SequenceToken {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("SequenceToken")
+			.field("position", &self.position)
+			.field("is_acknowledged", &self.is_acknowledged())
+			.finish()
+	}
+}
+
+impl Debug for Sequencer {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Sequencer").finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,263 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	env,
+	fmt::{self, Debug},
+	fs,
+	path::Path,
+	str::FromStr,
+	time::Duration,
+};
+
+use crate::{
+	config::PaperClientConfig,
+	error::{PaperClientError, PaperClientResult},
+};
+
+const ENV_ADDR: &str = "PAPER_ADDR";
+const ENV_AUTH_TOKEN: &str = "PAPER_AUTH_TOKEN";
+const ENV_POOL_SIZE: &str = "PAPER_POOL_SIZE";
+const ENV_HANDSHAKE_TIMEOUT_MS: &str = "PAPER_HANDSHAKE_TIMEOUT_MS";
+const ENV_CONNECT_RETRIES: &str = "PAPER_CONNECT_RETRIES";
+const ENV_CONNECT_BACKOFF_MS: &str = "PAPER_CONNECT_BACKOFF_MS";
+
+/// The settings used to construct a client or pool from the environment
+/// or from a configuration file.
+///
+/// The following settings are supported, with the environment variable
+/// and configuration file key of each:
+///
+/// | Setting           | Environment variable         | Key                    |
+/// |-------------------|------------------------------|------------------------|
+/// | Address           | `PAPER_ADDR`                 | `addr`                 |
+/// | Auth token        | `PAPER_AUTH_TOKEN`           | `auth_token`           |
+/// | Pool size         | `PAPER_POOL_SIZE`            | `pool_size`            |
+/// | Handshake timeout | `PAPER_HANDSHAKE_TIMEOUT_MS` | `handshake_timeout_ms` |
+/// | Connect retries   | `PAPER_CONNECT_RETRIES`      | `connect_retries`      |
+/// | Connect backoff   | `PAPER_CONNECT_BACKOFF_MS`   | `connect_backoff_ms`   |
+///
+/// Only the address is required. A handshake timeout of 0 disables the
+/// timeout.
+#[derive(Clone)]
+pub struct PaperSettings {
+	addr:       String,
+	auth_token: Option<String>,
+	pool_size:  usize,
+	config:     PaperClientConfig,
+}
+
+impl PaperSettings {
+	/// Reads the settings from the environment. If the address is missing
+	/// or any of the settings are invalid, `InvalidConfig` is returned.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperSettings;
+	///
+	/// match PaperSettings::from_env() {
+	///     Ok(settings) => println!("{}", settings.addr()),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn from_env() -> PaperClientResult<Self> {
+		let mut settings = SettingsBuilder::default();
+
+		let vars = [
+			(ENV_ADDR, "addr"),
+			(ENV_AUTH_TOKEN, "auth_token"),
+			(ENV_POOL_SIZE, "pool_size"),
+			(ENV_HANDSHAKE_TIMEOUT_MS, "handshake_timeout_ms"),
+			(ENV_CONNECT_RETRIES, "connect_retries"),
+			(ENV_CONNECT_BACKOFF_MS, "connect_backoff_ms"),
+		];
+
+		for (var, key) in vars {
+			if let Ok(value) = env::var(var) {
+				settings.set(key, value)?;
+			}
+		}
+
+		settings.build()
+	}
+
+	/// Reads the settings from the TOML file at the supplied path. Only
+	/// top-level keys with string or integer values are supported. If the
+	/// file could not be read, the address is missing, or any of the
+	/// settings are invalid, `InvalidConfig` is returned.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperSettings;
+	///
+	/// match PaperSettings::from_file("paper.toml") {
+	///     Ok(settings) => println!("{}", settings.addr()),
+	///     Err(err) => println!("{err:?}"),
+	/// }
+	/// ```
+	pub fn from_file(path: impl AsRef<Path>) -> PaperClientResult<Self> {
+		let contents = fs::read_to_string(path)
+			.map_err(|_| PaperClientError::InvalidConfig)?;
+
+		let mut settings = SettingsBuilder::default();
+
+		for line in contents.lines() {
+			let line = line.trim();
+
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			let (key, value) = line
+				.split_once('=')
+				.ok_or(PaperClientError::InvalidConfig)?;
+
+			settings.set(key.trim(), parse_toml_value(value.trim())?)?;
+		}
+
+		settings.build()
+	}
+
+	/// Returns the server's address.
+	#[must_use]
+	pub fn addr(&self) -> &str {
+		&self.addr
+	}
+
+	/// Returns the auth token, if one was supplied.
+	#[must_use]
+	pub fn auth_token(&self) -> Option<&str> {
+		self.auth_token.as_deref()
+	}
+
+	/// Returns the number of clients connected to each of a pool's nodes.
+	/// Defaults to 1.
+	#[must_use]
+	pub fn pool_size(&self) -> usize {
+		self.pool_size
+	}
+
+	/// Returns the client configuration.
+	#[must_use]
+	pub fn config(&self) -> &PaperClientConfig {
+		&self.config
+	}
+}
+
+impl Debug for PaperSettings {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("PaperSettings")
+			.field("addr", &self.addr)
+			.field("auth_token", &self.auth_token.as_ref().map(|_| "<redacted>"))
+			.field("pool_size", &self.pool_size)
+			.field("config", &self.config)
+			.finish()
+	}
+}
+
+#[derive(Default)]
+struct SettingsBuilder {
+	addr:       Option<String>,
+	auth_token: Option<String>,
+	pool_size:  Option<usize>,
+	config:     PaperClientConfig,
+}
+
+impl SettingsBuilder {
+	fn set(&mut self, key: &str, value: String) -> PaperClientResult<()> {
+		match key {
+			"addr" => self.addr = Some(value),
+			"auth_token" => self.auth_token = Some(value),
+			"pool_size" => self.pool_size = Some(parse_number(&value)?),
+
+			"handshake_timeout_ms" => {
+				let timeout = match parse_number(&value)? {
+					0 => None,
+					ms => Some(Duration::from_millis(ms)),
+				};
+
+				self.config.handshake_timeout = timeout;
+			},
+
+			"connect_retries" => self.config.connect_retries = parse_number(&value)?,
+			"connect_backoff_ms" => {
+				self.config.connect_backoff = Duration::from_millis(parse_number(&value)?);
+			},
+
+			_ => return Err(PaperClientError::InvalidConfig),
+		}
+
+		Ok(())
+	}
+
+	fn build(self) -> PaperClientResult<PaperSettings> {
+		let pool_size = self.pool_size.unwrap_or(1);
+
+		if pool_size == 0 {
+			return Err(PaperClientError::InvalidConfig);
+		}
+
+		let settings = PaperSettings {
+			addr: self.addr.ok_or(PaperClientError::InvalidConfig)?,
+			auth_token: self.auth_token,
+			pool_size,
+			config: self.config,
+		};
+
+		Ok(settings)
+	}
+}
+
+fn parse_number<T: FromStr>(value: &str) -> PaperClientResult<T> {
+	value
+		.replace('_', "")
+		.parse()
+		.map_err(|_| PaperClientError::InvalidConfig)
+}
+
+fn parse_toml_value(value: &str) -> PaperClientResult<String> {
+	let Some(quoted) = value.strip_prefix('"') else {
+		// bare (integer) values end at a trailing comment
+		let value = value
+			.split_once('#')
+			.map_or(value, |(value, _)| value)
+			.trim();
+
+		return match value.is_empty() {
+			true => Err(PaperClientError::InvalidConfig),
+			false => Ok(value.to_owned()),
+		};
+	};
+
+	let mut parsed = String::new();
+	let mut chars = quoted.chars();
+
+	while let Some(c) = chars.next() {
+		match c {
+			'"' => {
+				let rest = chars.as_str().trim();
+
+				return match rest.is_empty() || rest.starts_with('#') {
+					true => Ok(parsed),
+					false => Err(PaperClientError::InvalidConfig),
+				};
+			},
+
+			'\\' => match chars.next() {
+				Some('"') => parsed.push('"'),
+				Some('\\') => parsed.push('\\'),
+				Some('n') => parsed.push('\n'),
+				Some('t') => parsed.push('\t'),
+				_ => return Err(PaperClientError::InvalidConfig),
+			},
+
+			c => parsed.push(c),
+		}
+	}
+
+	Err(PaperClientError::InvalidConfig)
+}
@@ -0,0 +1,546 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	fmt::{self, Debug},
+	net::TcpStream,
+	sync::{
+		Arc,
+		atomic::{AtomicU64, Ordering},
+		mpsc::{self, SyncSender},
+	},
+	thread,
+};
+
+#[cfg(feature = "tokio")]
+use tokio::sync::{mpsc as async_mpsc, oneshot};
+
+#[cfg(feature = "tokio")]
+use crate::async_client::AsyncPaperClient;
+#[cfg(feature = "tokio")]
+use crate::transport::AsyncPaperTransport;
+use crate::{
+	arg::{AsPaperKey, AsPaperTtl},
+	client::PaperClient,
+	error::{PaperCacheError, PaperClientError, PaperClientResult},
+	transport::PaperTransport,
+	ttl::Ttl,
+	value::PaperValue,
+};
+
+// the maximum number of commands waiting to be mirrored, after which
+// further commands are dropped rather than slowing down the primary
+const SHADOW_QUEUE_CAPACITY: usize = 1024;
+
+/// A client which mirrors its writes (`set`, `del`, and `ttl`), and
+/// optionally a sample of its `get`s, to a shadow client connected to a
+/// secondary cache, for validating a new cluster or policy configuration
+/// with production traffic before cutover.
+///
+/// Commands are issued to the primary client as usual and then mirrored
+/// from a background thread, so the shadow never slows down or fails a
+/// command. A command is only mirrored if it succeeded on the primary, and
+/// is dropped if too many commands are waiting to be mirrored. Each
+/// mirrored command whose result diverges from the primary's (e.g., a
+/// sampled `get` which returned a different value, or a `del` of a key the
+/// shadow does not hold) is recorded as a mismatch.
+///
+/// # Examples
+/// ```
+/// use paper_client::{PaperClient, ShadowClient};
+///
+/// let primary = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+/// let shadow = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+///
+/// let mut client = ShadowClient::new(primary, shadow).with_read_sample_rate(0.1);
+///
+/// let _ = client.set("key", "value", None);
+/// let _ = client.get("key");
+///
+/// client.flush();
+/// println!("{:?}", client.metrics());
+/// ```
+#[derive(Debug)]
+pub struct ShadowClient<T = TcpStream> {
+	client:  PaperClient<T>,
+	sender:  SyncSender<ShadowCommand>,
+	sampler: ReadSampler,
+	metrics: Arc<ShadowMetricsRecorder>,
+}
+
+/// The async counterpart of `ShadowClient`, which mirrors commands from a
+/// background task rather than a thread.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncShadowClient<T = tokio::net::TcpStream> {
+	client:  AsyncPaperClient<T>,
+	sender:  async_mpsc::Sender<ShadowCommand>,
+	sampler: ReadSampler,
+	metrics: Arc<ShadowMetricsRecorder>,
+}
+
+/// A snapshot of the commands mirrored by a shadow client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShadowMetrics {
+	mirrored:   u64,
+	dropped:    u64,
+	errors:     u64,
+	mismatches: u64,
+}
+
+#[derive(Debug, Default)]
+struct ShadowMetricsRecorder {
+	mirrored:   AtomicU64,
+	dropped:    AtomicU64,
+	errors:     AtomicU64,
+	mismatches: AtomicU64,
+}
+
+// samples a fixed fraction of reads, spread evenly across the reads
+#[derive(Debug, Default)]
+struct ReadSampler {
+	rate:  f64,
+	reads: u64,
+}
+
+enum ShadowCommand {
+	// the value held by the primary, or None if the key was not found
+	Get(String, Option<PaperValue>),
+
+	Set(String, PaperValue, u32),
+	Del(String),
+	Ttl(String, u32),
+
+	// notifies the caller once all previously queued commands are mirrored
+	Flush(Box<dyn FnOnce() + Send>),
+}
+
+impl<T: PaperTransport> ShadowClient<T> {
+	/// Wraps the supplied primary client, mirroring its commands to the
+	/// supplied shadow client. The shadow client should already be
+	/// authorized if the secondary cache requires it.
+	#[must_use]
+	pub fn new(client: PaperClient<T>, mut shadow: PaperClient) -> Self {
+		let (sender, receiver) = mpsc::sync_channel(SHADOW_QUEUE_CAPACITY);
+
+		let metrics = Arc::new(ShadowMetricsRecorder::default());
+		let worker_metrics = metrics.clone();
+
+		// the thread stops once the client (and so the sender) is dropped
+		thread::spawn(move || {
+			for command in receiver {
+				let outcome = ShadowCommand::apply(command, &mut shadow);
+				worker_metrics.record(outcome);
+			}
+		});
+
+		ShadowClient {
+			client,
+			sender,
+			sampler: ReadSampler::default(),
+			metrics,
+		}
+	}
+
+	/// Sets the fraction (from 0 to 1) of `get`s which are mirrored.
+	/// Defaults to 0.
+	#[must_use]
+	pub fn with_read_sample_rate(mut self, rate: f64) -> Self {
+		self.sampler.rate = rate.clamp(0.0, 1.0);
+		self
+	}
+
+	/// Gets the value of the supplied key from the cache. See
+	/// `PaperClient::get`.
+	pub fn get(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = key.as_paper_key();
+		let result = self.client.get(key);
+
+		if self.sampler.sample()
+			&& let Some(expected) = expected_value(&result)
+		{
+			self.mirror(ShadowCommand::Get(key.to_owned(), expected));
+		}
+
+		result
+	}
+
+	/// Sets the supplied key, value, and ttl to the cache. See
+	/// `PaperClient::set`.
+	pub fn set(
+		&mut self,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let ttl = ttl.as_paper_ttl()?;
+
+		let value: PaperValue = value
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidValue)?;
+
+		self.client.set(key, value.clone(), Ttl::from_secs(ttl))?;
+		self.mirror(ShadowCommand::Set(key.to_owned(), value, ttl));
+
+		Ok(())
+	}
+
+	/// Deletes the value of the supplied key from the cache. See
+	/// `PaperClient::del`.
+	pub fn del(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+
+		self.client.del(key)?;
+		self.mirror(ShadowCommand::Del(key.to_owned()));
+
+		Ok(())
+	}
+
+	/// Sets the ttl of the supplied key. See `PaperClient::ttl`.
+	pub fn ttl(&mut self, key: impl AsPaperKey, ttl: impl AsPaperTtl) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let ttl = ttl.as_paper_ttl()?;
+
+		self.client.ttl(key, Ttl::from_secs(ttl))?;
+		self.mirror(ShadowCommand::Ttl(key.to_owned(), ttl));
+
+		Ok(())
+	}
+
+	/// Blocks until all of the commands mirrored so far have been issued to
+	/// the shadow client.
+	pub fn flush(&self) {
+		let (sender, receiver) = mpsc::sync_channel(1);
+
+		let notify = Box::new(move || {
+			let _ = sender.send(());
+		});
+
+		if self.sender.send(ShadowCommand::Flush(notify)).is_ok() {
+			let _ = receiver.recv();
+		}
+	}
+
+	/// Returns a snapshot of the commands mirrored so far.
+	#[must_use]
+	pub fn metrics(&self) -> ShadowMetrics {
+		self.metrics.snapshot()
+	}
+
+	/// Returns a reference to the primary client.
+	#[must_use]
+	pub fn inner(&self) -> &PaperClient<T> {
+		&self.client
+	}
+
+	/// Returns a mutable reference to the primary client. Commands issued
+	/// through it are not mirrored.
+	#[must_use]
+	pub fn inner_mut(&mut self) -> &mut PaperClient<T> {
+		&mut self.client
+	}
+
+	/// Unwraps the primary client. Commands which have not yet been
+	/// mirrored are still issued to the shadow client.
+	#[must_use]
+	pub fn into_inner(self) -> PaperClient<T> {
+		self.client
+	}
+
+	fn mirror(&self, command: ShadowCommand) {
+		let result = self.sender.try_send(command).is_ok();
+		self.metrics.enqueue(result);
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl<T: AsyncPaperTransport> AsyncShadowClient<T> {
+	/// Wraps the supplied primary client, mirroring its commands to the
+	/// supplied shadow client. The shadow client should already be
+	/// authorized if the secondary cache requires it. Must be called from
+	/// within a tokio runtime.
+	#[must_use]
+	pub fn new(client: AsyncPaperClient<T>, mut shadow: AsyncPaperClient) -> Self {
+		let (sender, mut receiver) = async_mpsc::channel(SHADOW_QUEUE_CAPACITY);
+
+		let metrics = Arc::new(ShadowMetricsRecorder::default());
+		let worker_metrics = metrics.clone();
+
+		// the task stops once the client (and so the sender) is dropped
+		tokio::spawn(async move {
+			while let Some(command) = receiver.recv().await {
+				let outcome = ShadowCommand::apply_async(command, &mut shadow).await;
+				worker_metrics.record(outcome);
+			}
+		});
+
+		AsyncShadowClient {
+			client,
+			sender,
+			sampler: ReadSampler::default(),
+			metrics,
+		}
+	}
+
+	/// Sets the fraction (from 0 to 1) of `get`s which are mirrored.
+	/// Defaults to 0.
+	#[must_use]
+	pub fn with_read_sample_rate(mut self, rate: f64) -> Self {
+		self.sampler.rate = rate.clamp(0.0, 1.0);
+		self
+	}
+
+	/// Gets the value of the supplied key from the cache. See
+	/// `AsyncPaperClient::get`.
+	pub async fn get(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = key.as_paper_key();
+		let result = self.client.get(key).await;
+
+		if self.sampler.sample()
+			&& let Some(expected) = expected_value(&result)
+		{
+			self.mirror(ShadowCommand::Get(key.to_owned(), expected));
+		}
+
+		result
+	}
+
+	/// Sets the supplied key, value, and ttl to the cache. See
+	/// `AsyncPaperClient::set`.
+	pub async fn set(
+		&mut self,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let ttl = ttl.as_paper_ttl()?;
+
+		let value: PaperValue = value
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidValue)?;
+
+		self.client.set(key, value.clone(), Ttl::from_secs(ttl)).await?;
+		self.mirror(ShadowCommand::Set(key.to_owned(), value, ttl));
+
+		Ok(())
+	}
+
+	/// Deletes the value of the supplied key from the cache. See
+	/// `AsyncPaperClient::del`.
+	pub async fn del(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+
+		self.client.del(key).await?;
+		self.mirror(ShadowCommand::Del(key.to_owned()));
+
+		Ok(())
+	}
+
+	/// Sets the ttl of the supplied key. See `AsyncPaperClient::ttl`.
+	pub async fn ttl(&mut self, key: impl AsPaperKey, ttl: impl AsPaperTtl) -> PaperClientResult<()> {
+		let key = key.as_paper_key();
+		let ttl = ttl.as_paper_ttl()?;
+
+		self.client.ttl(key, Ttl::from_secs(ttl)).await?;
+		self.mirror(ShadowCommand::Ttl(key.to_owned(), ttl));
+
+		Ok(())
+	}
+
+	/// Waits until all of the commands mirrored so far have been issued to
+	/// the shadow client.
+	pub async fn flush(&self) {
+		let (sender, receiver) = oneshot::channel();
+
+		let notify = Box::new(move || {
+			let _ = sender.send(());
+		});
+
+		if self.sender.send(ShadowCommand::Flush(notify)).await.is_ok() {
+			let _ = receiver.await;
+		}
+	}
+
+	/// Returns a snapshot of the commands mirrored so far.
+	#[must_use]
+	pub fn metrics(&self) -> ShadowMetrics {
+		self.metrics.snapshot()
+	}
+
+	/// Returns a reference to the primary client.
+	#[must_use]
+	pub fn inner(&self) -> &AsyncPaperClient<T> {
+		&self.client
+	}
+
+	/// Returns a mutable reference to the primary client. Commands issued
+	/// through it are not mirrored.
+	#[must_use]
+	pub fn inner_mut(&mut self) -> &mut AsyncPaperClient<T> {
+		&mut self.client
+	}
+
+	/// Unwraps the primary client. Commands which have not yet been
+	/// mirrored are still issued to the shadow client.
+	#[must_use]
+	pub fn into_inner(self) -> AsyncPaperClient<T> {
+		self.client
+	}
+
+	fn mirror(&self, command: ShadowCommand) {
+		let result = self.sender.try_send(command).is_ok();
+		self.metrics.enqueue(result);
+	}
+}
+
+impl ShadowMetrics {
+	/// Returns the number of commands queued to be mirrored.
+	#[must_use]
+	pub fn mirrored(&self) -> u64 {
+		self.mirrored
+	}
+
+	/// Returns the number of commands which were not mirrored because too
+	/// many commands were waiting to be mirrored.
+	#[must_use]
+	pub fn dropped(&self) -> u64 {
+		self.dropped
+	}
+
+	/// Returns the number of mirrored commands which failed on the shadow
+	/// (excluding mismatches).
+	#[must_use]
+	pub fn errors(&self) -> u64 {
+		self.errors
+	}
+
+	/// Returns the number of mirrored commands whose result on the shadow
+	/// diverged from the primary's.
+	#[must_use]
+	pub fn mismatches(&self) -> u64 {
+		self.mismatches
+	}
+}
+
+impl ShadowMetricsRecorder {
+	fn enqueue(&self, is_queued: bool) {
+		match is_queued {
+			true => self.mirrored.fetch_add(1, Ordering::Relaxed),
+			false => self.dropped.fetch_add(1, Ordering::Relaxed),
+		};
+	}
+
+	fn record(&self, outcome: PaperClientResult<bool>) {
+		match outcome {
+			Ok(true) => {},
+			Ok(false) => _ = self.mismatches.fetch_add(1, Ordering::Relaxed),
+			Err(_) => _ = self.errors.fetch_add(1, Ordering::Relaxed),
+		}
+	}
+
+	fn snapshot(&self) -> ShadowMetrics {
+		ShadowMetrics {
+			mirrored:   self.mirrored.load(Ordering::Relaxed),
+			dropped:    self.dropped.load(Ordering::Relaxed),
+			errors:     self.errors.load(Ordering::Relaxed),
+			mismatches: self.mismatches.load(Ordering::Relaxed),
+		}
+	}
+}
+
+impl ReadSampler {
+	fn sample(&mut self) -> bool {
+		let previous = (self.reads as f64 * self.rate).floor();
+		self.reads += 1;
+
+		(self.reads as f64 * self.rate).floor() > previous
+	}
+}
+
+impl ShadowCommand {
+	// returns whether the shadow's result matches the primary's
+	fn apply(self, shadow: &mut PaperClient) -> PaperClientResult<bool> {
+		match self {
+			ShadowCommand::Get(key, expected) => compare(shadow.get(&key), expected),
+			ShadowCommand::Set(key, value, ttl) => is_applied(shadow.set(&key, value, Ttl::from_secs(ttl))),
+			ShadowCommand::Del(key) => is_applied(shadow.del(&key)),
+			ShadowCommand::Ttl(key, ttl) => is_applied(shadow.ttl(&key, Ttl::from_secs(ttl))),
+
+			ShadowCommand::Flush(notify) => {
+				notify();
+				Ok(true)
+			},
+		}
+	}
+
+	#[cfg(feature = "tokio")]
+	async fn apply_async(self, shadow: &mut AsyncPaperClient) -> PaperClientResult<bool> {
+		match self {
+			ShadowCommand::Get(key, expected) => compare(shadow.get(&key).await, expected),
+			ShadowCommand::Set(key, value, ttl) => {
+				is_applied(shadow.set(&key, value, Ttl::from_secs(ttl)).await)
+			},
+
+			ShadowCommand::Del(key) => is_applied(shadow.del(&key).await),
+			ShadowCommand::Ttl(key, ttl) => is_applied(shadow.ttl(&key, Ttl::from_secs(ttl)).await),
+
+			ShadowCommand::Flush(notify) => {
+				notify();
+				Ok(true)
+			},
+		}
+	}
+}
+
+impl Debug for ShadowCommand {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ShadowCommand::Get(key, _) => write!(f, "Get({key:?})"),
+			ShadowCommand::Set(key, _, ttl) => write!(f, "Set({key:?}, {ttl})"),
+			ShadowCommand::Del(key) => write!(f, "Del({key:?})"),
+			ShadowCommand::Ttl(key, ttl) => write!(f, "Ttl({key:?}, {ttl})"),
+			ShadowCommand::Flush(_) => write!(f, "Flush"),
+		}
+	}
+}
+
+// the primary's value to compare the shadow's against, or None if the
+// primary's result cannot be compared
+fn expected_value(result: &PaperClientResult<PaperValue>) -> Option<Option<PaperValue>> {
+	match result {
+		Ok(value) => Some(Some(value.clone())),
+		Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => Some(None),
+		Err(_) => None,
+	}
+}
+
+fn compare(
+	result: PaperClientResult<PaperValue>,
+	expected: Option<PaperValue>,
+) -> PaperClientResult<bool> {
+	match (result, expected) {
+		(Ok(value), Some(expected)) => Ok(value == expected),
+		(Ok(_), None) => Ok(false),
+		(Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)), expected) => {
+			Ok(expected.is_none())
+		},
+
+		(Err(err), _) => Err(err),
+	}
+}
+
+// a write of a key which the shadow does not hold diverges from the
+// primary, on which the write succeeded
+fn is_applied(result: PaperClientResult<()>) -> PaperClientResult<bool> {
+	match result {
+		Ok(_) => Ok(true),
+		Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => Ok(false),
+		Err(err) => Err(err),
+	}
+}
@@ -0,0 +1,163 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{fmt::Debug, sync::Mutex};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+const DEFAULT_VIRTUAL_NODES: usize = 160;
+
+/// Maps a key onto one of a sharded pool's nodes.
+pub trait Sharder: Debug + Send + Sync {
+	/// Returns the index into `nodes` of the node responsible for `key`.
+	/// The supplied `nodes` slice is guaranteed to be non-empty.
+	fn shard(&self, key: &str, nodes: &[String]) -> usize;
+}
+
+/// Shards keys by taking their hash modulo the number of nodes. This is
+/// the cheapest strategy but remaps most keys when the node count changes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ModuloSharder;
+
+/// Shards keys using a consistent hash ring with a configurable number of
+/// virtual nodes per node, such that changing the node count only remaps
+/// a small fraction of the keys.
+#[derive(Debug)]
+pub struct ConsistentHashSharder {
+	virtual_nodes: usize,
+	ring:          Mutex<Option<HashRing>>,
+}
+
+/// Shards keys using rendezvous (highest random weight) hashing, which
+/// requires no precomputed state and remaps a minimal number of keys when
+/// the node count changes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RendezvousSharder;
+
+#[derive(Debug)]
+struct HashRing {
+	nodes:  Vec<String>,
+	points: Vec<(u64, usize)>,
+}
+
+impl Sharder for ModuloSharder {
+	fn shard(&self, key: &str, nodes: &[String]) -> usize {
+		(hash(key.as_bytes()) % nodes.len() as u64) as usize
+	}
+}
+
+impl ConsistentHashSharder {
+	/// Creates a new consistent hash sharder with `virtual_nodes` points
+	/// on the ring per node.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::ConsistentHashSharder;
+	///
+	/// let sharder = ConsistentHashSharder::new(100);
+	/// ```
+	#[must_use]
+	pub fn new(virtual_nodes: usize) -> Self {
+		assert!(virtual_nodes > 0);
+
+		ConsistentHashSharder {
+			virtual_nodes,
+			ring: Mutex::new(None),
+		}
+	}
+}
+
+impl Default for ConsistentHashSharder {
+	fn default() -> Self {
+		ConsistentHashSharder::new(DEFAULT_VIRTUAL_NODES)
+	}
+}
+
+impl Sharder for ConsistentHashSharder {
+	fn shard(&self, key: &str, nodes: &[String]) -> usize {
+		let mut ring = self.ring.lock().expect("Could not obtain hash ring.");
+
+		if ring
+			.as_ref()
+			.is_none_or(|ring| ring.nodes != nodes)
+		{
+			*ring = Some(HashRing::new(nodes, self.virtual_nodes));
+		}
+
+		ring.as_ref()
+			.expect("Hash ring was not initialized.")
+			.lookup(hash(key.as_bytes()))
+	}
+}
+
+impl Sharder for RendezvousSharder {
+	fn shard(&self, key: &str, nodes: &[String]) -> usize {
+		nodes
+			.iter()
+			.enumerate()
+			.max_by_key(|(_, node)| hash_pair(node.as_bytes(), key.as_bytes()))
+			.map(|(index, _)| index)
+			.unwrap_or_default()
+	}
+}
+
+impl HashRing {
+	fn new(nodes: &[String], virtual_nodes: usize) -> Self {
+		let mut points = Vec::with_capacity(nodes.len() * virtual_nodes);
+
+		for (index, node) in nodes.iter().enumerate() {
+			for replica in 0..virtual_nodes {
+				let point = hash_pair(node.as_bytes(), &replica.to_le_bytes());
+				points.push((point, index));
+			}
+		}
+
+		points.sort_unstable();
+
+		HashRing {
+			nodes: nodes.to_vec(),
+			points,
+		}
+	}
+
+	fn lookup(&self, hash: u64) -> usize {
+		let position = self
+			.points
+			.partition_point(|(point, _)| *point < hash);
+
+		// wrap around to the start of the ring
+		self.points
+			.get(position)
+			.unwrap_or(&self.points[0])
+			.1
+	}
+}
+
+fn hash(data: &[u8]) -> u64 {
+	mix(fnv1a(data.iter()))
+}
+
+fn hash_pair(first: &[u8], second: &[u8]) -> u64 {
+	mix(fnv1a(first.iter().chain(&[0xff]).chain(second)))
+}
+
+fn fnv1a<'a>(bytes: impl Iterator<Item = &'a u8>) -> u64 {
+	bytes.fold(FNV_OFFSET_BASIS, |hash, byte| {
+		(hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+	})
+}
+
+// FNV-1a has weak avalanche on its high bits, so the result is passed
+// through a finalizer before being used for placement
+fn mix(mut hash: u64) -> u64 {
+	hash ^= hash >> 33;
+	hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+	hash ^= hash >> 33;
+	hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+	hash ^ (hash >> 33)
+}
@@ -0,0 +1,41 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	process,
+	sync::atomic::{AtomicU64, Ordering},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The suffix appended to a key to form the key of its compute lock.
+pub const LOCK_KEY_SUFFIX: &str = "#lock";
+
+/// The ttl of a compute lock, after which it expires if its holder fails to
+/// release it. Processes waiting on a lock compute the value themselves
+/// once it has been held for this long.
+pub const LOCK_TTL: Duration = Duration::from_secs(30);
+
+pub(crate) const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+static LOCK_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) fn lock_key(key: &str) -> String {
+	format!("{key}{LOCK_KEY_SUFFIX}")
+}
+
+// a token which is unique to this acquisition across processes and hosts
+// which do not share a pid and start time
+pub(crate) fn lock_token() -> String {
+	let nanos = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|duration| duration.as_nanos())
+		.unwrap_or_default();
+
+	let count = LOCK_COUNTER.fetch_add(1, Ordering::Relaxed);
+
+	format!("{}:{nanos}:{count}", process::id())
+}
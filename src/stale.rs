@@ -0,0 +1,58 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::value::PaperValue;
+
+/// The suffix appended to a key to form the key of its stale marker.
+pub const STALE_MARKER_SUFFIX: &str = "#stale";
+
+pub(crate) const STALE_MARKER_VALUE: &str = "1";
+
+/// The result of a `get_allow_stale` command: the value, along with whether
+/// it was marked stale by `invalidate_soft`.
+///
+/// Only one reader observes each soft invalidation as stale, as reading
+/// the value consumes its marker. That reader is responsible for
+/// refreshing the value, while all other readers continue to be served the
+/// stale value until it is refreshed.
+#[derive(Debug)]
+pub struct StaleResult {
+	value:    PaperValue,
+	is_stale: bool,
+}
+
+impl StaleResult {
+	pub(crate) fn new(value: PaperValue, is_stale: bool) -> Self {
+		StaleResult {
+			value,
+			is_stale,
+		}
+	}
+
+	/// Returns `true` if the value was marked stale, in which case the
+	/// caller should refresh it.
+	#[must_use]
+	pub fn is_stale(&self) -> bool {
+		self.is_stale
+	}
+
+	/// Returns the value.
+	#[must_use]
+	pub fn value(&self) -> &PaperValue {
+		&self.value
+	}
+
+	/// Consumes the result, returning the value.
+	#[must_use]
+	pub fn into_value(self) -> PaperValue {
+		self.value
+	}
+}
+
+pub(crate) fn marker_key(key: &str) -> String {
+	format!("{key}{STALE_MARKER_SUFFIX}")
+}
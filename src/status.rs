@@ -5,9 +5,36 @@
  * LICENSE file in the root directory of this source tree.
  */
 
-use crate::policy::PaperPolicy;
-
-#[derive(Debug)]
+use std::{
+	fmt::{self, Display},
+	time::Duration,
+};
+
+use crate::{
+	error::{PaperClientError, PaperClientResult},
+	json::{JsonObjectWriter, JsonValue},
+	policy::PaperPolicy,
+};
+
+const BYTE_UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+/// The version of the JSON schema produced by [`Status::to_json`].
+pub const STATUS_SCHEMA_VERSION: u64 = 1;
+
+/// The cache's status, as returned by the `status` command. The status is
+/// displayed as an aligned, human-readable report.
+///
+/// # Examples
+/// ```
+/// use paper_client::PaperClient;
+///
+/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+///
+/// if let Ok(status) = client.status() {
+///     println!("{status}");
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
 pub struct Status {
 	pid: u32,
 
@@ -32,7 +59,8 @@ pub struct Status {
 }
 
 impl Status {
-	/// Creates a new instance of the cache's status.
+	/// Creates a new instance of the cache's status, with the uptime in
+	/// seconds.
 	#[allow(clippy::too_many_arguments)]
 	#[must_use]
 	pub fn new(
@@ -162,7 +190,217 @@ impl Status {
 
 	/// Returns the cache's uptime.
 	#[must_use]
-	pub fn uptime(&self) -> u64 {
+	pub fn uptime(&self) -> Duration {
+		Duration::from_secs(self.uptime)
+	}
+
+	/// Returns the cache's uptime in seconds.
+	#[must_use]
+	pub fn uptime_secs(&self) -> u64 {
 		self.uptime
 	}
+
+	/// Serializes the status as a single-line JSON object, so that status
+	/// snapshots can be consumed by tooling written in other languages.
+	///
+	/// The object has the following fields (version 1 of the schema):
+	///
+	/// | Field            | Type             | Description                     |
+	/// |------------------|------------------|---------------------------------|
+	/// | `schema_version` | integer          | [`STATUS_SCHEMA_VERSION`]       |
+	/// | `pid`            | integer          | the cache's PID                 |
+	/// | `max_size`       | integer          | the maximum size in bytes       |
+	/// | `used_size`      | integer          | the used size in bytes          |
+	/// | `num_objects`    | integer          | the number of objects           |
+	/// | `rss`            | integer          | the resident set size in bytes  |
+	/// | `hwm`            | integer          | the RSS high water mark         |
+	/// | `total_gets`     | integer          | the total number of gets        |
+	/// | `total_sets`     | integer          | the total number of sets        |
+	/// | `total_dels`     | integer          | the total number of dels        |
+	/// | `miss_ratio`     | number or `null` | the miss ratio (`null` if NaN)  |
+	/// | `policies`       | array of strings | the configured policies         |
+	/// | `policy`         | string           | the current policy              |
+	/// | `is_auto_policy` | boolean          | if the policy is auto           |
+	/// | `uptime_secs`    | integer          | the uptime in seconds           |
+	///
+	/// Policies are formatted as by their `Display` implementation (e.g.,
+	/// `"lfu"` or `"2q-0.25-0.5"`). Fields are only added to the schema
+	/// within a version, so consumers should ignore unknown fields.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperClient;
+	///
+	/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	///
+	/// if let Ok(status) = client.status() {
+	///     println!("{}", status.to_json());
+	/// }
+	/// ```
+	#[must_use]
+	pub fn to_json(&self) -> String {
+		JsonObjectWriter::new()
+			.u64("schema_version", STATUS_SCHEMA_VERSION)
+			.u64("pid", self.pid.into())
+			.u64("max_size", self.max_size)
+			.u64("used_size", self.used_size)
+			.u64("num_objects", self.num_objects)
+			.u64("rss", self.rss)
+			.u64("hwm", self.hwm)
+			.u64("total_gets", self.total_gets)
+			.u64("total_sets", self.total_sets)
+			.u64("total_dels", self.total_dels)
+			.f64("miss_ratio", self.miss_ratio)
+			.strs("policies", &self.policies)
+			.str("policy", &self.policy.to_string())
+			.bool("is_auto_policy", self.is_auto_policy)
+			.u64("uptime_secs", self.uptime)
+			.finish()
+	}
+
+	/// Deserializes a status serialized by [`Status::to_json`]. Unknown
+	/// fields are ignored. If the JSON is malformed, a field is missing, or
+	/// the schema version is not supported, `PaperClientError::InvalidJson`
+	/// is returned.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::Status;
+	///
+	/// let json = r#"{"schema_version":1,"pid":42,"max_size":1024,"used_size":0,
+	///     "num_objects":0,"rss":0,"hwm":0,"total_gets":0,"total_sets":0,
+	///     "total_dels":0,"miss_ratio":0,"policies":["lfu"],"policy":"lfu",
+	///     "is_auto_policy":false,"uptime_secs":10}"#;
+	///
+	/// let status = Status::from_json(json).unwrap();
+	/// assert_eq!(status.pid(), 42);
+	/// ```
+	pub fn from_json(json: &str) -> PaperClientResult<Self> {
+		let value = JsonValue::parse(json)?;
+
+		if json_field(&value, "schema_version", JsonValue::as_u64)? != STATUS_SCHEMA_VERSION {
+			return Err(PaperClientError::InvalidJson);
+		}
+
+		let pid = json_field(&value, "pid", JsonValue::as_u64)?
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidJson)?;
+
+		let policies = json_field(&value, "policies", JsonValue::as_array)?
+			.iter()
+			.map(json_policy)
+			.collect::<PaperClientResult<Vec<_>>>()?;
+
+		let policy = json_policy(
+			value
+				.field("policy")
+				.ok_or(PaperClientError::InvalidJson)?,
+		)?;
+
+		Ok(Status {
+			pid,
+
+			max_size:    json_field(&value, "max_size", JsonValue::as_u64)?,
+			used_size:   json_field(&value, "used_size", JsonValue::as_u64)?,
+			num_objects: json_field(&value, "num_objects", JsonValue::as_u64)?,
+
+			rss: json_field(&value, "rss", JsonValue::as_u64)?,
+			hwm: json_field(&value, "hwm", JsonValue::as_u64)?,
+
+			total_gets: json_field(&value, "total_gets", JsonValue::as_u64)?,
+			total_sets: json_field(&value, "total_sets", JsonValue::as_u64)?,
+			total_dels: json_field(&value, "total_dels", JsonValue::as_u64)?,
+
+			miss_ratio: json_field(&value, "miss_ratio", JsonValue::as_f64)?,
+
+			policies,
+			policy,
+			is_auto_policy: json_field(&value, "is_auto_policy", JsonValue::as_bool)?,
+
+			uptime: json_field(&value, "uptime_secs", JsonValue::as_u64)?,
+		})
+	}
+}
+
+impl Display for Status {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let used_ratio = match self.max_size {
+			0 => 0.0,
+			max_size => self.used_size as f64 / max_size as f64,
+		};
+
+		let policy = match self.is_auto_policy {
+			true => format!("{} (auto)", self.policy),
+			false => self.policy.to_string(),
+		};
+
+		let policies = self
+			.policies
+			.iter()
+			.map(PaperPolicy::to_string)
+			.collect::<Vec<_>>()
+			.join(", ");
+
+		writeln!(f, "pid:         {}", self.pid)?;
+		writeln!(f, "max size:    {}", format_bytes(self.max_size))?;
+		writeln!(f, "used size:   {} ({:.2}%)", format_bytes(self.used_size), used_ratio * 100.0)?;
+		writeln!(f, "objects:     {}", self.num_objects)?;
+		writeln!(f, "rss:         {}", format_bytes(self.rss))?;
+		writeln!(f, "hwm:         {}", format_bytes(self.hwm))?;
+		writeln!(f, "gets:        {}", self.total_gets)?;
+		writeln!(f, "sets:        {}", self.total_sets)?;
+		writeln!(f, "dels:        {}", self.total_dels)?;
+		writeln!(f, "miss ratio:  {:.2}%", self.miss_ratio * 100.0)?;
+		writeln!(f, "policy:      {policy}")?;
+		writeln!(f, "policies:    {policies}")?;
+		write!(f, "uptime:      {}", format_uptime(self.uptime))
+	}
+}
+
+fn json_field<'a, T>(
+	value: &'a JsonValue,
+	name: &str,
+	convert: impl FnOnce(&'a JsonValue) -> Option<T>,
+) -> PaperClientResult<T> {
+	value
+		.field(name)
+		.and_then(convert)
+		.ok_or(PaperClientError::InvalidJson)
+}
+
+fn json_policy(value: &JsonValue) -> PaperClientResult<PaperPolicy> {
+	value
+		.as_str()
+		.and_then(|policy| policy.parse().ok())
+		.ok_or(PaperClientError::InvalidJson)
+}
+
+fn format_bytes(bytes: u64) -> String {
+	let mut size = bytes as f64;
+	let mut unit = 0;
+
+	while size >= 1024.0 && unit < BYTE_UNITS.len() - 1 {
+		size /= 1024.0;
+		unit += 1;
+	}
+
+	match unit {
+		0 => format!("{bytes} B"),
+		unit => format!("{size:.2} {}", BYTE_UNITS[unit]),
+	}
+}
+
+// formats the supplied number of seconds, omitting leading zero units
+fn format_uptime(seconds: u64) -> String {
+	let days = seconds / 86_400;
+	let hours = seconds % 86_400 / 3_600;
+	let minutes = seconds % 3_600 / 60;
+	let seconds = seconds % 60;
+
+	match (days, hours, minutes) {
+		(0, 0, 0) => format!("{seconds}s"),
+		(0, 0, _) => format!("{minutes}m {seconds}s"),
+		(0, _, _) => format!("{hours}h {minutes}m {seconds}s"),
+		_ => format!("{days}d {hours}h {minutes}m {seconds}s"),
+	}
 }
@@ -0,0 +1,139 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	panic,
+	sync::{Arc, Mutex},
+};
+
+use tokio::task;
+
+use crate::{
+	client::PaperClient,
+	deadline::{current_deadline, with_deadline},
+	error::{PaperClientError, PaperClientResult},
+};
+
+/// A handle which calls a sync `PaperClient` from async code without
+/// blocking the runtime, for codebases which are migrating between
+/// `PaperClient` and `AsyncPaperClient`. Each call is run on tokio's
+/// blocking thread pool via `spawn_blocking`, so the handle must be used
+/// from within a tokio runtime.
+///
+/// Calls through the same handle (or its clones) are serialized, since
+/// they share the underlying client. A deadline set with
+/// `with_deadline_async` applies to the call as it would to an
+/// `AsyncPaperClient`.
+///
+/// # Examples
+/// ```ignore
+/// use paper_client::{PaperClient, SyncHandle};
+///
+/// let client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+/// let handle = SyncHandle::new(client);
+///
+/// match handle.run(|client| client.get("key")).await {
+///     Ok(value) => println!("{value:?}"),
+///     Err(err) => println!("{err:?}"),
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SyncHandle {
+	client: Arc<Mutex<PaperClient>>,
+}
+
+impl SyncHandle {
+	/// Creates a handle which owns the supplied client.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{PaperClient, SyncHandle};
+	///
+	/// let client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	/// let handle = SyncHandle::new(client);
+	/// ```
+	#[must_use]
+	pub fn new(client: PaperClient) -> Self {
+		SyncHandle {
+			client: Arc::new(Mutex::new(client)),
+		}
+	}
+
+	/// Runs the supplied closure with the handle's client on tokio's
+	/// blocking thread pool and returns its result. A panic in the closure
+	/// is resumed in the calling task. Returns `Internal` if the runtime
+	/// shuts down before the closure has run.
+	///
+	/// # Examples
+	/// ```ignore
+	/// use paper_client::{PaperClient, SyncHandle};
+	///
+	/// let client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	/// let handle = SyncHandle::new(client);
+	///
+	/// handle.run(|client| client.set("key", "value", None)).await.unwrap();
+	/// ```
+	pub async fn run<F, R>(&self, f: F) -> PaperClientResult<R>
+	where
+		F: FnOnce(&mut PaperClient) -> PaperClientResult<R> + Send + 'static,
+		R: Send + 'static,
+	{
+		let client = self.client.clone();
+
+		// the deadline of the calling task does not follow the closure onto
+		// the blocking thread, so it is set again there
+		let deadline = current_deadline();
+
+		let result = task::spawn_blocking(move || {
+			let mut client = client
+				.lock()
+				.expect("Could not obtain client.");
+
+			match deadline {
+				Some(deadline) => with_deadline(deadline, || f(&mut client)),
+				None => f(&mut client),
+			}
+		})
+		.await;
+
+		match result {
+			Ok(result) => result,
+			Err(err) if err.is_panic() => panic::resume_unwind(err.into_panic()),
+			Err(_) => Err(PaperClientError::Internal),
+		}
+	}
+
+	/// Returns the handle's client if no other clone of the handle exists,
+	/// otherwise returns the handle.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::{PaperClient, SyncHandle};
+	///
+	/// let client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+	/// let handle = SyncHandle::new(client);
+	///
+	/// let client = handle.into_inner().unwrap();
+	/// ```
+	pub fn into_inner(self) -> Result<PaperClient, Self> {
+		match Arc::try_unwrap(self.client) {
+			Ok(client) => Ok(client
+				.into_inner()
+				.expect("Could not obtain client.")),
+
+			Err(client) => Err(SyncHandle {
+				client,
+			}),
+		}
+	}
+}
+
+impl From<PaperClient> for SyncHandle {
+	fn from(client: PaperClient) -> Self {
+		SyncHandle::new(client)
+	}
+}
@@ -0,0 +1,423 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{
+	collections::HashMap,
+	net::TcpStream,
+	sync::{
+		Arc,
+		Mutex,
+		atomic::{AtomicU64, Ordering},
+	},
+};
+
+#[cfg(feature = "tokio")]
+use crate::{async_client::AsyncPaperClient, transport::AsyncPaperTransport};
+use crate::{
+	arg::{AsPaperKey, AsPaperTtl},
+	client::PaperClient,
+	error::{PaperCacheError, PaperClientError, PaperClientResult},
+	transport::PaperTransport,
+	value::PaperValue,
+};
+
+/// The delimiter between a tenant's name and its keys, so that the key
+/// `user:42` of the tenant `team-a` is stored as `team-a/user:42`.
+pub const TENANT_DELIMITER: char = '/';
+
+/// A view of a client for a single tenant (e.g., an internal team sharing a
+/// pool), created with `PaperClient::tenant`. The tenant's keys are
+/// namespaced by its name, and its commands and bytes are accounted for in
+/// the usage shared by every client created from the same configuration
+/// (e.g., every client of a pool), which is returned by `tenant_usage`.
+///
+/// Only the key commands (`get`, `set`, `del`, `has`, `peek`, `ttl`, and
+/// `size`) are available to a tenant.
+///
+/// # Examples
+/// ```
+/// use paper_client::PaperPool;
+///
+/// let pool = PaperPool::new("paper://127.0.0.1:3145", 4).unwrap();
+///
+/// let _ = pool.client().tenant("team-a").set("user:42", "value", None);
+/// let _ = pool.client().tenant("team-b").get("user:42");
+///
+/// for usage in pool.tenant_usage() {
+///     println!("{}: {} bytes written", usage.tenant(), usage.bytes_written());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Tenant<'a, T = TcpStream> {
+	client:    &'a mut PaperClient<T>,
+	namespace: String,
+	recorder:  Arc<TenantRecorder>,
+}
+
+/// The async counterpart of `Tenant`, created with
+/// `AsyncPaperClient::tenant`.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncTenant<'a, T = tokio::net::TcpStream> {
+	client:    &'a mut AsyncPaperClient<T>,
+	namespace: String,
+	recorder:  Arc<TenantRecorder>,
+}
+
+/// A snapshot of the commands issued and bytes transferred by a tenant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TenantUsage {
+	tenant: String,
+
+	requests: u64,
+	errors:   u64,
+
+	bytes_read:    u64,
+	bytes_written: u64,
+}
+
+/// The usage of every tenant, shared by the clients created from the same
+/// configuration.
+#[derive(Debug, Default)]
+pub(crate) struct TenantRegistry {
+	tenants: Mutex<HashMap<String, Arc<TenantRecorder>>>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct TenantRecorder {
+	requests: AtomicU64,
+	errors:   AtomicU64,
+
+	bytes_read:    AtomicU64,
+	bytes_written: AtomicU64,
+}
+
+impl<'a, T: PaperTransport> Tenant<'a, T> {
+	pub(crate) fn new(
+		client: &'a mut PaperClient<T>,
+		tenant: &str,
+		recorder: Arc<TenantRecorder>,
+	) -> Self {
+		Tenant {
+			client,
+			namespace: namespace(tenant),
+			recorder,
+		}
+	}
+
+	/// Gets the value of the supplied key from the tenant's namespace. See
+	/// `PaperClient::get`.
+	pub fn get(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = self.key(key);
+		let result = self.client.get(&key);
+
+		self.recorder.record_read(&result);
+		result
+	}
+
+	/// Sets the supplied key, value, and ttl to the tenant's namespace. See
+	/// `PaperClient::set`.
+	pub fn set(
+		&mut self,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let key = self.key(key);
+
+		let Ok(value) = value.try_into() else {
+			return Err(PaperClientError::InvalidValue);
+		};
+
+		let size = value.len();
+		let result = self.client.set(&key, value, ttl);
+
+		self.recorder.record_write(&result, size);
+		result
+	}
+
+	/// Deletes the value of the supplied key from the tenant's namespace.
+	/// See `PaperClient::del`.
+	pub fn del(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
+		let key = self.key(key);
+		let result = self.client.del(&key);
+
+		self.recorder.record(&result);
+		result
+	}
+
+	/// Checks if the tenant's namespace contains the supplied key. See
+	/// `PaperClient::has`.
+	pub fn has(&mut self, key: impl AsPaperKey) -> PaperClientResult<bool> {
+		let key = self.key(key);
+		let result = self.client.has(&key);
+
+		self.recorder.record(&result);
+		result
+	}
+
+	/// Peeks the value of the supplied key from the tenant's namespace. See
+	/// `PaperClient::peek`.
+	pub fn peek(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = self.key(key);
+		let result = self.client.peek(&key);
+
+		self.recorder.record_read(&result);
+		result
+	}
+
+	/// Sets the ttl of the supplied key in the tenant's namespace. See
+	/// `PaperClient::ttl`.
+	pub fn ttl(&mut self, key: impl AsPaperKey, ttl: impl AsPaperTtl) -> PaperClientResult<()> {
+		let key = self.key(key);
+		let result = self.client.ttl(&key, ttl);
+
+		self.recorder.record(&result);
+		result
+	}
+
+	/// Gets the size of the value of the supplied key in the tenant's
+	/// namespace. See `PaperClient::size`.
+	pub fn size(&mut self, key: impl AsPaperKey) -> PaperClientResult<u32> {
+		let key = self.key(key);
+		let result = self.client.size(&key);
+
+		self.recorder.record(&result);
+		result
+	}
+
+	/// Returns a snapshot of the tenant's usage.
+	#[must_use]
+	pub fn usage(&self) -> TenantUsage {
+		self.recorder.snapshot(self.tenant())
+	}
+
+	fn tenant(&self) -> &str {
+		&self.namespace[..self.namespace.len() - TENANT_DELIMITER.len_utf8()]
+	}
+
+	fn key(&self, key: impl AsPaperKey) -> String {
+		format!("{}{}", self.namespace, key.as_paper_key())
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl<'a, T: AsyncPaperTransport> AsyncTenant<'a, T> {
+	pub(crate) fn new(
+		client: &'a mut AsyncPaperClient<T>,
+		tenant: &str,
+		recorder: Arc<TenantRecorder>,
+	) -> Self {
+		AsyncTenant {
+			client,
+			namespace: namespace(tenant),
+			recorder,
+		}
+	}
+
+	/// Gets the value of the supplied key from the tenant's namespace. See
+	/// `AsyncPaperClient::get`.
+	pub async fn get(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = self.key(key);
+		let result = self.client.get(&key).await;
+
+		self.recorder.record_read(&result);
+		result
+	}
+
+	/// Sets the supplied key, value, and ttl to the tenant's namespace. See
+	/// `AsyncPaperClient::set`.
+	pub async fn set(
+		&mut self,
+		key: impl AsPaperKey,
+		value: impl TryInto<PaperValue>,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let key = self.key(key);
+
+		let Ok(value) = value.try_into() else {
+			return Err(PaperClientError::InvalidValue);
+		};
+
+		let size = value.len();
+		let result = self.client.set(&key, value, ttl).await;
+
+		self.recorder.record_write(&result, size);
+		result
+	}
+
+	/// Deletes the value of the supplied key from the tenant's namespace.
+	/// See `AsyncPaperClient::del`.
+	pub async fn del(&mut self, key: impl AsPaperKey) -> PaperClientResult<()> {
+		let key = self.key(key);
+		let result = self.client.del(&key).await;
+
+		self.recorder.record(&result);
+		result
+	}
+
+	/// Checks if the tenant's namespace contains the supplied key. See
+	/// `AsyncPaperClient::has`.
+	pub async fn has(&mut self, key: impl AsPaperKey) -> PaperClientResult<bool> {
+		let key = self.key(key);
+		let result = self.client.has(&key).await;
+
+		self.recorder.record(&result);
+		result
+	}
+
+	/// Peeks the value of the supplied key from the tenant's namespace. See
+	/// `AsyncPaperClient::peek`.
+	pub async fn peek(&mut self, key: impl AsPaperKey) -> PaperClientResult<PaperValue> {
+		let key = self.key(key);
+		let result = self.client.peek(&key).await;
+
+		self.recorder.record_read(&result);
+		result
+	}
+
+	/// Sets the ttl of the supplied key in the tenant's namespace. See
+	/// `AsyncPaperClient::ttl`.
+	pub async fn ttl(
+		&mut self,
+		key: impl AsPaperKey,
+		ttl: impl AsPaperTtl,
+	) -> PaperClientResult<()> {
+		let key = self.key(key);
+		let result = self.client.ttl(&key, ttl).await;
+
+		self.recorder.record(&result);
+		result
+	}
+
+	/// Gets the size of the value of the supplied key in the tenant's
+	/// namespace. See `AsyncPaperClient::size`.
+	pub async fn size(&mut self, key: impl AsPaperKey) -> PaperClientResult<u32> {
+		let key = self.key(key);
+		let result = self.client.size(&key).await;
+
+		self.recorder.record(&result);
+		result
+	}
+
+	/// Returns a snapshot of the tenant's usage.
+	#[must_use]
+	pub fn usage(&self) -> TenantUsage {
+		self.recorder.snapshot(self.tenant())
+	}
+
+	fn tenant(&self) -> &str {
+		&self.namespace[..self.namespace.len() - TENANT_DELIMITER.len_utf8()]
+	}
+
+	fn key(&self, key: impl AsPaperKey) -> String {
+		format!("{}{}", self.namespace, key.as_paper_key())
+	}
+}
+
+impl TenantUsage {
+	/// Returns the tenant's name.
+	#[must_use]
+	pub fn tenant(&self) -> &str {
+		&self.tenant
+	}
+
+	/// Returns the number of commands issued by the tenant.
+	#[must_use]
+	pub fn requests(&self) -> u64 {
+		self.requests
+	}
+
+	/// Returns the number of the tenant's commands which failed, other than
+	/// because the key was not found.
+	#[must_use]
+	pub fn errors(&self) -> u64 {
+		self.errors
+	}
+
+	/// Returns the number of value bytes read by the tenant's `get`s and
+	/// `peek`s.
+	#[must_use]
+	pub fn bytes_read(&self) -> u64 {
+		self.bytes_read
+	}
+
+	/// Returns the number of value bytes written by the tenant's `set`s.
+	#[must_use]
+	pub fn bytes_written(&self) -> u64 {
+		self.bytes_written
+	}
+}
+
+impl TenantRegistry {
+	/// Returns a snapshot of the usage of every tenant, ordered by name.
+	pub(crate) fn usage(&self) -> Vec<TenantUsage> {
+		let tenants = self.tenants.lock().expect("Could not obtain tenants.");
+
+		let mut usage = tenants
+			.iter()
+			.map(|(tenant, recorder)| recorder.snapshot(tenant))
+			.collect::<Vec<_>>();
+
+		usage.sort_by(|a, b| a.tenant.cmp(&b.tenant));
+		usage
+	}
+
+	pub(crate) fn recorder(&self, tenant: &str) -> Arc<TenantRecorder> {
+		self.tenants
+			.lock()
+			.expect("Could not obtain tenants.")
+			.entry(tenant.to_owned())
+			.or_default()
+			.clone()
+	}
+}
+
+impl TenantRecorder {
+	fn record<R>(&self, result: &PaperClientResult<R>) {
+		self.requests.fetch_add(1, Ordering::Relaxed);
+
+		match result {
+			Ok(_) | Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)) => {},
+			Err(_) => {
+				self.errors.fetch_add(1, Ordering::Relaxed);
+			},
+		}
+	}
+
+	fn record_read(&self, result: &PaperClientResult<PaperValue>) {
+		self.record(result);
+
+		if let Ok(value) = result {
+			self.bytes_read.fetch_add(value.len() as u64, Ordering::Relaxed);
+		}
+	}
+
+	fn record_write(&self, result: &PaperClientResult<()>, size: usize) {
+		self.record(result);
+
+		if result.is_ok() {
+			self.bytes_written.fetch_add(size as u64, Ordering::Relaxed);
+		}
+	}
+
+	fn snapshot(&self, tenant: &str) -> TenantUsage {
+		TenantUsage {
+			tenant: tenant.to_owned(),
+
+			requests: self.requests.load(Ordering::Relaxed),
+			errors:   self.errors.load(Ordering::Relaxed),
+
+			bytes_read:    self.bytes_read.load(Ordering::Relaxed),
+			bytes_written: self.bytes_written.load(Ordering::Relaxed),
+		}
+	}
+}
+
+fn namespace(tenant: &str) -> String {
+	format!("{tenant}{TENANT_DELIMITER}")
+}
@@ -0,0 +1,466 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+#[cfg(feature = "tokio")]
+use std::future::Future;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::{
+	fmt::{self, Debug},
+	io::{self, Read, Write},
+	net::{Shutdown, SocketAddr, TcpStream, ToSocketAddrs},
+	sync::mpsc::{self, RecvTimeoutError},
+	thread,
+	time::Duration,
+};
+
+#[cfg(feature = "tokio")]
+use tokio::{
+	io::{AsyncRead, AsyncWrite},
+	net,
+	task::JoinSet,
+	time,
+};
+
+use crate::{
+	config::PaperClientConfig,
+	connection_info::{ConnectionInfo, PeerAddr},
+	error::{PaperClientError, PaperClientResult},
+};
+
+// the delay before starting the next connection attempt when an address
+// resolves to multiple socket addresses, as recommended by RFC 8305
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// A bidirectional byte stream over which a `PaperClient` communicates
+/// with the server. Implementing this trait allows the client to be used
+/// over a new transport without any changes to the client itself.
+///
+/// The transport is implemented for `TcpStream` (the default) and, on Unix
+/// platforms, `UnixStream`, in which case the address is the path of the
+/// server's socket (e.g., `paper:///tmp/paper.sock`). When a TCP address
+/// resolves to multiple socket addresses (e.g., both IPv4 and IPv6), they
+/// are attempted in parallel with staggered starts and the first
+/// successful connection is kept.
+pub trait PaperTransport: Read + Write + Send + Sized {
+	/// Opens a new connection to the supplied address. If the connection
+	/// could not be established, `UnreachableServer` is returned.
+	fn open(addr: &str) -> PaperClientResult<Self>;
+
+	/// Opens a new connection to the supplied address with the client's
+	/// configuration. By default, the configuration is ignored and the
+	/// connection is opened with `open`.
+	fn open_with(addr: &str, config: &PaperClientConfig) -> PaperClientResult<Self> {
+		let _ = config;
+		Self::open(addr)
+	}
+
+	/// Sets the maximum time a read may block for. If `None`, reads block
+	/// indefinitely.
+	fn set_read_timeout(&mut self, timeout: Option<Duration>) -> PaperClientResult<()>;
+
+	/// Closes the connection. Any subsequent reads or writes fail. By
+	/// default, the connection is only closed when the transport is dropped.
+	fn shutdown(&mut self) -> PaperClientResult<()> {
+		Ok(())
+	}
+
+	/// Returns the details of the connection (e.g., the address of the
+	/// server it landed on, or its TLS parameters). By default, no details
+	/// are reported.
+	fn connection_info(&self) -> ConnectionInfo {
+		ConnectionInfo::default()
+	}
+}
+
+/// The object-safe part of a `PaperTransport`, which allows a transport to
+/// be boxed by a `BoxedTransport`. This trait is implemented for every
+/// `PaperTransport`.
+pub trait DynPaperTransport: Read + Write + Send {
+	/// Sets the maximum time a read may block for. If `None`, reads block
+	/// indefinitely.
+	fn set_read_timeout(&mut self, timeout: Option<Duration>) -> PaperClientResult<()>;
+
+	/// Closes the connection. Any subsequent reads or writes fail.
+	fn shutdown(&mut self) -> PaperClientResult<()>;
+
+	/// Returns the details of the connection.
+	fn connection_info(&self) -> ConnectionInfo;
+}
+
+/// Opens the connections of a `BoxedTransport` whose transport is not built
+/// into the client (e.g., a TLS stream). The connector is set with
+/// `PaperClientConfig::connector`.
+///
+/// # Examples
+/// ```
+/// use std::net::TcpStream;
+/// use paper_client::{
+///     DynPaperTransport,
+///     PaperConnector,
+///     PaperTransport,
+///     error::PaperClientResult,
+/// };
+///
+/// #[derive(Debug)]
+/// struct TcpConnector;
+///
+/// impl PaperConnector for TcpConnector {
+///     fn connect(&self, addr: &str) -> PaperClientResult<Box<dyn DynPaperTransport>> {
+///         let stream = <TcpStream as PaperTransport>::open(addr)?;
+///         Ok(Box::new(stream))
+///     }
+/// }
+/// ```
+pub trait PaperConnector: Debug + Send + Sync {
+	/// Opens a new connection to the supplied address. If the connection
+	/// could not be established, `UnreachableServer` should be returned.
+	fn connect(&self, addr: &str) -> PaperClientResult<Box<dyn DynPaperTransport>>;
+}
+
+/// A transport which is selected at run time, so that a `PaperClient` can
+/// connect over TCP, a Unix socket, or a transport opened by a
+/// `PaperConnector` (e.g., TLS) without the client's type depending on the
+/// choice.
+///
+/// If the client is configured with a `PaperConnector`, each connection is
+/// opened by the connector. Otherwise, an address which is an absolute path
+/// (e.g., `paper:///tmp/paper.sock`) is connected to over a Unix socket on
+/// Unix platforms, and any other address over TCP.
+///
+/// # Examples
+/// ```
+/// use paper_client::{BoxedTransport, PaperClient, PaperClientConfig};
+///
+/// let client = PaperClient::<BoxedTransport>::with_transport(
+///     "paper://127.0.0.1:3145",
+///     PaperClientConfig::default(),
+/// )
+/// .unwrap();
+/// ```
+pub enum BoxedTransport {
+	Tcp(TcpStream),
+	#[cfg(unix)]
+	Unix(UnixStream),
+	Custom(Box<dyn DynPaperTransport>),
+}
+
+/// A bidirectional byte stream over which an `AsyncPaperClient`
+/// communicates with the server. Implementing this trait allows the client
+/// to be used over a new transport without any changes to the client
+/// itself.
+///
+/// The transport is implemented for tokio's `TcpStream` (the default) and,
+/// on Unix platforms, `UnixStream`.
+#[cfg(feature = "tokio")]
+pub trait AsyncPaperTransport: AsyncRead + AsyncWrite + Unpin + Send + Sized {
+	/// Opens a new connection to the supplied address. If the connection
+	/// could not be established, `UnreachableServer` is returned.
+	fn open(addr: &str) -> impl Future<Output = PaperClientResult<Self>> + Send;
+
+	/// Returns the details of the connection (e.g., the address of the
+	/// server it landed on). By default, no details are reported.
+	fn connection_info(&self) -> ConnectionInfo {
+		ConnectionInfo::default()
+	}
+}
+
+impl<T: PaperTransport> DynPaperTransport for T {
+	fn set_read_timeout(&mut self, timeout: Option<Duration>) -> PaperClientResult<()> {
+		PaperTransport::set_read_timeout(self, timeout)
+	}
+
+	fn shutdown(&mut self) -> PaperClientResult<()> {
+		PaperTransport::shutdown(self)
+	}
+
+	fn connection_info(&self) -> ConnectionInfo {
+		PaperTransport::connection_info(self)
+	}
+}
+
+impl BoxedTransport {
+	fn stream(&mut self) -> &mut dyn DynPaperTransport {
+		match self {
+			BoxedTransport::Tcp(stream) => stream,
+			#[cfg(unix)]
+			BoxedTransport::Unix(stream) => stream,
+			BoxedTransport::Custom(stream) => stream.as_mut(),
+		}
+	}
+}
+
+impl PaperTransport for BoxedTransport {
+	fn open(addr: &str) -> PaperClientResult<Self> {
+		#[cfg(unix)]
+		if addr.starts_with('/') {
+			return UnixStream::open(addr).map(BoxedTransport::Unix);
+		}
+
+		TcpStream::open(addr).map(BoxedTransport::Tcp)
+	}
+
+	fn open_with(addr: &str, config: &PaperClientConfig) -> PaperClientResult<Self> {
+		match &config.connector {
+			Some(connector) => connector.connect(addr).map(BoxedTransport::Custom),
+			None => BoxedTransport::open(addr),
+		}
+	}
+
+	fn set_read_timeout(&mut self, timeout: Option<Duration>) -> PaperClientResult<()> {
+		self.stream().set_read_timeout(timeout)
+	}
+
+	fn shutdown(&mut self) -> PaperClientResult<()> {
+		self.stream().shutdown()
+	}
+
+	fn connection_info(&self) -> ConnectionInfo {
+		match self {
+			BoxedTransport::Tcp(stream) => PaperTransport::connection_info(stream),
+			#[cfg(unix)]
+			BoxedTransport::Unix(stream) => PaperTransport::connection_info(stream),
+			BoxedTransport::Custom(stream) => stream.connection_info(),
+		}
+	}
+}
+
+impl Read for BoxedTransport {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		self.stream().read(buf)
+	}
+}
+
+impl Write for BoxedTransport {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.stream().write(buf)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.stream().flush()
+	}
+}
+
+impl Debug for BoxedTransport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			BoxedTransport::Tcp(stream) => f.debug_tuple("Tcp").field(stream).finish(),
+			#[cfg(unix)]
+			BoxedTransport::Unix(stream) => f.debug_tuple("Unix").field(stream).finish(),
+			BoxedTransport::Custom(_) => f.debug_tuple("Custom").finish_non_exhaustive(),
+		}
+	}
+}
+
+impl PaperTransport for TcpStream {
+	fn open(addr: &str) -> PaperClientResult<Self> {
+		let stream = connect_tcp(addr).map_err(|_| PaperClientError::UnreachableServer)?;
+
+		if stream.set_nodelay(true).is_err() {
+			return Err(PaperClientError::Internal);
+		}
+
+		Ok(stream)
+	}
+
+	fn set_read_timeout(&mut self, timeout: Option<Duration>) -> PaperClientResult<()> {
+		TcpStream::set_read_timeout(self, timeout).map_err(|_| PaperClientError::Internal)
+	}
+
+	fn shutdown(&mut self) -> PaperClientResult<()> {
+		TcpStream::shutdown(self, Shutdown::Both).map_err(|_| PaperClientError::Internal)
+	}
+
+	fn connection_info(&self) -> ConnectionInfo {
+		ConnectionInfo::new(self.peer_addr().ok().map(PeerAddr::Tcp))
+	}
+}
+
+#[cfg(unix)]
+impl PaperTransport for UnixStream {
+	fn open(addr: &str) -> PaperClientResult<Self> {
+		UnixStream::connect(addr).map_err(|_| PaperClientError::UnreachableServer)
+	}
+
+	fn set_read_timeout(&mut self, timeout: Option<Duration>) -> PaperClientResult<()> {
+		UnixStream::set_read_timeout(self, timeout).map_err(|_| PaperClientError::Internal)
+	}
+
+	fn shutdown(&mut self) -> PaperClientResult<()> {
+		UnixStream::shutdown(self, Shutdown::Both).map_err(|_| PaperClientError::Internal)
+	}
+
+	fn connection_info(&self) -> ConnectionInfo {
+		let peer_addr = self
+			.peer_addr()
+			.ok()
+			.and_then(|addr| addr.as_pathname().map(|path| PeerAddr::Unix(path.to_owned())));
+
+		ConnectionInfo::new(peer_addr)
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncPaperTransport for tokio::net::TcpStream {
+	async fn open(addr: &str) -> PaperClientResult<Self> {
+		let stream = connect_tcp_async(addr)
+			.await
+			.map_err(|_| PaperClientError::UnreachableServer)?;
+
+		if stream.set_nodelay(true).is_err() {
+			return Err(PaperClientError::Internal);
+		}
+
+		Ok(stream)
+	}
+
+	fn connection_info(&self) -> ConnectionInfo {
+		ConnectionInfo::new(self.peer_addr().ok().map(PeerAddr::Tcp))
+	}
+}
+
+#[cfg(all(unix, feature = "tokio"))]
+impl AsyncPaperTransport for tokio::net::UnixStream {
+	async fn open(addr: &str) -> PaperClientResult<Self> {
+		tokio::net::UnixStream::connect(addr)
+			.await
+			.map_err(|_| PaperClientError::UnreachableServer)
+	}
+
+	fn connection_info(&self) -> ConnectionInfo {
+		let peer_addr = self
+			.peer_addr()
+			.ok()
+			.and_then(|addr| addr.as_pathname().map(|path| PeerAddr::Unix(path.to_owned())));
+
+		ConnectionInfo::new(peer_addr)
+	}
+}
+
+fn connect_tcp(addr: &str) -> io::Result<TcpStream> {
+	let addrs = interleave(addr.to_socket_addrs()?.collect());
+
+	if addrs.len() <= 1 {
+		return TcpStream::connect(addrs.as_slice());
+	}
+
+	let (sender, receiver) = mpsc::channel();
+
+	let mut started = 0;
+	let mut failed = 0;
+	let mut last_err = None;
+
+	loop {
+		if started < addrs.len() {
+			let addr = addrs[started];
+			let sender = sender.clone();
+
+			// if the attempt succeeds after another attempt has already been
+			// kept, the send fails and the stream is dropped
+			thread::spawn(move || {
+				let _ = sender.send(TcpStream::connect(addr));
+			});
+
+			started += 1;
+		}
+
+		let result = match started < addrs.len() {
+			true => receiver.recv_timeout(CONNECTION_ATTEMPT_DELAY),
+			false => receiver
+				.recv()
+				.map_err(|_| RecvTimeoutError::Disconnected),
+		};
+
+		match result {
+			Ok(Ok(stream)) => return Ok(stream),
+
+			Ok(Err(err)) => {
+				failed += 1;
+				last_err = Some(err);
+
+				if failed == addrs.len() {
+					break;
+				}
+			},
+
+			Err(RecvTimeoutError::Timeout) => {},
+			Err(RecvTimeoutError::Disconnected) => break,
+		}
+	}
+
+	Err(last_err.unwrap_or_else(|| io::Error::from(io::ErrorKind::NotConnected)))
+}
+
+#[cfg(feature = "tokio")]
+async fn connect_tcp_async(addr: &str) -> io::Result<net::TcpStream> {
+	let addrs = interleave(net::lookup_host(addr).await?.collect());
+
+	if addrs.len() <= 1 {
+		return net::TcpStream::connect(addrs.as_slice()).await;
+	}
+
+	// dropping the set aborts any attempts which are still in progress
+	let mut attempts = JoinSet::new();
+
+	let mut started = 0;
+	let mut last_err = None;
+
+	loop {
+		if started < addrs.len() {
+			attempts.spawn(net::TcpStream::connect(addrs[started]));
+			started += 1;
+		}
+
+		let result = match started < addrs.len() {
+			true => match time::timeout(CONNECTION_ATTEMPT_DELAY, attempts.join_next()).await {
+				Ok(result) => result,
+				Err(_) => continue,
+			},
+
+			false => attempts.join_next().await,
+		};
+
+		match result {
+			Some(Ok(Ok(stream))) => return Ok(stream),
+			Some(Ok(Err(err))) => last_err = Some(err),
+			Some(Err(err)) => last_err = Some(io::Error::other(err)),
+
+			None if started == addrs.len() => break,
+			None => {},
+		}
+	}
+
+	Err(last_err.unwrap_or_else(|| io::Error::from(io::ErrorKind::NotConnected)))
+}
+
+/// Orders the supplied addresses so that address families alternate,
+/// starting with the family of the first (preferred) address.
+fn interleave(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+	let Some(prefers_ipv6) = addrs.first().map(SocketAddr::is_ipv6) else {
+		return addrs;
+	};
+
+	let capacity = addrs.len();
+
+	let (preferred, other): (Vec<_>, Vec<_>) = addrs
+		.into_iter()
+		.partition(|addr| addr.is_ipv6() == prefers_ipv6);
+
+	let mut preferred = preferred.into_iter();
+	let mut other = other.into_iter();
+
+	let mut interleaved = Vec::with_capacity(capacity);
+
+	loop {
+		match (preferred.next(), other.next()) {
+			(None, None) => break,
+			(first, second) => interleaved.extend(first.into_iter().chain(second)),
+		}
+	}
+
+	interleaved
+}
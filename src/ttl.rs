@@ -0,0 +1,59 @@
+/*
+ * Copyright (c) Kia Shakiba
+ *
+ * This source code is licensed under the GNU AGPLv3 license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::{num::NonZeroU32, time::SystemTime};
+
+/// The expiry of a key, accepted wherever a ttl is (e.g., by `set` and
+/// `ttl`). Unlike a number of seconds, in which zero would ambiguously mean
+/// either no expiry or an immediate one, each expiry is explicit.
+///
+/// The server only accepts a ttl relative to when it receives the command,
+/// so `At` is converted to the number of seconds from now until the
+/// supplied time, rounded up. A time which is not in the future is rejected
+/// with `InvalidTtl`. The time is always measured against the system's wall
+/// clock, even if the client is configured with another `Clock` (e.g., a
+/// `MockClock`, which only replaces the client's monotonic time).
+///
+/// # Examples
+/// ```
+/// use std::{num::NonZeroU32, time::{Duration, SystemTime}};
+/// use paper_client::{PaperClient, Ttl};
+///
+/// let mut client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+///
+/// let _ = client.set("a", "value", Ttl::None);
+/// let _ = client.set("b", "value", Ttl::Seconds(NonZeroU32::new(60).unwrap()));
+/// let _ = client.set("c", "value", Ttl::At(SystemTime::now() + Duration::from_secs(60)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ttl {
+	/// The key never expires.
+	None,
+	/// The key expires after the number of seconds.
+	Seconds(NonZeroU32),
+	/// The key expires at the time.
+	At(SystemTime),
+}
+
+impl Ttl {
+	/// Returns the expiry corresponding to a ttl in the server's encoding,
+	/// in which zero means no expiry.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::Ttl;
+	///
+	/// assert_eq!(Ttl::from_secs(0), Ttl::None);
+	/// ```
+	#[must_use]
+	pub fn from_secs(secs: u32) -> Self {
+		match NonZeroU32::new(secs) {
+			Some(secs) => Ttl::Seconds(secs),
+			None => Ttl::None,
+		}
+	}
+}
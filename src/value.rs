@@ -6,13 +6,120 @@
  */
 
 use std::{
+	borrow::Cow,
 	fmt::{self, Formatter},
+	ops::Deref,
 	str::{self, Utf8Error},
 	string::FromUtf8Error,
 };
 
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, SecondsFormat, Utc};
+#[cfg(feature = "uuid")]
+use uuid::Uuid;
+
+#[cfg(any(feature = "uuid", feature = "chrono"))]
+use crate::error::PaperClientError;
+
+#[derive(Clone, PartialEq, Eq)]
 pub struct PaperValue(Box<[u8]>);
 
+impl PaperValue {
+	/// Returns the size of the value in bytes.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.0.len()
+	}
+
+	/// Returns `true` if the value has no bytes.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	/// Returns the value as a string slice, or a `Utf8Error` if the value
+	/// is not valid UTF-8.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperValue;
+	///
+	/// let value = PaperValue::from("value");
+	/// assert_eq!(value.as_str(), Ok("value"));
+	/// ```
+	pub fn as_str(&self) -> Result<&str, Utf8Error> {
+		str::from_utf8(&self.0)
+	}
+
+	/// Returns the value as a string, replacing any invalid UTF-8
+	/// sequences with `U+FFFD REPLACEMENT CHARACTER`. The value is only
+	/// copied if it contains invalid UTF-8.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperValue;
+	///
+	/// let value = PaperValue::from(vec![b'a', 0xff]);
+	/// assert_eq!(value.to_string_lossy(), "a\u{fffd}");
+	/// ```
+	#[must_use]
+	pub fn to_string_lossy(&self) -> Cow<'_, str> {
+		String::from_utf8_lossy(&self.0)
+	}
+
+	/// Consumes the value, returning it as a string without copying, or a
+	/// `FromUtf8Error` if the value is not valid UTF-8.
+	///
+	/// # Examples
+	/// ```
+	/// use paper_client::PaperValue;
+	///
+	/// let value = PaperValue::from("value");
+	/// assert_eq!(value.into_string().unwrap(), "value");
+	/// ```
+	pub fn into_string(self) -> Result<String, FromUtf8Error> {
+		String::from_utf8(self.0.into_vec())
+	}
+}
+
+impl Deref for PaperValue {
+	type Target = [u8];
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl AsRef<[u8]> for PaperValue {
+	fn as_ref(&self) -> &[u8] {
+		&self.0
+	}
+}
+
+impl PartialEq<[u8]> for PaperValue {
+	fn eq(&self, other: &[u8]) -> bool {
+		*self.0 == *other
+	}
+}
+
+impl PartialEq<&[u8]> for PaperValue {
+	fn eq(&self, other: &&[u8]) -> bool {
+		*self.0 == **other
+	}
+}
+
+impl PartialEq<str> for PaperValue {
+	fn eq(&self, other: &str) -> bool {
+		*self.0 == *other.as_bytes()
+	}
+}
+
+impl PartialEq<&str> for PaperValue {
+	fn eq(&self, other: &&str) -> bool {
+		*self.0 == *other.as_bytes()
+	}
+}
+
 impl From<Box<[u8]>> for PaperValue {
 	fn from(value: Box<[u8]>) -> Self {
 		PaperValue(value)
@@ -67,7 +174,7 @@ impl<'a> From<&'a PaperValue> for &'a [u8] {
 
 impl From<PaperValue> for Vec<u8> {
 	fn from(value: PaperValue) -> Self {
-		value.0.to_vec()
+		value.0.into_vec()
 	}
 }
 
@@ -75,7 +182,7 @@ impl<'a> TryFrom<&'a PaperValue> for &'a str {
 	type Error = Utf8Error;
 
 	fn try_from(value: &'a PaperValue) -> Result<Self, Self::Error> {
-		str::from_utf8(&value.0)
+		value.as_str()
 	}
 }
 
@@ -83,7 +190,52 @@ impl TryFrom<PaperValue> for String {
 	type Error = FromUtf8Error;
 
 	fn try_from(value: PaperValue) -> Result<Self, Self::Error> {
-		String::from_utf8(value.0.to_vec())
+		value.into_string()
+	}
+}
+
+/// Stores the UUID in its hyphenated, lowercase form (e.g.,
+/// `67e55044-10b1-426f-9247-bb680e5fe0c8`).
+#[cfg(feature = "uuid")]
+impl From<Uuid> for PaperValue {
+	fn from(value: Uuid) -> Self {
+		value.hyphenated().to_string().into()
+	}
+}
+
+/// Reads a UUID in any of the forms accepted by `Uuid::parse_str` (e.g.,
+/// hyphenated or simple), as written by clients in other languages.
+#[cfg(feature = "uuid")]
+impl TryFrom<PaperValue> for Uuid {
+	type Error = PaperClientError;
+
+	fn try_from(value: PaperValue) -> Result<Self, Self::Error> {
+		Uuid::try_parse_ascii(&value.0).map_err(|_| PaperClientError::InvalidConversion)
+	}
+}
+
+/// Stores the time in RFC 3339 form, in UTC with as many fractional digits
+/// as the time has (e.g., `2024-05-01T12:30:00Z` or
+/// `2024-05-01T12:30:00.250Z`).
+#[cfg(feature = "chrono")]
+impl From<DateTime<Utc>> for PaperValue {
+	fn from(value: DateTime<Utc>) -> Self {
+		value.to_rfc3339_opts(SecondsFormat::AutoSi, true).into()
+	}
+}
+
+/// Reads a time in RFC 3339 form with any offset, which is converted to
+/// UTC.
+#[cfg(feature = "chrono")]
+impl TryFrom<PaperValue> for DateTime<Utc> {
+	type Error = PaperClientError;
+
+	fn try_from(value: PaperValue) -> Result<Self, Self::Error> {
+		let value = value.as_str().map_err(|_| PaperClientError::InvalidConversion)?;
+
+		DateTime::parse_from_rfc3339(value)
+			.map(|time| time.with_timezone(&Utc))
+			.map_err(|_| PaperClientError::InvalidConversion)
 	}
 }
 
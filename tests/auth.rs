@@ -1,5 +1,32 @@
 mod common;
 
+use std::{
+	env,
+	fs,
+	io::Read,
+	sync::atomic::{AtomicUsize, Ordering},
+	thread,
+	time::Duration,
+};
+
+#[cfg(feature = "tokio")]
+use paper_client::{AsyncStreamReader, DecodePaperResponseAsync};
+use paper_client::{
+	CallbackTokenProvider,
+	Command,
+	DecodePaperResponse,
+	EnvTokenProvider,
+	FileTokenProvider,
+	PaperClientError,
+	StreamReader,
+	error::PaperClientResult,
+};
+#[cfg(feature = "tokio")]
+use tokio::io::AsyncRead;
+
+// fails without reading the payload, so that the next command reconnects
+struct Rejecting;
+
 #[test]
 fn auth_incorrect() {
 	let mut client = common::init_client(false);
@@ -33,3 +60,172 @@ async fn auth_correct_async() {
 	let result = client.auth("auth_token").await;
 	assert!(result.is_ok());
 }
+
+#[test]
+fn auth_with_callback() {
+	let mut client = common::init_client(false);
+
+	let provider = CallbackTokenProvider::new(|| Ok("auth_token".to_owned()));
+	assert!(client.auth_with(provider).is_ok());
+	assert!(client.has("key").is_ok());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn auth_with_callback_async() {
+	let mut client = common::init_async_client(false).await;
+
+	let provider = CallbackTokenProvider::new(|| Ok("auth_token".to_owned()));
+	assert!(client.auth_with(provider).await.is_ok());
+	assert!(client.has("key").await.is_ok());
+}
+
+#[test]
+fn auth_with_env_unavailable() {
+	let mut client = common::init_client(false);
+
+	let result = client.auth_with(EnvTokenProvider::new("PAPER_CLIENT_MISSING_TOKEN"));
+	assert_eq!(result, Err(PaperClientError::AuthTokenUnavailable));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn auth_with_env_unavailable_async() {
+	let mut client = common::init_async_client(false).await;
+
+	let result = client
+		.auth_with(EnvTokenProvider::new("PAPER_CLIENT_MISSING_TOKEN"))
+		.await;
+
+	assert_eq!(result, Err(PaperClientError::AuthTokenUnavailable));
+}
+
+#[test]
+fn auth_reauth_file() {
+	let path = env::temp_dir().join("paper_client_auth_reauth_file");
+	fs::write(&path, "incorrect_auth_token\n").unwrap();
+
+	let mut client = common::init_client(false);
+
+	assert!(client.auth_with(FileTokenProvider::new(&path)).is_err());
+
+	// ensure the modification time changes
+	thread::sleep(Duration::from_millis(10));
+	fs::write(&path, "auth_token\n").unwrap();
+
+	assert!(client.reauth().is_ok());
+	assert!(client.has("key").is_ok());
+
+	fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn auth_reauth_file_async() {
+	let path = env::temp_dir().join("paper_client_auth_reauth_file_async");
+	fs::write(&path, "incorrect_auth_token\n").unwrap();
+
+	let mut client = common::init_async_client(false).await;
+
+	assert!(client.auth_with(FileTokenProvider::new(&path)).await.is_err());
+
+	// ensure the modification time changes
+	thread::sleep(Duration::from_millis(10));
+	fs::write(&path, "auth_token\n").unwrap();
+
+	assert!(client.reauth().await.is_ok());
+	assert!(client.has("key").await.is_ok());
+
+	fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn auth_rotate_token() {
+	let mut client = common::init_client(false);
+
+	assert!(client.auth("incorrect_auth_token").is_err());
+	assert!(client.rotate_token("auth_token").is_ok());
+	assert!(client.has("key").is_ok());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn auth_rotate_token_async() {
+	let mut client = common::init_async_client(false).await;
+
+	assert!(client.auth("incorrect_auth_token").await.is_err());
+	assert!(client.rotate_token("auth_token").await.is_ok());
+	assert!(client.has("key").await.is_ok());
+}
+
+#[test]
+fn auth_pipelined_after_reconnect() {
+	let mut client = common::init_client(true);
+	force_reconnect(&mut client);
+
+	assert!(client.has("key").is_ok());
+
+	// the auth is sent along with the command rather than on its own
+	let stats = client.client_stats();
+	assert_eq!(stats.reconnects(), 1);
+	assert_eq!(stats.commands(), 4);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn auth_pipelined_after_reconnect_async() {
+	let mut client = common::init_async_client(true).await;
+
+	assert!(client.execute(Command::Ping, Rejecting).await.is_err());
+	assert!(client.has("key").await.is_ok());
+
+	let stats = client.client_stats();
+	assert_eq!(stats.reconnects(), 1);
+	assert_eq!(stats.commands(), 4);
+}
+
+#[test]
+fn auth_pipelined_rejected() {
+	let mut client = common::init_client(false);
+	let calls = AtomicUsize::default();
+
+	// the token is rejected once the client reconnects
+	let provider = CallbackTokenProvider::new(move || {
+		match calls.fetch_add(1, Ordering::Relaxed) {
+			0 => Ok("auth_token".to_owned()),
+			_ => Ok("incorrect_auth_token".to_owned()),
+		}
+	});
+
+	assert!(client.auth_with(provider).is_ok());
+
+	force_reconnect(&mut client);
+
+	// the command is unauthorized, but the connection is still in sync
+	assert!(matches!(client.has("key"), Err(PaperClientError::ServerError(_))));
+	assert!(client.ping().is_ok());
+}
+
+fn force_reconnect(client: &mut paper_client::PaperClient) {
+	assert!(client.execute(Command::Ping, Rejecting).is_err());
+}
+
+impl DecodePaperResponse for Rejecting {
+	type Output = ();
+
+	fn decode<R: Read>(&mut self, _: &mut StreamReader<'_, R>) -> PaperClientResult<()> {
+		Err(PaperClientError::InvalidValue)
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl DecodePaperResponseAsync for Rejecting {
+	type Output = ();
+
+	async fn decode_async<R>(&mut self, _: &mut AsyncStreamReader<'_, R>) -> PaperClientResult<()>
+	where
+		R: AsyncRead + Unpin + Send,
+	{
+		Err(PaperClientError::InvalidValue)
+	}
+}
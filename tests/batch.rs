@@ -0,0 +1,215 @@
+mod common;
+
+#[cfg(feature = "tokio")]
+use std::time::Duration;
+
+#[cfg(feature = "tokio")]
+use paper_client::{BatchFlushPolicy, BatchedWriter};
+use paper_client::{BatchResponse, PaperClientError, error::PaperCacheError};
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn batch_responses() {
+	let mut client = common::init_client(true);
+
+	let responses = client
+		.batch(|batch| {
+			batch.set("key", "value", None);
+			batch.get("key");
+			batch.has("key");
+			batch.size("key");
+			batch.get("other");
+			batch.del("key");
+			batch.has("key");
+		})
+		.unwrap();
+
+	assert_eq!(responses, vec![
+		Ok(BatchResponse::Ok),
+		Ok(BatchResponse::Value("value".into())),
+		Ok(BatchResponse::Has(true)),
+		Ok(BatchResponse::Size(5)),
+		Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)),
+		Ok(BatchResponse::Ok),
+		Ok(BatchResponse::Has(false)),
+	]);
+
+	// the connection is still in sync after the batch
+	assert!(client.ping().is_ok());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn batch_responses_async() {
+	let mut client = common::init_async_client(true).await;
+
+	let responses = client
+		.batch(|batch| {
+			batch.set("key", "value", None);
+			batch.get("key");
+			batch.has("key");
+			batch.size("key");
+			batch.get("other");
+			batch.del("key");
+			batch.has("key");
+		})
+		.await
+		.unwrap();
+
+	assert_eq!(responses, vec![
+		Ok(BatchResponse::Ok),
+		Ok(BatchResponse::Value("value".into())),
+		Ok(BatchResponse::Has(true)),
+		Ok(BatchResponse::Size(5)),
+		Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)),
+		Ok(BatchResponse::Ok),
+		Ok(BatchResponse::Has(false)),
+	]);
+
+	// the connection is still in sync after the batch
+	assert!(client.ping().await.is_ok());
+}
+
+#[test]
+#[serial]
+fn batch_empty() {
+	let mut client = common::init_client(true);
+
+	assert_eq!(client.batch(|_| {}), Ok(Vec::new()));
+	assert!(client.ping().is_ok());
+}
+
+#[test]
+#[serial]
+fn batch_invalid_ttl() {
+	let mut client = common::init_client(true);
+
+	let responses = client
+		.batch(|batch| {
+			batch.set("key", "value", Some(0));
+			batch.set("key", "value", None);
+			batch.ttl("key", Some(0));
+		})
+		.unwrap();
+
+	assert_eq!(responses, vec![
+		Err(PaperClientError::InvalidTtl),
+		Ok(BatchResponse::Ok),
+		Err(PaperClientError::InvalidTtl),
+	]);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn batched_writer_flush() {
+	let writer_client = common::init_async_client(true).await;
+	let mut client = common::init_async_client(true).await;
+
+	let policy = BatchFlushPolicy::default().max_delay(Duration::from_secs(60));
+	let writer = BatchedWriter::new(writer_client, policy);
+
+	assert!(writer.set("key", "value", None).await.is_ok());
+	assert!(writer.set("other", "value", None).await.is_ok());
+	assert!(writer.del("other").await.is_ok());
+	assert!(writer.del("non_existent").await.is_ok());
+
+	// the commands are queued until the writer is flushed
+	assert!(client.get("key").await.is_err());
+
+	writer.flush().await;
+
+	assert_eq!(client.get("key").await.unwrap(), "value");
+	assert!(client.get("other").await.is_err());
+
+	let metrics = writer.metrics();
+	assert_eq!(metrics.queued(), 4);
+	assert_eq!(metrics.written(), 4);
+	assert_eq!(metrics.flushes(), 1);
+	assert_eq!(metrics.errors(), 0);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn batched_writer_max_commands() {
+	let writer_client = common::init_async_client(true).await;
+	let mut client = common::init_async_client(true).await;
+
+	let policy = BatchFlushPolicy::default()
+		.max_commands(2)
+		.max_delay(Duration::from_secs(60));
+
+	let writer = BatchedWriter::new(writer_client, policy);
+
+	assert!(writer.set("a", "value", None).await.is_ok());
+	assert!(writer.set("b", "value", None).await.is_ok());
+	assert!(writer.set("c", "value", None).await.is_ok());
+
+	tokio::time::sleep(Duration::from_millis(100)).await;
+
+	assert!(client.has("a").await.unwrap());
+	assert!(client.has("b").await.unwrap());
+	assert!(!client.has("c").await.unwrap());
+
+	assert_eq!(writer.metrics().flushes(), 1);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn batched_writer_max_delay() {
+	let writer_client = common::init_async_client(true).await;
+	let mut client = common::init_async_client(true).await;
+
+	let policy = BatchFlushPolicy::default().max_delay(Duration::from_millis(5));
+	let writer = BatchedWriter::new(writer_client, policy);
+
+	assert!(writer.set("key", "value", None).await.is_ok());
+	tokio::time::sleep(Duration::from_millis(100)).await;
+
+	assert_eq!(client.get("key").await.unwrap(), "value");
+	assert_eq!(writer.metrics().flushes(), 1);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn batched_writer_max_bytes() {
+	let writer_client = common::init_async_client(true).await;
+
+	let policy = BatchFlushPolicy::default()
+		.max_bytes(16)
+		.max_delay(Duration::from_secs(60));
+
+	let writer = BatchedWriter::new(writer_client, policy);
+
+	// each set is 8 bytes of key and value
+	assert!(writer.set("key1", "abcd", None).await.is_ok());
+	assert!(writer.set("key2", "abcd", None).await.is_ok());
+
+	writer.flush().await;
+
+	// the first flush was triggered by size, the second had nothing to send
+	assert_eq!(writer.metrics().flushes(), 1);
+	assert_eq!(writer.metrics().written(), 2);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn batched_writer_drop() {
+	let writer_client = common::init_async_client(true).await;
+	let mut client = common::init_async_client(true).await;
+
+	let policy = BatchFlushPolicy::default().max_delay(Duration::from_secs(60));
+	let writer = BatchedWriter::new(writer_client, policy);
+
+	assert!(writer.set("key", "value", None).await.is_ok());
+	drop(writer);
+
+	tokio::time::sleep(Duration::from_millis(100)).await;
+	assert_eq!(client.get("key").await.unwrap(), "value");
+}
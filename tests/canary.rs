@@ -0,0 +1,88 @@
+mod common;
+
+use std::{
+	fmt::{self, Debug},
+	sync::Mutex,
+	time::{Duration, Instant},
+};
+
+#[cfg(feature = "tokio")]
+use paper_client::run_policy_canary_async;
+use paper_client::{
+	Clock,
+	PaperClient,
+	PaperClientConfig,
+	PaperPolicy,
+	run_policy_canary,
+};
+use serial_test::serial;
+
+// a clock which issues gets instead of sleeping, standing in for the
+// traffic served during the experiment's window
+struct TrafficClock(Mutex<PaperClient>);
+
+impl Clock for TrafficClock {
+	fn now(&self) -> Instant {
+		Instant::now()
+	}
+
+	fn sleep(&self, _: Duration) {
+		let mut client = self.0.lock().unwrap();
+
+		for _ in 0..4 {
+			let _ = client.get("key");
+		}
+	}
+}
+
+impl Debug for TrafficClock {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "TrafficClock")
+	}
+}
+
+#[test]
+#[serial]
+fn policy_canary() {
+	let mut baseline = common::init_client(true);
+	let previous = baseline.status().unwrap().policy().to_owned();
+
+	let clock = TrafficClock(Mutex::new(common::init_client(true)));
+	let config = PaperClientConfig::default().clock(clock);
+
+	let mut canary = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+	canary.auth("auth_token").unwrap();
+
+	let window = Duration::from_secs(600);
+	let comparison = run_policy_canary(&mut baseline, &mut canary, PaperPolicy::Fifo, window);
+	let comparison = comparison.unwrap();
+
+	assert_eq!(comparison.policy(), &PaperPolicy::Fifo);
+	assert_eq!(comparison.baseline().gets(), 4);
+	assert_eq!(comparison.canary().gets(), 4);
+	assert_eq!(comparison.canary().misses(), 1);
+	assert_eq!(comparison.miss_ratio_delta(), Some(0.0));
+
+	assert_eq!(*canary.status().unwrap().policy(), previous);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn policy_canary_async() {
+	let mut baseline = common::init_async_client(true).await;
+	let mut canary = common::init_async_client(true).await;
+
+	let previous = baseline.status().await.unwrap().policy().to_owned();
+
+	let window = Duration::from_millis(10);
+	let comparison = run_policy_canary_async(&mut baseline, &mut canary, PaperPolicy::Fifo, window)
+		.await
+		.unwrap();
+
+	assert_eq!(comparison.policy(), &PaperPolicy::Fifo);
+	assert_eq!(comparison.canary().gets(), 0);
+	assert_eq!(comparison.miss_ratio_delta(), None);
+
+	assert_eq!(*canary.status().await.unwrap().policy(), previous);
+}
@@ -0,0 +1,175 @@
+use std::{
+	io::{self, Write},
+	sync::{Arc, Mutex},
+};
+
+use paper_client::{KeyDisplay, PaperClient, PaperClientConfig, ProtocolCapture};
+use serial_test::serial;
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+	fn lines(&self) -> Vec<String> {
+		let buf = self.0.lock().unwrap();
+
+		String::from_utf8(buf.clone())
+			.unwrap()
+			.lines()
+			.map(str::to_owned)
+			.collect()
+	}
+}
+
+impl Write for SharedBuffer {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.0.lock().unwrap().extend_from_slice(buf);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+#[test]
+#[serial]
+fn capture_frames() {
+	let buffer = SharedBuffer::default();
+	let config = PaperClientConfig::default().capture(ProtocolCapture::new(buffer.clone()));
+
+	let mut client = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+	assert!(client.ping().is_ok());
+
+	assert_frames(&buffer.lines());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn capture_frames_async() {
+	use paper_client::AsyncPaperClient;
+
+	let buffer = SharedBuffer::default();
+	let config = PaperClientConfig::default().capture(ProtocolCapture::new(buffer.clone()));
+
+	let mut client = AsyncPaperClient::with_config("paper://127.0.0.1:3145", config)
+		.await
+		.unwrap();
+
+	assert!(client.ping().await.is_ok());
+
+	assert_frames(&buffer.lines());
+}
+
+#[test]
+#[serial]
+fn capture_redacted_keys() {
+	for key_display in [KeyDisplay::Hashed, KeyDisplay::Omitted] {
+		let buffer = SharedBuffer::default();
+
+		let config = PaperClientConfig::default()
+			.capture(ProtocolCapture::new(buffer.clone()))
+			.key_display(key_display);
+
+		let mut client = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+		assert!(client.auth("auth_token").is_ok());
+
+		assert!(client.set(KEY, "value", None).is_ok());
+		assert_eq!(client.get(KEY).unwrap(), "value");
+
+		assert_redacted(&buffer.lines(), key_display);
+	}
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn capture_redacted_keys_async() {
+	use paper_client::AsyncPaperClient;
+
+	for key_display in [KeyDisplay::Hashed, KeyDisplay::Omitted] {
+		let buffer = SharedBuffer::default();
+
+		let config = PaperClientConfig::default()
+			.capture(ProtocolCapture::new(buffer.clone()))
+			.key_display(key_display);
+
+		let mut client = AsyncPaperClient::with_config("paper://127.0.0.1:3145", config)
+			.await
+			.unwrap();
+
+		assert!(client.auth("auth_token").await.is_ok());
+
+		assert!(client.set(KEY, "value", None).await.is_ok());
+		assert_eq!(client.get(KEY).await.unwrap(), "value");
+
+		assert_redacted(&buffer.lines(), key_display);
+	}
+}
+
+#[test]
+#[serial]
+fn capture_redacted_token() {
+	let buffer = SharedBuffer::default();
+	let config = PaperClientConfig::default().capture(ProtocolCapture::new(buffer.clone()));
+
+	let mut client = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+	assert!(client.auth("auth_token").is_ok());
+	assert!(client.ping().is_ok());
+
+	let lines = buffer.lines();
+	assert_frames(&lines);
+
+	// the token is redacted even though keys are captured in full
+	assert!(lines[0].contains(&to_hex("<redacted>")));
+	assert!(lines.iter().all(|line| !line.contains(&to_hex("auth_token"))));
+}
+
+const KEY: &str = "user:jane@example.com";
+
+fn assert_redacted(lines: &[String], key_display: KeyDisplay) {
+	assert_frames(lines);
+
+	let sent = lines
+		.iter()
+		.filter(|line| line.contains(" >> "))
+		.collect::<Vec<_>>();
+
+	// the auth, set, and get commands
+	assert_eq!(sent.len(), 3);
+
+	let key = to_hex(KEY);
+	let displayed = to_hex(&key_display.display(KEY));
+
+	assert!(lines.iter().all(|line| !line.contains(&key)));
+	assert!(sent[1..].iter().all(|line| line.contains(&displayed)));
+
+	let token = to_hex("auth_token");
+	assert!(lines.iter().all(|line| !line.contains(&token)));
+}
+
+fn to_hex(value: &str) -> String {
+	value
+		.bytes()
+		.map(|byte| format!("{byte:02x}"))
+		.collect()
+}
+
+fn assert_frames(lines: &[String]) {
+	assert!(!lines.is_empty());
+
+	for line in lines {
+		let fields = line.split(' ').collect::<Vec<_>>();
+
+		assert_eq!(fields.len(), 4);
+		assert!(fields[0].parse::<f64>().is_ok());
+		assert!(fields[1] == ">>" || fields[1] == "<<");
+		assert_eq!(fields[2], "127.0.0.1:3145");
+		assert!(fields[3].len() % 2 == 0);
+		assert!(fields[3].chars().all(|c| c.is_ascii_hexdigit()));
+	}
+
+	assert!(lines[0].contains(" >> "));
+	assert!(lines.last().unwrap().contains(" << "));
+}
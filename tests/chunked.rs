@@ -0,0 +1,116 @@
+mod common;
+
+#[cfg(feature = "tokio")]
+use paper_client::AsyncChunkedClient;
+use paper_client::{ChunkedClient, PaperClientError, error::PaperCacheError};
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn chunked_set_get() {
+	let mut client = ChunkedClient::new(common::init_client(true), 4);
+	let value = b"abcdefghij".to_vec();
+
+	assert!(client.set("key", value.clone(), None).is_ok());
+	assert!(client.get("key").is_ok_and(|got| got == value.as_slice()));
+
+	// the manifest and three chunks
+	let status = client.inner_mut().status().unwrap();
+	assert_eq!(status.num_objects(), 4);
+
+	// the underlying client sees the manifest
+	assert!(client.inner_mut().get("key").is_ok_and(|got| got != value.as_slice()));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn chunked_set_get_async() {
+	let client = common::init_async_client(true).await;
+	let mut client = AsyncChunkedClient::new(client, 4);
+	let value = b"abcdefghij".to_vec();
+
+	assert!(client.set("key", value.clone(), None).await.is_ok());
+	assert!(client.get("key").await.is_ok_and(|got| got == value.as_slice()));
+
+	let status = client.inner_mut().status().await.unwrap();
+	assert_eq!(status.num_objects(), 4);
+}
+
+#[test]
+#[serial]
+fn chunked_small_value() {
+	let mut client = ChunkedClient::new(common::init_client(true), 4);
+
+	assert!(client.set("key", "abcd", None).is_ok());
+	assert!(client.inner_mut().get("key").is_ok_and(|got| got == "abcd"));
+	assert!(client.get("key").is_ok_and(|got| got == "abcd"));
+}
+
+#[test]
+#[serial]
+fn chunked_overwrite() {
+	let mut client = ChunkedClient::new(common::init_client(true), 4);
+
+	assert!(client.set("key", "abcdefghij", None).is_ok());
+	assert!(client.set("key", "klmnop", None).is_ok());
+	assert!(client.get("key").is_ok_and(|got| got == "klmnop"));
+
+	// the previous value's chunks are deleted
+	let status = client.inner_mut().status().unwrap();
+	assert_eq!(status.num_objects(), 3);
+
+	assert!(client.set("key", "qr", None).is_ok());
+
+	let status = client.inner_mut().status().unwrap();
+	assert_eq!(status.num_objects(), 1);
+}
+
+#[test]
+#[serial]
+fn chunked_del() {
+	let mut client = ChunkedClient::new(common::init_client(true), 4);
+
+	assert!(client.set("key", "abcdefghij", None).is_ok());
+	assert!(client.del("key").is_ok());
+
+	let status = client.inner_mut().status().unwrap();
+	assert_eq!(status.num_objects(), 0);
+
+	assert_eq!(
+		client.del("key"),
+		Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound))
+	);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn chunked_del_async() {
+	let client = common::init_async_client(true).await;
+	let mut client = AsyncChunkedClient::new(client, 4);
+
+	assert!(client.set("key", "abcdefghij", None).await.is_ok());
+	assert!(client.del("key").await.is_ok());
+
+	let status = client.inner_mut().status().await.unwrap();
+	assert_eq!(status.num_objects(), 0);
+}
+
+#[test]
+#[serial]
+fn chunked_missing_chunk() {
+	let mut client = ChunkedClient::new(common::init_client(true), 4);
+
+	assert!(client.set("key", "abcdefghij", None).is_ok());
+
+	// evicts every chunk but leaves the manifest
+	let manifest = client.inner_mut().get("key").unwrap();
+	assert!(client.inner_mut().wipe().is_ok());
+	assert!(client.inner_mut().set("key", manifest, None).is_ok());
+
+	assert_eq!(
+		client.get("key"),
+		Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound))
+	);
+}
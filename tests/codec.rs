@@ -0,0 +1,144 @@
+mod common;
+
+#[cfg(feature = "tokio")]
+use paper_client::AsyncPaperClient;
+use paper_client::{
+	BatchResponse,
+	PaperClient,
+	PaperClientConfig,
+	PaperClientError,
+	ValueCodec,
+	error::PaperClientResult,
+};
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn codec_set_get() {
+	let mut plain = common::init_client(true);
+	let mut client = init_codec_client();
+
+	assert!(client.set("key", "value", None).is_ok());
+
+	assert_eq!(client.get("key").unwrap(), "value");
+	assert_eq!(client.peek("key").unwrap(), "value");
+	assert_eq!(plain.get("key").unwrap(), "<value>");
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn codec_set_get_async() {
+	let mut plain = common::init_async_client(true).await;
+	let mut client = init_async_codec_client().await;
+
+	assert!(client.set("key", "value", None).await.is_ok());
+
+	assert_eq!(client.get("key").await.unwrap(), "value");
+	assert_eq!(client.peek("key").await.unwrap(), "value");
+	assert_eq!(plain.get("key").await.unwrap(), "<value>");
+}
+
+#[test]
+#[serial]
+fn codec_get_into() {
+	let mut client = init_codec_client();
+	assert!(client.set("key", "value", None).is_ok());
+
+	let mut buf = Vec::new();
+	assert_eq!(client.get_into("key", &mut buf), Ok(5));
+	assert_eq!(buf, b"value");
+
+	let mut slice = [0u8; 8];
+	assert_eq!(client.get_into_slice("key", &mut slice), Ok(5));
+	assert_eq!(&slice[..5], b"value");
+
+	let mut written = Vec::new();
+	assert_eq!(client.get_to_writer("key", &mut written), Ok(5));
+	assert_eq!(written, b"value");
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn codec_get_into_async() {
+	let mut client = init_async_codec_client().await;
+	assert!(client.set("key", "value", None).await.is_ok());
+
+	let mut buf = Vec::new();
+	assert_eq!(client.get_into("key", &mut buf).await, Ok(5));
+	assert_eq!(buf, b"value");
+
+	let mut slice = [0u8; 8];
+	assert_eq!(client.get_into_slice("key", &mut slice).await, Ok(5));
+	assert_eq!(&slice[..5], b"value");
+
+	let mut written = Vec::new();
+	assert_eq!(client.get_to_writer("key", &mut written).await, Ok(5));
+	assert_eq!(written, b"value");
+}
+
+#[test]
+#[serial]
+fn codec_invalid_encoding() {
+	let mut plain = common::init_client(true);
+	let mut client = init_codec_client();
+
+	assert!(plain.set("key", "value", None).is_ok());
+	assert_eq!(client.get("key"), Err(PaperClientError::InvalidEncoding));
+
+	// the connection is still usable
+	assert!(client.ping().is_ok());
+}
+
+#[test]
+#[serial]
+fn codec_batch() {
+	let mut client = init_codec_client();
+
+	let responses = client
+		.batch(|batch| {
+			batch.set("key", "value", None).get("key");
+		})
+		.unwrap();
+
+	assert_eq!(responses[1], Ok(BatchResponse::Value("value".into())));
+}
+
+// wraps each value in angle brackets
+#[derive(Debug)]
+struct BracketCodec;
+
+impl ValueCodec for BracketCodec {
+	fn encode(&self, value: &[u8]) -> PaperClientResult<Vec<u8>> {
+		Ok([b"<", value, b">"].concat())
+	}
+
+	fn decode(&self, value: &[u8]) -> PaperClientResult<Vec<u8>> {
+		value
+			.strip_prefix(b"<")
+			.and_then(|value| value.strip_suffix(b">"))
+			.map(<[u8]>::to_vec)
+			.ok_or(PaperClientError::InvalidEncoding)
+	}
+}
+
+fn init_codec_client() -> PaperClient {
+	let config = PaperClientConfig::default().value_codec(BracketCodec);
+
+	let mut client = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+	client.auth("auth_token").unwrap();
+	client
+}
+
+#[cfg(feature = "tokio")]
+async fn init_async_codec_client() -> AsyncPaperClient {
+	let config = PaperClientConfig::default().value_codec(BracketCodec);
+
+	let mut client = AsyncPaperClient::with_config("paper://127.0.0.1:3145", config)
+		.await
+		.unwrap();
+
+	client.auth("auth_token").await.unwrap();
+	client
+}
@@ -0,0 +1,72 @@
+#[cfg(feature = "tokio")]
+use paper_client::AsyncPaperClient;
+use paper_client::{CommandKind, CommandPolicy, PaperClient, PaperClientConfig, PaperClientError};
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn command_policy_deny() {
+	let policy = CommandPolicy::allow_all().deny(CommandKind::Wipe);
+	let config = PaperClientConfig::default().command_policy(policy);
+
+	let mut client = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+	assert!(client.auth("auth_token").is_ok());
+
+	assert!(client.set("key", "value", None).is_ok());
+	assert_eq!(client.wipe(), Err(PaperClientError::CommandDenied));
+
+	let value: String = client.get("key").unwrap().try_into().unwrap();
+	assert_eq!(value, "value");
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn command_policy_deny_async() {
+	let policy = CommandPolicy::allow_all().deny(CommandKind::Wipe);
+	let config = PaperClientConfig::default().command_policy(policy);
+
+	let mut client = AsyncPaperClient::with_config("paper://127.0.0.1:3145", config)
+		.await
+		.unwrap();
+
+	assert!(client.auth("auth_token").await.is_ok());
+
+	assert!(client.set("key", "value", None).await.is_ok());
+	assert_eq!(client.wipe().await, Err(PaperClientError::CommandDenied));
+
+	let value: String = client.get("key").await.unwrap().try_into().unwrap();
+	assert_eq!(value, "value");
+}
+
+#[test]
+#[serial]
+fn command_policy_allow() {
+	let policy = CommandPolicy::deny_all().allow(CommandKind::Get);
+	let config = PaperClientConfig::default().command_policy(policy);
+
+	let mut client = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+	assert!(client.auth("auth_token").is_ok());
+	assert!(client.ping().is_ok());
+
+	assert_eq!(client.set("key", "value", None), Err(PaperClientError::CommandDenied));
+	assert!(client.get("absent").is_err_and(|err| err != PaperClientError::CommandDenied));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn command_policy_allow_async() {
+	let policy = CommandPolicy::deny_all().allow(CommandKind::Get);
+	let config = PaperClientConfig::default().command_policy(policy);
+
+	let mut client = AsyncPaperClient::with_config("paper://127.0.0.1:3145", config)
+		.await
+		.unwrap();
+
+	assert!(client.auth("auth_token").await.is_ok());
+	assert!(client.ping().await.is_ok());
+
+	assert_eq!(client.set("key", "value", None).await, Err(PaperClientError::CommandDenied));
+	assert!(client.get("absent").await.is_err_and(|err| err != PaperClientError::CommandDenied));
+}
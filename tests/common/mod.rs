@@ -17,7 +17,9 @@ pub fn init_client(authed: bool) -> PaperClient {
 	client
 }
 
+// unused by the test files which only cover the sync client
 #[cfg(feature = "tokio")]
+#[allow(dead_code)]
 pub async fn init_async_client(authed: bool) -> AsyncPaperClient {
 	let mut client = AsyncPaperClient::new("paper://127.0.0.1:3145")
 		.await
@@ -0,0 +1,185 @@
+mod common;
+
+use std::{
+	io::Write,
+	net::TcpListener,
+	thread,
+	time::{Duration, Instant},
+};
+
+#[cfg(feature = "tokio")]
+use paper_client::{AsyncPaperClient, AsyncPaperPool, with_deadline_async};
+use paper_client::{
+	CheckoutOptions,
+	PaperClient,
+	PaperClientConfig,
+	PaperClientError,
+	PaperPool,
+	current_deadline,
+	with_deadline,
+};
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn deadline_expired() {
+	let mut client = common::init_client(true);
+	assert!(client.set("key", "value", None).is_ok());
+
+	let result = with_deadline(Instant::now(), || client.get("key"));
+	assert_eq!(result.err(), Some(PaperClientError::DeadlineExceeded));
+
+	let deadline = Instant::now() + Duration::from_secs(5);
+	assert!(with_deadline(deadline, || client.get("key")).is_ok());
+	assert!(client.get("key").is_ok());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn deadline_expired_async() {
+	let mut client = common::init_async_client(true).await;
+	assert!(client.set("key", "value", None).await.is_ok());
+
+	let result = with_deadline_async(Instant::now(), client.get("key")).await;
+	assert_eq!(result.err(), Some(PaperClientError::DeadlineExceeded));
+
+	let deadline = Instant::now() + Duration::from_secs(5);
+	assert!(with_deadline_async(deadline, client.get("key")).await.is_ok());
+	assert!(client.get("key").await.is_ok());
+}
+
+#[test]
+fn deadline_unresponsive() {
+	let addr = init_unresponsive_server();
+	let mut client = PaperClient::new(&addr).unwrap();
+
+	let start = Instant::now();
+	let deadline = start + Duration::from_millis(100);
+
+	let result = with_deadline(deadline, || client.get("key"));
+
+	assert_eq!(result.err(), Some(PaperClientError::DeadlineExceeded));
+	assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn deadline_unresponsive_async() {
+	let addr = init_unresponsive_server();
+	let mut client = AsyncPaperClient::new(&addr).await.unwrap();
+
+	let start = Instant::now();
+	let deadline = start + Duration::from_millis(100);
+
+	let result = with_deadline_async(deadline, client.get("key")).await;
+
+	assert_eq!(result.err(), Some(PaperClientError::DeadlineExceeded));
+	assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn deadline_command_timeout() {
+	let addr = init_unresponsive_server();
+	let config = PaperClientConfig::default().command_timeout(Some(Duration::from_secs(60)));
+
+	let pool = PaperPool::builder()
+		.addr(&addr)
+		.size(1)
+		.config(config)
+		.build()
+		.unwrap();
+
+	let start = Instant::now();
+	let options = CheckoutOptions::default().timeout(Duration::from_millis(100));
+
+	let mut client = pool.client_with(options);
+	assert_eq!(client.command_timeout(), Some(Duration::from_millis(100)));
+
+	assert_eq!(client.get("key").err(), Some(PaperClientError::DeadlineExceeded));
+	assert!(start.elapsed() < Duration::from_secs(2));
+	drop(client);
+
+	// the override only applies to its checkout
+	assert_eq!(pool.client().command_timeout(), Some(Duration::from_secs(60)));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn deadline_command_timeout_async() {
+	let addr = init_unresponsive_server();
+	let config = PaperClientConfig::default().command_timeout(Some(Duration::from_secs(60)));
+
+	let pool = AsyncPaperPool::builder()
+		.addr(&addr)
+		.size(1)
+		.config(config)
+		.build()
+		.await
+		.unwrap();
+
+	let start = Instant::now();
+	let options = CheckoutOptions::default().timeout(Duration::from_millis(100));
+
+	let mut client = pool.client_with(options).await;
+	assert_eq!(client.command_timeout(), Some(Duration::from_millis(100)));
+
+	assert_eq!(client.get("key").await.err(), Some(PaperClientError::DeadlineExceeded));
+	assert!(start.elapsed() < Duration::from_secs(2));
+	drop(client);
+
+	// the override only applies to its checkout
+	assert_eq!(pool.client().await.command_timeout(), Some(Duration::from_secs(60)));
+}
+
+#[test]
+fn deadline_nested() {
+	let early = Instant::now() + Duration::from_secs(1);
+	let late = early + Duration::from_secs(1);
+
+	assert_eq!(current_deadline(), None);
+
+	with_deadline(early, || {
+		assert_eq!(current_deadline(), Some(early));
+		with_deadline(late, || assert_eq!(current_deadline(), Some(early)));
+	});
+
+	with_deadline(late, || {
+		with_deadline(early, || assert_eq!(current_deadline(), Some(early)));
+		assert_eq!(current_deadline(), Some(late));
+	});
+
+	assert_eq!(current_deadline(), None);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn deadline_nested_async() {
+	let early = Instant::now() + Duration::from_secs(1);
+	let late = early + Duration::from_secs(1);
+
+	let deadline = with_deadline_async(late, async {
+		with_deadline_async(early, async { current_deadline() }).await
+	})
+	.await;
+
+	assert_eq!(deadline, Some(early));
+	assert_eq!(current_deadline(), None);
+}
+
+fn init_unresponsive_server() -> String {
+	// connections complete the handshake but commands are never answered
+	let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind listener.");
+	let addr = format!("paper://{}", listener.local_addr().unwrap());
+
+	thread::spawn(move || {
+		let mut streams = Vec::new();
+
+		for mut stream in listener.incoming().flatten() {
+			let _ = stream.write_all(b"!");
+			streams.push(stream);
+		}
+	});
+
+	addr
+}
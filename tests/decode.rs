@@ -0,0 +1,147 @@
+mod common;
+
+use std::io::Read;
+
+#[cfg(feature = "tokio")]
+use paper_client::{AsyncStreamReader, DecodePaperResponseAsync};
+use paper_client::{
+	Command,
+	DecodePaperResponse,
+	PaperClientError,
+	StreamReader,
+	error::{PaperCacheError, PaperClientResult},
+};
+use serial_test::serial;
+#[cfg(feature = "tokio")]
+use tokio::io::AsyncRead;
+
+struct RawSize;
+
+// fails without reading the payload
+struct Rejecting;
+
+#[test]
+#[serial]
+fn decode_existent() {
+	let mut client = common::init_client(true);
+
+	assert!(client.set("key", "value", None).is_ok());
+
+	let size = client.size("key").unwrap();
+	assert_eq!(client.execute(Command::Size("key"), RawSize), Ok(size));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn decode_existent_async() {
+	let mut client = common::init_async_client(true).await;
+
+	assert!(client.set("key", "value", None).await.is_ok());
+
+	let size = client.size("key").await.unwrap();
+	assert_eq!(client.execute(Command::Size("key"), RawSize).await, Ok(size));
+}
+
+#[test]
+#[serial]
+fn decode_non_existent() {
+	let mut client = common::init_client(true);
+
+	assert_eq!(
+		client.execute(Command::Size("key"), RawSize),
+		Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)),
+	);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn decode_non_existent_async() {
+	let mut client = common::init_async_client(true).await;
+
+	assert_eq!(
+		client.execute(Command::Size("key"), RawSize).await,
+		Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)),
+	);
+}
+
+#[test]
+#[serial]
+fn decode_partially_read() {
+	let mut client = common::init_client(true);
+
+	assert!(client.set("key", "value", None).is_ok());
+
+	assert_eq!(
+		client.execute(Command::Has("key"), Rejecting),
+		Err(PaperClientError::InvalidValue),
+	);
+
+	// the unread payload is not mistaken for the next command's response
+	assert_eq!(client.has("other"), Ok(false));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn decode_partially_read_async() {
+	let mut client = common::init_async_client(true).await;
+
+	assert!(client.set("key", "value", None).await.is_ok());
+
+	assert_eq!(
+		client.execute(Command::Has("key"), Rejecting).await,
+		Err(PaperClientError::InvalidValue),
+	);
+
+	assert_eq!(client.has("other").await, Ok(false));
+}
+
+impl DecodePaperResponse for RawSize {
+	type Output = u32;
+
+	fn decode<R: Read>(&mut self, reader: &mut StreamReader<'_, R>) -> PaperClientResult<u32> {
+		reader
+			.read_u32()
+			.map_err(|_| PaperClientError::InvalidResponse)
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl DecodePaperResponseAsync for RawSize {
+	type Output = u32;
+
+	async fn decode_async<R>(
+		&mut self,
+		reader: &mut AsyncStreamReader<'_, R>,
+	) -> PaperClientResult<u32>
+	where
+		R: AsyncRead + Unpin + Send,
+	{
+		reader
+			.read_u32()
+			.await
+			.map_err(|_| PaperClientError::InvalidResponse)
+	}
+}
+
+impl DecodePaperResponse for Rejecting {
+	type Output = ();
+
+	fn decode<R: Read>(&mut self, _: &mut StreamReader<'_, R>) -> PaperClientResult<()> {
+		Err(PaperClientError::InvalidValue)
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl DecodePaperResponseAsync for Rejecting {
+	type Output = ();
+
+	async fn decode_async<R>(&mut self, _: &mut AsyncStreamReader<'_, R>) -> PaperClientResult<()>
+	where
+		R: AsyncRead + Unpin + Send,
+	{
+		Err(PaperClientError::InvalidValue)
+	}
+}
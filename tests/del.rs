@@ -43,3 +43,48 @@ async fn del_non_existent_async() {
 	let result = client.del("key").await;
 	assert!(result.is_err());
 }
+
+#[test]
+#[serial]
+fn del_prefix() {
+	let mut client = common::init_client(true);
+
+	assert!(client.set("user:1", "value", None).is_ok());
+	assert!(client.set("user:2", "value", None).is_ok());
+	assert!(client.set("item:1", "value", None).is_ok());
+
+	let keys = ["user:1", "user:2", "user:3", "item:1"];
+
+	assert_eq!(client.del_prefix_dry_run("user:", keys), Ok(2));
+	assert_eq!(client.has("user:1"), Ok(true));
+
+	assert_eq!(client.del_prefix("user:", keys), Ok(2));
+	assert_eq!(client.has("user:1"), Ok(false));
+	assert_eq!(client.has("user:2"), Ok(false));
+	assert_eq!(client.has("item:1"), Ok(true));
+
+	assert_eq!(client.del_prefix_dry_run("user:", keys), Ok(0));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn del_prefix_async() {
+	let mut client = common::init_async_client(true).await;
+
+	assert!(client.set("user:1", "value", None).await.is_ok());
+	assert!(client.set("user:2", "value", None).await.is_ok());
+	assert!(client.set("item:1", "value", None).await.is_ok());
+
+	let keys = ["user:1", "user:2", "user:3", "item:1"];
+
+	assert_eq!(client.del_prefix_dry_run("user:", keys).await, Ok(2));
+	assert_eq!(client.has("user:1").await, Ok(true));
+
+	assert_eq!(client.del_prefix("user:", keys).await, Ok(2));
+	assert_eq!(client.has("user:1").await, Ok(false));
+	assert_eq!(client.has("user:2").await, Ok(false));
+	assert_eq!(client.has("item:1").await, Ok(true));
+
+	assert_eq!(client.del_prefix_dry_run("user:", keys).await, Ok(0));
+}
@@ -0,0 +1,73 @@
+mod common;
+
+#[cfg(feature = "tokio")]
+use paper_client::AsyncPaperPool;
+use paper_client::PaperPool;
+
+#[test]
+fn display_client() {
+	let mut client = common::init_client(false);
+	assert_eq!(client.to_string(), "PaperClient(paper://127.0.0.1:3145)");
+
+	let _ = client.auth("secret_token");
+	assert_eq!(client.to_string(), "PaperClient(paper://127.0.0.1:3145, authed)");
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn display_client_async() {
+	let mut client = common::init_async_client(false).await;
+	assert_eq!(client.to_string(), "AsyncPaperClient(paper://127.0.0.1:3145)");
+
+	let _ = client.auth("secret_token").await;
+	assert_eq!(client.to_string(), "AsyncPaperClient(paper://127.0.0.1:3145, authed)");
+}
+
+#[test]
+fn debug_redacts_token() {
+	let mut client = common::init_client(false);
+	let _ = client.auth("secret_token");
+
+	let debug = format!("{client:?}");
+	assert!(debug.contains("<redacted>"));
+	assert!(!debug.contains("secret_token"));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn debug_redacts_token_async() {
+	let mut client = common::init_async_client(false).await;
+	let _ = client.auth("secret_token").await;
+
+	let debug = format!("{client:?}");
+	assert!(debug.contains("<redacted>"));
+	assert!(!debug.contains("secret_token"));
+}
+
+#[test]
+fn debug_pool_redacts_token() {
+	let pool = PaperPool::new("paper://127.0.0.1:3145", 2).expect("Could not connect pool.");
+	let _ = pool.auth("secret_token");
+
+	assert!(!format!("{pool:?}").contains("secret_token"));
+	assert_eq!(
+		pool.to_string(),
+		"PaperPool(1 node(s), 0 replica(s), 2 client(s) per node)",
+	);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn debug_pool_redacts_token_async() {
+	let pool = AsyncPaperPool::new("paper://127.0.0.1:3145", 2)
+		.await
+		.expect("Could not connect pool.");
+
+	let _ = pool.auth("secret_token").await;
+
+	assert!(!format!("{pool:?}").contains("secret_token"));
+	assert_eq!(
+		pool.to_string(),
+		"AsyncPaperPool(1 node(s), 0 replica(s), 2 client(s) per node)",
+	);
+}
@@ -0,0 +1,57 @@
+use paper_client::{Command, PaperClientError, PaperPolicy, PaperValue, error::CommandError};
+
+#[test]
+fn encode_commands() {
+	let commands = [
+		(Command::Ping, b"\x00".to_vec()),
+		(Command::Get("key"), b"\x03\x03\x00\x00\x00key".to_vec()),
+		(
+			Command::Set("key", "value".into(), 5),
+			b"\x04\x03\x00\x00\x00key\x05\x00\x00\x00value\x05\x00\x00\x00".to_vec(),
+		),
+		(Command::Ttl("key", 1), b"\x08\x03\x00\x00\x00key\x01\x00\x00\x00".to_vec()),
+		(Command::Resize(1), b"\x0b\x01\x00\x00\x00\x00\x00\x00\x00".to_vec()),
+		(Command::Policy(PaperPolicy::TwoQ(0.25, 0.5)), b"\x0c\x0b\x00\x00\x002q-0.25-0.5".to_vec()),
+	];
+
+	// a single buffer is reused for every command
+	let mut buf = [0u8; 64];
+
+	for (command, expected) in commands {
+		assert_eq!(command.encoded_len(), expected.len());
+
+		let len = command.encode_into(&mut buf).unwrap();
+		assert_eq!(&buf[..len], expected.as_slice());
+	}
+}
+
+#[test]
+fn encode_buffer_too_small() {
+	let command = Command::Get("key");
+	let mut buf = [0u8; 7];
+
+	assert_eq!(
+		command.encode_into(&mut buf),
+		Err(PaperClientError::BufferTooSmall { required: 8 }),
+	);
+
+	assert_eq!(buf, [0u8; 7]);
+}
+
+#[test]
+fn encode_value_too_large() {
+	// the zeroed buffer is not written to, so its pages are never allocated
+	let size = u32::MAX as usize + 1;
+	let value = PaperValue::from(vec![0u8; size]);
+
+	let command = Command::Set("key", value, 0);
+	let mut buf = [0u8; 64];
+
+	assert_eq!(
+		command.encode_into(&mut buf),
+		Err(PaperClientError::CommandError(CommandError::ValueTooLarge {
+			size,
+			max: u32::MAX as usize,
+		})),
+	);
+}
@@ -1,5 +1,8 @@
 mod common;
 
+#[cfg(feature = "tokio")]
+use paper_client::AsyncPaperClient;
+use paper_client::{PaperClient, PaperClientConfig, PaperClientError};
 use serial_test::serial;
 
 #[test]
@@ -49,3 +52,206 @@ async fn get_non_existent_async() {
 	let result = client.get("key").await;
 	assert!(result.is_err());
 }
+
+#[test]
+#[serial]
+fn get_exceeding_max_response_size() {
+	let config = PaperClientConfig::default().max_response_size(Some(4));
+
+	let mut client = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+	client.auth("auth_token").unwrap();
+
+	assert!(client.set("small", "abc", None).is_ok());
+	assert!(client.set("large", "abcdefgh", None).is_ok());
+
+	assert_eq!(client.get("large").err(), Some(PaperClientError::ResponseTooLarge));
+
+	let value: String = client.get("small").unwrap().try_into().unwrap();
+	assert_eq!(value, "abc");
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn get_exceeding_max_response_size_async() {
+	let config = PaperClientConfig::default().max_response_size(Some(4));
+
+	let mut client = AsyncPaperClient::with_config("paper://127.0.0.1:3145", config)
+		.await
+		.unwrap();
+
+	client.auth("auth_token").await.unwrap();
+
+	assert!(client.set("small", "abc", None).await.is_ok());
+	assert!(client.set("large", "abcdefgh", None).await.is_ok());
+
+	assert_eq!(client.get("large").await.err(), Some(PaperClientError::ResponseTooLarge));
+
+	let value: String = client.get("small").await.unwrap().try_into().unwrap();
+	assert_eq!(value, "abc");
+}
+
+#[test]
+#[serial]
+fn get_to_writer() {
+	let config = PaperClientConfig::default().max_response_size(Some(4));
+
+	let mut client = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+	client.auth("auth_token").unwrap();
+
+	let value = "a".repeat(20_000);
+	assert!(client.set("key", value.as_str(), None).is_ok());
+
+	let mut written = Vec::new();
+
+	assert_eq!(client.get_to_writer("key", &mut written), Ok(20_000));
+	assert_eq!(written, value.as_bytes());
+
+	assert!(client.get_to_writer("other", &mut written).is_err());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn get_to_writer_async() {
+	let config = PaperClientConfig::default().max_response_size(Some(4));
+
+	let mut client = AsyncPaperClient::with_config("paper://127.0.0.1:3145", config)
+		.await
+		.unwrap();
+
+	client.auth("auth_token").await.unwrap();
+
+	let value = "a".repeat(20_000);
+	assert!(client.set("key", value.as_str(), None).await.is_ok());
+
+	let mut written = Vec::new();
+
+	assert_eq!(client.get_to_writer("key", &mut written).await, Ok(20_000));
+	assert_eq!(written, value.as_bytes());
+
+	assert!(client.get_to_writer("other", &mut written).await.is_err());
+}
+
+#[test]
+#[serial]
+fn get_range() {
+	let mut client = common::init_client(true);
+
+	let value = alphabet(20_000);
+	assert!(client.set("key", value.as_str(), None).is_ok());
+
+	// spans the chunks in which the value is read
+	assert_eq!(client.get_range("key", 8_190, 10).unwrap(), &value[8_190..8_200]);
+	assert_eq!(client.get_range("key", 19_995, 10).unwrap(), &value[19_995..]);
+	assert_eq!(client.get_range("key", 30_000, 10).unwrap(), "");
+
+	assert!(client.get_range("other", 0, 10).is_err());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn get_range_async() {
+	let mut client = common::init_async_client(true).await;
+
+	let value = alphabet(20_000);
+	assert!(client.set("key", value.as_str(), None).await.is_ok());
+
+	assert_eq!(client.get_range("key", 8_190, 10).await.unwrap(), &value[8_190..8_200]);
+	assert_eq!(client.get_range("key", 19_995, 10).await.unwrap(), &value[19_995..]);
+	assert_eq!(client.get_range("key", 30_000, 10).await.unwrap(), "");
+
+	assert!(client.get_range("other", 0, 10).await.is_err());
+}
+
+#[test]
+#[serial]
+fn get_result() {
+	let mut client = common::init_client(true);
+
+	assert!(client.set("key", "value", None).is_ok());
+
+	let hit = client.get_result("key").unwrap();
+	assert!(hit.is_hit());
+	assert_eq!(hit.size(), Some(5));
+
+	let value: String = hit.into_value().unwrap().try_into().unwrap();
+	assert_eq!(value, "value");
+
+	let miss = client.get_result("other").unwrap();
+	assert!(miss.is_miss());
+	assert_eq!(miss.size(), None);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn get_result_async() {
+	let mut client = common::init_async_client(true).await;
+
+	assert!(client.set("key", "value", None).await.is_ok());
+
+	let hit = client.get_result("key").await.unwrap();
+	assert!(hit.is_hit());
+	assert_eq!(hit.size(), Some(5));
+
+	let value: String = hit.into_value().unwrap().try_into().unwrap();
+	assert_eq!(value, "value");
+
+	let miss = client.get_result("other").await.unwrap();
+	assert!(miss.is_miss());
+	assert_eq!(miss.size(), None);
+}
+
+#[test]
+#[serial]
+fn get_into() {
+	let mut client = common::init_client(true);
+
+	assert!(client.set("key", "value", None).is_ok());
+
+	let mut buf = b"previous contents".to_vec();
+	assert_eq!(client.get_into("key", &mut buf), Ok(5));
+	assert_eq!(buf, b"value");
+
+	let mut slice = [0u8; 8];
+	assert_eq!(client.get_into_slice("key", &mut slice), Ok(5));
+	assert_eq!(&slice[..5], b"value");
+
+	let mut small = [0u8; 2];
+	assert_eq!(client.get_into_slice("key", &mut small), Err(PaperClientError::ResponseTooLarge));
+
+	assert!(client.get_into("other", &mut buf).is_err());
+	assert_eq!(client.get_into("key", &mut buf), Ok(5));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn get_into_async() {
+	let mut client = common::init_async_client(true).await;
+
+	assert!(client.set("key", "value", None).await.is_ok());
+
+	let mut buf = b"previous contents".to_vec();
+	assert_eq!(client.get_into("key", &mut buf).await, Ok(5));
+	assert_eq!(buf, b"value");
+
+	let mut slice = [0u8; 8];
+	assert_eq!(client.get_into_slice("key", &mut slice).await, Ok(5));
+	assert_eq!(&slice[..5], b"value");
+
+	let mut small = [0u8; 2];
+	assert_eq!(
+		client.get_into_slice("key", &mut small).await,
+		Err(PaperClientError::ResponseTooLarge)
+	);
+
+	assert!(client.get_into("other", &mut buf).await.is_err());
+	assert_eq!(client.get_into("key", &mut buf).await, Ok(5));
+}
+
+fn alphabet(len: usize) -> String {
+	('a'..='z').cycle().take(len).collect()
+}
@@ -0,0 +1,83 @@
+use std::{
+	net::TcpListener,
+	time::{Duration, Instant},
+};
+
+#[cfg(feature = "tokio")]
+use paper_client::AsyncPaperClient;
+use paper_client::{PaperClient, PaperClientConfig, PaperClientError};
+
+#[test]
+fn handshake_timeout() {
+	let (_listener, addr) = init_silent_server();
+
+	let config = PaperClientConfig::default().handshake_timeout(Some(Duration::from_millis(100)));
+
+	let result = PaperClient::with_config(&addr, config);
+	assert_eq!(result.unwrap_err(), PaperClientError::UnreachableServer);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn handshake_timeout_async() {
+	let (_listener, addr) = init_silent_server();
+
+	let config = PaperClientConfig::default().handshake_timeout(Some(Duration::from_millis(100)));
+
+	let result = AsyncPaperClient::with_config(&addr, config).await;
+	assert_eq!(result.unwrap_err(), PaperClientError::UnreachableServer);
+}
+
+#[test]
+fn handshake_retry() {
+	let (_listener, addr) = init_silent_server();
+
+	let config = PaperClientConfig::default()
+		.handshake_timeout(Some(Duration::from_millis(50)))
+		.connect_retries(2)
+		.connect_backoff(Duration::from_millis(50));
+
+	let start = Instant::now();
+	let result = PaperClient::with_config(&addr, config);
+
+	assert_eq!(result.unwrap_err(), PaperClientError::UnreachableServer);
+	assert!(start.elapsed() >= Duration::from_millis(300));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn handshake_retry_async() {
+	let (_listener, addr) = init_silent_server();
+
+	let config = PaperClientConfig::default()
+		.handshake_timeout(Some(Duration::from_millis(50)))
+		.connect_retries(2)
+		.connect_backoff(Duration::from_millis(50));
+
+	let start = Instant::now();
+	let result = AsyncPaperClient::with_config(&addr, config).await;
+
+	assert_eq!(result.unwrap_err(), PaperClientError::UnreachableServer);
+	assert!(start.elapsed() >= Duration::from_millis(300));
+}
+
+fn init_silent_server() -> (TcpListener, String) {
+	// connections are accepted by the OS backlog but never answered
+	let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind listener.");
+	let addr = format!("paper://{}", listener.local_addr().unwrap());
+
+	(listener, addr)
+}
+
+#[test]
+fn handshake_hostname() {
+	let client = PaperClient::new("paper://localhost:3145");
+	assert!(client.is_ok());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn handshake_hostname_async() {
+	let client = AsyncPaperClient::new("paper://localhost:3145").await;
+	assert!(client.is_ok());
+}
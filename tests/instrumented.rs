@@ -0,0 +1,82 @@
+mod common;
+
+#[cfg(feature = "tokio")]
+use paper_client::AsyncInstrumentedClient;
+use paper_client::{InstrumentedClient, OTHER_PREFIX};
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn instrumented_prefixes() {
+	let mut client = InstrumentedClient::new(common::init_client(true));
+
+	assert!(client.set("user:1", "value", None).is_ok());
+	assert!(client.get("user:1").is_ok());
+	assert!(client.get("user:2").is_err());
+	assert!(client.get("session:1").is_err());
+
+	let snapshot = client.snapshot();
+	assert_eq!(snapshot.prefixes().len(), 2);
+	assert_eq!(snapshot.prefixes()[0].prefix(), "user");
+
+	let user = snapshot.prefix("user").unwrap();
+	assert_eq!(user.requests(), 3);
+	assert_eq!(user.hits(), 1);
+	assert_eq!(user.misses(), 1);
+	assert_eq!(user.errors(), 0);
+	assert_eq!(user.hit_ratio(), Some(0.5));
+
+	let session = snapshot.prefix("session").unwrap();
+	assert_eq!(session.hit_ratio(), Some(0.0));
+
+	client.reset();
+	assert!(client.snapshot().prefixes().is_empty());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn instrumented_prefixes_async() {
+	let mut client = AsyncInstrumentedClient::new(common::init_async_client(true).await);
+
+	assert!(client.set("user:1", "value", None).await.is_ok());
+	assert!(client.get("user:1").await.is_ok());
+	assert!(client.get("user:2").await.is_err());
+	assert!(client.get("session:1").await.is_err());
+
+	let snapshot = client.snapshot();
+	assert_eq!(snapshot.prefixes().len(), 2);
+	assert_eq!(snapshot.prefixes()[0].prefix(), "user");
+
+	let user = snapshot.prefix("user").unwrap();
+	assert_eq!(user.requests(), 3);
+	assert_eq!(user.hits(), 1);
+	assert_eq!(user.misses(), 1);
+	assert_eq!(user.errors(), 0);
+	assert_eq!(user.hit_ratio(), Some(0.5));
+
+	let session = snapshot.prefix("session").unwrap();
+	assert_eq!(session.hit_ratio(), Some(0.0));
+
+	client.reset();
+	assert!(client.snapshot().prefixes().is_empty());
+}
+
+#[test]
+#[serial]
+fn instrumented_max_prefixes() {
+	let mut client = InstrumentedClient::new(common::init_client(true))
+		.with_delimiter('/')
+		.with_max_prefixes(1);
+
+	assert!(client.set("a/1", "value", None).is_ok());
+	assert!(client.set("b/1", "value", None).is_ok());
+	assert!(client.set("c/1", "value", None).is_ok());
+	assert!(client.set("a/2", "value", None).is_ok());
+
+	let snapshot = client.snapshot();
+
+	assert_eq!(snapshot.prefix("a").unwrap().requests(), 2);
+	assert_eq!(snapshot.prefix(OTHER_PREFIX).unwrap().requests(), 2);
+	assert!(snapshot.prefix("b").is_none());
+}
@@ -0,0 +1,224 @@
+mod common;
+
+use std::{
+	env,
+	fs,
+	io::Write,
+	net::TcpListener,
+	path::PathBuf,
+	thread::{self, JoinHandle},
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "tokio")]
+use paper_client::{AsyncJournaledClient, AsyncPaperClient};
+use paper_client::{JournaledClient, PaperClient};
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn journal_replay() {
+	let path = init_journal("journal_replay");
+
+	let client = init_closing_client();
+	let mut client = JournaledClient::new(client, &path).unwrap();
+
+	assert!(client.set("key1", "value1", None).is_ok());
+	assert!(client.set("key2", "value2", None).is_ok());
+	assert!(client.del("key1").is_ok());
+	assert_eq!(client.pending(), 3);
+
+	// the journaled writes outlive the client
+	drop(client);
+
+	let mut client = JournaledClient::new(common::init_client(true), &path).unwrap();
+	assert_eq!(client.pending(), 3);
+
+	assert_eq!(client.replay(), Ok(3));
+	assert_eq!(client.pending(), 0);
+
+	let inner = client.inner_mut();
+	assert!(inner.get("key2").is_ok_and(|value| value == "value2"));
+	assert_eq!(inner.has("key1"), Ok(false));
+
+	let client = JournaledClient::new(common::init_client(false), &path).unwrap();
+	assert_eq!(client.pending(), 0);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn journal_replay_async() {
+	let path = init_journal("journal_replay_async");
+
+	let client = init_closing_async_client().await;
+	let mut client = AsyncJournaledClient::new(client, &path).unwrap();
+
+	assert!(client.set("key1", "value1", None).await.is_ok());
+	assert!(client.set("key2", "value2", None).await.is_ok());
+	assert!(client.del("key1").await.is_ok());
+	assert_eq!(client.pending(), 3);
+
+	drop(client);
+
+	let client = common::init_async_client(true).await;
+	let mut client = AsyncJournaledClient::new(client, &path).unwrap();
+	assert_eq!(client.pending(), 3);
+
+	assert_eq!(client.replay().await, Ok(3));
+	assert_eq!(client.pending(), 0);
+
+	let inner = client.inner_mut();
+	assert!(inner.get("key2").await.is_ok_and(|value| value == "value2"));
+	assert_eq!(inner.has("key1").await, Ok(false));
+}
+
+#[test]
+#[serial]
+fn journal_replay_before_write() {
+	let path = init_journal("journal_replay_before_write");
+
+	let client = init_closing_client();
+	let mut client = JournaledClient::new(client, &path).unwrap();
+
+	assert!(client.set("key", "value1", None).is_ok());
+	assert_eq!(client.pending(), 1);
+
+	drop(client);
+
+	let mut client = JournaledClient::new(common::init_client(true), &path).unwrap();
+
+	// the journaled set is replayed before the new one is applied
+	assert!(client.set("key", "value2", None).is_ok());
+	assert_eq!(client.pending(), 0);
+
+	let value = client.inner_mut().get("key");
+	assert!(value.is_ok_and(|value| value == "value2"));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn journal_replay_before_write_async() {
+	let path = init_journal("journal_replay_before_write_async");
+
+	let client = init_closing_async_client().await;
+	let mut client = AsyncJournaledClient::new(client, &path).unwrap();
+
+	assert!(client.set("key", "value1", None).await.is_ok());
+	assert_eq!(client.pending(), 1);
+
+	drop(client);
+
+	let client = common::init_async_client(true).await;
+	let mut client = AsyncJournaledClient::new(client, &path).unwrap();
+
+	assert!(client.set("key", "value2", None).await.is_ok());
+	assert_eq!(client.pending(), 0);
+
+	let value = client.inner_mut().get("key").await;
+	assert!(value.is_ok_and(|value| value == "value2"));
+}
+
+#[test]
+fn journal_partial_entry() {
+	let path = init_journal("journal_partial_entry");
+
+	// a complete del of "key" followed by a set cut off mid-key
+	let mut file = fs::File::create(&path).unwrap();
+	file.write_all(b"\x01\x03\x00\x00\x00key\x00\x03\x00\x00").unwrap();
+	drop(file);
+
+	let client = init_closing_client();
+	let client = JournaledClient::new(client, &path).unwrap();
+
+	assert_eq!(client.pending(), 1);
+	assert_eq!(fs::read(&path).unwrap(), b"\x01\x03\x00\x00\x00key");
+}
+
+#[test]
+#[serial]
+fn journal_replay_expiry() {
+	let path = init_journal("journal_replay_expiry");
+
+	let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+	let expiry = now.as_millis() as u64 + 60_000;
+
+	// a set which has already expired, followed by one which expires in a
+	// minute
+	let mut buf = b"\x00\x07\x00\x00\x00expired\x05\x00\x00\x00value".to_vec();
+	buf.extend_from_slice(&1u64.to_le_bytes());
+	buf.extend_from_slice(b"\x00\x04\x00\x00\x00live\x05\x00\x00\x00value");
+	buf.extend_from_slice(&expiry.to_le_bytes());
+	fs::write(&path, buf).unwrap();
+
+	let mut client = JournaledClient::new(common::init_client(true), &path).unwrap();
+	assert_eq!(client.pending(), 2);
+
+	// the expired set is replayed as a deletion
+	assert_eq!(client.replay(), Ok(2));
+	assert_eq!(client.pending(), 0);
+
+	let inner = client.inner_mut();
+	assert_eq!(inner.has("expired"), Ok(false));
+	assert_eq!(inner.has("live"), Ok(true));
+}
+
+#[test]
+#[serial]
+fn journal_replay_expired_overwrite() {
+	let path = init_journal("journal_replay_expired_overwrite");
+
+	// a set which never expires, overwritten by one which has since expired
+	let mut buf = b"\x00\x03\x00\x00\x00key\x06\x00\x00\x00value1".to_vec();
+	buf.extend_from_slice(&0u64.to_le_bytes());
+	buf.extend_from_slice(b"\x00\x03\x00\x00\x00key\x06\x00\x00\x00value2");
+	buf.extend_from_slice(&1u64.to_le_bytes());
+	fs::write(&path, buf).unwrap();
+
+	let mut client = JournaledClient::new(common::init_client(true), &path).unwrap();
+
+	// the overwritten value is not left behind once the overwrite expires
+	assert_eq!(client.replay(), Ok(2));
+	assert_eq!(client.inner_mut().has("key"), Ok(false));
+}
+
+fn init_journal(name: &str) -> PathBuf {
+	let path = env::temp_dir().join(format!("paper_client_{name}"));
+	let _ = fs::remove_file(&path);
+	path
+}
+
+fn init_closing_client() -> PaperClient {
+	let (addr, server) = init_closing_server();
+
+	let client = PaperClient::new(addr).unwrap();
+	server.join().unwrap();
+
+	client
+}
+
+#[cfg(feature = "tokio")]
+async fn init_closing_async_client() -> AsyncPaperClient {
+	let (addr, server) = init_closing_server();
+
+	let client = AsyncPaperClient::new(&addr).await.unwrap();
+	server.join().unwrap();
+
+	client
+}
+
+fn init_closing_server() -> (String, JoinHandle<()>) {
+	// the only connection completes the handshake and is then closed, after
+	// which the server is unreachable
+	let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind listener.");
+	let addr = format!("paper://{}", listener.local_addr().unwrap());
+
+	let server = thread::spawn(move || {
+		if let Ok((mut stream, _)) = listener.accept() {
+			let _ = stream.write_all(b"!");
+		}
+	});
+
+	(addr, server)
+}
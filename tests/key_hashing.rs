@@ -0,0 +1,97 @@
+mod common;
+
+#[cfg(feature = "tokio")]
+use paper_client::AsyncPaperClient;
+use paper_client::{BatchResponse, PaperClient, PaperClientConfig};
+use serial_test::serial;
+use sha2::{Digest, Sha256};
+
+#[test]
+#[serial]
+fn key_hashing_long_key() {
+	let mut plain = common::init_client(true);
+	let mut client = init_hashing_client();
+
+	let key = "k".repeat(32);
+
+	assert!(client.set(key.as_str(), "value", None).is_ok());
+	assert_eq!(client.get(key.as_str()).unwrap(), "value");
+
+	assert!(plain.get(key.as_str()).is_err());
+	assert_eq!(plain.get(hashed(&key)).unwrap(), "value");
+
+	assert!(client.set("short", "value", None).is_ok());
+	assert_eq!(plain.get("short").unwrap(), "value");
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn key_hashing_long_key_async() {
+	let mut plain = common::init_async_client(true).await;
+
+	let config = PaperClientConfig::default().max_key_length(Some(16));
+
+	let mut client = AsyncPaperClient::with_config("paper://127.0.0.1:3145", config)
+		.await
+		.unwrap();
+
+	client.auth("auth_token").await.unwrap();
+
+	let key = "k".repeat(32);
+
+	assert!(client.set(key.as_str(), "value", None).await.is_ok());
+	assert_eq!(client.get(key.as_str()).await.unwrap(), "value");
+
+	assert!(plain.get(key.as_str()).await.is_err());
+	assert_eq!(plain.get(hashed(&key)).await.unwrap(), "value");
+}
+
+#[test]
+#[serial]
+fn key_hashing_prefixed_key() {
+	let mut plain = common::init_client(true);
+	let mut client = init_hashing_client();
+
+	assert!(client.set("sha256:abc", "value", None).is_ok());
+
+	assert!(plain.get("sha256:abc").is_err());
+	assert_eq!(plain.get(hashed("sha256:abc")).unwrap(), "value");
+}
+
+#[test]
+#[serial]
+fn key_hashing_batch() {
+	let mut plain = common::init_client(true);
+	let mut client = init_hashing_client();
+
+	let key = "k".repeat(32);
+
+	let responses = client
+		.batch(|batch| {
+			batch.set(key.as_str(), "value", None).get(key.as_str());
+		})
+		.unwrap();
+
+	assert_eq!(responses[1], Ok(BatchResponse::Value("value".into())));
+	assert_eq!(plain.get(hashed(&key)).unwrap(), "value");
+}
+
+fn init_hashing_client() -> PaperClient {
+	let config = PaperClientConfig::default().max_key_length(Some(16));
+
+	let mut client = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+	client.auth("auth_token").unwrap();
+	client
+}
+
+fn hashed(key: &str) -> String {
+	let digest = Sha256::digest(key.as_bytes());
+
+	let hex = digest
+		.iter()
+		.map(|byte| format!("{byte:02x}"))
+		.collect::<String>();
+
+	format!("sha256:{hex}")
+}
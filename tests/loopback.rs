@@ -0,0 +1,291 @@
+#[cfg(feature = "tokio")]
+use paper_client::AsyncPaperClient;
+use paper_client::{
+	Command,
+	LoopbackTransport,
+	PaperClient,
+	PaperClientError,
+	PaperPolicy,
+	PaperValue,
+	ScriptedResponder,
+	error::{PaperCacheError, PaperServerError},
+};
+
+#[test]
+fn loopback_replies() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Set("key", PaperValue::from("value"), 0))
+		.reply_ok()
+		.expect(Command::Get("key"))
+		.reply("value")
+		.expect(Command::Has("key"))
+		.reply_has(true)
+		.expect(Command::Size("key"))
+		.reply_size(5)
+		.expect(Command::Del("other"))
+		.reply_error(PaperClientError::CacheError(PaperCacheError::KeyNotFound));
+
+	let mut client = init_client(&responder);
+
+	assert!(client.set("key", "value", None).is_ok());
+
+	let value: String = client.get("key").unwrap().try_into().unwrap();
+	assert_eq!(value, "value");
+
+	assert_eq!(client.has("key"), Ok(true));
+	assert_eq!(client.size("key"), Ok(5));
+
+	assert_eq!(
+		client.del("other"),
+		Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)),
+	);
+
+	assert!(responder.is_done());
+	responder.verify();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn loopback_replies_async() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Set("key", PaperValue::from("value"), 0))
+		.reply_ok()
+		.expect(Command::Get("key"))
+		.reply("value")
+		.expect(Command::Has("key"))
+		.reply_has(true)
+		.expect(Command::Size("key"))
+		.reply_size(5)
+		.expect(Command::Del("other"))
+		.reply_error(PaperClientError::CacheError(PaperCacheError::KeyNotFound));
+
+	let mut client = init_async_client(&responder).await;
+
+	assert!(client.set("key", "value", None).await.is_ok());
+
+	let value: String = client.get("key").await.unwrap().try_into().unwrap();
+	assert_eq!(value, "value");
+
+	assert_eq!(client.has("key").await, Ok(true));
+	assert_eq!(client.size("key").await, Ok(5));
+
+	assert_eq!(
+		client.del("other").await,
+		Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound)),
+	);
+
+	assert!(responder.is_done());
+	responder.verify();
+}
+
+#[test]
+#[should_panic(expected = "unexpected command")]
+fn loopback_unexpected_command() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Get("key"))
+		.reply("value");
+
+	let mut client = init_client(&responder);
+	let _ = client.get("other");
+}
+
+#[test]
+#[should_panic(expected = "were not sent")]
+fn loopback_verify_unsent() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Get("key"))
+		.reply("value");
+
+	let _client = init_client(&responder);
+	responder.verify();
+}
+
+#[test]
+fn loopback_unknown_policy() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Status)
+		.reply_raw(status_reply(&[b"lfu", b"future-policy"], b"future-policy"));
+
+	let mut client = init_client(&responder);
+	let status = client.status().unwrap();
+
+	let unknown = PaperPolicy::Unknown("future-policy".to_owned());
+
+	assert_eq!(status.policies(), &[PaperPolicy::Lfu, unknown.clone()]);
+	assert_eq!(status.policy(), &unknown);
+	assert_eq!(unknown.to_string(), "future-policy");
+
+	// only a policy reported by the server is accepted as unknown
+	assert!(PaperPolicy::try_from("future-policy").is_err());
+	assert_eq!(PaperPolicy::try_from("2q-0.1-0.5"), Ok(PaperPolicy::TwoQ(0.1, 0.5)));
+
+	responder.verify();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn loopback_unknown_policy_async() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Status)
+		.reply_raw(status_reply(&[b"lfu", b"future-policy"], b"future-policy"));
+
+	let mut client = init_async_client(&responder).await;
+	let status = client.status().await.unwrap();
+
+	let unknown = PaperPolicy::Unknown("future-policy".to_owned());
+
+	assert_eq!(status.policies(), &[PaperPolicy::Lfu, unknown.clone()]);
+	assert_eq!(status.policy(), &unknown);
+
+	responder.verify();
+}
+
+#[test]
+fn loopback_unknown_error_codes() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Get("key"))
+		.reply_raw(b"?\x00\x2a".as_slice())
+		.expect(Command::Del("key"))
+		.reply_error(PaperClientError::ServerError(PaperServerError::Unknown(9)))
+		.expect(Command::Ping)
+		.reply("pong");
+
+	let mut client = init_client(&responder);
+
+	let err = client.get("key").unwrap_err();
+
+	assert_eq!(err, PaperClientError::CacheError(PaperCacheError::Unknown(42)));
+	assert_eq!(err.to_string(), "an unknown error occurred (code 42)");
+
+	assert_eq!(
+		client.del("key"),
+		Err(PaperClientError::ServerError(PaperServerError::Unknown(9))),
+	);
+
+	// the unknown errors are read in full
+	assert!(client.ping().is_ok());
+
+	responder.verify();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn loopback_unknown_error_codes_async() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Get("key"))
+		.reply_raw(b"?\x00\x2a".as_slice())
+		.expect(Command::Del("key"))
+		.reply_error(PaperClientError::ServerError(PaperServerError::Unknown(9)))
+		.expect(Command::Ping)
+		.reply("pong");
+
+	let mut client = init_async_client(&responder).await;
+
+	assert_eq!(
+		client.get("key").await,
+		Err(PaperClientError::CacheError(PaperCacheError::Unknown(42))),
+	);
+
+	assert_eq!(
+		client.del("key").await,
+		Err(PaperClientError::ServerError(PaperServerError::Unknown(9))),
+	);
+
+	assert!(client.ping().await.is_ok());
+
+	responder.verify();
+}
+
+#[test]
+fn loopback_resync_malformed() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Status)
+		.reply_raw(status_reply(&[b"lfu", b"\xff\xfe"], b"lfu"))
+		.expect(Command::Ping)
+		.reply("pong");
+
+	let mut client = init_client(&responder);
+
+	assert_eq!(client.status().err(), Some(PaperClientError::MalformedResponse));
+
+	let value: String = client.ping().unwrap().try_into().unwrap();
+	assert_eq!(value, "pong");
+
+	responder.verify();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn loopback_resync_malformed_async() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Status)
+		.reply_raw(status_reply(&[b"lfu", b"\xff\xfe"], b"lfu"))
+		.expect(Command::Ping)
+		.reply("pong");
+
+	let mut client = init_async_client(&responder).await;
+
+	assert_eq!(client.status().await.err(), Some(PaperClientError::MalformedResponse));
+
+	let value: String = client.ping().await.unwrap().try_into().unwrap();
+	assert_eq!(value, "pong");
+
+	responder.verify();
+}
+
+fn init_client(responder: &ScriptedResponder) -> PaperClient<LoopbackTransport> {
+	PaperClient::from_transport("paper://loopback", responder.transport())
+		.expect("Could not initialize client.")
+}
+
+#[cfg(feature = "tokio")]
+async fn init_async_client(
+	responder: &ScriptedResponder,
+) -> AsyncPaperClient<LoopbackTransport> {
+	AsyncPaperClient::from_transport("paper://loopback", responder.transport())
+		.await
+		.expect("Could not initialize client.")
+}
+
+fn status_reply(policies: &[&[u8]], policy: &[u8]) -> Vec<u8> {
+	let mut reply = vec![b'!'];
+
+	reply.extend(1u32.to_le_bytes());
+
+	for _ in 0..8 {
+		reply.extend(0u64.to_le_bytes());
+	}
+
+	reply.extend(0f64.to_le_bytes());
+	reply.extend((policies.len() as u32).to_le_bytes());
+
+	for policy in policies.iter().chain([&policy]) {
+		reply.extend((policy.len() as u32).to_le_bytes());
+		reply.extend(*policy);
+	}
+
+	reply.push(b'?');
+	reply.extend(0u64.to_le_bytes());
+
+	reply
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use std::{
+	sync::{
+		Arc,
+		atomic::{AtomicU64, Ordering},
+	},
+	time::Duration,
+};
+
+#[cfg(feature = "tokio")]
+use paper_client::migrate_async;
+use paper_client::{Clock, MigrateOptions, MockClock, PaperClient, PaperClientConfig, migrate};
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn migrate_keys() {
+	let mut source = common::init_client(true);
+	let mut dest = common::init_client(true);
+
+	assert!(source.set("key1", "value1", None).is_ok());
+	assert!(source.set("key2", "value2", None).is_ok());
+
+	let reports = Arc::new(AtomicU64::default());
+	let counter = reports.clone();
+
+	let options = MigrateOptions::default()
+		.ttl(Some(60))
+		.on_progress(move |_| {
+			counter.fetch_add(1, Ordering::Relaxed);
+		});
+
+	let progress = migrate(&mut source, &mut dest, ["key1", "key2", "absent"], options).unwrap();
+
+	assert_eq!(progress.copied(), 2);
+	assert_eq!(progress.missing(), 1);
+	assert_eq!(reports.load(Ordering::Relaxed), 3);
+
+	assert_eq!(dest.get("key2").unwrap(), "value2");
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn migrate_keys_async() {
+	let mut source = common::init_async_client(true).await;
+	let mut dest = common::init_async_client(true).await;
+
+	assert!(source.set("key1", "value1", None).await.is_ok());
+	assert!(source.set("key2", "value2", None).await.is_ok());
+
+	let options = MigrateOptions::default().ttl(Some(60));
+
+	let progress = migrate_async(&mut source, &mut dest, ["key1", "key2", "absent"], options)
+		.await
+		.unwrap();
+
+	assert_eq!(progress.copied(), 2);
+	assert_eq!(progress.missing(), 1);
+
+	assert_eq!(dest.get("key2").await.unwrap(), "value2");
+}
+
+#[test]
+#[serial]
+fn migrate_rate_limit() {
+	let clock = MockClock::new();
+	let start = clock.now();
+
+	let mut dest = common::init_client(true);
+
+	let config = PaperClientConfig::default().clock(clock.clone());
+	let mut source = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+	source.auth("auth_token").unwrap();
+
+	let options = MigrateOptions::default().rate_limit(Some(1));
+	let progress = migrate(&mut source, &mut dest, ["key1", "key2", "key3"], options).unwrap();
+
+	assert_eq!(progress.missing(), 3);
+	assert!(clock.now() - start >= Duration::from_secs(2));
+}
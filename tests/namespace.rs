@@ -0,0 +1,95 @@
+mod common;
+
+#[cfg(feature = "tokio")]
+use paper_client::AsyncNamespaceClient;
+use paper_client::{NamespaceClient, PaperClientError, error::PaperCacheError};
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn namespace_swap() {
+	let mut client = NamespaceClient::new(common::init_client(true), "users");
+
+	assert!(client.load("v1", "key1", "value1", None).is_ok());
+	assert!(client.load("v1", "key2", "value2", None).is_ok());
+
+	// the loaded namespace is not read until it is swapped in
+	assert_eq!(
+		client.get("key1"),
+		Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound))
+	);
+
+	assert!(client.swap_namespace("v0", "v1").is_ok());
+	assert!(client.namespace().is_ok_and(|namespace| namespace == "v1"));
+	assert!(client.get("key1").is_ok_and(|value| value == "value1"));
+
+	assert!(client.load("v2", "key1", "value3", None).is_ok());
+	assert!(client.get("key1").is_ok_and(|value| value == "value1"));
+
+	assert!(client.swap_namespace("v1", "v2").is_ok());
+	assert!(client.get("key1").is_ok_and(|value| value == "value3"));
+
+	// the old namespace's keys are deleted
+	let inner = client.inner_mut();
+	assert_eq!(inner.has("users/v1/key1"), Ok(false));
+	assert_eq!(inner.has("users/v1/key2"), Ok(false));
+	assert_eq!(inner.has("users/v1#keys"), Ok(false));
+	assert_eq!(inner.has("users/v2/key1"), Ok(true));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn namespace_swap_async() {
+	let client = common::init_async_client(true).await;
+	let mut client = AsyncNamespaceClient::new(client, "users");
+
+	assert!(client.load("v1", "key1", "value1", None).await.is_ok());
+	assert!(client.swap_namespace("v0", "v1").await.is_ok());
+	assert!(client.get("key1").await.is_ok_and(|value| value == "value1"));
+
+	assert!(client.load("v2", "key1", "value2", None).await.is_ok());
+	assert!(client.swap_namespace("v1", "v2").await.is_ok());
+	assert!(client.get("key1").await.is_ok_and(|value| value == "value2"));
+
+	assert_eq!(client.inner_mut().has("users/v1/key1").await, Ok(false));
+}
+
+#[test]
+#[serial]
+fn namespace_swap_mismatch() {
+	let mut client = NamespaceClient::new(common::init_client(true), "users");
+
+	assert!(client.load("v1", "key", "value1", None).is_ok());
+	assert!(client.swap_namespace("v0", "v1").is_ok());
+
+	assert!(client.load("v3", "key", "value3", None).is_ok());
+	assert_eq!(
+		client.swap_namespace("v2", "v3"),
+		Err(PaperClientError::NamespaceMismatch)
+	);
+
+	assert!(client.get("key").is_ok_and(|value| value == "value1"));
+}
+
+#[test]
+#[serial]
+fn namespace_swap_other_client() {
+	let mut loader = NamespaceClient::new(common::init_client(true), "users");
+
+	assert!(loader.load("v1", "key", "value1", None).is_ok());
+	assert!(loader.swap_namespace("v0", "v1").is_ok());
+
+	// a namespace loaded by one client is deleted by another's swap
+	let mut client = common::init_client(false);
+	assert!(client.auth("auth_token").is_ok());
+
+	let mut client = NamespaceClient::new(client, "users");
+
+	assert!(client.get("key").is_ok_and(|value| value == "value1"));
+	assert!(client.load("v2", "key", "value2", None).is_ok());
+	assert!(client.swap_namespace("v1", "v2").is_ok());
+
+	assert_eq!(loader.inner_mut().has("users/v1/key"), Ok(false));
+	assert!(loader.get("key").is_ok_and(|value| value == "value2"));
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use paper_client::{NumericValue, PaperClientError, PaperValue};
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn numeric_round_trip() {
+	let mut client = common::init_client(true);
+
+	assert!(client.set_num("int", -42i64, None).is_ok());
+	assert!(client.set_num("float", 0.1f64, None).is_ok());
+
+	assert_eq!(client.get("int").unwrap(), "-42");
+	assert_eq!(client.get("float").unwrap(), "0.1");
+
+	assert_eq!(client.get_num::<i64>("int"), Ok(-42));
+	assert_eq!(client.get_num::<f64>("float"), Ok(0.1));
+	assert_eq!(client.get_num::<f64>("int"), Ok(-42.0));
+
+	assert_eq!(client.get_num::<u64>("int"), Err(PaperClientError::InvalidNumber));
+	assert_eq!(client.set_num("nan", f64::NAN, None), Err(PaperClientError::InvalidValue));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn numeric_round_trip_async() {
+	let mut client = common::init_async_client(true).await;
+
+	assert!(client.set_num("int", -42i64, None).await.is_ok());
+	assert!(client.set_num("float", 0.1f64, None).await.is_ok());
+
+	assert_eq!(client.get("int").await.unwrap(), "-42");
+	assert_eq!(client.get("float").await.unwrap(), "0.1");
+
+	assert_eq!(client.get_num::<i64>("int").await, Ok(-42));
+	assert_eq!(client.get_num::<f64>("float").await, Ok(0.1));
+	assert_eq!(client.get_num::<f64>("int").await, Ok(-42.0));
+
+	assert_eq!(client.get_num::<u64>("int").await, Err(PaperClientError::InvalidNumber));
+	assert_eq!(client.set_num("nan", f64::NAN, None).await, Err(PaperClientError::InvalidValue));
+}
+
+#[test]
+fn numeric_formatting() {
+	// floats are never written in exponent notation
+	assert_eq!(1e21f64.to_paper_value().unwrap(), "1000000000000000000000");
+	assert_eq!(1e-7f64.to_paper_value().unwrap(), "0.0000001");
+	assert_eq!(0.1f32.to_paper_value().unwrap(), "0.1");
+	assert_eq!(u128::MAX.to_paper_value().unwrap(), u128::MAX.to_string().as_str());
+
+	// numbers written by other clients are read
+	assert_eq!(f64::from_paper_value(&PaperValue::from("1e-07")), Ok(1e-7));
+	assert_eq!(u8::from_paper_value(&PaperValue::from("256")), Err(PaperClientError::InvalidNumber));
+	assert_eq!(i32::from_paper_value(&PaperValue::from(vec![0xff])), Err(PaperClientError::InvalidNumber));
+	assert!(f64::INFINITY.to_paper_value().is_err());
+}
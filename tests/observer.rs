@@ -0,0 +1,421 @@
+use std::{
+	io,
+	net::{Shutdown, TcpListener, TcpStream},
+	sync::{
+		Arc,
+		Mutex,
+		atomic::{AtomicBool, Ordering},
+	},
+	thread,
+	time::Duration,
+};
+
+#[cfg(feature = "tokio")]
+use paper_client::{
+	AsyncPaperClient,
+	AsyncPaperPool,
+	PaperPolicy,
+	StatusThresholds,
+	ThresholdMetric,
+};
+use paper_client::{
+	AuditCommand,
+	EjectionPolicy,
+	FailoverEvent,
+	MockClock,
+	NodeState,
+	PaperClient,
+	PaperClientConfig,
+	PaperEvent,
+	PaperObserver,
+	PaperPool,
+};
+use serial_test::serial;
+
+#[derive(Debug, Clone, Default)]
+struct RecordingObserver {
+	events: Arc<Mutex<Vec<PaperEvent>>>,
+}
+
+// forwards connections to the mock server until it is taken down, which
+// closes every forwarded connection and refuses new ones
+#[derive(Debug, Clone, Default)]
+struct Proxy {
+	is_down: Arc<AtomicBool>,
+	streams: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+#[test]
+#[serial]
+fn observer_audit() {
+	let observer = RecordingObserver::default();
+	let config = PaperClientConfig::default().observer(observer.clone());
+
+	let mut client = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+
+	assert!(client.wipe().is_err());
+
+	client.auth("auth_token").unwrap();
+	assert!(client.wipe_with_reason("rebuild").is_ok());
+
+	observer.assert_audits();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn observer_audit_async() {
+	let observer = RecordingObserver::default();
+	let config = PaperClientConfig::default().observer(observer.clone());
+
+	let mut client = AsyncPaperClient::with_config("paper://127.0.0.1:3145", config)
+		.await
+		.unwrap();
+
+	assert!(client.wipe().await.is_err());
+
+	client.auth("auth_token").await.unwrap();
+	assert!(client.wipe_with_reason("rebuild").await.is_ok());
+
+	observer.assert_audits();
+}
+
+#[test]
+#[serial]
+fn observer_failover() {
+	let proxy = Proxy::default();
+	let proxy_addr = proxy.start();
+
+	let observer = RecordingObserver::default();
+	let clock = MockClock::new();
+
+	let config = PaperClientConfig::default()
+		.observer(observer.clone())
+		.clock(clock.clone());
+
+	let policy = EjectionPolicy::new(1, Duration::from_secs(60)).healthy_probes(2);
+
+	let pool = PaperPool::builder()
+		.addr("paper://127.0.0.1:3145")
+		.addr(&proxy_addr)
+		.config(config)
+		.auth("auth_token")
+		.build()
+		.expect("Could not connect pool.")
+		.with_ejection_policy(policy);
+
+	let route = || {
+		for index in 0..10 {
+			let key = format!("key{index}");
+			let _ = pool.client_for(&key).get(&key);
+		}
+	};
+
+	let proxy_state = || pool.cluster_status()[1].state();
+
+	proxy.set_down(true);
+	route();
+	assert_eq!(proxy_state(), NodeState::Ejected);
+
+	// the node is restored by probes alone, without any traffic
+	proxy.set_down(false);
+	clock.advance(Duration::from_secs(61));
+	pool.probe_ejected();
+
+	// a single healthy probe does not restore the node
+	assert_eq!(proxy_state(), NodeState::Ejected);
+
+	clock.advance(Duration::from_secs(61));
+	pool.probe_ejected();
+	assert_eq!(proxy_state(), NodeState::Healthy);
+
+	observer.assert_failovers(&pool.addrs()[1]);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn observer_failover_async() {
+	let proxy = Proxy::default();
+	let proxy_addr = proxy.start();
+
+	let observer = RecordingObserver::default();
+	let clock = MockClock::new();
+
+	let config = PaperClientConfig::default()
+		.observer(observer.clone())
+		.clock(clock.clone());
+
+	let policy = EjectionPolicy::new(1, Duration::from_millis(20)).healthy_probes(2);
+
+	let pool = AsyncPaperPool::builder()
+		.addr("paper://127.0.0.1:3145")
+		.addr(&proxy_addr)
+		.config(config)
+		.auth("auth_token")
+		.build()
+		.await
+		.expect("Could not connect pool.")
+		.with_ejection_policy(policy);
+
+	proxy.set_down(true);
+	route_async(&pool).await;
+	assert_eq!(pool.cluster_status()[1].state(), NodeState::Ejected);
+
+	// the node is restored by the pool's background probes alone, each of
+	// which waits for the clock to pass the ejection duration
+	proxy.set_down(false);
+
+	let requests = pool.cluster_status()[1].requests();
+	clock.advance(Duration::from_millis(21));
+
+	for _ in 0..100 {
+		if pool.cluster_status()[1].requests() > requests {
+			break;
+		}
+
+		tokio::time::sleep(Duration::from_millis(10)).await;
+	}
+
+	// a single healthy probe does not restore the node
+	tokio::time::sleep(Duration::from_millis(50)).await;
+	assert!(pool.cluster_status()[1].requests() > requests);
+	assert_eq!(pool.cluster_status()[1].state(), NodeState::Ejected);
+	assert_eq!(observer.failovers().len(), 1);
+
+	clock.advance(Duration::from_millis(21));
+
+	for _ in 0..100 {
+		if pool.cluster_status()[1].state() == NodeState::Healthy {
+			break;
+		}
+
+		tokio::time::sleep(Duration::from_millis(10)).await;
+	}
+
+	assert_eq!(pool.cluster_status()[1].state(), NodeState::Healthy);
+	assert!(
+		pool.background_tasks()
+			.iter()
+			.any(|task| task.name() == "paper-client::failback")
+	);
+
+	observer.assert_failovers(&pool.addrs()[1]);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn observer_threshold_async() {
+	let observer = RecordingObserver::default();
+	let config = PaperClientConfig::default().observer(observer.clone());
+
+	let thresholds = StatusThresholds::default()
+		.miss_ratio(0.1)
+		.used_size(u64::MAX);
+
+	let pool = AsyncPaperPool::builder()
+		.addr("paper://127.0.0.1:3145")
+		.size(2)
+		.config(config)
+		.build()
+		.await
+		.unwrap();
+
+	pool.auth("auth_token").await.unwrap();
+
+	let pool = pool.with_status_monitor(Duration::from_millis(10), thresholds);
+
+	let tasks = pool.background_tasks();
+	assert_eq!(tasks.len(), 1);
+	assert_eq!(tasks[0].name(), "paper-client::status_monitor");
+
+	tokio::time::sleep(Duration::from_millis(50)).await;
+	assert_eq!(pool.abort_background_tasks(), 1);
+
+	// the miss ratio is only reported when it first exceeds its threshold
+	let events = observer.events.lock().unwrap();
+	assert_eq!(events.len(), 1);
+
+	let PaperEvent::Threshold(event) = &events[0] else {
+		panic!("unexpected event: {:?}", events[0]);
+	};
+
+	assert_eq!(event.metric(), ThresholdMetric::MissRatio);
+	assert_eq!(event.value(), 0.25);
+	assert_eq!(event.threshold(), 0.1);
+	assert!(event.is_exceeded());
+
+	assert_eq!(
+		event.to_string(),
+		"threshold: miss ratio on paper://127.0.0.1:3145 exceeded (0.25 against 0.1)",
+	);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn observer_policy_change_async() {
+	let observer = RecordingObserver::default();
+	let config = PaperClientConfig::default().observer(observer.clone());
+
+	let pool = AsyncPaperPool::builder()
+		.addr("paper://127.0.0.1:3145")
+		.size(2)
+		.config(config)
+		.build()
+		.await
+		.unwrap();
+
+	pool.auth("auth_token").await.unwrap();
+
+	let mut admin = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+	admin.auth("auth_token").await.unwrap();
+	admin.policy(PaperPolicy::Lfu).await.unwrap();
+
+	let pool = pool.with_status_monitor(Duration::from_millis(10), StatusThresholds::default());
+
+	// the first status only establishes the policy
+	tokio::time::sleep(Duration::from_millis(50)).await;
+	assert!(observer.events.lock().unwrap().is_empty());
+
+	admin.policy(PaperPolicy::Fifo).await.unwrap();
+
+	tokio::time::sleep(Duration::from_millis(50)).await;
+	admin.policy(PaperPolicy::Lfu).await.unwrap();
+	pool.abort_background_tasks();
+
+	let events = observer.events.lock().unwrap();
+
+	let PaperEvent::PolicyChange(event) = &events[0] else {
+		panic!("unexpected event: {:?}", events[0]);
+	};
+
+	assert_eq!(event.previous(), &PaperPolicy::Lfu);
+	assert_eq!(event.current(), &PaperPolicy::Fifo);
+	assert!(!event.is_auto_policy());
+
+	assert_eq!(
+		event.to_string(),
+		"policy: paper://127.0.0.1:3145 changed from lfu to fifo",
+	);
+}
+
+impl RecordingObserver {
+	fn assert_audits(&self) {
+		let events = self.events.lock().unwrap();
+		assert_eq!(events.len(), 2);
+
+		let audits = events
+			.iter()
+			.map(|event| match event {
+				PaperEvent::Audit(event) => event,
+				_ => panic!("unexpected event: {event:?}"),
+			})
+			.collect::<Vec<_>>();
+
+		assert_eq!(audits[0].command(), &AuditCommand::Wipe);
+		assert_eq!(audits[0].reason(), None);
+		assert!(!audits[0].is_ok());
+
+		assert_eq!(audits[1].command(), &AuditCommand::Wipe);
+		assert_eq!(audits[1].reason(), Some("rebuild"));
+		assert!(audits[1].is_ok());
+		assert!(!audits[1].is_forced());
+
+		assert_eq!(
+			audits[1].to_string(),
+			"audit: wipe on paper://127.0.0.1:3145 (ok), reason: \"rebuild\"",
+		);
+	}
+
+	fn failovers(&self) -> Vec<FailoverEvent> {
+		self.events
+			.lock()
+			.unwrap()
+			.iter()
+			.filter_map(|event| match event {
+				PaperEvent::Failover(event) => Some(event.clone()),
+				_ => None,
+			})
+			.collect()
+	}
+
+	fn assert_failovers(&self, addr: &str) {
+		let failovers = self.failovers();
+
+		assert_eq!(failovers.len(), 2);
+
+		assert_eq!(failovers[0].addr(), addr);
+		assert!(failovers[0].is_ejected());
+
+		assert_eq!(failovers[1].addr(), addr);
+		assert!(!failovers[1].is_ejected());
+
+		assert_eq!(failovers[1].to_string(), format!("failover: paper://{addr} restored"));
+	}
+}
+
+#[cfg(feature = "tokio")]
+async fn route_async(pool: &AsyncPaperPool) {
+	for index in 0..10 {
+		let key = format!("key{index}");
+		let _ = pool.client_for(&key).await.get(&key).await;
+	}
+}
+
+impl Proxy {
+	fn start(&self) -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind listener.");
+		let addr = format!("paper://{}", listener.local_addr().unwrap());
+		let proxy = self.clone();
+
+		thread::spawn(move || {
+			for client in listener.incoming().flatten() {
+				if proxy.is_down.load(Ordering::SeqCst) {
+					continue;
+				}
+
+				if let Ok(server) = TcpStream::connect("127.0.0.1:3145") {
+					proxy.forward(client, server);
+				}
+			}
+		});
+
+		addr
+	}
+
+	fn forward(&self, client: TcpStream, server: TcpStream) {
+		let mut streams = self.streams.lock().unwrap();
+		streams.push(client.try_clone().unwrap());
+		streams.push(server.try_clone().unwrap());
+
+		let directions = [
+			(client.try_clone().unwrap(), server.try_clone().unwrap()),
+			(server, client),
+		];
+
+		for (mut from, mut to) in directions {
+			thread::spawn(move || {
+				let _ = io::copy(&mut from, &mut to);
+				let _ = to.shutdown(Shutdown::Both);
+			});
+		}
+	}
+
+	fn set_down(&self, is_down: bool) {
+		self.is_down.store(is_down, Ordering::SeqCst);
+
+		if is_down {
+			for stream in self.streams.lock().unwrap().drain(..) {
+				let _ = stream.shutdown(Shutdown::Both);
+			}
+		}
+	}
+}
+
+impl PaperObserver for RecordingObserver {
+	fn on_event(&self, event: &PaperEvent) {
+		self.events.lock().unwrap().push(event.clone());
+	}
+}
@@ -22,3 +22,36 @@ async fn ping_async() {
 	let value: String = result.unwrap().try_into().unwrap();
 	assert_eq!(value, "pong");
 }
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn ping_if_idle_async() {
+	use std::time::Duration;
+
+	let mut client = common::init_async_client(false).await;
+
+	assert_eq!(client.ping_if_idle(Duration::from_secs(60)).await, Ok(false));
+	assert_eq!(client.ping_if_idle(Duration::ZERO).await, Ok(true));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn ping_if_idle_clock_async() {
+	use std::time::Duration;
+
+	use paper_client::{AsyncPaperClient, MockClock, PaperClientConfig};
+
+	let clock = MockClock::new();
+	let config = PaperClientConfig::default().clock(clock.clone());
+
+	let mut client = AsyncPaperClient::with_config("paper://127.0.0.1:3145", config)
+		.await
+		.unwrap();
+
+	assert_eq!(client.ping_if_idle(Duration::from_secs(60)).await, Ok(false));
+
+	// the client is idle once the configured clock has passed the duration
+	clock.advance(Duration::from_secs(61));
+	assert_eq!(client.ping_if_idle(Duration::from_secs(60)).await, Ok(true));
+	assert_eq!(client.ping_if_idle(Duration::from_secs(60)).await, Ok(false));
+}
@@ -1,8 +1,17 @@
 mod common;
 
+use std::time::Duration;
+
 #[cfg(feature = "tokio")]
 use paper_client::AsyncPaperClient;
-use paper_client::{PaperClient, PaperPolicy};
+use paper_client::{
+	Guardrails,
+	MockClock,
+	PaperClient,
+	PaperClientConfig,
+	PaperClientError,
+	PaperPolicy,
+};
 use serial_test::serial;
 
 const INITIAL_POLICY: PaperPolicy = PaperPolicy::Lfu;
@@ -45,19 +54,75 @@ async fn policy_async() {
 	assert_eq!(policy, UPDATED_POLICY);
 }
 
+#[test]
+#[serial]
+fn policy_guarded() {
+	let clock = MockClock::new();
+
+	let config = PaperClientConfig::default()
+		.guardrails(Guardrails::default().min_policy_interval(Duration::from_secs(60)))
+		.clock(clock.clone());
+
+	let mut client = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+	client.auth("auth_token").unwrap();
+
+	assert!(client.policy(INITIAL_POLICY).is_ok());
+	assert_eq!(client.policy(UPDATED_POLICY), Err(PaperClientError::PolicyRefused));
+	assert_eq!(get_cache_policy(&mut client), INITIAL_POLICY);
+
+	clock.advance(Duration::from_secs(60));
+
+	assert!(client.policy(UPDATED_POLICY).is_ok());
+	assert_eq!(get_cache_policy(&mut client), UPDATED_POLICY);
+
+	// a forced change is allowed, but still restarts the interval
+	assert!(client.force_policy(INITIAL_POLICY, "roll back").is_ok());
+	assert_eq!(get_cache_policy(&mut client), INITIAL_POLICY);
+
+	clock.advance(Duration::from_secs(30));
+	assert_eq!(client.policy(UPDATED_POLICY), Err(PaperClientError::PolicyRefused));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn policy_guarded_async() {
+	let clock = MockClock::new();
+
+	let config = PaperClientConfig::default()
+		.guardrails(Guardrails::default().min_policy_interval(Duration::from_secs(60)))
+		.clock(clock.clone());
+
+	let mut client = AsyncPaperClient::with_config("paper://127.0.0.1:3145", config)
+		.await
+		.unwrap();
+
+	client.auth("auth_token").await.unwrap();
+
+	assert!(client.policy(INITIAL_POLICY).await.is_ok());
+	assert_eq!(client.policy(UPDATED_POLICY).await, Err(PaperClientError::PolicyRefused));
+	assert_eq!(get_cache_policy_async(&mut client).await, INITIAL_POLICY);
+
+	clock.advance(Duration::from_secs(60));
+
+	assert!(client.policy(UPDATED_POLICY).await.is_ok());
+	assert_eq!(get_cache_policy_async(&mut client).await, UPDATED_POLICY);
+}
+
 fn get_cache_policy(client: &mut PaperClient) -> PaperPolicy {
 	let status = client
 		.status()
 		.expect("Could not get cache status.");
 
-	*status.policy()
+	status.policy().clone()
 }
 
+#[cfg(feature = "tokio")]
 async fn get_cache_policy_async(client: &mut AsyncPaperClient) -> PaperPolicy {
 	let status = client
 		.status()
 		.await
 		.expect("Could not get cache status.");
 
-	*status.policy()
+	status.policy().clone()
 }
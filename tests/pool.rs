@@ -1,6 +1,27 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+#[cfg(feature = "tokio")]
+use futures_util::StreamExt;
 #[cfg(feature = "tokio")]
 use paper_client::AsyncPaperPool;
-use paper_client::PaperPool;
+use paper_client::{
+	AuthTokenProvider,
+	ClientSelection,
+	EjectionPolicy,
+	InstrumentedClient,
+	NodeRole,
+	NodeState,
+	PaperCacheOps,
+	PaperClientConfig,
+	PaperClientError,
+	PaperPool,
+	PaperPoolBuilder,
+	PaperValue,
+	ReadPreference,
+	RendezvousSharder,
+	Status,
+	error::{PaperCacheError, PaperClientResult},
+};
 use serial_test::serial;
 
 #[test]
@@ -32,6 +53,34 @@ async fn pool_client_async() {
 	}
 }
 
+#[test]
+#[serial]
+fn pool_builder() {
+	let pool = PaperPool::builder()
+		.addr("paper://127.0.0.1:3145")
+		.size(2)
+		.config(PaperClientConfig::default().connect_retries(1))
+		.build()
+		.expect("Could not connect pool.");
+
+	assert!(pool.client().ping().is_ok());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_builder_async() {
+	let pool = AsyncPaperPool::builder()
+		.addr("paper://127.0.0.1:3145")
+		.size(2)
+		.config(PaperClientConfig::default().connect_retries(1))
+		.build()
+		.await
+		.expect("Could not connect pool.");
+
+	assert!(pool.client().await.ping().await.is_ok());
+}
+
 #[test]
 #[serial]
 fn pool_auth_invalid() {
@@ -77,12 +126,902 @@ async fn pool_auth_valid_async() {
 	assert!(result.is_ok());
 }
 
+#[test]
+#[serial]
+fn pool_auth_replicas() {
+	let pool = init_pool();
+
+	pool.auth("auth_token")
+		.expect("Could not authorize pool.");
+
+	let pool = pool
+		.with_replicas(&["paper://127.0.0.1:3145"], ReadPreference::PreferReplica)
+		.expect("Could not connect replicas.");
+
+	let result = pool.read_client().has("key");
+	assert!(result.is_ok());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_auth_replicas_async() {
+	let pool = init_async_pool().await;
+
+	pool.auth("auth_token")
+		.await
+		.expect("Could not authorize pool.");
+
+	let pool = pool
+		.with_replicas(&["paper://127.0.0.1:3145"], ReadPreference::PreferReplica)
+		.await
+		.expect("Could not connect replicas.");
+
+	let result = pool.read_client().await.has("key").await;
+	assert!(result.is_ok());
+}
+
+#[test]
+#[serial]
+fn pool_builder_auth() {
+	let pool = PaperPool::builder()
+		.addr("paper://127.0.0.1:3145")
+		.size(2)
+		.auth("auth_token")
+		.build()
+		.expect("Could not connect pool.");
+
+	for _ in 0..4 {
+		assert!(pool.client().has("key").is_ok());
+	}
+
+	let result = PaperPool::builder()
+		.addr("paper://127.0.0.1:3145")
+		.auth("incorrect_auth_token")
+		.build();
+
+	assert!(result.is_err());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_builder_auth_async() {
+	let pool = AsyncPaperPool::builder()
+		.addr("paper://127.0.0.1:3145")
+		.size(2)
+		.auth("auth_token")
+		.build()
+		.await
+		.expect("Could not connect pool.");
+
+	for _ in 0..4 {
+		assert!(pool.client().await.has("key").await.is_ok());
+	}
+
+	let result = AsyncPaperPool::builder()
+		.addr("paper://127.0.0.1:3145")
+		.auth("incorrect_auth_token")
+		.build()
+		.await;
+
+	assert!(result.is_err());
+}
+
+#[test]
+#[serial]
+fn pool_rotate_token() {
+	let pool = init_pool();
+
+	assert!(pool.auth("incorrect_auth_token").is_err());
+	assert!(pool.rotate_token("auth_token").is_ok());
+	assert!(pool.reauth().is_ok());
+
+	for _ in 0..4 {
+		assert!(pool.client().has("key").is_ok());
+	}
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_rotate_token_async() {
+	let pool = init_async_pool().await;
+
+	assert!(pool.auth("incorrect_auth_token").await.is_err());
+	assert!(pool.rotate_token("auth_token").await.is_ok());
+	assert!(pool.reauth().await.is_ok());
+
+	for _ in 0..4 {
+		assert!(pool.client().await.has("key").await.is_ok());
+	}
+}
+
+#[test]
+#[serial]
+fn pool_sharded_client_for() {
+	let pool = init_sharded_pool();
+
+	pool.auth("auth_token")
+		.expect("Could not authorize pool.");
+
+	assert!(pool.client_for("key").set("key", "value", None).is_ok());
+
+	let result = pool.client_for("key").get("key");
+	assert!(result.is_ok());
+
+	let value: String = result.unwrap().try_into().unwrap();
+	assert_eq!(value, "value");
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_sharded_client_for_async() {
+	let pool = init_async_sharded_pool().await;
+
+	pool.auth("auth_token")
+		.await
+		.expect("Could not authorize pool.");
+
+	assert!(
+		pool.client_for("key")
+			.await
+			.set("key", "value", None)
+			.await
+			.is_ok()
+	);
+
+	let result = pool.client_for("key").await.get("key").await;
+	assert!(result.is_ok());
+
+	let value: String = result.unwrap().try_into().unwrap();
+	assert_eq!(value, "value");
+}
+
+#[test]
+#[serial]
+fn pool_cluster_status() {
+	let pool = init_sharded_pool().with_ejection_policy(EjectionPolicy::default());
+
+	for _ in 0..10 {
+		assert!(pool.client().ping().is_ok());
+	}
+
+	let status = pool.cluster_status();
+	assert_eq!(status.len(), 2);
+
+	let requests = status.iter().map(|node| node.requests()).sum::<u64>();
+	assert_eq!(requests, 10);
+
+	for node in status {
+		assert_eq!(node.state(), NodeState::Healthy);
+		assert_eq!(node.errors(), 0);
+	}
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_cluster_status_async() {
+	let pool = init_async_sharded_pool()
+		.await
+		.with_ejection_policy(EjectionPolicy::default());
+
+	for _ in 0..10 {
+		assert!(pool.client().await.ping().await.is_ok());
+	}
+
+	let status = pool.cluster_status();
+	assert_eq!(status.len(), 2);
+
+	let requests = status.iter().map(|node| node.requests()).sum::<u64>();
+	assert_eq!(requests, 10);
+
+	for node in status {
+		assert_eq!(node.state(), NodeState::Healthy);
+		assert_eq!(node.errors(), 0);
+	}
+}
+
+#[test]
+#[serial]
+fn pool_read_replica() {
+	for preference in [
+		ReadPreference::PrimaryOnly,
+		ReadPreference::PreferReplica,
+		ReadPreference::NearestByLatency,
+	] {
+		let pool = init_pool()
+			.with_replicas(&["paper://127.0.0.1:3145"], preference)
+			.expect("Could not connect replicas.");
+
+		pool.auth("auth_token")
+			.expect("Could not authorize pool.");
+
+		assert!(pool.client_for("key").set("key", "value", None).is_ok());
+
+		let result = pool.read_client_for("key").get("key");
+		assert!(result.is_ok());
+
+		let value: String = result.unwrap().try_into().unwrap();
+		assert_eq!(value, "value");
+
+		let roles = pool
+			.cluster_status()
+			.iter()
+			.map(|node| node.role())
+			.collect::<Vec<_>>();
+
+		assert_eq!(roles, [NodeRole::Primary, NodeRole::Replica]);
+	}
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_read_replica_async() {
+	for preference in [
+		ReadPreference::PrimaryOnly,
+		ReadPreference::PreferReplica,
+		ReadPreference::NearestByLatency,
+	] {
+		let pool = init_async_pool()
+			.await
+			.with_replicas(&["paper://127.0.0.1:3145"], preference)
+			.await
+			.expect("Could not connect replicas.");
+
+		pool.auth("auth_token")
+			.await
+			.expect("Could not authorize pool.");
+
+		assert!(
+			pool.client_for("key")
+				.await
+				.set("key", "value", None)
+				.await
+				.is_ok()
+		);
+
+		let result = pool.read_client_for("key").await.get("key").await;
+		assert!(result.is_ok());
+
+		let value: String = result.unwrap().try_into().unwrap();
+		assert_eq!(value, "value");
+	}
+}
+
+#[test]
+#[serial]
+fn pool_metrics() {
+	let pool = init_pool();
+
+	{
+		let _client = pool.client();
+
+		let metrics = pool.metrics();
+		assert_eq!(metrics.in_use(), 1);
+		assert_eq!(metrics.idle(), 1);
+	}
+
+	for _ in 0..9 {
+		assert!(pool.client().ping().is_ok());
+	}
+
+	let metrics = pool.metrics();
+	assert_eq!(metrics.in_use(), 0);
+	assert_eq!(metrics.idle(), 2);
+	assert_eq!(metrics.checkouts(), 10);
+	assert_eq!(metrics.timeouts(), 0);
+	assert_eq!(metrics.replacements(), 0);
+	assert_eq!(metrics.wait_times().count(), 10);
+	assert_eq!(metrics.reconnects(), &[0, 0]);
+	assert_eq!(metrics.client_checkouts(), &[5, 5]);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_metrics_async() {
+	let pool = init_async_pool().await;
+
+	{
+		let _client = pool.client().await;
+
+		let metrics = pool.metrics();
+		assert_eq!(metrics.in_use(), 1);
+		assert_eq!(metrics.idle(), 1);
+	}
+
+	for _ in 0..9 {
+		assert!(pool.client().await.ping().await.is_ok());
+	}
+
+	let metrics = pool.metrics();
+	assert_eq!(metrics.in_use(), 0);
+	assert_eq!(metrics.idle(), 2);
+	assert_eq!(metrics.checkouts(), 10);
+	assert_eq!(metrics.timeouts(), 0);
+	assert_eq!(metrics.replacements(), 0);
+	assert_eq!(metrics.wait_times().count(), 10);
+	assert_eq!(metrics.reconnects(), &[0, 0]);
+	assert_eq!(metrics.client_checkouts(), &[5, 5]);
+}
+
+#[test]
+#[serial]
+fn pool_concurrent_checkouts() {
+	let pool = init_pool();
+
+	std::thread::scope(|scope| {
+		for _ in 0..4 {
+			scope.spawn(|| {
+				for _ in 0..25 {
+					assert!(pool.client().ping().is_ok());
+				}
+			});
+		}
+	});
+
+	let metrics = pool.metrics();
+	assert_eq!(metrics.checkouts(), 100);
+	assert_eq!(metrics.client_checkouts(), &[50, 50]);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_concurrent_checkouts_async() {
+	let pool = std::sync::Arc::new(init_async_pool().await);
+
+	let tasks = (0..4)
+		.map(|_| {
+			let pool = pool.clone();
+
+			tokio::spawn(async move {
+				for _ in 0..25 {
+					assert!(pool.client().await.ping().await.is_ok());
+				}
+			})
+		})
+		.collect::<Vec<_>>();
+
+	for task in tasks {
+		task.await.unwrap();
+	}
+
+	let metrics = pool.metrics();
+	assert_eq!(metrics.checkouts(), 100);
+	assert_eq!(metrics.client_checkouts(), &[50, 50]);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_worker_pinning_async() {
+	let pool = init_async_pool().await.with_worker_pinning();
+
+	let tasks = (0..4)
+		.map(|worker| {
+			let pool = pool.clone();
+
+			tokio::spawn(paper_client::pin_to_worker(worker, async move {
+				for _ in 0..5 {
+					assert!(pool.client().await.ping().await.is_ok());
+				}
+
+				assert!(pool.client_for("key").await.ping().await.is_ok());
+			}))
+		})
+		.collect::<Vec<_>>();
+
+	for task in tasks {
+		task.await.unwrap();
+	}
+
+	assert_eq!(pool.metrics().client_checkouts(), &[12, 12]);
+
+	paper_client::pin_to_worker(1, async {
+		assert!(pool.client().await.ping().await.is_ok());
+	})
+	.await;
+
+	assert_eq!(pool.metrics().client_checkouts(), &[12, 13]);
+
+	// unpinned tasks are handed clients in turn
+	for _ in 0..2 {
+		assert!(pool.client().await.ping().await.is_ok());
+	}
+
+	assert_eq!(pool.metrics().client_checkouts(), &[13, 14]);
+}
+
+#[test]
+#[serial]
+fn pool_aggregate_stats() {
+	let pool = init_pool();
+
+	for _ in 0..3 {
+		assert!(pool.client().ping().is_ok());
+	}
+
+	assert!(pool.client().get("pool-stats").is_err());
+
+	pool.auth("auth_token").unwrap();
+
+	// a missing key is a response rather than a failure
+	assert!(pool.client().get("pool-stats").is_err());
+
+	let stats = pool.aggregate_stats();
+
+	assert_eq!(stats.connections().len(), 2);
+	assert_eq!(stats.connections()[0].addr(), "127.0.0.1:3145");
+	assert_eq!(stats.commands(), 7);
+	assert_eq!(stats.errors(), 1);
+	assert_eq!(stats.reconnects(), 0);
+
+	let per_connection = stats
+		.connections()
+		.iter()
+		.map(|connection| connection.commands())
+		.sum::<u64>();
+
+	assert_eq!(per_connection, stats.commands());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_aggregate_stats_async() {
+	let pool = init_async_pool().await;
+
+	for _ in 0..3 {
+		assert!(pool.client().await.ping().await.is_ok());
+	}
+
+	assert!(pool.client().await.get("pool-stats").await.is_err());
+
+	pool.auth("auth_token").await.unwrap();
+
+	// a missing key is a response rather than a failure
+	assert!(pool.client().await.get("pool-stats").await.is_err());
+
+	let stats = pool.aggregate_stats();
+
+	assert_eq!(stats.connections().len(), 2);
+	assert_eq!(stats.commands(), 7);
+	assert_eq!(stats.errors(), 1);
+	assert_eq!(stats.reconnects(), 0);
+}
+
+#[test]
+#[serial]
+fn pool_try_client_timeout() {
+	let pool = init_pool();
+
+	let _first = pool.client();
+	let _second = pool.client();
+
+	let result = pool.try_client(Duration::from_millis(10));
+	assert!(matches!(result, Err(PaperClientError::PoolTimeout)));
+
+	let metrics = pool.metrics();
+	assert_eq!(metrics.in_use(), 2);
+	assert_eq!(metrics.timeouts(), 1);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_try_client_timeout_async() {
+	let pool = init_async_pool().await;
+
+	let _first = pool.client().await;
+	let _second = pool.client().await;
+
+	let result = pool.try_client(Duration::from_millis(10)).await;
+	assert!(matches!(result, Err(PaperClientError::PoolTimeout)));
+
+	let metrics = pool.metrics();
+	assert_eq!(metrics.in_use(), 2);
+	assert_eq!(metrics.timeouts(), 1);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_stream_get_async() {
+	let pool = init_async_pool().await;
+	pool.auth("auth_token").await.unwrap();
+
+	let mut client = pool.client().await;
+	assert!(client.wipe().await.is_ok());
+
+	for i in 0..10 {
+		let key = format!("key{i}");
+		assert!(client.set(&key, "value", None).await.is_ok());
+	}
+
+	drop(client);
+
+	let keys = (0..12).map(|i| format!("key{i}"));
+	let mut results = pool
+		.stream_get(keys, 3)
+		.collect::<Vec<_>>()
+		.await;
+
+	results.sort_by(|(a, _), (b, _)| a.cmp(b));
+	assert_eq!(results.len(), 12);
+
+	for (key, value) in results {
+		match key.as_str() {
+			"key10" | "key11" => assert!(value.is_err()),
+			_ => {
+				let value: String = value.unwrap().try_into().unwrap();
+				assert_eq!(value, "value");
+			},
+		}
+	}
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_set_nowait_async() {
+	let pool = init_async_pool().await;
+	pool.auth("auth_token").await.unwrap();
+	assert!(pool.client().await.wipe().await.is_ok());
+
+	for i in 0..10 {
+		assert!(pool.set_nowait(format!("key{i}"), "value", None).is_ok());
+	}
+
+	while !pool.background_tasks().is_empty() {
+		tokio::time::sleep(Duration::from_millis(1)).await;
+	}
+
+	for i in 0..10 {
+		let value = pool.client().await.get(format!("key{i}")).await;
+		assert_eq!(value, Ok(PaperValue::from("value")));
+	}
+
+	assert_eq!(pool.metrics().nowait_errors(), 0);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_set_nowait_errors_async() {
+	// the pool is not authorized, so its writes fail
+	let pool = init_async_pool().await;
+	assert!(pool.set_nowait("key", "value", None).is_ok());
+
+	while !pool.background_tasks().is_empty() {
+		tokio::time::sleep(Duration::from_millis(1)).await;
+	}
+
+	assert_eq!(pool.metrics().nowait_errors(), 1);
+
+	let pool = pool.with_nowait_capacity(0);
+
+	assert_eq!(
+		pool.set_nowait("key", "value", None),
+		Err(PaperClientError::WriteQueueFull),
+	);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_abort_background_tasks_async() {
+	let pool = init_async_pool().await;
+	pool.auth("auth_token").await.unwrap();
+
+	let keys = (0..100).map(|i| format!("key{i}"));
+	let stream = pool.stream_get(keys, 1);
+
+	let tasks = pool.background_tasks();
+	assert_eq!(tasks.len(), 1);
+	assert_eq!(tasks[0].name(), "paper-client::stream_get");
+
+	assert_eq!(pool.abort_background_tasks(), 1);
+	assert!(pool.background_tasks().is_empty());
+
+	let results = stream.collect::<Vec<_>>().await;
+	assert!(results.len() < 100);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_keep_alive_async() {
+	let pool = init_async_pool()
+		.await
+		.with_keep_alive(Duration::from_millis(10));
+
+	let tasks = pool.background_tasks();
+	assert_eq!(tasks.len(), 1);
+	assert_eq!(tasks[0].name(), "paper-client::keep_alive");
+
+	tokio::time::sleep(Duration::from_millis(50)).await;
+	assert!(pool.client().await.ping().await.is_ok());
+
+	assert_eq!(pool.abort_background_tasks(), 1);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_ejection_policy_replaced_async() {
+	let pool = init_async_pool()
+		.await
+		.with_ejection_policy(EjectionPolicy::default())
+		.with_ejection_policy(EjectionPolicy::default());
+
+	// the first policy's task is aborted in favour of the second's
+	for _ in 0..100 {
+		if pool.background_tasks().len() == 1 {
+			break;
+		}
+
+		tokio::time::sleep(Duration::from_millis(1)).await;
+	}
+
+	let tasks = pool.background_tasks();
+	assert_eq!(tasks.len(), 1);
+	assert_eq!(tasks[0].name(), "paper-client::failback");
+
+	assert_eq!(pool.abort_background_tasks(), 1);
+}
+
+#[test]
+#[serial]
+fn pool_drain() {
+	let pool = init_pool();
+	pool.auth("auth_token").unwrap();
+
+	let guard = pool.client();
+
+	assert_eq!(pool.drain(Duration::from_millis(10)), Err(PaperClientError::PoolTimeout));
+	assert!(pool.is_drained());
+
+	drop(guard);
+
+	assert!(pool.drain(Duration::from_millis(10)).is_ok());
+	assert_eq!(pool.try_client(Duration::from_millis(10)).err(), Some(PaperClientError::PoolDrained));
+	assert_eq!(pool.client().ping().err(), Some(PaperClientError::Disconnected));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_drain_async() {
+	let pool = init_async_pool().await;
+	pool.auth("auth_token").await.unwrap();
+
+	let guard = pool.client().await;
+
+	assert_eq!(
+		pool.drain(Duration::from_millis(10)).await,
+		Err(PaperClientError::PoolTimeout),
+	);
+
+	assert!(pool.is_drained());
+
+	drop(guard);
+
+	assert!(pool.drain(Duration::from_millis(10)).await.is_ok());
+
+	assert_eq!(
+		pool.try_client(Duration::from_millis(10)).await.err(),
+		Some(PaperClientError::PoolDrained),
+	);
+
+	assert_eq!(pool.client().await.ping().await.err(), Some(PaperClientError::Disconnected));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_drain_nowait_async() {
+	let pool = init_async_pool().await;
+	pool.auth("auth_token").await.unwrap();
+	assert!(pool.client().await.wipe().await.is_ok());
+
+	for i in 0..20 {
+		assert!(pool.set_nowait(format!("key{i}"), "value", None).is_ok());
+	}
+
+	// the accepted writes complete before the clients are closed
+	assert!(pool.drain(Duration::from_secs(1)).await.is_ok());
+	assert_eq!(pool.metrics().nowait_errors(), 0);
+
+	let reader = init_async_pool().await;
+	reader.auth("auth_token").await.unwrap();
+
+	for i in 0..20 {
+		let value = reader.client().await.get(format!("key{i}")).await;
+		assert_eq!(value, Ok(PaperValue::from("value")));
+	}
+}
+
+#[test]
+#[serial]
+fn pool_latency_weighted() {
+	let pool = init_pool().with_client_selection(ClientSelection::LatencyWeighted);
+
+	for _ in 0..20 {
+		assert!(pool.client().ping().is_ok());
+		assert!(pool.client_for("key").ping().is_ok());
+	}
+
+	assert_eq!(pool.metrics().checkouts(), 40);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn pool_latency_weighted_async() {
+	let pool = init_async_pool()
+		.await
+		.with_client_selection(ClientSelection::LatencyWeighted);
+
+	for _ in 0..20 {
+		assert!(pool.client().await.ping().await.is_ok());
+		assert!(pool.client_for("key").await.ping().await.is_ok());
+	}
+
+	assert_eq!(pool.metrics().checkouts(), 40);
+}
+
+#[test]
+fn pool_mock_clients() {
+	let pool: PaperPool<MemoryClient> = PaperPoolBuilder::default()
+		.addr("paper://127.0.0.1:1")
+		.size(2)
+		.build()
+		.expect("Could not connect pool.");
+
+	assert!(pool.auth("auth_token").is_ok());
+
+	let mut client = pool.client();
+
+	assert!(client.set("key", "value", None).is_ok());
+	assert_eq!(client.has("key"), Ok(true));
+	assert_eq!(client.size("key"), Ok(5));
+	assert!(client.get("other").is_err());
+
+	assert_eq!(client.token.as_deref(), Some("auth_token"));
+}
+
+#[test]
+#[serial]
+fn pool_instrumented_clients() {
+	let pool: PaperPool<InstrumentedClient> = PaperPoolBuilder::default()
+		.addr("paper://127.0.0.1:3145")
+		.size(1)
+		.build()
+		.expect("Could not connect pool.");
+
+	assert!(pool.auth("auth_token").is_ok());
+
+	assert!(pool.client().set("user:1", "value", None).is_ok());
+	assert!(pool.client().get("user:1").is_ok());
+
+	let metrics = pool.client().snapshot();
+	assert_eq!(metrics.prefix("user").map(|prefix| prefix.requests()), Some(2));
+}
+
 fn init_pool() -> PaperPool {
 	PaperPool::new("paper://127.0.0.1:3145", 2).expect("Could not connect pool.")
 }
 
+#[cfg(feature = "tokio")]
 async fn init_async_pool() -> AsyncPaperPool {
 	AsyncPaperPool::new("paper://127.0.0.1:3145", 2)
 		.await
 		.expect("Could not connect pool.")
 }
+
+fn init_sharded_pool() -> PaperPool {
+	PaperPool::new_sharded(
+		&["paper://127.0.0.1:3145", "paper://127.0.0.1:3145"],
+		2,
+		RendezvousSharder,
+	)
+	.expect("Could not connect pool.")
+}
+
+#[cfg(feature = "tokio")]
+async fn init_async_sharded_pool() -> AsyncPaperPool {
+	AsyncPaperPool::new_sharded(
+		&["paper://127.0.0.1:3145", "paper://127.0.0.1:3145"],
+		2,
+		RendezvousSharder,
+	)
+	.await
+	.expect("Could not connect pool.")
+}
+
+// an in-memory client which does not connect to a server
+#[derive(Debug, Default)]
+struct MemoryClient {
+	values: HashMap<String, PaperValue>,
+	token:  Option<String>,
+}
+
+impl PaperCacheOps for MemoryClient {
+	fn connect(_: &str, _: &PaperClientConfig) -> PaperClientResult<Self> {
+		Ok(MemoryClient::default())
+	}
+
+	fn ping(&mut self) -> PaperClientResult<PaperValue> {
+		Ok(PaperValue::from("pong"))
+	}
+
+	fn auth_with_provider(
+		&mut self,
+		provider: Arc<dyn AuthTokenProvider>,
+	) -> PaperClientResult<()> {
+		self.token = Some(provider.token()?);
+		Ok(())
+	}
+
+	fn reauth(&mut self) -> PaperClientResult<()> {
+		Ok(())
+	}
+
+	fn get(&mut self, key: &str) -> PaperClientResult<PaperValue> {
+		self.values
+			.get(key)
+			.cloned()
+			.ok_or(PaperClientError::CacheError(PaperCacheError::KeyNotFound))
+	}
+
+	fn set(
+		&mut self,
+		key: &str,
+		value: impl TryInto<PaperValue>,
+		_: Option<u32>,
+	) -> PaperClientResult<()> {
+		let value = value
+			.try_into()
+			.map_err(|_| PaperClientError::InvalidValue)?;
+
+		self.values.insert(key.to_owned(), value);
+		Ok(())
+	}
+
+	fn del(&mut self, key: &str) -> PaperClientResult<()> {
+		self.values
+			.remove(key)
+			.map(|_| ())
+			.ok_or(PaperClientError::CacheError(PaperCacheError::KeyNotFound))
+	}
+
+	fn has(&mut self, key: &str) -> PaperClientResult<bool> {
+		Ok(self.values.contains_key(key))
+	}
+
+	fn peek(&mut self, key: &str) -> PaperClientResult<PaperValue> {
+		PaperCacheOps::get(self, key)
+	}
+
+	fn ttl(&mut self, _: &str, _: Option<u32>) -> PaperClientResult<()> {
+		Ok(())
+	}
+
+	fn size(&mut self, key: &str) -> PaperClientResult<u32> {
+		PaperCacheOps::get(self, key).map(|value| value.len() as u32)
+	}
+
+	fn wipe(&mut self) -> PaperClientResult<()> {
+		self.values.clear();
+		Ok(())
+	}
+
+	fn status(&mut self) -> PaperClientResult<Status> {
+		Err(PaperClientError::Internal)
+	}
+}
@@ -2,7 +2,7 @@ mod common;
 
 #[cfg(feature = "tokio")]
 use paper_client::AsyncPaperClient;
-use paper_client::PaperClient;
+use paper_client::{Guardrails, PaperClient, PaperClientConfig, PaperClientError};
 use serial_test::serial;
 
 const INITIAL_SIZE: u64 = 10 * 1024u64.pow(2);
@@ -45,6 +45,46 @@ async fn resize_async() {
 	assert_eq!(size, UPDATED_SIZE);
 }
 
+#[test]
+#[serial]
+fn resize_guarded() {
+	let config = PaperClientConfig::default().guardrails(Guardrails::default().max_shrink(0.5));
+	let mut client = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+	client.auth("auth_token").unwrap();
+
+	assert!(client.resize(UPDATED_SIZE).is_ok());
+
+	// shrinking by exactly half is allowed, but not by any more
+	assert_eq!(client.resize(UPDATED_SIZE / 4), Err(PaperClientError::ResizeRefused));
+	assert_eq!(get_cache_size(&mut client), UPDATED_SIZE);
+
+	assert!(client.resize(INITIAL_SIZE).is_ok());
+	assert_eq!(get_cache_size(&mut client), INITIAL_SIZE);
+
+	assert!(client.force_resize(INITIAL_SIZE / 4, "reclaim memory").is_ok());
+	assert_eq!(get_cache_size(&mut client), INITIAL_SIZE / 4);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn resize_guarded_async() {
+	let config = PaperClientConfig::default().guardrails(Guardrails::default().max_shrink(0.5));
+
+	let mut client = AsyncPaperClient::with_config("paper://127.0.0.1:3145", config)
+		.await
+		.unwrap();
+
+	client.auth("auth_token").await.unwrap();
+
+	assert!(client.resize(UPDATED_SIZE).await.is_ok());
+	assert_eq!(client.resize(UPDATED_SIZE / 4).await, Err(PaperClientError::ResizeRefused));
+	assert_eq!(get_cache_size_async(&mut client).await, UPDATED_SIZE);
+
+	assert!(client.force_resize(INITIAL_SIZE / 4, "reclaim memory").await.is_ok());
+	assert_eq!(get_cache_size_async(&mut client).await, INITIAL_SIZE / 4);
+}
+
 fn get_cache_size(client: &mut PaperClient) -> u64 {
 	let status = client
 		.status()
@@ -53,6 +93,7 @@ fn get_cache_size(client: &mut PaperClient) -> u64 {
 	status.max_size()
 }
 
+#[cfg(feature = "tokio")]
 async fn get_cache_size_async(client: &mut AsyncPaperClient) -> u64 {
 	let status = client
 		.status()
@@ -0,0 +1,427 @@
+use std::{
+	io::{self, Read, Write},
+	time::Duration,
+};
+#[cfg(feature = "tokio")]
+use std::{
+	pin::Pin,
+	task::{Context, Poll},
+};
+
+#[cfg(feature = "tokio")]
+use paper_client::{AsyncPaperClient, AsyncPaperTransport};
+use paper_client::{
+	Clock,
+	Command,
+	LoopbackTransport,
+	MockClock,
+	PaperClient,
+	PaperClientConfig,
+	PaperClientError,
+	PaperTransport,
+	RetryBudget,
+	RetryableError,
+	ScriptedResponder,
+	error::PaperClientResult,
+};
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+// completes the handshake, after which every write fails as though the
+// server had closed the connection
+struct ClosedTransport(LoopbackTransport);
+
+// accepts every connection, but closes it after the handshake without
+// responding to any command
+#[derive(Default)]
+struct ResetTransport {
+	is_handshake_sent: bool,
+}
+
+#[test]
+fn retry_budget_exhausted() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Get("key"))
+		.reply_raw(b"x".to_vec());
+
+	let config = PaperClientConfig::default().retry_budget(RetryBudget::new(0.1, 0));
+
+	let mut client =
+		PaperClient::from_transport_with_config("paper://loopback", responder.transport(), config)
+			.unwrap();
+
+	assert_eq!(client.get("key").err(), Some(PaperClientError::RetryBudgetExhausted));
+	responder.verify();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn retry_budget_exhausted_async() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Get("key"))
+		.reply_raw(b"x".to_vec());
+
+	let config = PaperClientConfig::default().retry_budget(RetryBudget::new(0.1, 0));
+
+	let mut client =
+		AsyncPaperClient::from_transport_with_config("paper://loopback", responder.transport(), config)
+			.await
+			.unwrap();
+
+	assert_eq!(client.get("key").await.err(), Some(PaperClientError::RetryBudgetExhausted));
+	responder.verify();
+}
+
+#[test]
+fn retry_budget_available() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Get("key"))
+		.reply_raw(b"x".to_vec());
+
+	let config = PaperClientConfig::default().retry_budget(RetryBudget::default());
+
+	let mut client =
+		PaperClient::from_transport_with_config("paper://loopback", responder.transport(), config)
+			.unwrap();
+
+	// the retry is attempted, but the loopback transport cannot reconnect
+	assert_eq!(client.get("key").err(), Some(PaperClientError::UnreachableServer));
+	responder.verify();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn retry_budget_available_async() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Get("key"))
+		.reply_raw(b"x".to_vec());
+
+	let config = PaperClientConfig::default().retry_budget(RetryBudget::default());
+
+	let mut client =
+		AsyncPaperClient::from_transport_with_config("paper://loopback", responder.transport(), config)
+			.await
+			.unwrap();
+
+	assert_eq!(client.get("key").await.err(), Some(PaperClientError::UnreachableServer));
+	responder.verify();
+}
+
+#[test]
+fn retry_budget_burst() {
+	assert_eq!(RetryBudget::new(0.1, 5).available(), 5);
+	assert_eq!(RetryBudget::default().available(), 10);
+}
+
+#[test]
+fn retry_on_disconnected() {
+	let responder = ScriptedResponder::new();
+	let transport = ClosedTransport(responder.transport());
+
+	let mut client = PaperClient::from_transport("paper://loopback", transport).unwrap();
+	assert_eq!(client.get("key").err(), Some(PaperClientError::Disconnected));
+
+	let config = PaperClientConfig::default()
+		.retry_on([RetryableError::InvalidResponse, RetryableError::Disconnected]);
+
+	let transport = ClosedTransport(responder.transport());
+
+	let mut client =
+		PaperClient::from_transport_with_config("paper://loopback", transport, config).unwrap();
+
+	// the retry is attempted, but the closed transport cannot reconnect
+	assert_eq!(client.get("key").err(), Some(PaperClientError::UnreachableServer));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn retry_on_disconnected_async() {
+	let responder = ScriptedResponder::new();
+
+	let config = PaperClientConfig::default()
+		.retry_on([RetryableError::InvalidResponse, RetryableError::Disconnected]);
+
+	let transport = ClosedTransport(responder.transport());
+
+	let mut client =
+		AsyncPaperClient::from_transport_with_config("paper://loopback", transport, config)
+			.await
+			.unwrap();
+
+	assert_eq!(client.get("key").await.err(), Some(PaperClientError::UnreachableServer));
+}
+
+#[test]
+fn retry_on_none() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Get("key"))
+		.reply_raw(b"x".to_vec());
+
+	let config = PaperClientConfig::default().retry_on([]);
+
+	let mut client =
+		PaperClient::from_transport_with_config("paper://loopback", responder.transport(), config)
+			.unwrap();
+
+	assert_eq!(client.get("key").err(), Some(PaperClientError::InvalidResponse));
+	responder.verify();
+}
+
+#[test]
+fn reconnect_exhausted() {
+	let config = PaperClientConfig::default().reconnect_backoff(Duration::ZERO);
+
+	let mut client =
+		PaperClient::from_transport_with_config("paper://reset", ResetTransport::default(), config)
+			.unwrap();
+
+	let err = client.get("key").unwrap_err();
+
+	assert_eq!(err, PaperClientError::ReconnectExhausted {
+		attempts:   3,
+		last_error: Box::new(PaperClientError::InvalidResponse),
+	});
+
+	assert_eq!(client.reconnect_attempts(), 3);
+}
+
+#[test]
+fn reconnect_backoff_clock() {
+	let clock = MockClock::new();
+	let start = clock.now();
+
+	let config = PaperClientConfig::default()
+		.reconnect_backoff(Duration::from_secs(3600))
+		.clock(clock.clone());
+
+	let mut client =
+		PaperClient::from_transport_with_config("paper://reset", ResetTransport::default(), config)
+			.unwrap();
+
+	assert!(client.get("key").is_err());
+
+	// the second and third attempts wait for at least half of one and two
+	// hours respectively, without blocking
+	assert!(clock.now() - start >= Duration::from_secs(5400));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn reconnect_exhausted_async() {
+	let config = PaperClientConfig::default().reconnect_backoff(Duration::ZERO);
+
+	let mut client =
+		AsyncPaperClient::from_transport_with_config("paper://reset", ResetTransport::default(), config)
+			.await
+			.unwrap();
+
+	let err = client.get("key").await.unwrap_err();
+
+	assert_eq!(err, PaperClientError::ReconnectExhausted {
+		attempts:   3,
+		last_error: Box::new(PaperClientError::InvalidResponse),
+	});
+
+	assert_eq!(client.reconnect_attempts(), 3);
+}
+
+#[test]
+fn strict_no_retry() {
+	let responder = ScriptedResponder::new();
+
+	responder
+		.expect(Command::Get("key"))
+		.reply_raw(b"x".to_vec());
+
+	let config = PaperClientConfig::default()
+		.retry_on([RetryableError::InvalidResponse, RetryableError::Disconnected])
+		.strict(true);
+
+	let mut client =
+		PaperClient::from_transport_with_config("paper://loopback", responder.transport(), config)
+			.unwrap();
+
+	assert_eq!(client.get("key").err(), Some(PaperClientError::InvalidResponse));
+	responder.verify();
+}
+
+#[test]
+fn strict_no_reconnect() {
+	let config = PaperClientConfig::default()
+		.reconnect_backoff(Duration::ZERO)
+		.strict(true);
+
+	let mut client =
+		PaperClient::from_transport_with_config("paper://reset", ResetTransport::default(), config)
+			.unwrap();
+
+	assert_eq!(client.get("key").err(), Some(PaperClientError::InvalidResponse));
+	assert_eq!(client.reconnect_attempts(), 0);
+
+	// the failed connection is not replaced on behalf of the next command
+	assert_eq!(client.get("key").err(), Some(PaperClientError::Disconnected));
+	assert_eq!(client.reconnect_attempts(), 0);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn strict_no_reconnect_async() {
+	let config = PaperClientConfig::default()
+		.reconnect_backoff(Duration::ZERO)
+		.strict(true);
+
+	let mut client =
+		AsyncPaperClient::from_transport_with_config("paper://reset", ResetTransport::default(), config)
+			.await
+			.unwrap();
+
+	assert_eq!(client.get("key").await.err(), Some(PaperClientError::InvalidResponse));
+	assert_eq!(client.reconnect_attempts(), 0);
+
+	assert_eq!(client.get("key").await.err(), Some(PaperClientError::Disconnected));
+	assert_eq!(client.reconnect_attempts(), 0);
+}
+
+impl Read for ClosedTransport {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		self.0.read(buf)
+	}
+}
+
+impl Write for ClosedTransport {
+	fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+		Err(io::ErrorKind::BrokenPipe.into())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Err(io::ErrorKind::BrokenPipe.into())
+	}
+}
+
+impl PaperTransport for ClosedTransport {
+	fn open(_: &str) -> PaperClientResult<Self> {
+		Err(PaperClientError::UnreachableServer)
+	}
+
+	fn set_read_timeout(&mut self, _: Option<Duration>) -> PaperClientResult<()> {
+		Ok(())
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncRead for ClosedTransport {
+	fn poll_read(
+		mut self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &mut ReadBuf<'_>,
+	) -> Poll<io::Result<()>> {
+		Pin::new(&mut self.0).poll_read(cx, buf)
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncWrite for ClosedTransport {
+	fn poll_write(self: Pin<&mut Self>, _: &mut Context<'_>, _: &[u8]) -> Poll<io::Result<usize>> {
+		Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()))
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()))
+	}
+
+	fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Poll::Ready(Ok(()))
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncPaperTransport for ClosedTransport {
+	async fn open(_: &str) -> PaperClientResult<Self> {
+		Err(PaperClientError::UnreachableServer)
+	}
+}
+
+impl ResetTransport {
+	fn read_handshake(&mut self, buf: &mut [u8]) -> usize {
+		if self.is_handshake_sent || buf.is_empty() {
+			return 0;
+		}
+
+		self.is_handshake_sent = true;
+		buf[0] = b'!';
+
+		1
+	}
+}
+
+impl Read for ResetTransport {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		Ok(self.read_handshake(buf))
+	}
+}
+
+impl Write for ResetTransport {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+impl PaperTransport for ResetTransport {
+	fn open(_: &str) -> PaperClientResult<Self> {
+		Ok(ResetTransport::default())
+	}
+
+	fn set_read_timeout(&mut self, _: Option<Duration>) -> PaperClientResult<()> {
+		Ok(())
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncRead for ResetTransport {
+	fn poll_read(
+		mut self: Pin<&mut Self>,
+		_: &mut Context<'_>,
+		buf: &mut ReadBuf<'_>,
+	) -> Poll<io::Result<()>> {
+		let len = self.read_handshake(buf.initialize_unfilled());
+		buf.advance(len);
+
+		Poll::Ready(Ok(()))
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncWrite for ResetTransport {
+	fn poll_write(self: Pin<&mut Self>, _: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+		Poll::Ready(Ok(buf.len()))
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Poll::Ready(Ok(()))
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncPaperTransport for ResetTransport {
+	async fn open(_: &str) -> PaperClientResult<Self> {
+		Ok(ResetTransport::default())
+	}
+}
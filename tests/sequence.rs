@@ -0,0 +1,88 @@
+mod common;
+
+use std::{thread, time::Duration};
+
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn sequence_acknowledged() {
+	let mut client = common::init_client(true);
+
+	// the client is wiped once it has been initialized
+	let token = client.sequence();
+	assert_eq!(token.position(), 1);
+	assert!(token.is_acknowledged());
+
+	assert!(client.set("key", "value", None).is_ok());
+	assert!(client.get("key").is_ok());
+	assert_eq!(client.sequence().position(), 2);
+
+	// a mutation which the server rejects is still acknowledged
+	assert!(client.del("non_existent").is_err());
+
+	let token = client.sequence();
+	assert_eq!(token.position(), 3);
+	assert!(token.is_acknowledged());
+	assert!(token.wait(Duration::from_millis(10)).is_ok());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn sequence_acknowledged_async() {
+	let mut client = common::init_async_client(true).await;
+
+	// the client is wiped once it has been initialized
+	let token = client.sequence();
+	assert_eq!(token.position(), 1);
+	assert!(token.is_acknowledged());
+
+	assert!(client.set("key", "value", None).await.is_ok());
+	assert!(client.get("key").await.is_ok());
+	assert_eq!(client.sequence().position(), 2);
+
+	// a mutation which the server rejects is still acknowledged
+	assert!(client.del("non_existent").await.is_err());
+
+	let token = client.sequence();
+	assert_eq!(token.position(), 3);
+	assert!(token.is_acknowledged());
+	assert!(token.wait_async(Duration::from_millis(10)).await.is_ok());
+}
+
+#[test]
+#[serial]
+fn sequence_batch() {
+	let mut client = common::init_client(true);
+
+	let result = client.batch(|batch| {
+		batch.set("key", "value", None);
+		batch.get("key");
+		batch.del("key");
+	});
+
+	assert!(result.is_ok());
+
+	let token = client.sequence();
+	assert_eq!(token.position(), 3);
+	assert!(token.is_acknowledged());
+}
+
+#[test]
+#[serial]
+fn sequence_across_connections() {
+	let mut writer = common::init_client(true);
+	let mut reader = common::init_client(false);
+	reader.auth("auth_token").unwrap();
+
+	let token = thread::spawn(move || {
+		assert!(writer.set("key", "value", None).is_ok());
+		writer.sequence()
+	})
+	.join()
+	.unwrap();
+
+	assert!(token.wait(Duration::from_secs(1)).is_ok());
+	assert_eq!(reader.get("key").unwrap(), "value");
+}
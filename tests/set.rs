@@ -2,6 +2,7 @@ mod common;
 
 use std::{thread, time::Duration};
 
+use paper_client::{PaperClientError, PaperValue, error::CommandError};
 use serial_test::serial;
 
 #[test]
@@ -76,3 +77,49 @@ async fn set_ttl_expiry_async() {
 	let expired = client.get("key").await;
 	assert!(expired.is_err());
 }
+
+#[cfg(target_pointer_width = "64")]
+#[test]
+#[ignore = "allocates a 4 GiB value"]
+#[serial]
+fn set_value_too_large() {
+	let mut client = common::init_client(true);
+
+	let size = u32::MAX as usize + 1;
+	let value = PaperValue::from(vec![0u8; size]);
+
+	assert_eq!(
+		client.set("key", value, None),
+		Err(PaperClientError::CommandError(CommandError::ValueTooLarge {
+			size,
+			max: u32::MAX as usize,
+		})),
+	);
+
+	// nothing was sent, so the connection is still in sync
+	assert!(client.set("key", "value", None).is_ok());
+	assert_eq!(client.client_stats().reconnects(), 0);
+}
+
+#[cfg(feature = "tokio")]
+#[cfg(target_pointer_width = "64")]
+#[tokio::test]
+#[ignore = "allocates a 4 GiB value"]
+#[serial]
+async fn set_value_too_large_async() {
+	let mut client = common::init_async_client(true).await;
+
+	let size = u32::MAX as usize + 1;
+	let value = PaperValue::from(vec![0u8; size]);
+
+	assert_eq!(
+		client.set("key", value, None).await,
+		Err(PaperClientError::CommandError(CommandError::ValueTooLarge {
+			size,
+			max: u32::MAX as usize,
+		})),
+	);
+
+	assert!(client.set("key", "value", None).await.is_ok());
+	assert_eq!(client.client_stats().reconnects(), 0);
+}
@@ -0,0 +1,150 @@
+use std::{env, fs, path::PathBuf};
+
+#[cfg(feature = "tokio")]
+use paper_client::{AsyncPaperClient, AsyncPaperPool};
+use paper_client::{PaperClient, PaperClientError, PaperPool, PaperSettings};
+use serial_test::serial;
+
+const CONFIG: &str = r#"
+# PaperCache connection settings
+addr = "paper://127.0.0.1:3145"
+auth_token = "auth_token" # used by every client
+pool_size = 2
+handshake_timeout_ms = 1_000
+connect_retries = 3
+connect_backoff_ms = 50
+"#;
+
+#[test]
+fn settings_from_file() {
+	let path = write_config("settings_from_file", CONFIG);
+	let settings = PaperSettings::from_file(&path).expect("Could not read settings.");
+	fs::remove_file(&path).unwrap();
+
+	assert_eq!(settings.addr(), "paper://127.0.0.1:3145");
+	assert_eq!(settings.auth_token(), Some("auth_token"));
+	assert_eq!(settings.pool_size(), 2);
+}
+
+#[test]
+fn settings_from_file_invalid() {
+	let missing_addr = write_config("settings_missing_addr", "pool_size = 2\n");
+	let unknown_key = write_config(
+		"settings_unknown_key",
+		"addr = \"paper://127.0.0.1:3145\"\nsize = 2\n",
+	);
+
+	let invalid_value = write_config(
+		"settings_invalid_value",
+		"addr = \"paper://127.0.0.1:3145\"\npool_size = two\n",
+	);
+
+	for path in [missing_addr, unknown_key, invalid_value] {
+		let result = PaperSettings::from_file(&path);
+		fs::remove_file(&path).unwrap();
+
+		assert_eq!(result.err(), Some(PaperClientError::InvalidConfig));
+	}
+
+	let result = PaperSettings::from_file("/nonexistent/paper.toml");
+	assert_eq!(result.err(), Some(PaperClientError::InvalidConfig));
+}
+
+#[test]
+#[serial]
+fn settings_from_env() {
+	// SAFETY: tests which access the environment are run serially
+	unsafe {
+		env::set_var("PAPER_ADDR", "paper://127.0.0.1:3145");
+		env::set_var("PAPER_AUTH_TOKEN", "auth_token");
+		env::set_var("PAPER_POOL_SIZE", "3");
+	}
+
+	let settings = PaperSettings::from_env();
+
+	unsafe {
+		env::remove_var("PAPER_ADDR");
+		env::remove_var("PAPER_AUTH_TOKEN");
+		env::remove_var("PAPER_POOL_SIZE");
+	}
+
+	let settings = settings.expect("Could not read settings.");
+
+	assert_eq!(settings.addr(), "paper://127.0.0.1:3145");
+	assert_eq!(settings.auth_token(), Some("auth_token"));
+	assert_eq!(settings.pool_size(), 3);
+}
+
+#[test]
+#[serial]
+fn settings_from_env_missing_addr() {
+	let result = PaperSettings::from_env();
+	assert_eq!(result.err(), Some(PaperClientError::InvalidConfig));
+}
+
+#[test]
+fn client_from_config() {
+	let path = write_config("client_from_config", CONFIG);
+	let client = PaperClient::from_config(&path);
+	fs::remove_file(&path).unwrap();
+
+	let mut client = client.expect("Could not connect client.");
+	assert!(client.has("key").is_ok());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn client_from_config_async() {
+	let path = write_config("client_from_config_async", CONFIG);
+	let client = AsyncPaperClient::from_config(&path).await;
+	fs::remove_file(&path).unwrap();
+
+	let mut client = client.expect("Could not connect client.");
+	assert!(client.has("key").await.is_ok());
+}
+
+#[test]
+fn pool_from_config() {
+	let path = write_config("pool_from_config", CONFIG);
+	let pool = PaperPool::from_config(&path);
+	fs::remove_file(&path).unwrap();
+
+	let pool = pool.expect("Could not connect pool.");
+	assert_eq!(pool.metrics().idle(), 2);
+	assert!(pool.client().has("key").is_ok());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn pool_from_config_async() {
+	let path = write_config("pool_from_config_async", CONFIG);
+	let pool = AsyncPaperPool::from_config(&path).await;
+	fs::remove_file(&path).unwrap();
+
+	let pool = pool.expect("Could not connect pool.");
+	assert_eq!(pool.metrics().idle(), 2);
+	assert!(pool.client().await.has("key").await.is_ok());
+}
+
+#[test]
+fn settings_handshake_timeout_disabled() {
+	let path = write_config(
+		"settings_handshake_timeout_disabled",
+		"addr = \"paper://127.0.0.1:3145\"\nhandshake_timeout_ms = 0\n",
+	);
+
+	let settings = PaperSettings::from_file(&path);
+	fs::remove_file(&path).unwrap();
+
+	let settings = settings.expect("Could not read settings.");
+	assert!(format!("{:?}", settings.config()).contains("handshake_timeout: None"));
+
+	let client = PaperClient::from_settings(settings);
+	assert!(client.is_ok());
+}
+
+fn write_config(name: &str, contents: &str) -> PathBuf {
+	let path = env::temp_dir().join(format!("paper_client_{name}.toml"));
+	fs::write(&path, contents).expect("Could not write config.");
+	path
+}
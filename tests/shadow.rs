@@ -0,0 +1,93 @@
+mod common;
+
+#[cfg(feature = "tokio")]
+use paper_client::AsyncShadowClient;
+use paper_client::ShadowClient;
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn shadow_mirrors_writes() {
+	let primary = common::init_client(true);
+	let shadow = common::init_client(true);
+
+	let mut client = ShadowClient::new(primary, shadow);
+
+	assert!(client.set("key", "value", None).is_ok());
+	assert!(client.ttl("key", Some(60)).is_ok());
+	assert!(client.get("key").is_ok());
+	assert!(client.del("absent").is_err());
+
+	client.flush();
+
+	let metrics = client.metrics();
+	assert_eq!(metrics.mirrored(), 2);
+	assert_eq!(metrics.dropped(), 0);
+	assert_eq!(metrics.errors(), 0);
+	assert_eq!(metrics.mismatches(), 0);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn shadow_mirrors_writes_async() {
+	let primary = common::init_async_client(true).await;
+	let shadow = common::init_async_client(true).await;
+
+	let mut client = AsyncShadowClient::new(primary, shadow);
+
+	assert!(client.set("key", "value", None).await.is_ok());
+	assert!(client.ttl("key", Some(60)).await.is_ok());
+	assert!(client.get("key").await.is_ok());
+	assert!(client.del("absent").await.is_err());
+
+	client.flush().await;
+
+	let metrics = client.metrics();
+	assert_eq!(metrics.mirrored(), 2);
+	assert_eq!(metrics.dropped(), 0);
+	assert_eq!(metrics.errors(), 0);
+	assert_eq!(metrics.mismatches(), 0);
+}
+
+#[test]
+#[serial]
+fn shadow_samples_reads() {
+	let primary = common::init_client(true);
+	let shadow = common::init_client(true);
+
+	let mut client = ShadowClient::new(primary, shadow).with_read_sample_rate(0.5);
+
+	assert!(client.set("key", "value", None).is_ok());
+
+	for _ in 0..4 {
+		assert!(client.get("key").is_ok());
+	}
+
+	client.flush();
+
+	let metrics = client.metrics();
+	assert_eq!(metrics.mirrored(), 3);
+	assert_eq!(metrics.mismatches(), 0);
+}
+
+#[test]
+#[serial]
+fn shadow_mismatch() {
+	let primary = common::init_client(true);
+	let shadow = common::init_client(true);
+
+	let mut client = ShadowClient::new(primary, shadow);
+
+	assert!(client.set("key", "value", None).is_ok());
+	client.flush();
+
+	// the shadow shares the primary's cache, so the key has already been
+	// deleted by the time the del is mirrored
+	assert!(client.del("key").is_ok());
+	client.flush();
+
+	let metrics = client.metrics();
+	assert_eq!(metrics.mirrored(), 2);
+	assert_eq!(metrics.mismatches(), 1);
+}
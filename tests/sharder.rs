@@ -0,0 +1,64 @@
+use paper_client::{ConsistentHashSharder, ModuloSharder, RendezvousSharder, Sharder};
+
+#[test]
+fn sharder_stable() {
+	let nodes = init_nodes(4);
+
+	for sharder in init_sharders() {
+		for key in ["a", "b", "c", "key"] {
+			let shard = sharder.shard(key, &nodes);
+
+			assert!(shard < nodes.len());
+			assert_eq!(shard, sharder.shard(key, &nodes));
+		}
+	}
+}
+
+#[test]
+fn sharder_distributes() {
+	let nodes = init_nodes(4);
+
+	for sharder in init_sharders() {
+		let mut counts = [0usize; 4];
+
+		for i in 0..4000 {
+			counts[sharder.shard(&format!("key_{i}"), &nodes)] += 1;
+		}
+
+		assert!(counts.iter().all(|count| *count > 500));
+	}
+}
+
+#[test]
+fn sharder_minimal_remap() {
+	let nodes = init_nodes(4);
+	let grown = init_nodes(5);
+
+	let sharders: [Box<dyn Sharder>; 2] = [
+		Box::new(ConsistentHashSharder::default()),
+		Box::new(RendezvousSharder),
+	];
+
+	for sharder in sharders {
+		let moved = (0..4000)
+			.map(|i| format!("key_{i}"))
+			.filter(|key| sharder.shard(key, &nodes) != sharder.shard(key, &grown))
+			.count();
+
+		assert!(moved < 1600);
+	}
+}
+
+fn init_sharders() -> [Box<dyn Sharder>; 3] {
+	[
+		Box::new(ModuloSharder),
+		Box::new(ConsistentHashSharder::default()),
+		Box::new(RendezvousSharder),
+	]
+}
+
+fn init_nodes(count: usize) -> Vec<String> {
+	(0..count)
+		.map(|i| format!("127.0.0.1:{}", 3145 + i))
+		.collect()
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use std::{thread, time::Duration};
+
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn get_or_compute_coordinated_existent() {
+	let mut client = common::init_client(true);
+
+	assert!(client.set("key", "value", None).is_ok());
+
+	let result = client.get_or_compute_coordinated("key", None, || -> &str {
+		panic!("computed an existent value")
+	});
+
+	assert_eq!(result.unwrap(), "value");
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn get_or_compute_coordinated_existent_async() {
+	let mut client = common::init_async_client(true).await;
+
+	assert!(client.set("key", "value", None).await.is_ok());
+
+	let result = client
+		.get_or_compute_coordinated("key", None, || async {
+			panic!("computed an existent value") as &str
+		})
+		.await;
+
+	assert_eq!(result.unwrap(), "value");
+}
+
+#[test]
+#[serial]
+fn get_or_compute_coordinated_non_existent() {
+	let mut client = common::init_client(true);
+
+	let result = client.get_or_compute_coordinated("key", None, || "value");
+	assert_eq!(result.unwrap(), "value");
+
+	assert_eq!(client.get("key").unwrap(), "value");
+	assert!(!client.has("key#lock").unwrap());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn get_or_compute_coordinated_non_existent_async() {
+	let mut client = common::init_async_client(true).await;
+
+	let result = client
+		.get_or_compute_coordinated("key", None, || async { "value" })
+		.await;
+
+	assert_eq!(result.unwrap(), "value");
+
+	assert_eq!(client.get("key").await.unwrap(), "value");
+	assert!(!client.has("key#lock").await.unwrap());
+}
+
+#[test]
+#[serial]
+fn get_or_compute_coordinated_locked() {
+	let mut client = common::init_client(true);
+	let mut holder_client = common::init_client(true);
+
+	assert!(client.set("key#lock", "holder", None).is_ok());
+
+	let holder = thread::spawn(move || {
+		thread::sleep(Duration::from_millis(50));
+		holder_client.set("key", "value", None)
+	});
+
+	let result = client.get_or_compute_coordinated("key", None, || -> &str {
+		panic!("computed a locked value")
+	});
+
+	assert!(holder.join().unwrap().is_ok());
+	assert_eq!(result.unwrap(), "value");
+}
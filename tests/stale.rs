@@ -0,0 +1,95 @@
+mod common;
+
+use paper_client::{PaperClientError, error::PaperCacheError};
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn get_allow_stale_fresh() {
+	let mut client = common::init_client(true);
+
+	assert!(client.set("key", "value", None).is_ok());
+
+	let result = client.get_allow_stale("key").unwrap();
+
+	assert!(!result.is_stale());
+	assert_eq!(result.into_value(), "value");
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn get_allow_stale_fresh_async() {
+	let mut client = common::init_async_client(true).await;
+
+	assert!(client.set("key", "value", None).await.is_ok());
+
+	let result = client.get_allow_stale("key").await.unwrap();
+
+	assert!(!result.is_stale());
+	assert_eq!(result.into_value(), "value");
+}
+
+#[test]
+#[serial]
+fn get_allow_stale_invalidated() {
+	let mut client = common::init_client(true);
+
+	assert!(client.set("key", "value", None).is_ok());
+	assert!(client.invalidate_soft("key").is_ok());
+
+	let first = client.get_allow_stale("key").unwrap();
+	let second = client.get_allow_stale("key").unwrap();
+
+	assert!(first.is_stale());
+	assert_eq!(first.value(), "value");
+
+	assert!(!second.is_stale());
+	assert_eq!(second.value(), "value");
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn get_allow_stale_invalidated_async() {
+	let mut client = common::init_async_client(true).await;
+
+	assert!(client.set("key", "value", None).await.is_ok());
+	assert!(client.invalidate_soft("key").await.is_ok());
+
+	let first = client.get_allow_stale("key").await.unwrap();
+	let second = client.get_allow_stale("key").await.unwrap();
+
+	assert!(first.is_stale());
+	assert_eq!(first.value(), "value");
+
+	assert!(!second.is_stale());
+	assert_eq!(second.value(), "value");
+}
+
+#[test]
+#[serial]
+fn get_allow_stale_non_existent() {
+	let mut client = common::init_client(true);
+
+	let result = client.get_allow_stale("key");
+
+	assert!(matches!(
+		result,
+		Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound))
+	));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn get_allow_stale_non_existent_async() {
+	let mut client = common::init_async_client(true).await;
+
+	let result = client.get_allow_stale("key").await;
+
+	assert!(matches!(
+		result,
+		Err(PaperClientError::CacheError(PaperCacheError::KeyNotFound))
+	));
+}
@@ -1,5 +1,9 @@
 mod common;
 
+use std::time::Duration;
+
+use paper_client::{PaperClientError, PaperPolicy, Status};
+
 #[test]
 fn status() {
 	let mut client = common::init_client(true);
@@ -16,3 +20,108 @@ async fn status_async() {
 	let result = client.status().await;
 	assert!(result.is_ok());
 }
+
+#[test]
+fn status_display() {
+	let status = Status::new(
+		42,
+		20 * 1024 * 1024,
+		5 * 1024 * 1024,
+		3,
+		1000,
+		1536,
+		10,
+		5,
+		1,
+		0.25,
+		vec![PaperPolicy::Lfu, PaperPolicy::Lru],
+		PaperPolicy::Lru,
+		true,
+		90_061,
+	);
+
+	let expected = "\
+pid:         42
+max size:    20.00 MiB
+used size:   5.00 MiB (25.00%)
+objects:     3
+rss:         1000 B
+hwm:         1.50 KiB
+gets:        10
+sets:        5
+dels:        1
+miss ratio:  25.00%
+policy:      lru (auto)
+policies:    lfu, lru
+uptime:      1d 1h 1m 1s";
+
+	assert_eq!(status.to_string(), expected);
+	assert_eq!(status.uptime(), Duration::from_secs(90_061));
+	assert_eq!(status.uptime_secs(), 90_061);
+}
+
+#[test]
+fn status_json() {
+	let status = Status::new(
+		42,
+		1024,
+		512,
+		3,
+		1000,
+		1536,
+		10,
+		5,
+		1,
+		0.25,
+		vec![PaperPolicy::Lfu, PaperPolicy::TwoQ(0.25, 0.5)],
+		PaperPolicy::TwoQ(0.25, 0.5),
+		false,
+		100,
+	);
+
+	let expected = "{\
+\"schema_version\":1,\"pid\":42,\"max_size\":1024,\"used_size\":512,\"num_objects\":3,\
+\"rss\":1000,\"hwm\":1536,\"total_gets\":10,\"total_sets\":5,\"total_dels\":1,\
+\"miss_ratio\":0.25,\"policies\":[\"lfu\",\"2q-0.25-0.5\"],\"policy\":\"2q-0.25-0.5\",\
+\"is_auto_policy\":false,\"uptime_secs\":100}";
+
+	assert_eq!(status.to_json(), expected);
+	assert_eq!(Status::from_json(expected), Ok(status));
+}
+
+#[test]
+fn status_json_unknown_fields() {
+	let json = r#"{
+		"schema_version": 1, "pid": 42, "max_size": 1024, "used_size": 512,
+		"num_objects": 3, "rss": 1000, "hwm": 1536, "total_gets": 10,
+		"total_sets": 5, "total_dels": 1, "miss_ratio": 0.25,
+		"policies": ["lfu"], "policy": "lfu", "is_auto_policy": true,
+		"uptime_secs": 100, "region": "us-east-1"
+	}"#;
+
+	let status = Status::from_json(json).unwrap();
+
+	assert_eq!(status.pid(), 42);
+	assert_eq!(status.policy(), &PaperPolicy::Lfu);
+	assert!(status.is_auto_policy());
+}
+
+#[test]
+fn status_json_invalid() {
+	let status = Status::new(42, 0, 0, 0, 0, 0, 0, 0, 0, 0.0, vec![], PaperPolicy::Lfu, false, 0);
+	let json = status.to_json();
+
+	assert!(Status::from_json(&json).is_ok());
+
+	let invalid = [
+		json.replace("\"schema_version\":1", "\"schema_version\":2"),
+		json.replace("\"pid\":42,", ""),
+		json.replace("\"lfu\"", "\"unknown\""),
+		json[..json.len() - 1].to_owned(),
+		format!("{json}{{}}"),
+	];
+
+	for json in invalid {
+		assert_eq!(Status::from_json(&json), Err(PaperClientError::InvalidJson));
+	}
+}
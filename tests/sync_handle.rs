@@ -0,0 +1,102 @@
+#![cfg(feature = "tokio")]
+
+mod common;
+
+use std::{
+	io::Write,
+	net::TcpListener,
+	thread,
+	time::{Duration, Instant},
+};
+
+use paper_client::{PaperClient, PaperClientError, SyncHandle, with_deadline_async};
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn sync_handle_run() {
+	let handle = SyncHandle::new(common::init_client(true));
+
+	let result = handle
+		.run(|client| client.set("key", "value", None))
+		.await;
+
+	assert!(result.is_ok());
+
+	let value = handle.run(|client| client.get("key")).await;
+	assert!(value.is_ok_and(|value| value == "value"));
+}
+
+#[tokio::test]
+#[serial]
+async fn sync_handle_shared() {
+	let handle = SyncHandle::new(common::init_client(true));
+
+	let tasks = (0..4)
+		.map(|index| {
+			let handle = handle.clone();
+
+			tokio::spawn(async move {
+				handle
+					.run(move |client| client.set(format!("key{index}"), "value", None))
+					.await
+			})
+		})
+		.collect::<Vec<_>>();
+
+	for task in tasks {
+		assert!(task.await.unwrap().is_ok());
+	}
+
+	let has = handle.run(|client| client.has("key3")).await;
+	assert_eq!(has, Ok(true));
+}
+
+#[tokio::test]
+async fn sync_handle_deadline() {
+	let client = PaperClient::new(init_unresponsive_server()).unwrap();
+	let handle = SyncHandle::new(client);
+
+	let deadline = Instant::now() + Duration::from_millis(50);
+	let result = with_deadline_async(deadline, handle.run(|client| client.get("key"))).await;
+
+	assert_eq!(result.err(), Some(PaperClientError::DeadlineExceeded));
+}
+
+#[tokio::test]
+#[serial]
+#[should_panic(expected = "closure panicked")]
+async fn sync_handle_panic() {
+	let handle = SyncHandle::new(common::init_client(true));
+	let _ = handle.run(|_| -> Result<(), _> { panic!("closure panicked") }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn sync_handle_into_inner() {
+	let handle = SyncHandle::new(common::init_client(true));
+	let clone = handle.clone();
+
+	let handle = handle.into_inner().unwrap_err();
+	drop(clone);
+
+	let mut client = handle.into_inner().unwrap();
+	assert!(client.ping().is_ok());
+}
+
+fn init_unresponsive_server() -> String {
+	// connections complete the handshake but commands are never answered
+	let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind listener.");
+	let addr = format!("paper://{}", listener.local_addr().unwrap());
+
+	thread::spawn(move || {
+		let mut streams = Vec::new();
+
+		for mut stream in listener.incoming().flatten() {
+			let _ = stream.write_all(b"!");
+			streams.push(stream);
+		}
+	});
+
+	addr
+}
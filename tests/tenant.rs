@@ -0,0 +1,119 @@
+mod common;
+
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn tenant_namespaced() {
+	let mut client = common::init_client(true);
+
+	assert!(client.tenant("team-a").set("key", "value", None).is_ok());
+
+	assert!(client.tenant("team-b").get("key").is_err());
+	assert!(client.get("key").is_err());
+
+	let value = client.get("team-a/key");
+	assert!(value.is_ok());
+	assert_eq!(value.unwrap(), "value");
+
+	let mut tenant = client.tenant("team-a");
+
+	assert_eq!(tenant.get("key").unwrap(), "value");
+	assert!(tenant.has("key").unwrap());
+	assert_eq!(tenant.size("key").unwrap(), 5);
+	assert!(tenant.del("key").is_ok());
+	assert!(!tenant.has("key").unwrap());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn tenant_namespaced_async() {
+	let mut client = common::init_async_client(true).await;
+
+	assert!(client.tenant("team-a").set("key", "value", None).await.is_ok());
+
+	assert!(client.tenant("team-b").get("key").await.is_err());
+	assert!(client.get("key").await.is_err());
+
+	let value = client.get("team-a/key").await;
+	assert!(value.is_ok());
+	assert_eq!(value.unwrap(), "value");
+
+	let mut tenant = client.tenant("team-a");
+
+	assert_eq!(tenant.get("key").await.unwrap(), "value");
+	assert!(tenant.has("key").await.unwrap());
+	assert_eq!(tenant.size("key").await.unwrap(), 5);
+	assert!(tenant.del("key").await.is_ok());
+	assert!(!tenant.has("key").await.unwrap());
+}
+
+#[test]
+#[serial]
+fn tenant_usage() {
+	let mut client = common::init_client(true);
+	assert!(client.tenant_usage().is_empty());
+
+	let mut tenant = client.tenant("team-b");
+	assert!(tenant.set("key", "value", None).is_ok());
+	assert!(tenant.get("key").is_ok());
+	assert!(tenant.peek("key").is_ok());
+
+	let mut tenant = client.tenant("team-a");
+	assert!(tenant.get("key").is_err());
+	assert!(tenant.set("key", "longer value", None).is_ok());
+	assert!(tenant.ttl("key", Some(10)).is_ok());
+
+	let usage = client.tenant_usage();
+	assert_eq!(usage.len(), 2);
+
+	assert_eq!(usage[0].tenant(), "team-a");
+	assert_eq!(usage[0].requests(), 3);
+	assert_eq!(usage[0].errors(), 0);
+	assert_eq!(usage[0].bytes_read(), 0);
+	assert_eq!(usage[0].bytes_written(), 12);
+
+	assert_eq!(usage[1].tenant(), "team-b");
+	assert_eq!(usage[1].requests(), 3);
+	assert_eq!(usage[1].bytes_read(), 10);
+	assert_eq!(usage[1].bytes_written(), 5);
+
+	// a tenant's usage persists across its views
+	assert_eq!(client.tenant("team-b").usage(), usage[1]);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn tenant_usage_async() {
+	let mut client = common::init_async_client(true).await;
+	assert!(client.tenant_usage().is_empty());
+
+	let mut tenant = client.tenant("team-b");
+	assert!(tenant.set("key", "value", None).await.is_ok());
+	assert!(tenant.get("key").await.is_ok());
+	assert!(tenant.peek("key").await.is_ok());
+
+	let mut tenant = client.tenant("team-a");
+	assert!(tenant.get("key").await.is_err());
+	assert!(tenant.set("key", "longer value", None).await.is_ok());
+	assert!(tenant.ttl("key", Some(10)).await.is_ok());
+
+	let usage = client.tenant_usage();
+	assert_eq!(usage.len(), 2);
+
+	assert_eq!(usage[0].tenant(), "team-a");
+	assert_eq!(usage[0].requests(), 3);
+	assert_eq!(usage[0].errors(), 0);
+	assert_eq!(usage[0].bytes_read(), 0);
+	assert_eq!(usage[0].bytes_written(), 12);
+
+	assert_eq!(usage[1].tenant(), "team-b");
+	assert_eq!(usage[1].requests(), 3);
+	assert_eq!(usage[1].bytes_read(), 10);
+	assert_eq!(usage[1].bytes_written(), 5);
+
+	// a tenant's usage persists across its views
+	assert_eq!(client.tenant("team-b").usage(), usage[1]);
+}
@@ -0,0 +1,274 @@
+#![cfg(unix)]
+
+use std::{
+	env,
+	fs,
+	io::{Read, Write},
+	os::unix::net::{UnixListener, UnixStream},
+	path::PathBuf,
+	sync::{
+		Arc,
+		atomic::{AtomicUsize, Ordering},
+	},
+	thread,
+	time::Duration,
+};
+
+#[cfg(feature = "tokio")]
+use paper_client::AsyncPaperClient;
+use paper_client::{
+	BoxedTransport,
+	ConnectionInfo,
+	DynPaperTransport,
+	PaperClient,
+	PaperClientConfig,
+	PaperClientError,
+	PaperConnector,
+	PaperTransport,
+	PeerAddr,
+	TlsInfo,
+	error::PaperClientResult,
+};
+use serial_test::serial;
+
+#[test]
+fn transport_unix() {
+	let path = serve_unix("transport_unix");
+
+	let mut client = PaperClient::<UnixStream>::with_transport(
+		format!("paper://{}", path.display()),
+		PaperClientConfig::default(),
+	)
+	.expect("Could not connect client.");
+
+	let value: String = client.ping().unwrap().try_into().unwrap();
+	assert_eq!(value, "pong");
+
+	fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn transport_unix_async() {
+	use paper_client::AsyncPaperClient;
+
+	let path = serve_unix("transport_unix_async");
+
+	let mut client = AsyncPaperClient::<tokio::net::UnixStream>::with_transport(
+		format!("paper://{}", path.display()),
+		PaperClientConfig::default(),
+	)
+	.await
+	.expect("Could not connect client.");
+
+	let value: String = client.ping().await.unwrap().try_into().unwrap();
+	assert_eq!(value, "pong");
+
+	fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn transport_unix_unreachable() {
+	let path = env::temp_dir().join("paper_client_transport_unreachable.sock");
+
+	let result = PaperClient::<UnixStream>::with_transport(
+		format!("paper://{}", path.display()),
+		PaperClientConfig::default(),
+	);
+
+	assert_eq!(result.err(), Some(PaperClientError::UnreachableServer));
+}
+
+#[test]
+#[serial]
+fn transport_boxed_tcp() {
+	let mut client = PaperClient::<BoxedTransport>::with_transport(
+		"paper://127.0.0.1:3145",
+		PaperClientConfig::default(),
+	)
+	.expect("Could not connect client.");
+
+	let value: String = client.ping().unwrap().try_into().unwrap();
+	assert_eq!(value, "pong");
+}
+
+#[test]
+fn transport_boxed_unix() {
+	let path = serve_unix("transport_boxed_unix");
+
+	let mut client = PaperClient::<BoxedTransport>::with_transport(
+		format!("paper://{}", path.display()),
+		PaperClientConfig::default(),
+	)
+	.expect("Could not connect client.");
+
+	let value: String = client.ping().unwrap().try_into().unwrap();
+	assert_eq!(value, "pong");
+
+	fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn transport_boxed_connector() {
+	#[derive(Debug)]
+	struct UnixConnector {
+		path:        PathBuf,
+		connections: Arc<AtomicUsize>,
+	}
+
+	impl PaperConnector for UnixConnector {
+		fn connect(&self, _: &str) -> PaperClientResult<Box<dyn DynPaperTransport>> {
+			self.connections.fetch_add(1, Ordering::Relaxed);
+
+			let stream = UnixStream::open(self.path.to_str().unwrap())?;
+			Ok(Box::new(stream))
+		}
+	}
+
+	let path = serve_unix("transport_boxed_connector");
+	let connections = Arc::new(AtomicUsize::default());
+
+	let connector = UnixConnector {
+		path:        path.clone(),
+		connections: connections.clone(),
+	};
+
+	// the connector is used regardless of the address
+	let mut client = PaperClient::<BoxedTransport>::with_transport(
+		"paper://unused:3145",
+		PaperClientConfig::default().connector(connector),
+	)
+	.expect("Could not connect client.");
+
+	let value: String = client.ping().unwrap().try_into().unwrap();
+	assert_eq!(value, "pong");
+	assert_eq!(connections.load(Ordering::Relaxed), 1);
+
+	fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[serial]
+fn transport_peer_addr() {
+	let client = PaperClient::new("paper://127.0.0.1:3145").unwrap();
+
+	let addr = "127.0.0.1:3145".parse().unwrap();
+	assert_eq!(client.peer_addr(), Some(PeerAddr::Tcp(addr)));
+	assert_eq!(client.connection_info().tls(), None);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn transport_peer_addr_async() {
+	let client = AsyncPaperClient::new("paper://127.0.0.1:3145").await.unwrap();
+
+	let addr = "127.0.0.1:3145".parse().unwrap();
+	assert_eq!(client.peer_addr(), Some(PeerAddr::Tcp(addr)));
+	assert_eq!(client.connection_info().tls(), None);
+}
+
+#[test]
+fn transport_unix_peer_addr() {
+	let path = serve_unix("transport_unix_peer_addr");
+
+	let client = PaperClient::<BoxedTransport>::with_transport(
+		format!("paper://{}", path.display()),
+		PaperClientConfig::default(),
+	)
+	.expect("Could not connect client.");
+
+	assert_eq!(client.peer_addr(), Some(PeerAddr::Unix(path.clone())));
+
+	fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn transport_connector_tls_info() {
+	// reports the parameters of a TLS session, as a TLS stream would
+	struct TlsStream(UnixStream);
+
+	#[derive(Debug)]
+	struct TlsConnector(PathBuf);
+
+	impl Read for TlsStream {
+		fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+			self.0.read(buf)
+		}
+	}
+
+	impl Write for TlsStream {
+		fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+			self.0.write(buf)
+		}
+
+		fn flush(&mut self) -> std::io::Result<()> {
+			self.0.flush()
+		}
+	}
+
+	impl PaperTransport for TlsStream {
+		fn open(addr: &str) -> PaperClientResult<Self> {
+			UnixStream::open(addr).map(TlsStream)
+		}
+
+		fn set_read_timeout(&mut self, timeout: Option<Duration>) -> PaperClientResult<()> {
+			PaperTransport::set_read_timeout(&mut self.0, timeout)
+		}
+
+		fn connection_info(&self) -> ConnectionInfo {
+			let tls = TlsInfo::new("TLSv1.3", "TLS13_AES_128_GCM_SHA256").with_alpn(b"paper");
+			PaperTransport::connection_info(&self.0).with_tls(tls)
+		}
+	}
+
+	impl PaperConnector for TlsConnector {
+		fn connect(&self, _: &str) -> PaperClientResult<Box<dyn DynPaperTransport>> {
+			let stream = TlsStream::open(self.0.to_str().unwrap())?;
+			Ok(Box::new(stream))
+		}
+	}
+
+	let path = serve_unix("transport_connector_tls_info");
+
+	let client = PaperClient::<BoxedTransport>::with_transport(
+		"paper://unused:3145",
+		PaperClientConfig::default().connector(TlsConnector(path.clone())),
+	)
+	.expect("Could not connect client.");
+
+	let info = client.connection_info();
+	let tls = info.tls().unwrap();
+
+	assert_eq!(info.peer_addr(), Some(&PeerAddr::Unix(path.clone())));
+	assert_eq!(tls.protocol(), "TLSv1.3");
+	assert_eq!(tls.cipher(), "TLS13_AES_128_GCM_SHA256");
+	assert_eq!(tls.alpn(), Some(b"paper".as_slice()));
+
+	fs::remove_file(&path).unwrap();
+}
+
+/// Serves a single connection which responds to pings.
+fn serve_unix(name: &str) -> PathBuf {
+	let path = env::temp_dir().join(format!("paper_client_{name}.sock"));
+	let _ = fs::remove_file(&path);
+
+	let listener = UnixListener::bind(&path).expect("Could not bind socket.");
+
+	thread::spawn(move || {
+		let (mut stream, _) = listener.accept().unwrap();
+		stream.write_all(b"!").unwrap();
+
+		let mut command = [0u8];
+
+		while stream.read_exact(&mut command).is_ok() {
+			assert_eq!(command[0], 0);
+
+			stream.write_all(b"!").unwrap();
+			stream.write_all(&4u32.to_le_bytes()).unwrap();
+			stream.write_all(b"pong").unwrap();
+		}
+	});
+
+	path
+}
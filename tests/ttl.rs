@@ -1,5 +1,11 @@
 mod common;
 
+use std::{
+	num::NonZeroU32,
+	time::{Duration, SystemTime},
+};
+
+use paper_client::{PaperClientError, Ttl};
 use serial_test::serial;
 
 #[test]
@@ -43,3 +49,90 @@ async fn ttl_non_existent_async() {
 	let result = client.ttl("key", Some(1)).await;
 	assert!(result.is_err());
 }
+
+#[test]
+#[serial]
+fn ttl_duration() {
+	let mut client = common::init_client(true);
+
+	assert!(client.set("key", "value", Duration::from_secs(60)).is_ok());
+	assert!(client.ttl("key", Duration::from_millis(1500)).is_ok());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn ttl_duration_async() {
+	let mut client = common::init_async_client(true).await;
+
+	assert!(client.set("key", "value", Duration::from_secs(60)).await.is_ok());
+	assert!(client.ttl("key", Duration::from_millis(1500)).await.is_ok());
+}
+
+#[test]
+#[serial]
+fn ttl_enum() {
+	let mut client = common::init_client(true);
+
+	let at = SystemTime::now() + Duration::from_secs(60);
+
+	assert!(client.set("key", "value", Ttl::None).is_ok());
+	assert!(client.ttl("key", Ttl::Seconds(NonZeroU32::new(60).unwrap())).is_ok());
+	assert!(client.ttl("key", Ttl::At(at)).is_ok());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn ttl_enum_async() {
+	let mut client = common::init_async_client(true).await;
+
+	let at = SystemTime::now() + Duration::from_secs(60);
+
+	assert!(client.set("key", "value", Ttl::None).await.is_ok());
+	assert!(client.ttl("key", Ttl::Seconds(NonZeroU32::new(60).unwrap())).await.is_ok());
+	assert!(client.ttl("key", Ttl::At(at)).await.is_ok());
+}
+
+#[test]
+#[serial]
+fn ttl_invalid() {
+	let mut client = common::init_client(true);
+
+	let past = SystemTime::now() - Duration::from_secs(1);
+
+	assert_eq!(client.set("key", "value", Some(0)), Err(PaperClientError::InvalidTtl));
+	assert_eq!(client.set("key", "value", Duration::ZERO), Err(PaperClientError::InvalidTtl));
+	assert_eq!(client.set("key", "value", Ttl::At(past)), Err(PaperClientError::InvalidTtl));
+
+	// the rejected sets were not sent
+	assert!(client.get("key").is_err());
+
+	assert!(client.set("key", "value", None).is_ok());
+	assert_eq!(client.ttl("key", Some(0)), Err(PaperClientError::InvalidTtl));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn ttl_invalid_async() {
+	let mut client = common::init_async_client(true).await;
+
+	let past = SystemTime::now() - Duration::from_secs(1);
+
+	assert_eq!(client.set("key", "value", Some(0)).await, Err(PaperClientError::InvalidTtl));
+	assert_eq!(client.set("key", "value", Duration::ZERO).await, Err(PaperClientError::InvalidTtl));
+	assert_eq!(client.set("key", "value", Ttl::At(past)).await, Err(PaperClientError::InvalidTtl));
+
+	// the rejected sets were not sent
+	assert!(client.get("key").await.is_err());
+
+	assert!(client.set("key", "value", None).await.is_ok());
+	assert_eq!(client.ttl("key", Some(0)).await, Err(PaperClientError::InvalidTtl));
+}
+
+#[test]
+fn ttl_from_secs() {
+	assert_eq!(Ttl::from_secs(0), Ttl::None);
+	assert_eq!(Ttl::from_secs(5), Ttl::Seconds(NonZeroU32::new(5).unwrap()));
+}
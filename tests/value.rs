@@ -0,0 +1,82 @@
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, TimeZone, Utc};
+#[cfg(any(feature = "uuid", feature = "chrono"))]
+use paper_client::PaperClientError;
+use paper_client::PaperValue;
+#[cfg(feature = "uuid")]
+use uuid::Uuid;
+
+#[test]
+fn value_bytes() {
+	let value = PaperValue::from("value");
+
+	assert_eq!(value.len(), 5);
+	assert!(!value.is_empty());
+	assert!(PaperValue::from("").is_empty());
+
+	assert_eq!(&value[..2], b"va");
+	assert_eq!(value.as_ref(), b"value");
+	assert!(value.starts_with(b"val"));
+}
+
+#[test]
+fn value_eq() {
+	let value = PaperValue::from("value");
+
+	assert_eq!(value, "value");
+	assert_eq!(value, b"value".as_slice());
+	assert_ne!(value, "other");
+
+	assert_eq!(value.clone(), value);
+	assert_ne!(value, PaperValue::from(vec![0, 1]));
+}
+
+#[test]
+fn value_strings() {
+	let value = PaperValue::from("value");
+
+	assert_eq!(value.as_str(), Ok("value"));
+	assert_eq!(value.to_string_lossy(), "value");
+	assert_eq!(value.into_string().unwrap(), "value");
+
+	let invalid = PaperValue::from(vec![b'a', 0xff]);
+
+	assert!(invalid.as_str().is_err());
+	assert_eq!(invalid.to_string_lossy(), "a\u{fffd}");
+	assert!(invalid.into_string().is_err());
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn value_uuid() {
+	let uuid = Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8);
+	let value = PaperValue::from(uuid);
+
+	assert_eq!(value, "67e55044-10b1-426f-9247-bb680e5fe0c8");
+	assert_eq!(Uuid::try_from(value), Ok(uuid));
+
+	let simple = PaperValue::from("67e5504410b1426f9247bb680e5fe0c8");
+	assert_eq!(Uuid::try_from(simple), Ok(uuid));
+
+	let invalid = PaperValue::from("not-a-uuid");
+	assert_eq!(Uuid::try_from(invalid), Err(PaperClientError::InvalidConversion));
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn value_date_time() {
+	let time = Utc.with_ymd_and_hms(2024, 5, 1, 12, 30, 0).unwrap();
+	let value = PaperValue::from(time);
+
+	assert_eq!(value, "2024-05-01T12:30:00Z");
+	assert_eq!(DateTime::<Utc>::try_from(value), Ok(time));
+
+	let millis = time + chrono::Duration::milliseconds(250);
+	assert_eq!(PaperValue::from(millis), "2024-05-01T12:30:00.250Z");
+
+	let offset = PaperValue::from("2024-05-01T14:30:00+02:00");
+	assert_eq!(DateTime::<Utc>::try_from(offset), Ok(time));
+
+	let invalid = PaperValue::from("yesterday");
+	assert_eq!(DateTime::<Utc>::try_from(invalid), Err(PaperClientError::InvalidConversion));
+}
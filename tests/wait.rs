@@ -0,0 +1,82 @@
+mod common;
+
+use std::{thread, time::Duration};
+
+use paper_client::{Clock, MockClock, PaperClient, PaperClientConfig, PaperClientError};
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn wait_for_existent() {
+	let mut client = common::init_client(true);
+
+	assert!(client.set("key", "value", None).is_ok());
+
+	let result = client.wait_for("key", Duration::from_secs(1), Duration::from_millis(10));
+	assert_eq!(result.unwrap(), "value");
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn wait_for_existent_async() {
+	let mut client = common::init_async_client(true).await;
+
+	assert!(client.set("key", "value", None).await.is_ok());
+
+	let result = client
+		.wait_for("key", Duration::from_secs(1), Duration::from_millis(10))
+		.await;
+
+	assert_eq!(result.unwrap(), "value");
+}
+
+#[test]
+#[serial]
+fn wait_for_handoff() {
+	let mut client = common::init_client(true);
+
+	let producer = thread::spawn(|| {
+		let mut client = common::init_client(true);
+
+		thread::sleep(Duration::from_millis(50));
+		client.set("key", "value", None)
+	});
+
+	let result = client.wait_for("key", Duration::from_secs(5), Duration::from_millis(5));
+
+	assert!(producer.join().unwrap().is_ok());
+	assert_eq!(result.unwrap(), "value");
+}
+
+#[test]
+#[serial]
+fn wait_for_timeout() {
+	let clock = MockClock::new();
+	let start = clock.now();
+
+	common::init_client(true);
+
+	let config = PaperClientConfig::default().clock(clock.clone());
+
+	let mut client = PaperClient::with_config("paper://127.0.0.1:3145", config).unwrap();
+	client.auth("auth_token").unwrap();
+
+	let result = client.wait_for("key", Duration::from_secs(60), Duration::from_secs(1));
+
+	assert_eq!(result, Err(PaperClientError::WaitTimeout));
+	assert_eq!(clock.now() - start, Duration::from_secs(60));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+#[serial]
+async fn wait_for_timeout_async() {
+	let mut client = common::init_async_client(true).await;
+
+	let result = client
+		.wait_for("key", Duration::from_millis(50), Duration::from_millis(5))
+		.await;
+
+	assert_eq!(result, Err(PaperClientError::WaitTimeout));
+}